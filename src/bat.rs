@@ -1,21 +1,46 @@
-use std::{io, path::Path, process::Command};
+use std::{
+    io,
+    io::{IsTerminal, Write},
+    path::Path,
+    process::Command,
+};
 
 use crate::utils::Program;
 
 pub struct Support {
+    /// The program and its arguments to use for previews instead of `bat`.
+    command: Option<Vec<String>>,
     bat: Program,
+    /// If `false`, previews are printed to stdout instead of being shown by a program that waits for the user.
+    interactive: bool,
 }
 
 impl Default for Support {
     fn default() -> Self {
-        Self::new()
+        Self::new(None)
     }
 }
 
 impl Support {
-    pub fn new() -> Self {
+    /// Create a new instance which previews with `command`, or `bat` if `None`.
+    ///
+    /// `command` is split on whitespace into the program and its arguments, with the path to the file to preview
+    /// passed as last argument, like `delta`, `glow -p` or `less -R`.
+    pub fn new(command: Option<&str>) -> Self {
         Support {
+            command: command
+                .map(|c| c.split_whitespace().map(ToOwned::to_owned).collect::<Vec<_>>())
+                .filter(|args| !args.is_empty()),
             bat: Program::named("bat"),
+            interactive: true,
+        }
+    }
+
+    /// Print previews as they are instead of showing them by a program that waits for the user to close it.
+    pub fn non_interactive(self) -> Self {
+        Support {
+            interactive: false,
+            ..self
         }
     }
 
@@ -25,15 +50,41 @@ impl Support {
         path_for_title: &Path,
         additional_title: impl AsRef<str>,
     ) -> io::Result<()> {
+        if !self.interactive || !io::stdout().is_terminal() {
+            let mut stdout = io::stdout().lock();
+            writeln!(stdout, "{} ({})", path_for_title.display(), additional_title.as_ref())?;
+            return stdout.write_all(&std::fs::read(path)?);
+        }
+        if let Some((program, args)) = self.command.as_ref().and_then(|c| c.split_first()) {
+            let status = Command::new(program).args(args).arg(path).status().map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("Could not run preview command '{}': {err}", self.command_line()),
+                )
+            })?;
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(io::Error::other(format!(
+                    "Preview command '{}' exited with an error",
+                    self.command_line()
+                )))
+            };
+        }
         if !self.bat.found {
-            log::warn!(
+            tracing::warn!(
                 "Would want to use 'bat' for colored preview of '{}', but it wasn't available in the PATH.",
                 path.display()
             );
             return Ok(());
         }
         if Command::new("bat")
-            .args(["--paging=always", "-l=md", "--file-name"])
+            .args([
+                "--paging=always",
+                "-l=md",
+                crate::color::arg(crate::color::stdout()),
+                "--file-name",
+            ])
             .arg(format!("{} ({})", path_for_title.display(), additional_title.as_ref()))
             .arg(path)
             .status()?
@@ -44,4 +95,8 @@ impl Support {
             Err(io::Error::other("bat exited with an error"))
         }
     }
+
+    fn command_line(&self) -> String {
+        self.command.as_deref().unwrap_or_default().join(" ")
+    }
 }
@@ -1,12 +1,11 @@
 use anyhow::Context;
-use cargo_metadata::{
-    camino::{Utf8Path, Utf8PathBuf},
-    Package,
-};
+use cargo_metadata::Package;
 
 use crate::{
-    changelog::{section::segment, Section},
-    commit, ChangeLog,
+    changelog::{section::segment, tracking, Section},
+    commit,
+    version::VersionScheme,
+    ChangeLog,
 };
 
 #[derive(Clone, Copy)]
@@ -34,6 +33,8 @@ pub struct Outcome {
     pub state: State,
     pub lock: gix::lock::File,
     pub previous_content: Option<String>,
+    /// A lock on the sidecar file holding tracking data, if tracking data isn't stored in the changelog itself.
+    pub tracking_lock: Option<gix::lock::File>,
 }
 
 impl ChangeLog {
@@ -42,18 +43,23 @@ impl ChangeLog {
         history: &commit::History,
         ctx: &'a crate::Context,
         selection: segment::Selection,
+        sidecar: bool,
     ) -> anyhow::Result<Outcome> {
-        let mut generated = ChangeLog::from_history_segments(
-            package,
-            &crate::git::history::crate_ref_segments(
+        let mut generated = crate::stats::time(format!("generate changelog of {}", package.name), || {
+            anyhow::Ok(ChangeLog::from_history_segments(
                 package,
-                ctx,
-                history,
-                crate::git::history::SegmentScope::EntireHistory,
-            )?,
-            &ctx.repo,
-            selection,
-        );
+                &crate::git::history::crate_ref_segments(
+                    package,
+                    ctx,
+                    history,
+                    crate::git::history::SegmentScope::EntireHistory,
+                )?,
+                ctx.repo_of(package),
+                ctx.version_scheme.as_ref(),
+                selection,
+                ctx.history_options.authors,
+            ))
+        })?;
         generated.sections.insert(
             0,
             Section::Verbatim {
@@ -61,7 +67,8 @@ impl ChangeLog {
                 generated: true,
             },
         );
-        let changelog_path = path_from_manifest(&package.manifest_path);
+        let changelog_path = crate::utils::changelog_path(package, &ctx.meta);
+        crate::git::assure_path_is_checked_out(ctx.repo_of(package), changelog_path.as_std_path())?;
         let lock =
             gix::lock::File::acquire_to_update_resource(&changelog_path, gix::lock::acquire::Fail::Immediately, None)
                 .with_context(|| {
@@ -70,8 +77,26 @@ impl ChangeLog {
                     changelog_path, package.name
                 )
             })?;
-        let (log, state, previous_content) = if let Ok(markdown) = std::fs::read_to_string(changelog_path) {
-            let existing_log = ChangeLog::from_markdown(&markdown);
+        let tracking_path = tracking::path_for_changelog(&changelog_path);
+        crate::git::assure_path_is_checked_out(ctx.repo_of(package), &tracking_path)?;
+        let tracking_state = tracking::State::from_path(&tracking_path)?;
+        let tracking_lock = (sidecar || tracking_state.is_some())
+            .then(|| {
+                gix::lock::File::acquire_to_update_resource(&tracking_path, gix::lock::acquire::Fail::Immediately, None)
+                    .with_context(|| {
+                        format!(
+                            "While locking changelog tracking state '{}' for crate '{}'",
+                            tracking_path.display(),
+                            package.name
+                        )
+                    })
+            })
+            .transpose()?;
+        let (log, state, previous_content) = if let Ok(markdown) = std::fs::read_to_string(&changelog_path) {
+            let mut existing_log = ChangeLog::from_markdown(&markdown, &ctx.changelog_locale);
+            if let Some(tracking_state) = &tracking_state {
+                existing_log.apply_tracking_state(tracking_state);
+            }
             let copy_of_existing = existing_log.clone();
             let merged = existing_log
                 .merge_generated_with_conventional_pruning(
@@ -93,6 +118,7 @@ impl ChangeLog {
             state,
             lock,
             previous_content,
+            tracking_lock,
         })
     }
 
@@ -101,8 +127,9 @@ impl ChangeLog {
         history: &commit::History,
         ctx: &'a crate::Context,
         selection: segment::Selection,
+        sidecar: bool,
     ) -> anyhow::Result<(Outcome, &'a Package)> {
-        let out = Self::for_package_with_write_lock(package, history, ctx, selection)?;
+        let out = Self::for_package_with_write_lock(package, history, ctx, selection, sidecar)?;
         Ok((out, package))
     }
 
@@ -110,7 +137,9 @@ impl ChangeLog {
         package: &Package,
         segments: &[commit::history::Segment<'_>],
         repo: &gix::Repository,
+        scheme: &dyn VersionScheme,
         selection: segment::Selection,
+        attribute_authors: bool,
     ) -> Self {
         ChangeLog {
             sections: {
@@ -119,20 +148,26 @@ impl ChangeLog {
                         package,
                         &segments[0],
                         repo,
+                        scheme,
                         selection,
+                        attribute_authors,
                         (&segments[1]).into(),
                     ));
                     acc
                 });
                 if let Some(segment) = segments.last() {
-                    s.push(Section::from_history_segment(package, segment, repo, selection, None))
+                    s.push(Section::from_history_segment(
+                        package,
+                        segment,
+                        repo,
+                        scheme,
+                        selection,
+                        attribute_authors,
+                        None,
+                    ))
                 }
                 s
             },
         }
     }
 }
-
-fn path_from_manifest(path: &Utf8Path) -> Utf8PathBuf {
-    path.parent().expect("parent for Cargo.toml").join("CHANGELOG.md")
-}
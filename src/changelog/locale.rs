@@ -0,0 +1,115 @@
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use anyhow::{bail, Context};
+
+use crate::changelog::section::segment;
+
+/// The keys of a table of headings besides the git-conventional kinds, for the segments with fixed titles.
+const SEGMENT_KEYS: &[&str] = &["statistics", "details", "clippy", "breaking"];
+
+/// Translations which are built in, by their language code and name, as tables of headings.
+const BUILT_IN: &[(&str, &str, &str)] = &[
+    ("de", "german", include_str!("locale/de.toml")),
+    ("fr", "french", include_str!("locale/fr.toml")),
+    ("es", "spanish", include_str!("locale/es.toml")),
+];
+
+/// The headings used for generated changelog content, which are English unless a table of headings provides others.
+///
+/// Tables map the git-conventional kinds like `fix` or `feat`, as well as `statistics`, `details` and `clippy`, to
+/// their heading. Kinds which aren't listed keep their English heading. `breaking` is the heading under which breaking
+/// changes are documented by hand.
+///
+/// The parser accepts headings of English, the built-in translations and the locale it's given, so changelogs can be
+/// round-tripped no matter which of these they were written with.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Locale {
+    headings: BTreeMap<String, String>,
+}
+
+impl Locale {
+    /// Obtain a built-in locale from its language code or name, like `en`, `de` or `german`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if matches!(name.as_str(), "en" | "english") {
+            return Some(Locale::default());
+        }
+        BUILT_IN
+            .iter()
+            .find(|(code, long_name, _)| name == *code || name == *long_name)
+            .map(|(_, _, table)| Locale::from_toml(table).expect("built-in tables are valid"))
+    }
+
+    /// Parse a table of headings like `fix = "Bug Fixes"` from the TOML document `content`.
+    pub fn from_toml(content: &str) -> anyhow::Result<Self> {
+        let doc: toml_edit::DocumentMut = content.parse()?;
+        let headings = doc
+            .iter()
+            .map(|(key, item)| {
+                if segment::conventional::as_headline(key).is_none() && !SEGMENT_KEYS.contains(&key) {
+                    bail!(
+                        "'{key}' isn't a git-conventional kind or one of {}",
+                        SEGMENT_KEYS.join(", ")
+                    );
+                }
+                let heading = item
+                    .as_str()
+                    .with_context(|| format!("The heading of '{key}' must be a string"))?;
+                Ok((key.to_owned(), heading.to_owned()))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Locale { headings })
+    }
+
+    /// English and all built-in translations, whose headings are understood when parsing.
+    pub fn built_in() -> &'static [Locale] {
+        static ALL: OnceLock<Vec<Locale>> = OnceLock::new();
+        ALL.get_or_init(|| {
+            std::iter::once(Locale::default())
+                .chain(
+                    BUILT_IN
+                        .iter()
+                        .map(|(_, _, table)| Locale::from_toml(table).expect("built-in tables are valid")),
+                )
+                .collect()
+        })
+    }
+
+    /// The headline for the git-conventional `kind`, or `None` if it doesn't have one and shouldn't be written.
+    pub fn conventional_headline(&self, kind: &str) -> Option<&str> {
+        let english = segment::conventional::as_headline(kind)?;
+        let key = if kind == "added" { "add" } else { kind };
+        Some(self.headings.get(key).map_or(english, String::as_str))
+    }
+
+    /// The title of the commit statistics segment.
+    pub fn statistics_title(&self) -> &str {
+        self.heading("statistics", segment::CommitStatistics::TITLE)
+    }
+
+    /// The title of the commit details segment.
+    pub fn details_title(&self) -> &str {
+        self.heading("details", segment::Details::TITLE)
+    }
+
+    /// The title of the segment thanking clippy.
+    pub fn clippy_title(&self) -> &str {
+        self.heading("clippy", segment::ThanksClippy::TITLE)
+    }
+
+    /// The heading under which breaking changes are documented by hand.
+    pub fn breaking_title(&self) -> &str {
+        self.heading("breaking", "Breaking Changes")
+    }
+
+    /// Return `true` if `title` is the heading of hand-written breaking changes in this locale or any built-in one.
+    pub fn is_breaking_title(&self, title: &str) -> bool {
+        std::iter::once(self)
+            .chain(Locale::built_in())
+            .any(|locale| locale.breaking_title().eq_ignore_ascii_case(title.trim()))
+    }
+
+    fn heading<'a>(&'a self, key: &str, english: &'a str) -> &'a str {
+        self.headings.get(key).map_or(english, String::as_str)
+    }
+}
@@ -258,7 +258,12 @@ fn merge_conventional(
             }) => {
                 for src_message in src.messages.clone() {
                     match src_message {
-                        conventional::Message::Generated { id, title, body } => {
+                        conventional::Message::Generated {
+                            id,
+                            title,
+                            body,
+                            author,
+                        } => {
                             if removed.contains(&id)
                                 || removed_in_release.contains(&id)
                                 || messages.iter().any(
@@ -274,7 +279,15 @@ fn merge_conventional(
                                 .map(|(pos, _)| pos + 1)
                                 .last()
                                 .unwrap_or(messages.len());
-                            messages.insert(pos, conventional::Message::Generated { id, title, body });
+                            messages.insert(
+                                pos,
+                                conventional::Message::Generated {
+                                    id,
+                                    title,
+                                    body,
+                                    author,
+                                },
+                            );
                         }
                         conventional::Message::User { .. } => bail!("User messages are never generated"),
                     }
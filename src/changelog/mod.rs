@@ -3,11 +3,14 @@ use std::cmp::Ordering;
 use crate::{changelog::section::segment::conventional::as_headline, ChangeLog};
 
 pub mod init;
+pub mod locale;
 mod merge;
 mod parse;
 pub mod section;
 #[cfg(test)]
 mod tests;
+pub mod title;
+pub mod tracking;
 pub mod write;
 
 pub const DEFAULT_HEADING_LEVEL: usize = 2;
@@ -110,4 +113,72 @@ impl Section {
             }
         }
     }
+
+    /// Return the most severe kind of change that was written by hand into this section, or `None` if there is no
+    /// hand-written breaking change or feature.
+    ///
+    /// These are entries without commit in conventional segments like `### New Features (BREAKING)`, or list items in
+    /// user-authored markdown directly below the heading of breaking changes in `locale`, like `### Breaking Changes`.
+    pub fn manually_documented_change(&self, locale: &locale::Locale) -> Option<crate::version::Change> {
+        use crate::version::Change;
+        let Section::Release { segments, .. } = self else {
+            return None;
+        };
+        segments
+            .iter()
+            .filter_map(|segment| match segment {
+                section::Segment::Conventional(section::segment::Conventional {
+                    kind,
+                    is_breaking,
+                    messages,
+                    ..
+                }) if messages
+                    .iter()
+                    .any(|m| matches!(m, section::segment::conventional::Message::User { .. })) =>
+                {
+                    if *is_breaking {
+                        Some(Change::Breaking)
+                    } else if *kind == "feat" {
+                        Some(Change::Feature)
+                    } else {
+                        None
+                    }
+                }
+                section::Segment::User { markdown } => {
+                    let mut below_breaking_heading = false;
+                    markdown
+                        .lines()
+                        .any(|line| {
+                            if let Some(title) = line.strip_prefix('#') {
+                                // Items below any following heading aren't directly below this one anymore.
+                                below_breaking_heading = locale.is_breaking_title(title.trim_start_matches('#'));
+                                return false;
+                            }
+                            below_breaking_heading && is_list_item(line)
+                        })
+                        .then_some(Change::Breaking)
+                }
+                _ => None,
+            })
+            .max()
+    }
+}
+
+/// Return `true` if `line` starts an item of a bullet or ordered markdown list.
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    let rest = match line.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+            if rest.len() == line.len() {
+                return false;
+            }
+            match rest.strip_prefix(['.', ')']) {
+                Some(rest) => rest,
+                None => return false,
+            }
+        }
+    };
+    rest.is_empty() || rest.starts_with([' ', '\t'])
 }
@@ -18,6 +18,7 @@ use winnow::{
 use crate::{
     changelog,
     changelog::{
+        locale::Locale,
         section,
         section::{segment::Conventional, Segment},
         Section,
@@ -31,13 +32,26 @@ const CONVENTIONAL_KINDS: &[&str] = &[
 
 impl ChangeLog {
     /// Obtain as much information as possible from `input` and keep everything we didn't understand in respective sections.
-    pub fn from_markdown(input: &str) -> ChangeLog {
+    ///
+    /// Generated headings are recognized if they are English, of a built-in translation, or of `locale`.
+    pub fn from_markdown(input: &str, locale: &Locale) -> ChangeLog {
         let mut sections = Vec::new();
         let mut section_body = String::new();
         let mut previous_headline = None::<Headline>;
         let mut first_heading_level = None;
-        for line in input.as_bytes().as_bstr().lines_with_terminator() {
-            let line = line.to_str().expect("valid UTF-8");
+        let mut lines = input
+            .as_bytes()
+            .as_bstr()
+            .lines_with_terminator()
+            .map(|line| line.to_str().expect("valid UTF-8"))
+            .peekable();
+        while let Some(line) = lines.next() {
+            if Section::parse_anchor(line).is_some()
+                && lines.peek().is_some_and(|next| Headline::try_from(*next).is_ok())
+            {
+                // Anchors are re-generated along with the headline they belong to.
+                continue;
+            }
             match Headline::try_from(line) {
                 Ok(headline) => {
                     first_heading_level.get_or_insert(headline.level);
@@ -47,6 +61,7 @@ impl ChangeLog {
                             sections.push(Section::from_headline_and_body(
                                 headline,
                                 std::mem::take(&mut section_body),
+                                locale,
                             ));
                         }
                         None => {
@@ -71,6 +86,7 @@ impl ChangeLog {
                 sections.push(Section::from_headline_and_body(
                     headline,
                     std::mem::take(&mut section_body),
+                    locale,
                 ));
             }
             None => sections.push(Section::Verbatim {
@@ -133,6 +149,15 @@ impl ChangeLog {
 }
 
 impl Section {
+    /// Return the name of the anchor if `line` is one we would write before a release headline.
+    pub(crate) fn parse_anchor(line: &str) -> Option<&str> {
+        let line = line.trim();
+        line.strip_prefix(Section::ANCHOR_START)
+            .or_else(|| line.strip_prefix("<a name=\""))?
+            .strip_suffix(Section::ANCHOR_END)
+            .filter(|name| !name.is_empty() && !name.contains('"'))
+    }
+
     fn from_headline_and_body(
         Headline {
             level,
@@ -141,6 +166,7 @@ impl Section {
             date,
         }: Headline,
         body: String,
+        locale: &Locale,
     ) -> Self {
         let mut events = pulldown_cmark::Parser::new_ext(&body, pulldown_cmark::Options::all())
             .into_offset_iter()
@@ -180,22 +206,24 @@ impl Section {
                     }
                     let state = match events.next() {
                         Some((Event::Text(title), _range))
-                            if title.starts_with(section::segment::ThanksClippy::TITLE) =>
+                            if known_locales(locale).any(|l| title.starts_with(l.clippy_title())) =>
                         {
                             segments.push(Segment::Clippy(section::Data::Parsed));
                             State::SkipGenerated
                         }
                         Some((Event::Text(title), _range))
-                            if title.starts_with(section::segment::CommitStatistics::TITLE) =>
+                            if known_locales(locale).any(|l| title.starts_with(l.statistics_title())) =>
                         {
                             segments.push(Segment::Statistics(section::Data::Parsed));
                             State::SkipGenerated
                         }
-                        Some((Event::Text(title), _range)) if title.starts_with(section::segment::Details::TITLE) => {
+                        Some((Event::Text(title), _range))
+                            if known_locales(locale).any(|l| title.starts_with(l.details_title())) =>
+                        {
                             segments.push(Segment::Details(section::Data::Parsed));
                             State::SkipGenerated
                         }
-                        Some((Event::Text(title), _range)) if is_conventional_title(&title) => {
+                        Some((Event::Text(title), _range)) if is_conventional_title(&title, locale) => {
                             State::ParseConventional {
                                 title: title.into_string(),
                             }
@@ -222,6 +250,7 @@ impl Section {
                             segments.push(parse_conventional_to_next_section_title(
                                 &body,
                                 title,
+                                locale,
                                 &mut events,
                                 indent,
                                 &mut unknown,
@@ -255,12 +284,13 @@ impl Section {
 fn parse_conventional_to_next_section_title(
     markdown: &str,
     title: String,
+    locale: &Locale,
     events: &mut Peekable<OffsetIter<'_>>,
     level: HeadingLevel,
     unknown: &mut String,
 ) -> Segment {
     let is_breaking = title.ends_with(section::segment::Conventional::BREAKING_TITLE_ENCLOSED);
-    let kind = conventional_kind_for_title(&title)
+    let kind = conventional_kind_for_title(&title, locale)
         .expect("BUG: this list needs an update too if new kinds of conventional messages are added");
 
     let mut conventional = section::segment::Conventional {
@@ -342,17 +372,24 @@ fn parse_conventional_to_next_section_title(
     section::Segment::Conventional(conventional)
 }
 
-fn is_conventional_title(title: &str) -> bool {
-    conventional_kind_for_title(title).is_some()
+fn is_conventional_title(title: &str, locale: &Locale) -> bool {
+    conventional_kind_for_title(title, locale).is_some()
+}
+
+/// The locales whose headings are recognized, `locale` and all built-in ones.
+fn known_locales(locale: &Locale) -> impl Iterator<Item = &Locale> {
+    std::iter::once(locale).chain(Locale::built_in())
 }
 
-fn conventional_kind_for_title(title: &str) -> Option<&'static str> {
-    CONVENTIONAL_KINDS.iter().copied().find(|kind| {
-        let headline = section::segment::conventional::as_headline(kind).unwrap_or(*kind);
-        title
-            .get(..headline.len())
-            .map(|title_prefix| title_prefix.eq_ignore_ascii_case(headline))
-            .unwrap_or(false)
+fn conventional_kind_for_title(title: &str, locale: &Locale) -> Option<&'static str> {
+    known_locales(locale).find_map(|locale| {
+        CONVENTIONAL_KINDS.iter().copied().find(|kind| {
+            let headline = locale.conventional_headline(kind).unwrap_or(*kind);
+            title
+                .get(..headline.len())
+                .map(|title_prefix| title_prefix.eq_ignore_ascii_case(headline))
+                .unwrap_or(false)
+        })
     })
 }
 
@@ -378,11 +415,13 @@ fn parse_id_fallback_to_user_message(
                     .as_bstr()
                     .lines_with_terminator()
                     .map(|b| b.to_str().expect("always valid as source is UTF-8"));
+                let (title, author) = parse_title_and_author(lines.next().unwrap_or(""));
                 conventional
                     .messages
                     .push(section::segment::conventional::Message::Generated {
                         id,
-                        title: lines.next().map_or("", |l| l.trim()).to_owned(),
+                        title,
+                        author,
                         body: lines
                             .map(|l| {
                                 match l
@@ -407,6 +446,23 @@ fn parse_id_fallback_to_user_message(
     };
 }
 
+fn parse_title_and_author(line: &str) -> (String, Option<String>) {
+    let line = line.trim();
+    match line.rsplit_once(section::segment::Conventional::AUTHOR_HTML_TAG) {
+        Some((title, author)) => (
+            title.trim_end().to_owned(),
+            Some(
+                author
+                    .strip_prefix("(by ")
+                    .and_then(|a| a.strip_suffix(')'))
+                    .unwrap_or(author)
+                    .to_owned(),
+            ),
+        ),
+        None => (line.to_owned(), None),
+    }
+}
+
 fn make_user_message_and_consume_item(
     markdown: &str,
     events: &mut Peekable<OffsetIter<'_>>,
@@ -471,7 +527,7 @@ fn record_unknown_range(out: &mut Vec<section::Segment>, range: Option<Range<usi
 }
 
 fn track_unknown_event(unknown_event: Event<'_>, unknown: &mut String) {
-    log::trace!("Cannot handle {unknown_event:?}");
+    tracing::trace!("Cannot handle {unknown_event:?}");
     match unknown_event {
         Event::Html(text)
         | Event::InlineHtml(text)
@@ -498,6 +554,19 @@ fn skip_to_next_section_title(events: &mut Peekable<OffsetIter<'_>>, level: Head
     }
 }
 
+/// Split `token` into the prefix of a version, like `v` or a custom one of a version scheme, and the version itself.
+fn prefixed_version(token: &str) -> Result<(Option<&str>, Option<semver::Version>), ()> {
+    token
+        .char_indices()
+        .filter(|(_, c)| c.is_ascii_digit())
+        .find_map(|(pos, _)| {
+            semver::Version::parse(&token[pos..])
+                .ok()
+                .map(|version| (Some(&token[..pos]), Some(version)))
+        })
+        .ok_or(())
+}
+
 struct Headline {
     level: usize,
     version_prefix: String,
@@ -525,11 +594,7 @@ fn headline<'a, E: ParserError<&'a str> + FromExternalError<&'a str, ()>>(i: &mu
                 hashes,
                 greedy_whitespace,
                 alt((
-                    (
-                        opt("v"),
-                        take_till(0.., char::is_whitespace)
-                            .try_map(|v| semver::Version::parse(v).map_err(|_| ()).map(Some)),
-                    ),
+                    take_till(1.., char::is_whitespace).try_map(prefixed_version),
                     literal(ascii::Caseless("unreleased")).map(|_| (None, None)),
                 )),
             ),
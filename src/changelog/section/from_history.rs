@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use cargo_metadata::Package;
 use gix::prelude::ObjectIdExt;
@@ -10,8 +10,9 @@ use crate::{
         section::{segment::Selection, Segment},
         Section,
     },
-    commit, utils,
+    commit,
     utils::{is_top_level_package, time_to_zoned_time},
+    version::VersionScheme,
 };
 
 impl Section {
@@ -21,7 +22,9 @@ impl Section {
         package: &Package,
         segment: &commit::history::Segment<'_>,
         repo: &gix::Repository,
+        scheme: &dyn VersionScheme,
         selection: section::segment::Selection,
+        attribute_authors: bool,
         prev_segment: Option<&commit::history::Segment<'_>>,
     ) -> Self {
         let date_time = segment_head_time(segment, repo);
@@ -32,7 +35,7 @@ impl Section {
         if !history.is_empty() {
             if selection.contains(Selection::GIT_CONVENTIONAL) {
                 let mut mapping = BTreeMap::default();
-                for (id, kind, title, is_breaking, body) in history.iter().filter_map(|i| {
+                for (id, kind, title, is_breaking, body, author) in history.iter().filter_map(|i| {
                     i.message.kind.as_ref().map(|kind| {
                         (
                             i.id,
@@ -40,13 +43,18 @@ impl Section {
                             i.message.title.clone(),
                             i.message.breaking,
                             i.message.body.clone(),
+                            attribute_authors.then(|| i.author.name.clone()),
                         )
                     })
                 }) {
-                    mapping
-                        .entry((is_breaking, kind))
-                        .or_insert_with(Vec::new)
-                        .push(section::segment::conventional::Message::Generated { id, title, body })
+                    mapping.entry((is_breaking, kind)).or_insert_with(Vec::new).push(
+                        section::segment::conventional::Message::Generated {
+                            id,
+                            title,
+                            body,
+                            author,
+                        },
+                    )
                 }
                 // TODO: proper sorting
                 segments.extend(mapping.into_iter().map(|((is_breaking, kind), messages)| {
@@ -65,15 +73,16 @@ impl Section {
                     for &item in history {
                         let mut issue_associations = 0;
                         for possibly_issue in &item.message.additions {
-                            match possibly_issue {
+                            let category = match possibly_issue {
                                 commit::message::Addition::IssueId(issue) => {
-                                    mapping
-                                        .entry(section::segment::details::Category::Issue(issue.to_owned()))
-                                        .or_insert_with(Vec::new)
-                                        .push(item.into());
-                                    issue_associations += 1;
+                                    section::segment::details::Category::Issue(issue.to_owned())
+                                }
+                                commit::message::Addition::PullRequestId(pr) => {
+                                    section::segment::details::Category::PullRequest(pr.to_owned())
                                 }
-                            }
+                            };
+                            mapping.entry(category).or_insert_with(Vec::new).push(item.into());
+                            issue_associations += 1;
                         }
                         if issue_associations == 0 {
                             mapping
@@ -107,10 +116,23 @@ impl Section {
                         duration,
                         time_passed_since_last_release,
                         conventional_count: history.iter().filter(|item| item.message.kind.is_some()).count(),
+                        authors: attribute_authors.then(|| {
+                            history
+                                .iter()
+                                .map(|item| item.author.email.to_lowercase())
+                                .collect::<BTreeSet<_>>()
+                                .len()
+                        }),
                         unique_issues: {
                             let mut v = commits_by_category
                                 .keys()
-                                .filter(|c| matches!(c, section::segment::details::Category::Issue(_)))
+                                .filter(|c| {
+                                    matches!(
+                                        c,
+                                        section::segment::details::Category::Issue(_)
+                                            | section::segment::details::Category::PullRequest(_)
+                                    )
+                                })
                                 .cloned()
                                 .collect::<Vec<_>>();
                             v.sort();
@@ -146,7 +168,8 @@ impl Section {
                 let package_name =
                     (!is_top_level_package(&package.manifest_path, repo)).then_some(package.name.as_str());
                 changelog::Version::Semantic(
-                    utils::parse_possibly_prefixed_tag_version(package_name, tag_name)
+                    scheme
+                        .parse_tag_name(package_name, tag_name)
                         .expect("here we always have a valid version as it passed a filter when creating it"),
                 )
             },
@@ -160,7 +183,7 @@ impl Section {
             name: version,
             date,
             heading_level: changelog::DEFAULT_HEADING_LEVEL,
-            version_prefix: Self::DEFAULT_PREFIX.to_owned(),
+            version_prefix: scheme.headline_prefix().to_owned(),
             segments,
             removed_messages: Default::default(),
             unknown: Default::default(),
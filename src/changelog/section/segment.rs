@@ -16,6 +16,8 @@ pub mod conventional {
             id: gix::ObjectId,
             title: String,
             body: Option<String>,
+            /// The name of the commit author, if attribution is desired.
+            author: Option<String>,
         },
     }
 
@@ -56,6 +58,8 @@ pub struct Conventional {
 
 impl Conventional {
     pub const REMOVED_HTML_PREFIX: &'static str = "<csr-id-";
+    /// Separates the title of a generated message from the name of its author.
+    pub const AUTHOR_HTML_TAG: &'static str = "<csr-author/>";
     pub const BREAKING_TITLE: &'static str = "BREAKING";
     pub const BREAKING_TITLE_ENCLOSED: &'static str = "(BREAKING)";
 }
@@ -66,6 +70,7 @@ pub mod details {
     #[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Clone)]
     pub enum Category {
         Issue(String),
+        PullRequest(String),
         Uncategorized,
     }
 
@@ -73,7 +78,7 @@ pub mod details {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             match self {
                 Category::Uncategorized => f.write_str("Uncategorized"),
-                Category::Issue(issue) => write!(f, "#{issue}"),
+                Category::Issue(issue) | Category::PullRequest(issue) => write!(f, "#{issue}"),
             }
         }
     }
@@ -117,6 +122,8 @@ pub struct CommitStatistics {
     pub unique_issues: Vec<details::Category>,
     /// The duration, in days, from the release before this one, if this isn't the first release.
     pub time_passed_since_last_release: Option<i32>,
+    /// The amount of distinct mailmap-resolved authors of the commits, if they were counted.
+    pub authors: Option<usize>,
 }
 
 impl CommitStatistics {
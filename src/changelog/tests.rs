@@ -20,3 +20,74 @@ mod repository_url {
         }
     }
 }
+
+mod manually_documented_change {
+    use crate::{changelog::locale::Locale, version::Change, ChangeLog};
+
+    fn unreleased_change(markdown: &str) -> Option<Change> {
+        unreleased_change_in(markdown, &Default::default())
+    }
+
+    fn unreleased_change_in(markdown: &str, locale: &Locale) -> Option<Change> {
+        ChangeLog::from_markdown(markdown, locale).sections[0].manually_documented_change(locale)
+    }
+
+    #[test]
+    fn hand_written_entries_in_conventional_segments() {
+        assert_eq!(
+            unreleased_change("## Unreleased\n\n### New Features (BREAKING)\n\n - remove `foo()`\n"),
+            Some(Change::Breaking)
+        );
+        assert_eq!(
+            unreleased_change("## Unreleased\n\n### New Features\n\n - add `foo()`\n"),
+            Some(Change::Feature)
+        );
+        assert_eq!(
+            unreleased_change("## Unreleased\n\n### Bug Fixes\n\n - fix `foo()`\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn user_markdown_with_breaking_heading() {
+        assert_eq!(
+            unreleased_change("## Unreleased\n\n### Breaking Changes\n\n - `foo()` was removed\n"),
+            Some(Change::Breaking)
+        );
+        assert_eq!(unreleased_change("## Unreleased\n\nNothing breaking here.\n"), None);
+    }
+
+    #[test]
+    fn only_list_items_directly_below_the_breaking_heading_count() {
+        assert_eq!(
+            unreleased_change(
+                "## Unreleased\n\n### Breaking Changes\n\nNone this time.\n\n### Migration Guide\n\n - run `migrate`\n"
+            ),
+            None,
+            "items below the following heading belong to it"
+        );
+        assert_eq!(
+            unreleased_change("## Unreleased\n\n### Non-breaking changes\n\n - `foo()` is faster\n"),
+            None,
+            "headings merely mentioning breaking changes don't match"
+        );
+        assert_eq!(
+            unreleased_change(
+                "## Unreleased\n\n### Notes\n\n - a note\n\n### Breaking Changes\n\n1. `foo()` was removed\n"
+            ),
+            Some(Change::Breaking)
+        );
+    }
+
+    #[test]
+    fn breaking_headings_of_the_locale() {
+        let german = Locale::from_name("de").unwrap();
+        let markdown = "## Unreleased\n\n### Inkompatible Änderungen\n\n - `foo()` wurde entfernt\n";
+        assert_eq!(unreleased_change_in(markdown, &german), Some(Change::Breaking));
+
+        let custom = Locale::from_toml(r#"breaking = "Incompatible""#).unwrap();
+        let markdown = "## Unreleased\n\n### Incompatible\n\n - `foo()` was removed\n";
+        assert_eq!(unreleased_change_in(markdown, &custom), Some(Change::Breaking));
+        assert_eq!(unreleased_change(markdown), None, "only known headings match");
+    }
+}
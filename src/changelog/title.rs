@@ -0,0 +1,187 @@
+//! Normalization of generated commit titles, to make changelogs look uniform even if the style of commit messages varies.
+use std::borrow::Cow;
+
+/// Options to control how generated titles are normalized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Normalization {
+    /// If set, the first letter of the title will be upper-case.
+    pub capitalize: bool,
+    /// If set, a single trailing period is removed, while ellipsis are kept.
+    pub strip_trailing_period: bool,
+    /// If set, a leading verb in past tense, third person or gerund form is turned into its imperative form,
+    /// like `Added` or `Adds` into `Add`.
+    ///
+    /// This only works for common verbs which are known to us, anything else is kept as is.
+    pub imperative_mood: bool,
+}
+
+impl Normalization {
+    /// Return `title` normalized according to our configuration.
+    pub fn apply<'a>(&self, title: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+        let mut title = title.into();
+        if self.imperative_mood {
+            if let Some(imperative) = imperative_mood(&title) {
+                title = imperative.into();
+            }
+        }
+        if self.strip_trailing_period && title.ends_with('.') && !title.ends_with("..") {
+            let len = title.trim_end_matches('.').trim_end().len();
+            match &mut title {
+                Cow::Borrowed(t) => *t = &t[..len],
+                Cow::Owned(t) => t.truncate(len),
+            }
+        }
+        if self.capitalize {
+            title = capitalize(title);
+        }
+        title
+    }
+}
+
+/// Return `title` with its first letter in upper-case.
+pub fn capitalize<'a>(title: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+    let mut title = title.into();
+    let mut chars = title.chars();
+    if let Some(first) = chars
+        .next()
+        .and_then(|c| (c.to_uppercase().next() != Some(c)).then_some(c))
+    {
+        *title.to_mut() = first.to_uppercase().chain(chars).collect();
+    }
+    title
+}
+
+/// Verbs commonly found at the beginning of commit titles, in their imperative form.
+const VERBS: &[&str] = &[
+    "add",
+    "adjust",
+    "allow",
+    "avoid",
+    "bump",
+    "change",
+    "clean",
+    "correct",
+    "create",
+    "delete",
+    "deprecate",
+    "disable",
+    "document",
+    "drop",
+    "enable",
+    "ensure",
+    "expose",
+    "extend",
+    "fix",
+    "handle",
+    "implement",
+    "improve",
+    "increase",
+    "introduce",
+    "make",
+    "merge",
+    "move",
+    "optimize",
+    "prevent",
+    "reduce",
+    "refactor",
+    "release",
+    "remove",
+    "rename",
+    "replace",
+    "revert",
+    "rewrite",
+    "show",
+    "simplify",
+    "support",
+    "switch",
+    "update",
+    "upgrade",
+    "use",
+    "write",
+];
+
+/// Forms that don't follow the rules implemented in [`inflections()`].
+const IRREGULAR: &[(&str, &str)] = &[
+    ("made", "make"),
+    ("rewrote", "rewrite"),
+    ("rewritten", "rewrite"),
+    ("wrote", "write"),
+    ("written", "write"),
+    ("shown", "show"),
+    ("dropped", "drop"),
+    ("dropping", "drop"),
+];
+
+/// Return the third person, past tense and gerund forms of the regular `verb`.
+fn inflections(verb: &str) -> [String; 3] {
+    if let Some(stem) = verb.strip_suffix('y') {
+        [format!("{stem}ies"), format!("{stem}ied"), format!("{verb}ing")]
+    } else if let Some(stem) = verb.strip_suffix('e') {
+        [format!("{verb}s"), format!("{verb}d"), format!("{stem}ing")]
+    } else if ["s", "x", "z", "ch", "sh"].iter().any(|suffix| verb.ends_with(suffix)) {
+        [format!("{verb}es"), format!("{verb}ed"), format!("{verb}ing")]
+    } else {
+        [format!("{verb}s"), format!("{verb}ed"), format!("{verb}ing")]
+    }
+}
+
+fn imperative_of(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    IRREGULAR
+        .iter()
+        .find_map(|(form, verb)| (*form == word).then_some(*verb))
+        .or_else(|| VERBS.iter().find(|verb| inflections(verb).contains(&word)).copied())
+}
+
+/// Return `title` with its first word in imperative mood, or `None` if it wasn't changed.
+fn imperative_mood(title: &str) -> Option<String> {
+    let end = title.find(|c: char| !c.is_alphabetic()).unwrap_or(title.len());
+    let (word, rest) = title.split_at(end);
+    let verb = imperative_of(word)?;
+    let verb = if word.starts_with(char::is_uppercase) {
+        capitalize(verb).into_owned()
+    } else {
+        verb.to_owned()
+    };
+    Some(format!("{verb}{rest}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Normalization;
+
+    #[test]
+    fn imperative_mood_of_known_verbs() {
+        let n = Normalization {
+            imperative_mood: true,
+            ..Default::default()
+        };
+        for (input, expected) in [
+            ("Added support for foo", "Add support for foo"),
+            ("fixes crash on startup", "fix crash on startup"),
+            ("Removing dead code", "Remove dead code"),
+            ("simplified parsing", "simplify parsing"),
+            ("Wrote docs", "Write docs"),
+            ("Dropped: old API", "Drop: old API"),
+            ("Add support for foo", "Add support for foo"),
+            ("Processes are faster", "Processes are faster"),
+            ("", ""),
+        ] {
+            assert_eq!(n.apply(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn trailing_periods_and_capitalization() {
+        let n = Normalization {
+            capitalize: true,
+            strip_trailing_period: true,
+            imperative_mood: false,
+        };
+        assert_eq!(n.apply("fix the thing."), "Fix the thing");
+        assert_eq!(n.apply("fix the thing ."), "Fix the thing");
+        assert_eq!(n.apply("to be continued..."), "To be continued...");
+        assert_eq!(n.apply("Already fine"), "Already fine");
+        assert_eq!(Normalization::default().apply("kept as is."), "kept as is.");
+    }
+}
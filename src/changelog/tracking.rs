@@ -0,0 +1,339 @@
+//! Keep the data needed to track generated changelog content in a sidecar file instead of `<csr-*>` tags.
+//!
+//! Without tags, generated messages can't be told apart from those written by users when parsing markdown.
+//! The sidecar remembers the ids of generated messages along with the markdown they were rendered to, so
+//! that unchanged messages can be recognized again. Messages that were edited or deleted by the user are
+//! considered removed, just like it's the case when removing the text of a tagged message.
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::Context;
+
+use crate::{
+    changelog::{section, section::segment, write, Section, Version},
+    ChangeLog,
+};
+
+/// The name of the file next to `CHANGELOG.md` which holds the tracking data.
+pub const FILE_NAME: &str = ".changelog-state.toml";
+
+/// Return the path to the sidecar file belonging to the changelog at `changelog_path`.
+pub fn path_for_changelog(changelog_path: impl AsRef<Path>) -> PathBuf {
+    changelog_path
+        .as_ref()
+        .parent()
+        .expect("changelogs are files in a directory")
+        .join(FILE_NAME)
+}
+
+/// The tracking data of all release sections of a changelog.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct State {
+    pub releases: Vec<Release>,
+}
+
+/// The tracking data of a single release section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// The version of the release without prefix, or `Unreleased`.
+    pub name: String,
+    /// Ids of removed messages which aren't associated with a segment.
+    pub removed: Vec<gix::ObjectId>,
+    /// Content we couldn't understand, which isn't written to the changelog without tags.
+    pub unknown: String,
+    pub segments: Vec<Conventional>,
+}
+
+/// The tracking data of a git-conventional segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conventional {
+    pub kind: String,
+    pub is_breaking: bool,
+    pub removed: Vec<gix::ObjectId>,
+    pub messages: Vec<Message>,
+}
+
+/// A generated message along with the markdown it was written as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub id: gix::ObjectId,
+    pub title: String,
+    pub body: Option<String>,
+    pub author: Option<String>,
+    pub markdown: String,
+}
+
+fn release_name(name: &Version) -> String {
+    match name {
+        Version::Unreleased => "Unreleased".into(),
+        Version::Semantic(v) => v.to_string(),
+    }
+}
+
+impl ChangeLog {
+    /// Extract all tracking information from this changelog, assuming it's written with `options`.
+    pub fn tracking_state(&self, options: &write::Options) -> State {
+        let releases = self
+            .sections
+            .iter()
+            .filter_map(|section| match section {
+                Section::Verbatim { .. } => None,
+                Section::Release {
+                    name,
+                    unknown,
+                    removed_messages,
+                    segments,
+                    ..
+                } => Some(Release {
+                    name: release_name(name),
+                    removed: removed_messages.clone(),
+                    unknown: unknown.clone(),
+                    segments: segments
+                        .iter()
+                        .filter_map(|segment| match segment {
+                            section::Segment::Conventional(conventional) => Some(Conventional {
+                                kind: conventional.kind.to_owned(),
+                                is_breaking: conventional.is_breaking,
+                                removed: conventional.removed.clone(),
+                                messages: conventional
+                                    .messages
+                                    .iter()
+                                    .filter_map(|message| match message {
+                                        segment::conventional::Message::User { .. } => None,
+                                        segment::conventional::Message::Generated {
+                                            id,
+                                            title,
+                                            body,
+                                            author,
+                                        } => {
+                                            let mut markdown = String::new();
+                                            write::write_generated_message(message, false, options, &mut markdown)
+                                                .expect("writing to a string never fails");
+                                            Some(Message {
+                                                id: *id,
+                                                title: title.clone(),
+                                                body: body.clone(),
+                                                author: author.clone(),
+                                                markdown: markdown.trim().to_owned(),
+                                            })
+                                        }
+                                    })
+                                    .collect(),
+                            }),
+                            _ => None,
+                        })
+                        .collect(),
+                }),
+            })
+            .collect();
+        State { releases }
+    }
+
+    /// Restore tracking information in this changelog, which was freshly parsed from markdown without tags.
+    pub fn apply_tracking_state(&mut self, state: &State) {
+        for section in &mut self.sections {
+            let Section::Release {
+                name,
+                unknown,
+                removed_messages,
+                segments,
+                ..
+            } = section
+            else {
+                continue;
+            };
+            let name = release_name(name);
+            let Some(release) = state.releases.iter().find(|r| r.name == name) else {
+                continue;
+            };
+            extend_unique(removed_messages, &release.removed);
+            if unknown.is_empty() {
+                unknown.clone_from(&release.unknown);
+            }
+            for tracked in &release.segments {
+                let conventional = segments.iter_mut().find_map(|segment| match segment {
+                    section::Segment::Conventional(c)
+                        if c.kind == tracked.kind && c.is_breaking == tracked.is_breaking =>
+                    {
+                        Some(c)
+                    }
+                    _ => None,
+                });
+                let Some(conventional) = conventional else {
+                    // The whole segment was removed by the user, so none of its messages should come back.
+                    extend_unique(removed_messages, &tracked.removed);
+                    extend_unique(removed_messages, tracked.messages.iter().map(|m| &m.id));
+                    continue;
+                };
+                extend_unique(&mut conventional.removed, &tracked.removed);
+                for tracked_message in &tracked.messages {
+                    let user_message = conventional.messages.iter_mut().find(|m| {
+                        matches!(m, segment::conventional::Message::User { markdown } if markdown.trim() == tracked_message.markdown)
+                    });
+                    match user_message {
+                        Some(message) => {
+                            *message = segment::conventional::Message::Generated {
+                                id: tracked_message.id,
+                                title: tracked_message.title.clone(),
+                                body: tracked_message.body.clone(),
+                                author: tracked_message.author.clone(),
+                            }
+                        }
+                        None => extend_unique(&mut conventional.removed, Some(&tracked_message.id)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn extend_unique<'a>(ids: &mut Vec<gix::ObjectId>, new: impl IntoIterator<Item = &'a gix::ObjectId>) {
+    for id in new {
+        if !ids.contains(id) {
+            ids.push(*id);
+        }
+    }
+}
+
+impl State {
+    /// Read the state from the sidecar file at `path`, or return `None` if it doesn't exist.
+    pub fn from_path(path: &Path) -> anyhow::Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content.parse().with_context(|| {
+                format!("Could not parse changelog tracking state at '{}'", path.display())
+            })?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Serialize this instance into a TOML document.
+    pub fn to_toml(&self) -> String {
+        use toml_edit::{value, Array, ArrayOfTables, DocumentMut, Item, Table};
+        fn ids(ids: &[gix::ObjectId]) -> Item {
+            value(ids.iter().map(ToString::to_string).collect::<Array>())
+        }
+        let mut doc = DocumentMut::new();
+        let mut releases = ArrayOfTables::new();
+        for release in &self.releases {
+            let mut table = Table::new();
+            table["name"] = value(release.name.as_str());
+            if !release.removed.is_empty() {
+                table["removed"] = ids(&release.removed);
+            }
+            if !release.unknown.is_empty() {
+                table["unknown"] = value(release.unknown.as_str());
+            }
+            let mut segments = ArrayOfTables::new();
+            for segment in &release.segments {
+                let mut segment_table = Table::new();
+                segment_table["kind"] = value(segment.kind.as_str());
+                segment_table["breaking"] = value(segment.is_breaking);
+                if !segment.removed.is_empty() {
+                    segment_table["removed"] = ids(&segment.removed);
+                }
+                let mut messages = ArrayOfTables::new();
+                for message in &segment.messages {
+                    let mut message_table = Table::new();
+                    message_table["id"] = value(message.id.to_string());
+                    message_table["title"] = value(message.title.as_str());
+                    if let Some(body) = &message.body {
+                        message_table["body"] = value(body.as_str());
+                    }
+                    if let Some(author) = &message.author {
+                        message_table["author"] = value(author.as_str());
+                    }
+                    message_table["markdown"] = value(message.markdown.as_str());
+                    messages.push(message_table);
+                }
+                if !messages.is_empty() {
+                    segment_table["message"] = Item::ArrayOfTables(messages);
+                }
+                segments.push(segment_table);
+            }
+            if !segments.is_empty() {
+                table["segment"] = Item::ArrayOfTables(segments);
+            }
+            releases.push(table);
+        }
+        doc["release"] = Item::ArrayOfTables(releases);
+        doc.to_string()
+    }
+}
+
+impl FromStr for State {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use anyhow::bail;
+        fn string(table: &toml_edit::Table, key: &str) -> anyhow::Result<Option<String>> {
+            table
+                .get(key)
+                .map(|v| match v.as_str() {
+                    Some(v) => Ok(v.to_owned()),
+                    None => bail!("'{key}' must be a string"),
+                })
+                .transpose()
+        }
+        fn ids(table: &toml_edit::Table, key: &str) -> anyhow::Result<Vec<gix::ObjectId>> {
+            let Some(array) = table.get(key) else {
+                return Ok(Vec::new());
+            };
+            let Some(array) = array.as_array() else {
+                bail!("'{key}' must be an array of object ids")
+            };
+            array
+                .iter()
+                .map(|v| match v.as_str() {
+                    Some(hex) => Ok(gix::ObjectId::from_hex(hex.as_bytes())?),
+                    None => bail!("'{key}' must be an array of object ids"),
+                })
+                .collect()
+        }
+        fn tables<'a>(
+            table: &'a toml_edit::Table,
+            key: &str,
+        ) -> anyhow::Result<Box<dyn Iterator<Item = &'a toml_edit::Table> + 'a>> {
+            Ok(match table.get(key) {
+                None => Box::new(std::iter::empty()),
+                Some(item) => match item.as_array_of_tables() {
+                    Some(tables) => Box::new(tables.iter()),
+                    None => bail!("'{key}' must be an array of tables"),
+                },
+            })
+        }
+
+        let doc = toml_edit::DocumentMut::from_str(s)?;
+        let mut releases = Vec::new();
+        for release in tables(doc.as_table(), "release")? {
+            let mut segments = Vec::new();
+            for segment in tables(release, "segment")? {
+                let mut messages = Vec::new();
+                for message in tables(segment, "message")? {
+                    messages.push(Message {
+                        id: gix::ObjectId::from_hex(string(message, "id")?.context("'id' is required")?.as_bytes())?,
+                        title: string(message, "title")?.unwrap_or_default(),
+                        body: string(message, "body")?,
+                        author: string(message, "author")?,
+                        markdown: string(message, "markdown")?.unwrap_or_default(),
+                    });
+                }
+                segments.push(Conventional {
+                    kind: string(segment, "kind")?.context("'kind' is required")?,
+                    is_breaking: segment.get("breaking").and_then(|v| v.as_bool()).unwrap_or(false),
+                    removed: ids(segment, "removed")?,
+                    messages,
+                });
+            }
+            releases.push(Release {
+                name: string(release, "name")?.context("'name' is required")?,
+                removed: ids(release, "removed")?,
+                unknown: string(release, "unknown")?.unwrap_or_default(),
+                segments,
+            });
+        }
+        Ok(State { releases })
+    }
+}
@@ -1,13 +1,12 @@
-use std::borrow::Cow;
-
 use gix::{bstr::ByteSlice, url::Scheme, Url};
 
 use crate::{
     changelog,
     changelog::{
+        locale::Locale,
         section,
         section::{segment, segment::details::Category, Segment},
-        Section,
+        title, Section,
     },
     ChangeLog,
 };
@@ -92,10 +91,47 @@ bitflags::bitflags! {
     }
 }
 
+/// Options to control the presentation of generated content, independently of which `Components` are written.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// If set, the first letter of generated commit titles will be upper-case.
+    pub capitalize_commit: bool,
+    /// The headings to use for generated content.
+    pub locale: Locale,
+    /// If set, an HTML anchor named after the version is written before the heading of each release,
+    /// allowing stable links to specific versions.
+    pub anchors: bool,
+    /// If set, the commit details segment lists commit titles only, without their hashes.
+    pub omit_commit_hashes: bool,
+    /// If set, a single trailing period is removed from generated commit titles.
+    pub strip_trailing_period: bool,
+    /// If set, generated commit titles starting with a known verb in past tense or third person are turned into
+    /// imperative mood.
+    pub imperative_mood: bool,
+}
+
+impl Options {
+    /// The normalization to apply to titles of generated conventional messages.
+    pub fn title_normalization(&self) -> title::Normalization {
+        title::Normalization {
+            capitalize: self.capitalize_commit,
+            strip_trailing_period: self.strip_trailing_period,
+            imperative_mood: self.imperative_mood,
+        }
+    }
+}
+
+/// Return `true` if `markdown` contains anchors as written with [`Options::anchors`].
+pub fn has_anchors(markdown: &str) -> bool {
+    markdown.lines().any(|line| Section::parse_anchor(line).is_some())
+}
+
 impl Section {
     pub const UNKNOWN_TAG_START: &'static str = "<csr-unknown>";
     pub const UNKNOWN_TAG_END: &'static str = "<csr-unknown/>";
     pub const READONLY_TAG: &'static str = "<csr-read-only-do-not-edit/>\n"; // needs a newline to not interfere with formatting
+    pub const ANCHOR_START: &'static str = "<a id=\"";
+    pub const ANCHOR_END: &'static str = "\"></a>";
     #[cfg(windows)]
     pub const NL: &'static str = "\r\n";
     #[cfg(not(windows))]
@@ -108,7 +144,7 @@ impl Section {
         mut out: impl std::fmt::Write,
         link_mode: &Linkables,
         components: Components,
-        capitalize_commit: bool,
+        options: &Options,
     ) -> std::fmt::Result {
         match self {
             Section::Verbatim { text, .. } => {
@@ -125,6 +161,15 @@ impl Section {
                 unknown,
             } => {
                 if components.contains(Components::SECTION_TITLE) {
+                    if options.anchors && matches!(name, changelog::Version::Semantic(_)) {
+                        writeln!(
+                            out,
+                            "{}{}{}",
+                            Section::ANCHOR_START,
+                            PrefixedVersion { version_prefix, name },
+                            Section::ANCHOR_END
+                        )?;
+                    }
                     write!(
                         out,
                         "{} {}",
@@ -151,7 +196,7 @@ impl Section {
 
                 let section_level = *heading_level + 1;
                 for segment in segments {
-                    segment.write_to(section_level, link_mode, components, capitalize_commit, &mut out)?;
+                    segment.write_to(section_level, link_mode, components, options, &mut out)?;
                 }
                 if !unknown.is_empty() && components.contains(Components::HTML_TAGS) {
                     writeln!(out, "{}", Section::UNKNOWN_TAG_START)?;
@@ -183,10 +228,10 @@ impl ChangeLog {
         mut out: impl std::fmt::Write,
         link_mode: &Linkables,
         components: Components,
-        capitalize_commit: bool,
+        options: &Options,
     ) -> std::fmt::Result {
         for section in &self.sections {
-            section.write_to(&mut out, link_mode, components, capitalize_commit)?;
+            section.write_to(&mut out, link_mode, components, options)?;
         }
         Ok(())
     }
@@ -198,7 +243,7 @@ impl section::Segment {
         section_level: usize,
         link_mode: &Linkables,
         components: Components,
-        capitalize_commit: bool,
+        options: &Options,
         mut out: impl std::fmt::Write,
     ) -> std::fmt::Result {
         let write_html = components.contains(Components::HTML_TAGS);
@@ -212,7 +257,11 @@ impl section::Segment {
                 is_breaking,
                 removed,
                 messages,
-            }) => match segment::conventional::as_headline(kind).or_else(|| is_breaking.then(|| *kind)) {
+            }) => match options
+                .locale
+                .conventional_headline(kind)
+                .or_else(|| is_breaking.then(|| *kind))
+            {
                 Some(headline) => {
                     writeln!(
                         out,
@@ -236,31 +285,8 @@ impl section::Segment {
                     use segment::conventional::Message;
                     for message in messages {
                         match message {
-                            Message::Generated { title, id, body } => {
-                                let title = if capitalize_commit {
-                                    capitalize_message_title(title)
-                                } else {
-                                    Cow::Borrowed(title.as_str())
-                                };
-                                if write_html {
-                                    writeln!(
-                                        out,
-                                        " - {}{}/> {}",
-                                        segment::Conventional::REMOVED_HTML_PREFIX,
-                                        id,
-                                        title
-                                    )?;
-                                } else {
-                                    writeln!(out, " - {title}")?;
-                                }
-                                if let Some(body) = body {
-                                    for line in body.as_bytes().as_bstr().lines_with_terminator() {
-                                        write!(out, "   {}", line.to_str().expect("cannot fail as original is UTF-8"))?;
-                                    }
-                                    if !body.ends_with('\n') {
-                                        writeln!(out)?;
-                                    }
-                                }
+                            Message::Generated { .. } => {
+                                write_generated_message(message, write_html, options, &mut out)?;
                             }
                             Message::User { markdown } => {
                                 out.write_str(markdown)?;
@@ -272,7 +298,7 @@ impl section::Segment {
                     }
                     writeln!(out)?;
                 }
-                None => log::trace!(
+                None => tracing::trace!(
                     "Skipping unknown git-conventional kind {:?} and all {} message(s) in it.",
                     kind,
                     messages.len()
@@ -282,20 +308,28 @@ impl section::Segment {
                 if !commits_by_category.is_empty() =>
             {
                 let write_details_tags = components.contains(Components::DETAIL_TAGS);
-                writeln!(out, "{} {}\n", heading(section_level), segment::Details::TITLE)?;
+                writeln!(out, "{} {}\n", heading(section_level), options.locale.details_title())?;
                 if write_details_tags {
                     writeln!(out, "{}", Section::READONLY_TAG)?;
                     writeln!(out, "{}\n", segment::Details::HTML_PREFIX)?;
                 }
+                let normalization = title::Normalization {
+                    capitalize: true,
+                    ..options.title_normalization()
+                };
                 for (category, messages) in commits_by_category.iter() {
                     writeln!(out, " * **{}**", format_category(category, link_mode))?;
                     for message in messages {
-                        writeln!(
-                            out,
-                            "    - {} ({})",
-                            capitalize_message_title(&message.title),
-                            format_oid(&message.id, link_mode)
-                        )?;
+                        if options.omit_commit_hashes {
+                            writeln!(out, "    - {}", normalization.apply(&message.title))?;
+                        } else {
+                            writeln!(
+                                out,
+                                "    - {} ({})",
+                                normalization.apply(&message.title),
+                                format_oid(&message.id, link_mode)
+                            )?;
+                        }
                     }
                 }
                 if write_details_tags {
@@ -309,8 +343,14 @@ impl section::Segment {
                 conventional_count,
                 unique_issues,
                 time_passed_since_last_release,
+                authors,
             })) => {
-                writeln!(out, "{} {}\n", heading(section_level), segment::CommitStatistics::TITLE)?;
+                writeln!(
+                    out,
+                    "{} {}\n",
+                    heading(section_level),
+                    options.locale.statistics_title()
+                )?;
                 if write_html {
                     writeln!(out, "{}", Section::READONLY_TAG)?;
                 }
@@ -328,6 +368,14 @@ impl section::Segment {
                         _ => ".".into(),
                     }
                 )?;
+                if let Some(authors) = authors {
+                    writeln!(
+                        out,
+                        " - {} {} contributed to the release.",
+                        authors,
+                        if *authors == 1 { "author" } else { "authors" }
+                    )?;
+                }
                 if let Some(days_between_releases) = time_passed_since_last_release.filter(|d| *d > 0) {
                     writeln!(
                         out,
@@ -362,7 +410,7 @@ impl section::Segment {
                 writeln!(out)?;
             }
             Segment::Clippy(section::Data::Generated(segment::ThanksClippy { count })) if *count > 0 => {
-                writeln!(out, "{} {}\n", heading(section_level), segment::ThanksClippy::TITLE)?;
+                writeln!(out, "{} {}\n", heading(section_level), options.locale.clippy_title())?;
                 if write_html {
                     writeln!(out, "{}", Section::READONLY_TAG)?;
                 }
@@ -381,6 +429,52 @@ impl section::Segment {
     }
 }
 
+/// Write the generated conventional `message`, doing nothing if it is a user message.
+pub(crate) fn write_generated_message(
+    message: &segment::conventional::Message,
+    write_html: bool,
+    options: &Options,
+    mut out: impl std::fmt::Write,
+) -> std::fmt::Result {
+    let segment::conventional::Message::Generated {
+        title,
+        id,
+        body,
+        author,
+    } = message
+    else {
+        return Ok(());
+    };
+    let title = options.title_normalization().apply(title.as_str());
+    if write_html {
+        write!(
+            out,
+            " - {}{}/> {}",
+            segment::Conventional::REMOVED_HTML_PREFIX,
+            id,
+            title
+        )?;
+        if let Some(author) = author {
+            write!(out, " {}(by {author})", segment::Conventional::AUTHOR_HTML_TAG)?;
+        }
+        writeln!(out)?;
+    } else {
+        match author {
+            Some(author) => writeln!(out, " - {title} (by {author})")?,
+            None => writeln!(out, " - {title}")?,
+        }
+    }
+    if let Some(body) = body {
+        for line in body.as_bytes().as_bstr().lines_with_terminator() {
+            write!(out, "   {}", line.to_str().expect("cannot fail as original is UTF-8"))?;
+        }
+        if !body.ends_with('\n') {
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
 fn format_category(cat: &Category, link_mode: &Linkables) -> String {
     match (cat, link_mode) {
         (Category::Issue(id), Linkables::AsLinks { repository_url }) => match repository_url.github_https() {
@@ -389,6 +483,12 @@ fn format_category(cat: &Category, link_mode: &Linkables) -> String {
             }
             None => format_category(cat, &Linkables::AsText),
         },
+        (Category::PullRequest(id), Linkables::AsLinks { repository_url }) => match repository_url.github_https() {
+            Some(base_url) => {
+                format!("[#{id}]({base_url}/pull/{id})")
+            }
+            None => format_category(cat, &Linkables::AsText),
+        },
         (_, _) => cat.to_string(),
     }
 }
@@ -404,15 +504,3 @@ fn format_oid(id: &gix::oid, link_mode: &Linkables) -> String {
         },
     }
 }
-
-fn capitalize_message_title<'a>(title: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
-    let mut title = title.into();
-    let mut chars = title.chars();
-    if let Some(first) = chars
-        .next()
-        .and_then(|c| (c.to_uppercase().next() != Some(c)).then_some(c))
-    {
-        *title.to_mut() = first.to_uppercase().chain(chars).collect();
-    }
-    title
-}
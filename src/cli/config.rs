@@ -0,0 +1,188 @@
+//! Defaults for the flags of `cargo smart-release` from `[workspace.metadata.smart-release]`, and for those of
+//! `cargo smart-release` and `cargo changelog` from the user configuration file, so they don't have to be passed on each
+//! invocation.
+use std::{ffi::OsString, path::PathBuf};
+
+use anyhow::{bail, Context};
+use clap::{parser::ValueSource, ArgAction, ArgMatches};
+
+use crate::options::Args;
+
+/// Flags which must be passed each time, as configuring them would be surprising.
+const NOT_CONFIGURABLE: &[&str] = &["execute", "resume", "help", "version"];
+
+/// Keys configuring a flag under another name as the name of the flag would be ambiguous, along with that flag, which is
+/// set if the key is `false`.
+const INVERTED: &[(&str, &str)] = &[("git-hooks", "no-verify")];
+
+/// Return `args` with flags added for each key named like a flag of the subcommand, unless that flag was given on the
+/// command-line or in its environment variable already.
+///
+/// Keys of `[workspace.metadata.smart-release]` take precedence over those of the table named like the subcommand in
+/// the [user configuration file](user_config_path()).
+/// Keys which aren't named like a flag are left to the release itself to interpret.
+/// If the arguments don't parse or the workspace can't be read, `args` are returned unchanged to fail later.
+pub fn with_defaults(args: Vec<OsString>) -> anyhow::Result<Vec<OsString>> {
+    let command = Args::command_with_env();
+    let Ok(matches) = command.clone().try_get_matches_from(&args) else {
+        return Ok(args);
+    };
+    let Some((name @ ("smart-release" | "changelog"), matches)) = matches.subcommand() else {
+        return Ok(args);
+    };
+    let subcommand = command.find_subcommand(name).expect("defined in options");
+
+    let workspace_config = (name == "smart-release")
+        .then(|| cargo_metadata::MetadataCommand::new().no_deps().exec().ok())
+        .flatten()
+        .and_then(|meta| {
+            meta.workspace_metadata
+                .get("smart-release")
+                .and_then(serde_json::Value::as_object)
+                .cloned()
+        })
+        .unwrap_or_default();
+    let mut defaults = default_args(&workspace_config, subcommand, matches)
+        .context("In [workspace.metadata.smart-release]")?;
+    if let Some(path) = user_config_path() {
+        let mut user_config = user_config(&path, name)?;
+        user_config.retain(|key, _| !workspace_config.contains_key(key));
+        defaults.extend(
+            default_args(&user_config, subcommand, matches)
+                .with_context(|| format!("In the user configuration at '{}'", path.display()))?,
+        );
+    }
+    if defaults.is_empty() {
+        return Ok(args);
+    }
+    let pos = args
+        .iter()
+        .skip(1)
+        .position(|arg| arg == name)
+        .map_or(1, |pos| pos + 2);
+    let mut args = args;
+    args.splice(pos..pos, defaults.into_iter().map(OsString::from));
+    Ok(args)
+}
+
+/// Return the path to the user configuration file, `$XDG_CONFIG_HOME/smart-release/config.toml` or
+/// `~/.config/smart-release/config.toml`.
+fn user_config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home::home_dir().map(|home| home.join(".config")))?;
+    Some(config_dir.join("smart-release").join("config.toml"))
+}
+
+/// Return the keys of the table named `subcommand` in the user configuration file at `path`, or nothing if it doesn't
+/// exist.
+fn user_config(path: &std::path::Path, subcommand: &str) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Default::default()),
+        Err(err) => return Err(err).with_context(|| format!("Could not read the user configuration at '{}'", path.display())),
+    };
+    parse_user_config(&content, subcommand)
+        .with_context(|| format!("Could not parse the user configuration at '{}'", path.display()))
+}
+
+fn parse_user_config(content: &str, subcommand: &str) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    let doc: toml_edit::DocumentMut = content.parse()?;
+    let Some(table) = doc.get(subcommand) else {
+        return Ok(Default::default());
+    };
+    let table = table
+        .as_table_like()
+        .with_context(|| format!("'{subcommand}' must be a table"))?;
+    table
+        .iter()
+        .map(|(key, item)| {
+            let value = item
+                .as_value()
+                .and_then(to_json)
+                .with_context(|| format!("'{key}' must be true or false, a string, a number or a list of them"))?;
+            Ok((key.to_owned(), value))
+        })
+        .collect()
+}
+
+fn to_json(value: &toml_edit::Value) -> Option<serde_json::Value> {
+    Some(match value {
+        toml_edit::Value::Boolean(value) => (*value.value()).into(),
+        toml_edit::Value::Integer(value) => (*value.value()).into(),
+        toml_edit::Value::String(value) => value.value().as_str().into(),
+        toml_edit::Value::Array(values) => values.iter().map(to_json).collect::<Option<Vec<_>>>()?.into(),
+        _ => return None,
+    })
+}
+
+/// Return the flags to add to the arguments of `command` for each key in `config` named like one of its flags that
+/// isn't given in `matches`, on the command-line or in its environment variable.
+///
+/// Flags that can be repeated to count, like `verbose`, may also be set to the number of times to repeat them.
+fn default_args(
+    config: &serde_json::Map<String, serde_json::Value>,
+    command: &clap::Command,
+    matches: &ArgMatches,
+) -> anyhow::Result<Vec<String>> {
+    let mut args = Vec::new();
+    for (key, value) in config {
+        if let Some((name, _)) = INVERTED.iter().find(|(_, flag)| flag == key) {
+            if command.get_arguments().any(|arg| arg.get_long() == Some(key.as_str())) {
+                bail!("'{key}' can't be configured as it would be mistaken for the setting of packages with the same name, set '{name} = false' instead");
+            }
+        }
+        let (key, value) = match INVERTED.iter().find(|(name, _)| name == key) {
+            Some((name, flag)) => match value {
+                serde_json::Value::Bool(enabled) => (flag.to_string(), serde_json::Value::Bool(!enabled)),
+                _ => bail!("'{name}' must be true or false"),
+            },
+            None => (key.clone(), value.clone()),
+        };
+        let Some(arg) = command.get_arguments().find(|arg| arg.get_long() == Some(key.as_str())) else {
+            continue;
+        };
+        if NOT_CONFIGURABLE.contains(&key.as_str()) {
+            bail!("'{key}' can't be configured, pass --{key} instead");
+        }
+        if matches!(
+            matches.value_source(arg.get_id().as_str()),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        ) {
+            continue;
+        }
+        let takes_value = arg.get_action().takes_values();
+        let value_is_optional = arg.get_num_args().is_some_and(|num| num.min_values() == 0);
+        match &value {
+            serde_json::Value::Bool(enabled) if !takes_value || value_is_optional => {
+                if *enabled {
+                    args.push(format!("--{key}"));
+                }
+            }
+            serde_json::Value::Number(times) if matches!(arg.get_action(), ArgAction::Count) => {
+                let times = times
+                    .as_u64()
+                    .with_context(|| format!("'{key}' must be true, false or how often to repeat it"))?;
+                args.extend((0..times).map(|_| format!("--{key}")));
+            }
+            serde_json::Value::String(value) if takes_value => args.push(format!("--{key}={value}")),
+            serde_json::Value::Number(value) if takes_value => args.push(format!("--{key}={value}")),
+            serde_json::Value::Array(values) if takes_value => {
+                for value in values {
+                    match value {
+                        serde_json::Value::String(value) => args.push(format!("--{key}={value}")),
+                        serde_json::Value::Number(value) => args.push(format!("--{key}={value}")),
+                        _ => bail!("'{key}' must be a list of strings"),
+                    }
+                }
+            }
+            _ if takes_value => {
+                bail!("'{key}' must be a string, a number or a list of them")
+            }
+            _ => bail!("'{key}' must be true or false"),
+        }
+    }
+    Ok(args)
+}
+
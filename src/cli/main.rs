@@ -1,50 +1,296 @@
+mod config;
 mod options;
-use clap::Parser;
-use options::{Args, SubCommands};
+use std::{collections::BTreeMap, ffi::OsString, io::IsTerminal, rc::Rc};
 
-use cargo_smart_release::command;
+use clap::FromArgMatches;
+use options::{Args, ChangelogSubCommands, SmartReleaseSubCommands, SubCommands};
+
+use cargo_smart_release::{
+    color, command,
+    version::{ReleaseChannel, SemanticVersioning, VersionScheme},
+};
 
 fn main() -> anyhow::Result<()> {
     unsafe {
         // SAFETY: We do nothing that could block.
         gix::interrupt::init_handler(2, || {})?;
     }
-    let args: Args = Args::parse();
-    match args.subcommands {
+    let raw_args = config::with_defaults(std::env::args_os().collect())?;
+    let matches = Args::command_with_env().get_matches_from(&raw_args);
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    reject_subcommand_named_like_crate(&matches, &raw_args)?;
+    cargo_smart_release::color::set(to_color_mode(&args.color)?);
+    if matches!(
+        args.subcommands,
+        SubCommands::SmartRelease { stats: true, .. } | SubCommands::Changelog { stats: true, .. }
+    ) {
+        cargo_smart_release::stats::enable();
+    }
+    let res = run(args.subcommands);
+    if let Some(report) = cargo_smart_release::stats::report() {
+        eprint!("{report}");
+    }
+    res
+}
+
+/// Fail if the subcommand of `cargo smart-release` was given by a name which is also the name of a workspace crate, as
+/// it's ambiguous whether to run the subcommand or to release the crate.
+///
+/// `args` are needed to know which name of the subcommand was used, as the `matches` only know the canonical one.
+/// If the workspace can't be read, there is no collision to report and the subcommand fails later.
+fn reject_subcommand_named_like_crate(matches: &clap::ArgMatches, args: &[OsString]) -> anyhow::Result<()> {
+    let Some(("smart-release", matches)) = matches.subcommand() else {
+        return Ok(());
+    };
+    let Some(name) = matches.subcommand_name() else {
+        return Ok(());
+    };
+    let command = Args::command_with_env();
+    let subcommand = command
+        .find_subcommand("smart-release")
+        .and_then(|command| command.find_subcommand(name))
+        .expect("defined in options");
+    let names: Vec<_> = std::iter::once(subcommand.get_name())
+        .chain(subcommand.get_all_aliases())
+        .collect();
+    let Some(given) = args
+        .iter()
+        .filter_map(|arg| arg.to_str())
+        .find(|arg| names.contains(arg))
+    else {
+        return Ok(());
+    };
+    let Ok(meta) = cargo_metadata::MetadataCommand::new().no_deps().exec() else {
+        return Ok(());
+    };
+    if meta
+        .workspace_packages()
+        .iter()
+        .any(|package| package.name.as_str() == given)
+    {
+        anyhow::bail!(
+            "'{given}' is the name of a subcommand and of a workspace crate. Use 'cargo smart-release -- {given}' to release the crate"
+        );
+    }
+    Ok(())
+}
+
+fn run(subcommands: SubCommands) -> anyhow::Result<()> {
+    match subcommands {
+        SubCommands::Changelog {
+            subcommand:
+                Some(ChangelogSubCommands::Show {
+                    crate_name,
+                    version,
+                    no_links,
+                    components,
+                }),
+            locale,
+            verbose,
+            quiet,
+            ..
+        } => {
+            init_logging(verbose, quiet, LogFormat::Text, false)?;
+            command::show(
+                command::show::Options {
+                    no_links,
+                    components: names_to_components(&components)?,
+                    locale: to_locale(locale.as_deref())?,
+                },
+                crate_name,
+                version,
+            )?
+        }
         SubCommands::Changelog {
             write,
             execute,
+            check,
             crates,
             no_dependencies,
             no_preview,
+            progress,
             no_links,
             without,
             allow_dirty,
             capitalize_commit,
+            strip_trailing_period,
+            imperative_mood,
+            locale,
+            pull_request_titles,
+            references_are_pull_requests,
+            authors,
+            write_commit_graph,
+            sidecar,
+            anchors,
+            no_commit_hashes,
+            scope,
+            preview_command,
+            channel,
+            verbose,
+            quiet,
+            stats: _,
+            subcommand: subcommand @ (None | Some(ChangelogSubCommands::Preview { .. })),
         } => {
-            init_logging(false);
+            let _progress = init_logging(verbose, quiet, LogFormat::Text, progress)?;
+            let preview_crate = match subcommand {
+                Some(ChangelogSubCommands::Preview { crate_name }) => Some(crate_name),
+                _ => None,
+            };
+            let render = preview_crate.is_some();
             command::changelog(
                 command::changelog::Options {
-                    dry_run: !(write || execute),
-                    allow_dirty,
+                    dry_run: render || !(write || execute),
+                    render,
+                    check: check && !render,
+                    allow_dirty: allow_dirty.as_ref().is_some_and(Vec::is_empty),
+                    allow_dirty_paths: allow_dirty.unwrap_or_default(),
                     no_links,
                     preview: !no_preview,
-                    dependencies: !no_dependencies,
+                    preview_command,
+                    dependencies: !(no_dependencies || render),
                     generator_segments: names_to_segment_selection(&without)?,
                     capitalize_commit,
+                    strip_trailing_period,
+                    imperative_mood,
+                    locale: to_locale(locale.as_deref())?,
+                    pull_request_titles,
+                    references_are_pull_requests,
+                    authors,
+                    write_commit_graph,
+                    sidecar,
+                    anchors,
+                    commit_hashes: !no_commit_hashes,
+                    scopes: scope,
+                    version_scheme: to_version_scheme(channel.as_deref())?,
+                },
+                match preview_crate {
+                    Some(crate_name) => crate_name.into_iter().collect(),
+                    None => crates,
+                },
+            )?
+        }
+        SubCommands::SmartRelease {
+            subcommand:
+                Some(SmartReleaseSubCommands::Yank {
+                    execute,
+                    crate_name,
+                    version,
+                    registry,
+                    no_changelog,
+                    plan_dependents,
+                }),
+            verbose,
+            quiet,
+            log_format,
+            ..
+        } => {
+            init_logging(at_least_verbose(verbose, quiet), quiet, to_log_format(&log_format)?, false)?;
+            command::yank(
+                command::yank::Options {
+                    dry_run: !execute,
+                    registry,
+                    changelog: !no_changelog,
+                    plan_dependents,
+                },
+                crate_name,
+                version,
+            )?
+        }
+        SubCommands::SmartRelease {
+            subcommand:
+                Some(SmartReleaseSubCommands::Init {
+                    execute,
+                    merge_driver,
+                    crates,
+                }),
+            verbose,
+            quiet,
+            log_format,
+            capitalize_commit,
+            changelog_strip_trailing_period,
+            changelog_imperative_mood,
+            changelog_locale,
+            changelog_sidecar,
+            changelog_anchors,
+            no_changelog_commit_hashes,
+            ..
+        } => {
+            init_logging(verbose, quiet, to_log_format(&log_format)?, false)?;
+            command::init(
+                command::init::Options {
+                    dry_run: !execute,
+                    merge_driver,
+                    write_options: cargo_smart_release::changelog::write::Options {
+                        capitalize_commit,
+                        locale: to_locale(changelog_locale.as_deref())?,
+                        anchors: changelog_anchors,
+                        omit_commit_hashes: no_changelog_commit_hashes,
+                        strip_trailing_period: changelog_strip_trailing_period,
+                        imperative_mood: changelog_imperative_mood,
+                    },
+                    sidecar: changelog_sidecar,
                 },
                 crates,
             )?
         }
+        SubCommands::SmartRelease {
+            subcommand:
+                Some(SmartReleaseSubCommands::Undo {
+                    execute,
+                    no_publish: undo_no_publish,
+                }),
+            no_publish,
+            verbose,
+            quiet,
+            log_format,
+            ..
+        } => {
+            init_logging(at_least_verbose(verbose, quiet), quiet, to_log_format(&log_format)?, false)?;
+            command::undo(command::undo::Options {
+                dry_run: !execute,
+                skip_publish: no_publish || undo_no_publish,
+            })?
+        }
+        SubCommands::SmartRelease {
+            subcommand: Some(SmartReleaseSubCommands::Status { crates }),
+            verbose,
+            quiet,
+            log_format,
+            progress,
+            ..
+        } => {
+            let _progress = init_logging(verbose, quiet, to_log_format(&log_format)?, progress)?;
+            command::status(crates)?
+        }
         SubCommands::SmartRelease {
             execute,
             verbose,
+            quiet,
+            log_format,
+            progress,
+            explain,
+            print_graph,
+            interactive,
+            force,
+            pin,
+            exclude,
+            resume,
+            offline,
+            confirm,
+            yes,
             bump,
             bump_dependencies,
+            dependent_bump,
+            semver_checks,
+            build_metadata,
+            channel,
             crates,
             allow_dirty,
+            autostash,
             ignore_instability,
             no_publish,
+            changelog_only,
+            summary,
             no_tag,
             no_push,
             changelog_without,
@@ -52,6 +298,12 @@ fn main() -> anyhow::Result<()> {
             auto_publish_of_stable_crates,
             no_conservative_pre_release_version_handling,
             dry_run_cargo_publish,
+            no_preflight,
+            publish_timeout,
+            publish_retries,
+            publish_backoff,
+            availability_timeout,
+            no_verify_checksum,
             update_crates_index,
             no_bump_on_demand,
             no_changelog,
@@ -63,47 +315,172 @@ fn main() -> anyhow::Result<()> {
             no_dependencies,
             no_isolate_dependencies_from_breaking_changes,
             capitalize_commit,
+            changelog_strip_trailing_period,
+            changelog_imperative_mood,
+            changelog_locale,
+            changelog_pull_request_titles,
+            changelog_references_are_pull_requests,
+            changelog_authors,
+            write_commit_graph,
+            changelog_sidecar,
+            changelog_anchors,
+            no_changelog_commit_hashes,
+            changelog_preview_command,
             registry,
+            mirror_registry,
+            remote,
+            release_branch,
+            on_existing_tag,
+            signed_push,
+            release_author,
+            no_atomic_push,
+            verify_tags,
+            dangerously_replace_release,
+            amend_release,
+            separate_changelog_commit,
+            no_verify,
+            branch,
+            open_pull_request,
+            publish_after_merge,
+            credential_provider,
+            sigstore,
             target,
             publish_uses_docs_rs_metadata,
             signoff,
+            sign,
             commit_prefix,
+            commit_message,
+            stats: _,
+            subcommand,
         } => {
-            let verbose = execute || verbose;
-            init_logging(verbose);
+            let (plan_format, doctor, crates, execute) = match subcommand {
+                Some(SmartReleaseSubCommands::Plan {
+                    format,
+                    crates: plan_crates,
+                }) => {
+                    if execute {
+                        anyhow::bail!("The release plan is only printed, it can't be executed");
+                    }
+                    (Some(to_plan_format(&format)?), false, plan_crates, false)
+                }
+                Some(SmartReleaseSubCommands::Doctor { crates: doctor_crates }) => {
+                    if execute {
+                        anyhow::bail!("The release is only checked, it can't be executed");
+                    }
+                    (None, true, doctor_crates, false)
+                }
+                Some(
+                    SmartReleaseSubCommands::Yank { .. }
+                    | SmartReleaseSubCommands::Status { .. }
+                    | SmartReleaseSubCommands::Init { .. }
+                    | SmartReleaseSubCommands::Undo { .. },
+                ) => {
+                    unreachable!("handled above")
+                }
+                None => (None, false, crates, execute),
+            };
+            let verbose = if execute {
+                at_least_verbose(verbose, quiet)
+            } else {
+                verbose
+            };
+            let _progress = init_logging(verbose, quiet, to_log_format(&log_format)?, progress)?;
+            let (bump, bump_overrides) = to_bump_specs(&bump)?;
             command::release(
                 command::release::Options {
                     dry_run: !execute,
-                    verbose,
+                    verbose: verbose > 0,
                     conservative_pre_release_version_handling: !no_conservative_pre_release_version_handling,
                     bump_when_needed: !no_bump_on_demand,
                     isolate_dependencies_from_breaking_changes: !no_isolate_dependencies_from_breaking_changes,
-                    allow_dirty,
+                    allow_dirty: allow_dirty.as_ref().is_some_and(Vec::is_empty),
+                    allow_dirty_paths: allow_dirty.unwrap_or_default(),
+                    autostash,
                     ignore_instability,
                     skip_publish: no_publish,
+                    changelog_only,
+                    summary,
                     changelog: !no_changelog,
                     skip_tag: no_tag,
                     skip_push: no_push,
+                    resume,
+                    offline,
+                    confirm,
+                    yes,
                     dependencies: !no_dependencies,
                     dry_run_cargo_publish,
+                    skip_preflight: no_preflight,
+                    publish_policy: command::release::PublishPolicy {
+                        timeout: publish_timeout.map(std::time::Duration::from_secs),
+                        retries: publish_retries,
+                        backoff: to_backoff(&publish_backoff)?,
+                    },
+                    availability_timeout: std::time::Duration::from_secs(availability_timeout),
+                    skip_checksum_verification: no_verify_checksum,
                     no_verify: dangerously_pass_no_verify,
                     allow_auto_publish_of_stable_crates: auto_publish_of_stable_crates,
                     update_crates_index,
                     preview: !no_changelog_preview,
+                    preview_command: changelog_preview_command,
                     generator_segments: names_to_segment_selection(&changelog_without)?,
                     allow_fully_generated_changelogs,
                     allow_empty_release_message,
                     changelog_links: !no_changelog_links,
                     allow_changelog_github_release: !no_changelog_github_release,
                     capitalize_commit,
+                    changelog_strip_trailing_period,
+                    changelog_imperative_mood,
+                    changelog_locale: to_locale(changelog_locale.as_deref())?,
+                    changelog_pull_request_titles,
+                    changelog_references_are_pull_requests,
+                    changelog_authors,
+                    write_commit_graph,
+                    changelog_sidecar,
+                    changelog_anchors,
+                    changelog_commit_hashes: !no_changelog_commit_hashes,
                     registry,
+                    mirror_registries: mirror_registry,
+                    remote,
+                    release_branch: release_branch.or_else(|| {
+                        open_pull_request.then(|| command::release::DEFAULT_RELEASE_BRANCH.to_owned())
+                    }),
+                    atomic_push: !no_atomic_push,
+                    verify_tags,
+                    signed_push: signed_push.as_deref().map(to_signed_push).transpose()?,
+                    release_author,
+                    on_existing_tag: to_tag_conflict(&on_existing_tag)?,
+                    git_hooks: !no_verify,
+                    branch,
+                    open_pull_request,
+                    publish_after_merge,
+                    replace_release: dangerously_replace_release,
+                    amend_release,
+                    separate_changelog_commit,
+                    force_with_lease: Default::default(),
+                    credential_provider,
+                    sigstore,
                     target,
                     publish_uses_docs_rs_metadata,
                     signoff,
+                    sign,
                     commit_prefix,
+                    commit_message,
+                    semver_checks: semver_checks.as_deref().map(to_semver_checks_policy).transpose()?,
+                    build_metadata,
+                    bump_overrides,
+                    dependent_bump: dependent_bump.as_deref().map(to_dependent_bump).transpose()?,
+                    explain,
+                    print_graph: print_graph.as_deref().map(to_graph_format).transpose()?,
+                    plan_format,
+                    doctor,
+                    interactive,
+                    force,
+                    pinned: pin,
+                    excluded: exclude,
+                    version_scheme: to_version_scheme(channel.as_deref())?,
                 },
                 crates,
-                to_bump_spec(bump.as_deref().unwrap_or(DEFAULT_BUMP_SPEC))?,
+                bump.map_or_else(|| to_bump_spec(DEFAULT_BUMP_SPEC), Ok)?,
                 to_bump_spec(bump_dependencies.as_deref().unwrap_or(DEFAULT_BUMP_SPEC))?,
             )?
         }
@@ -114,18 +491,105 @@ fn main() -> anyhow::Result<()> {
 
 const DEFAULT_BUMP_SPEC: &str = "auto";
 
+fn to_version_scheme(channel: Option<&str>) -> anyhow::Result<Rc<dyn VersionScheme>> {
+    Ok(match channel {
+        None | Some("stable") => Rc::new(SemanticVersioning),
+        Some(name) => Rc::new(ReleaseChannel::new(name, jiff::Zoned::now().date())?),
+    })
+}
+
 fn to_bump_spec(spec: &str) -> anyhow::Result<cargo_smart_release::version::BumpSpec> {
-    use cargo_smart_release::version::BumpSpec::*;
-    Ok(match spec {
-        "patch" | "Patch" => Patch,
-        "minor" | "Minor" => Minor,
-        "major" | "Major" => Major,
-        "keep" | "Keep" => Keep,
-        "auto" | "Auto" => Auto,
-        unknown_spec => anyhow::bail!("Unknown bump specification: {:?}", unknown_spec),
+    spec.parse()
+}
+
+/// Split `specs` into the one without crate name, and those like `crate=minor` which apply to the named crate only.
+fn to_bump_specs(
+    specs: &[String],
+) -> anyhow::Result<(
+    Option<cargo_smart_release::version::BumpSpec>,
+    BTreeMap<String, cargo_smart_release::version::BumpSpec>,
+)> {
+    let mut global = None;
+    let mut by_crate = BTreeMap::new();
+    for spec in specs {
+        match spec.split_once('=') {
+            Some((name, spec)) => {
+                by_crate.insert(name.to_owned(), to_bump_spec(spec)?);
+            }
+            None => {
+                if global.replace(to_bump_spec(spec)?).is_some() {
+                    anyhow::bail!("Only one bump specification without crate name can be given, like '-b minor'")
+                }
+            }
+        }
+    }
+    Ok((global, by_crate))
+}
+
+/// Return the built-in locale named `name`, or the one in the table of headings at the path `name`.
+fn to_locale(name: Option<&str>) -> anyhow::Result<cargo_smart_release::changelog::locale::Locale> {
+    use anyhow::Context;
+    use cargo_smart_release::changelog::locale::Locale;
+    let Some(name) = name else {
+        return Ok(Locale::default());
+    };
+    if let Some(locale) = Locale::from_name(name) {
+        return Ok(locale);
+    }
+    let content = match std::fs::read_to_string(name) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            anyhow::bail!("Unknown changelog locale: {name:?}, use 'en', 'de', 'fr', 'es' or the path to a table of headings")
+        }
+        Err(err) => return Err(err).with_context(|| format!("Could not read the table of headings at '{name}'")),
+    };
+    Locale::from_toml(&content).with_context(|| format!("Could not parse the table of headings at '{name}'"))
+}
+
+fn to_dependent_bump(name: &str) -> anyhow::Result<cargo_smart_release::version::DependentBump> {
+    cargo_smart_release::version::DependentBump::from_name(name).ok_or_else(|| {
+        anyhow::anyhow!("Unknown dependent bump policy: {:?}, use 'mirror', 'minor' or 'patch'", name)
+    })
+}
+
+fn to_backoff(spec: &str) -> anyhow::Result<command::release::Backoff> {
+    command::release::Backoff::from_spec(spec).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown backoff strategy: {:?}, use 'none', 'fixed:<seconds>' or 'exponential:<seconds>'",
+            spec
+        )
     })
 }
 
+fn to_graph_format(name: &str) -> anyhow::Result<command::release::GraphFormat> {
+    command::release::GraphFormat::from_name(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown graph format: {:?}, use 'dot' or 'mermaid'", name))
+}
+
+fn to_plan_format(name: &str) -> anyhow::Result<command::release::PlanFormat> {
+    command::release::PlanFormat::from_name(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown plan format: {:?}, use 'json'", name))
+}
+
+fn to_tag_conflict(name: &str) -> anyhow::Result<command::release::TagConflict> {
+    command::release::TagConflict::from_name(name).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown tag conflict policy: {:?}, use 'fail', 'skip' or 'replace-if-unpushed'",
+            name
+        )
+    })
+}
+
+fn to_signed_push(name: &str) -> anyhow::Result<command::release::SignedPush> {
+    command::release::SignedPush::from_name(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown signed push mode: {:?}, use 'true' or 'if-asked'", name))
+}
+
+fn to_semver_checks_policy(name: &str) -> anyhow::Result<cargo_smart_release::semver_checks::Policy> {
+    cargo_smart_release::semver_checks::Policy::from_name(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown semver-checks policy: {:?}, use 'refuse' or 'upgrade'", name))
+}
+
 fn names_to_segment_selection(
     names: &[String],
 ) -> anyhow::Result<cargo_smart_release::changelog::section::segment::Selection> {
@@ -147,10 +611,207 @@ fn names_to_segment_selection(
     })
 }
 
-fn init_logging(verbose: bool) {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(if verbose { "trace" } else { "info" }))
-        .format_module_path(false)
-        .format_target(false)
-        .format_timestamp(None)
-        .init();
+fn names_to_components(names: &[String]) -> anyhow::Result<cargo_smart_release::changelog::write::Components> {
+    use cargo_smart_release::changelog::write::Components;
+    let mut components = Components::empty();
+    for name in names {
+        components |= match name.as_str() {
+            "title" => Components::SECTION_TITLE,
+            "details" => Components::DETAIL_TAGS,
+            "html-tags" => Components::HTML_TAGS,
+            other => anyhow::bail!("Invalid changelog component: {other:?}, choose from 'title', 'details' and 'html-tags'"),
+        };
+    }
+    Ok(components)
+}
+
+/// How log records are written.
+#[derive(Debug, Clone, Copy)]
+enum LogFormat {
+    /// Messages for humans, without events.
+    Text,
+    /// One JSON object per line with timestamp, level, message and key-values, including events.
+    Json,
+}
+
+fn to_log_format(name: &str) -> anyhow::Result<LogFormat> {
+    Ok(match name {
+        "text" => LogFormat::Text,
+        "json" => LogFormat::Json,
+        _ => anyhow::bail!("Invalid log format: {name:?}, choose one of 'text' or 'json'"),
+    })
+}
+
+fn to_color_mode(name: &str) -> anyhow::Result<color::Mode> {
+    Ok(match name {
+        "auto" => color::Mode::Auto,
+        "always" => color::Mode::Always,
+        "never" => color::Mode::Never,
+        _ => anyhow::bail!("Invalid color mode: {name:?}, choose one of 'auto', 'always' or 'never'"),
+    })
+}
+
+/// Raise `verbose` to at least one, unless fewer messages were asked for with `quiet`.
+fn at_least_verbose(verbose: u8, quiet: u8) -> u8 {
+    if quiet == 0 {
+        verbose.max(1)
+    } else {
+        verbose
+    }
+}
+
+/// Return the filter directives for the given number of `-v` and `-q` flags, used unless `RUST_LOG` is set.
+fn default_filter(verbose: u8, quiet: u8) -> &'static str {
+    match (verbose, quiet) {
+        (_, 1) => "warn",
+        (_, 2) => "error",
+        (_, 3..) => "off",
+        (0, _) => "info",
+        (1, _) => "info,cargo_smart_release=trace",
+        (2.., _) => "trace",
+    }
+}
+
+/// Initialize logging with messages filtered by the number of `-v` and `-q` flags, or by `RUST_LOG` if set, and if
+/// `progress` is `true` and progress can be shown, render it until the returned handle is dropped.
+///
+/// While progress is shown, log records are shown as its messages so both don't overwrite each other.
+fn init_logging(
+    verbose: u8,
+    quiet: u8,
+    format: LogFormat,
+    progress: bool,
+) -> anyhow::Result<Option<prodash::render::line::JoinHandle>> {
+    use tracing_subscriber::fmt::writer::BoxMakeWriter;
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_filter(verbose, quiet)))
+        .add_directive(
+            format!(
+                "{}={}",
+                cargo_smart_release::event::TARGET,
+                match format {
+                    LogFormat::Text => "off",
+                    LogFormat::Json => "info",
+                }
+            )
+            .parse()?,
+        );
+    let show_progress = progress && matches!(format, LogFormat::Text) && std::io::stderr().is_terminal();
+    let (writer, render) = if show_progress {
+        let root = cargo_smart_release::progress::enable();
+        let options = prodash::render::line::Options {
+            frames_per_second: 6.0,
+            initial_delay: Some(std::time::Duration::from_millis(500)),
+            throughput: true,
+            ..Default::default()
+        }
+        .auto_configure(prodash::render::line::StreamKind::Stderr);
+        let render = prodash::render::line(
+            std::io::stderr(),
+            std::sync::Arc::downgrade(&root),
+            prodash::render::line::Options {
+                colored: cargo_smart_release::color::stderr(),
+                ..options
+            },
+        );
+        (
+            BoxMakeWriter::new(std::sync::Mutex::new(LogToProgress(root.add_child("log")))),
+            Some(render),
+        )
+    } else {
+        (BoxMakeWriter::new(std::io::stderr), None)
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(!show_progress && cargo_smart_release::color::stderr())
+        .event_format(Format(format))
+        .try_init()
+        .map_err(|err| anyhow::anyhow!(err))?;
+    Ok(render)
+}
+
+/// Show each line written to it as message of the progress it holds.
+struct LogToProgress(prodash::tree::Item);
+
+impl std::io::Write for LogToProgress {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        use prodash::Progress;
+        for line in String::from_utf8_lossy(buf).lines() {
+            self.0.info(line.to_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write log records in the given format, one per line.
+struct Format(LogFormat);
+
+impl<S, N> tracing_subscriber::fmt::FormatEvent<S, N> for Format
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &tracing_subscriber::fmt::FmtContext<'_, S, N>,
+        mut writer: tracing_subscriber::fmt::format::Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> std::fmt::Result {
+        let level = *event.metadata().level();
+        match self.0 {
+            LogFormat::Text => {
+                if writer.has_ansi_escapes() {
+                    let color = match level {
+                        tracing::Level::ERROR => 31,
+                        tracing::Level::WARN => 33,
+                        tracing::Level::INFO => 32,
+                        tracing::Level::DEBUG => 34,
+                        tracing::Level::TRACE => 36,
+                    };
+                    write!(writer, "[\x1b[{color}m{level:<5}\x1b[0m] ")?;
+                } else {
+                    write!(writer, "[{level:<5}] ")?;
+                }
+                ctx.field_format().format_fields(writer.by_ref(), event)?;
+            }
+            LogFormat::Json => {
+                let mut object = serde_json::Map::new();
+                object.insert("timestamp".into(), jiff::Timestamp::now().to_string().into());
+                object.insert("level".into(), level.as_str().to_ascii_lowercase().into());
+                event.record(&mut JsonFields(&mut object));
+                write!(writer, "{}", serde_json::Value::Object(object))?;
+            }
+        }
+        writeln!(writer)
+    }
+}
+
+/// Add the fields of a log record to a JSON object, with its message as `message`.
+struct JsonFields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl tracing::field::Visit for JsonFields<'_> {
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().into(), format!("{value:?}").into());
+    }
 }
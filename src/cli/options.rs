@@ -9,14 +9,60 @@
 pub struct Args {
     #[clap(subcommand)]
     pub subcommands: SubCommands,
+
+    /// When to color log messages, diffs, changelog previews and the output of cargo: `auto`, `always` or `never`.
+    ///
+    /// With `auto`, output is colored if it goes to a terminal and the `NO_COLOR` environment variable isn't set.
+    #[clap(long, global = true, value_name = "WHEN", default_value = "auto")]
+    pub color: String,
+}
+
+/// The prefix of the environment variables flags are read from, like `SMART_RELEASE_NO_PUSH` for `--no-push`.
+const ENV_PREFIX: &str = "SMART_RELEASE_";
+
+impl Args {
+    /// Return the command to parse [`Args`] with, which reads each flag not given on the command-line from an
+    /// environment variable named like it with the [`ENV_PREFIX`].
+    pub fn command_with_env() -> clap::Command {
+        with_env(<Self as clap::CommandFactory>::command())
+    }
+}
+
+fn with_env(command: clap::Command) -> clap::Command {
+    command
+        .mut_args(|arg| match arg.get_long() {
+            Some(long) => {
+                let name = format!("{ENV_PREFIX}{}", long.replace('-', "_").to_ascii_uppercase());
+                let arg = arg.env(name);
+                // Accept `1`, `yes` or `on` as well, and treat empty values as not set.
+                if matches!(arg.get_action(), clap::ArgAction::SetTrue) {
+                    arg.value_parser(clap::builder::FalseyValueParser::new())
+                } else {
+                    arg
+                }
+            }
+            None => arg,
+        })
+        .mut_subcommands(with_env)
 }
 
 #[derive(clap::Parser)]
+#[allow(clippy::large_enum_variant)]
 pub enum SubCommands {
     #[clap(name = "smart-release", long_version = option_env!("CARGO_SMART_RELEASE_VERSION"))]
     /// Release workspace crates fearlessly.
     ///
     /// Use --execute to actually perform the operation.
+    ///
+    /// Defaults for flags can be set in `[workspace.metadata.smart-release]` with keys named like the flags, like
+    /// `no-push = true`, `bump = ["minor"]` or `changelog-without = ["clippy"]`, which are used unless the flag is given.
+    ///
+    /// Personal defaults can be set the same way in the `[smart-release]` table of `~/.config/smart-release/config.toml`,
+    /// or of `$XDG_CONFIG_HOME/smart-release/config.toml`, like `sign = true` or `verbose = 1`, which are used unless
+    /// the workspace sets them.
+    ///
+    /// Flags can also be set with environment variables named like them with a `SMART_RELEASE_` prefix, like
+    /// `SMART_RELEASE_NO_PUSH=true`, which take precedence over the defaults but not over the command-line.
     SmartRelease {
         /// Actually perform a release. Dry-run mode is the default
         #[clap(long, short = 'e', help_heading = Some("MAJOR"))]
@@ -30,6 +76,9 @@ pub enum SubCommands {
         /// Don't generate a changelog automatically or update existing ones. This is useful if a manual changelog
         /// is preferred or if its format strays to far from the suggestions on <https://keepachangelog.com>, making
         /// generated content impossible to properly integrate with what's there.
+        ///
+        /// Changelogs are `CHANGELOG.md` next to the manifest, unless `changelog = "docs/CHANGELOG.md"` sets another path
+        /// relative to it in `[package.metadata.smart-release]`, or for all crates in `[workspace.metadata.smart-release]`.
         #[clap(long, help_heading = Some("MAJOR"))]
         no_changelog: bool,
 
@@ -37,38 +86,214 @@ pub enum SubCommands {
         ///
         /// Can be 'major', 'minor' or 'patch', 'keep' and 'auto'.
         /// With 'keep', the current version will be kept, useful if versions are specified by hand in the manifest.
+        /// With 'alpha', 'beta' or 'rc', a pre-release like '1.3.0-rc.1' of the 'auto' version is produced, or the counter of
+        /// an existing pre-release is incremented. 'release' turns a pre-release into its final version.
         ///
         /// The default is 'auto', which derives the necessary information from the git commit history and occasional
         /// conventional messages.
+        ///
+        /// Use 'crate=spec' like '-b crate-a=minor' to set the bump for a single crate, which may also be a dependency,
+        /// and which takes precedence over `bump = "…"` in its `[package.metadata.smart-release]`.
+        /// This flag can be repeated.
         #[clap(long, short = 'b', help_heading = Some("MAJOR"))]
-        bump: Option<String>,
+        bump: Vec<String>,
 
         /// Specify the kind of version bump to apply to dependencies only.
         ///
         /// Can be 'major', 'minor' or 'patch', 'keep' and 'auto'.
         /// With 'keep', the current version will be kept, useful if versions are specified by hand in the manifest.
+        /// With 'alpha', 'beta' or 'rc', a pre-release like '1.3.0-rc.1' of the 'auto' version is produced, or the counter of
+        /// an existing pre-release is incremented. 'release' turns a pre-release into its final version.
         ///
         /// The default is 'auto', which derives the necessary information from the git commit history and occasional
         /// conventional messages.
         #[clap(long, short = 'd', help_heading = Some("MAJOR"))]
         bump_dependencies: Option<String>,
 
+        /// How to bump crates which are released because of breaking changes in their dependencies.
+        ///
+        /// Can be 'mirror' to use a breaking bump like the dependency, which is the default, 'minor' or 'patch'.
+        /// This can also be set with `dependent-bump = "…"` in `[workspace.metadata.smart-release]`.
+        #[clap(long, value_name = "POLICY", help_heading = Some("MAJOR"))]
+        dependent_bump: Option<String>,
+
+        /// Validate 'auto' version bumps of previously released crates with 'cargo semver-checks'.
+        ///
+        /// Can be 'refuse' to abort the release if the API has breaking changes that the commit history doesn't mention,
+        /// or 'upgrade' to use a breaking version bump instead.
+        #[clap(long, value_name = "POLICY", help_heading = Some("MAJOR"))]
+        semver_checks: Option<String>,
+
+        /// Append semver build metadata to the versions of new releases, like 'build.20240601' to produce '1.2.3+build.20240601'.
+        ///
+        /// '{commit}' is replaced with the short hash of the HEAD commit. Note that build metadata doesn't make versions distinct.
+        /// This can also be set with `build-metadata = "…"` in `[workspace.metadata.smart-release]`.
+        #[clap(long, value_name = "METADATA", help_heading = Some("MAJOR"))]
+        build_metadata: Option<String>,
+
+        /// Release on the given channel, like 'nightly', instead of the 'stable' one.
+        ///
+        /// Channel releases get pre-release versions with the channel name and the current date, like '1.3.0-nightly.20240601',
+        /// and their tags live in a namespace of their own, like 'nightly/v1.3.0-nightly.20240601'.
+        #[clap(long, value_name = "CHANNEL", help_heading = Some("MAJOR"))]
+        channel: Option<String>,
+
         /// The name of the crates to be released, along with all of their dependencies if needed.
         ///
-        /// Defaults to the top-level workspace crate if unset.
+        /// Defaults to the top-level workspace crate if unset. Glob patterns like `gix-*` select all matching workspace
+        /// crates, and paths like `./crates/parser` select the workspace crate in that directory.
+        /// Crates named like a subcommand, like `plan`, are released by passing them after `--`, like in
+        /// `cargo smart-release -- plan`.
         crates: Vec<String>,
 
-        /// Provide more detailed messages on the INFO log level in dry-run mode.
+        #[clap(subcommand)]
+        subcommand: Option<SmartReleaseSubCommands>,
+
+        /// Provide more detailed messages on the INFO log level in dry-run mode, and show traces of smart-release.
         ///
+        /// Pass it twice to trace its dependencies as well. Messages can also be filtered by module with `RUST_LOG`,
+        /// like `RUST_LOG=cargo_smart_release::changelog=trace`, which takes precedence.
         /// Note --verbose is implied with --execute.
-        #[clap(long, short = 'v', help_heading = Some("CUSTOMIZATION"))]
-        verbose: bool,
+        #[clap(long, short = 'v', action = clap::ArgAction::Count, help_heading = Some("CUSTOMIZATION"))]
+        verbose: u8,
+
+        /// Show only warnings and errors, only errors if passed twice, and nothing if passed three times.
+        #[clap(long, short = 'q', action = clap::ArgAction::Count, conflicts_with = "verbose", help_heading = Some("CUSTOMIZATION"))]
+        quiet: u8,
+
+        /// The format of the log, `text` for humans or `json` for one JSON object per line with a timestamp.
+        ///
+        /// With `json`, events like `step-started`, `crate-published`, `tag-created` and `push-done` are logged as well,
+        /// along with the crate, version and tag they relate to, so CI systems and dashboards can track the release.
+        #[clap(long, value_name = "FORMAT", default_value = "text", help_heading = Some("CUSTOMIZATION"))]
+        log_format: String,
+
+        /// Show progress bars for long operations, like traversing the history, publishing and waiting for published
+        /// crates to arrive in the index.
+        ///
+        /// It's ignored if stderr isn't a terminal or if the log format isn't `text`.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        progress: bool,
+
+        /// Print the wall-time spent in each phase at the end of the run, like collecting metadata, traversing the
+        /// history, generating the changelog of each crate, publishing, waiting for crates to be available and pushing.
+        ///
+        /// Useful to find out why releases of large workspaces are slow.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        stats: bool,
+
+        /// Explain the version bump of each crate by listing the commits causing it, along with the rule that was applied.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        explain: bool,
 
-        /// Additionally run 'cargo publish --dry-run' when --execute is not set. This can be useful to see which local
-        /// crates do not build with the released versions of their workspace dependencies anymore.
+        /// Print the dependency graph of the crates to release and the order they would be published in, and exit.
+        ///
+        /// FORMAT is `dot` for Graphviz, like in `cargo smart-release --print-graph dot | dot -Tsvg > release.svg`,
+        /// or `mermaid` for a flowchart that renders in markdown. Crates that won't be published are drawn dashed.
+        #[clap(long, value_name = "FORMAT", help_heading = Some("CUSTOMIZATION"))]
+        print_graph: Option<String>,
+
+        /// Before releasing, list the crates of the release with their version changes, and let the user toggle crates,
+        /// adjust their bumps and preview their changelogs until confirming.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        interactive: bool,
+
+        /// Release the provided crates even if they have no releasable changes since their last release, using a patch
+        /// bump if the version wouldn't change otherwise.
+        ///
+        /// Changes to paths matching the `ignore = ["benches/**", "**/*.md"]` patterns in `[package.metadata.smart-release]`
+        /// don't make a crate releasable.
+        #[clap(long, help_heading = Some("MAJOR"))]
+        force: bool,
+
+        /// Never bump the version of or publish the given crate, even if it changed or its dependencies have breaking changes.
+        ///
+        /// Requirements of other crates on it are left as is, while its own dependency requirements are still updated.
+        /// This can also be set with `frozen = true` in its `[package.metadata.smart-release]`.
+        /// This flag can be repeated.
+        #[clap(long, value_name = "CRATE", help_heading = Some("MAJOR"))]
+        pin: Vec<String>,
+
+        /// Don't select the workspace crates matching the given glob pattern, like in
+        /// `cargo smart-release 'gix-*' --exclude 'gix-testtools'`.
+        ///
+        /// They may still be released if crates that are selected depend on them.
+        /// This flag can be repeated.
+        #[clap(long, value_name = "GLOB", help_heading = Some("MAJOR"))]
+        exclude: Vec<String>,
+
+        /// Continue a release with --execute that failed midway, like when publishing a crate failed, from where it stopped.
+        ///
+        /// Crates that were published and tags that were created already are skipped, and nothing is recomputed.
+        /// The state of the release is kept in '.git/smart-release-state.json' until it is complete.
+        #[clap(long, help_heading = Some("MAJOR"))]
+        resume: bool,
+
+        /// Compute the release plan from local data only, like existing tags and the cached crates index, without
+        /// accessing the network.
+        ///
+        /// This is useful to review what a release would do on an air-gapped machine, and can't be combined with
+        /// --execute, --update-crates-index or --semver-checks.
+        #[clap(long, help_heading = Some("MAJOR"))]
+        offline: bool,
+
+        /// With --execute, ask to type a short code shown along with the crates to release before anything is changed,
+        /// so a release can't be started by accident, like by replaying the shell history.
+        ///
+        /// This can also be enabled for the whole workspace with `confirm = true` in `[workspace.metadata.smart-release]`.
+        #[clap(long, help_heading = Some("MAJOR"))]
+        confirm: bool,
+
+        /// Accept all prompts without asking, for unattended releases like in CI.
+        ///
+        /// The release isn't confirmed with a code even with --confirm, an unpushed release is amended, changelog
+        /// previews are printed without waiting for the pager to be closed, and git and gh fail instead of asking for
+        /// credentials.
+        #[clap(long, short = 'y', conflicts_with = "interactive", help_heading = Some("MAJOR"))]
+        yes: bool,
+
+        /// Additionally package crates with 'cargo package' like before uploading them when --execute is not set. This can
+        /// be useful to see which local crates do not build with the released versions of their workspace dependencies
+        /// anymore.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         dry_run_cargo_publish: bool,
 
+        /// Don't package all crates with 'cargo package' before publishing the first one, and don't check that they are
+        /// owned by the user publishing them or that their docs.rs configuration is valid.
+        ///
+        /// By default, a release is aborted before anything is published if any of its crates fails to package or is
+        /// too large to be accepted by crates.io, or
+        /// before anything is changed if the user authenticated with crates.io isn't an owner of all of them.
+        #[clap(long, help_heading = Some("EXPERT"))]
+        no_preflight: bool,
+
+        /// Stop packaging or uploading a crate if it didn't finish after this many seconds, and consider the attempt failed.
+        #[clap(long, value_name = "SECONDS", help_heading = Some("EXPERT"))]
+        publish_timeout: Option<u64>,
+
+        /// How often to try publishing a crate again if it failed, before giving up on the release.
+        #[clap(long, value_name = "N", default_value_t = 2, help_heading = Some("EXPERT"))]
+        publish_retries: usize,
+
+        /// How long to wait before trying to publish a crate again if it failed.
+        ///
+        /// Can be 'none', 'fixed:<seconds>' or 'exponential:<seconds>', which doubles the wait after each retry.
+        /// Waiting for rate limits of the registry to pass is independent of this.
+        #[clap(long, value_name = "STRATEGY", default_value = "none", help_heading = Some("EXPERT"))]
+        publish_backoff: String,
+
+        /// How long to wait for each published crate to arrive in the index of the registry, and to be available for
+        /// download from crates.io to verify its checksum.
+        #[clap(long, value_name = "SECONDS", default_value_t = 60, help_heading = Some("EXPERT"))]
+        availability_timeout: u64,
+
+        /// Don't download crates published to crates.io to verify that they match the `.crate` files that were uploaded.
+        ///
+        /// By default, the release fails before its tags are pushed if a published crate differs, or isn't available
+        /// for download within --availability-timeout.
+        #[clap(long, help_heading = Some("EXPERT"))]
+        no_verify_checksum: bool,
+
         /// Always bump versions as specified by --bump or --bump-dependencies even if this is not required
         /// to publish a new version to crates.io.
         ///
@@ -102,15 +327,43 @@ pub enum SubCommands {
         #[clap(long, help_heading = Some("CHANGELOG"))]
         changelog_without: Vec<String>,
 
-        /// If unset, about-to-be changed changelogs will be previewed using 'bat', if available, and when executing.
+        /// If unset, about-to-be changed changelogs will be previewed using 'bat' or --changelog-preview-command, if available, and when executing.
         ///
         /// If set, no preview will ever be displayed, but note that empty changelogs will always stop the release process.
         #[clap(long, help_heading = Some("CHANGELOG"))]
         no_changelog_preview: bool,
 
+        /// Commit the updated changelogs first and on their own, followed by a commit with the version changes in manifests.
+        ///
+        /// Useful if review policies require changes to documentation and to versions to be separate. The tags point to
+        /// the second commit.
+        #[clap(
+            long,
+            conflicts_with_all = ["dangerously_replace_release", "amend_release"],
+            help_heading = Some("CHANGELOG")
+        )]
+        separate_changelog_commit: bool,
+
         /// Allow publishes to take place on a dirty working tree. Really not recommended alongside --execute.
-        #[clap(long, help_heading = Some("EXPERT"))]
-        allow_dirty: bool,
+        ///
+        /// With `--allow-dirty=<path>`, only changes at or below the repository-relative path are allowed, like
+        /// generated docs that are rebuilt locally, while other changes still prevent the release. It can be repeated.
+        #[clap(
+            long,
+            value_name = "PATH",
+            num_args = 0..=1,
+            require_equals = true,
+            action = clap::ArgAction::Append,
+            help_heading = Some("EXPERT")
+        )]
+        allow_dirty: Option<Vec<std::path::PathBuf>>,
+
+        /// Stash changes in the working tree, including untracked files, before the release and restore them afterwards,
+        /// instead of refusing to release from a dirty working tree.
+        ///
+        /// If the changes can't be restored cleanly, they are kept in the stash to be restored by hand.
+        #[clap(long, conflicts_with = "allow_dirty", help_heading = Some("CUSTOMIZATION"))]
+        autostash: bool,
 
         /// Allow to also publish stable crates when discovering changed crates, bumping their version according to `-d <spec>`.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
@@ -133,15 +386,111 @@ pub enum SubCommands {
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         no_publish: bool,
 
+        /// Only bump, update the changelog and tag the crate with the given name, without publishing it, while the
+        /// other crates are released fully. It can be repeated.
+        ///
+        /// Useful for internal crates in the same dependency chain which are never published.
+        #[clap(long, value_name = "CRATE", help_heading = Some("CUSTOMIZATION"))]
+        changelog_only: Vec<String>,
+
+        /// Write a JSON summary of the release to the given file once it's done, even if it failed midway.
+        ///
+        /// It lists the crates with their versions and if they were published, the tags with their ids, the release
+        /// commit, if it was pushed and the steps that were skipped, for automation like announcements to act on.
+        #[clap(long, value_name = "PATH", help_heading = Some("CUSTOMIZATION"))]
+        summary: Option<std::path::PathBuf>,
+
         /// Don't create tags indicating the version numbers of all crates that are to be published after changing
         /// their manifests.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         no_tag: bool,
 
-        /// Don't push tags and the HEAD branch after any successful run of `cargo publish`.
+        /// Don't push tags and the HEAD branch after publishing crates.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         no_push: bool,
 
+        /// The remote to push the release commit and tags to, instead of the push remote of the current branch.
+        ///
+        /// Useful in triangular workflows, where the current branch tracks a fork but releases go to the upstream repository.
+        /// Changelog links also point to this remote.
+        #[clap(long, value_name = "NAME", help_heading = Some("CUSTOMIZATION"))]
+        remote: Option<String>,
+
+        /// Create the release commit on a new branch and push that instead of committing to the current branch.
+        ///
+        /// Useful if the default branch is protected, so the release has to be merged with a pull request.
+        /// `{name}` and `{version}` in the branch name are replaced by the name and new version of the crate that is
+        /// released last, which usually is the one that was selected. The new branch remains checked out.
+        #[clap(
+            long,
+            value_name = "NAME",
+            num_args = 0..=1,
+            default_missing_value = cargo_smart_release::command::release::DEFAULT_RELEASE_BRANCH,
+            help_heading = Some("CUSTOMIZATION")
+        )]
+        release_branch: Option<String>,
+
+        /// What to do if the tag of a release exists already, like after an aborted release.
+        ///
+        /// POLICY is `fail` to abort, `skip` to keep the existing tag without pushing it, or `replace-if-unpushed` to
+        /// replace it unless it's on the remote already.
+        #[clap(long, value_name = "POLICY", default_value = "replace-if-unpushed", help_heading = Some("CUSTOMIZATION"))]
+        on_existing_tag: String,
+
+        /// Sign the push of the release commit and tags with a push certificate, for remotes which require certified pushes.
+        ///
+        /// MODE is `true` to always sign, or `if-asked` to sign only if the remote supports it. Without it, pushes are
+        /// signed as configured with 'push.gpgSign'. The key is chosen like for signed tags.
+        #[clap(
+            long,
+            value_name = "MODE",
+            num_args = 0..=1,
+            default_missing_value = "true",
+            help_heading = Some("CUSTOMIZATION")
+        )]
+        signed_push: Option<String>,
+
+        /// The identity to author and commit the release commit as and to tag the release with, like 'Bot <bot@example.com>'.
+        ///
+        /// Useful to release from automation with a dedicated identity instead of the one configured for git.
+        /// This can also be set with the `SMART_RELEASE_RELEASE_AUTHOR` environment variable, or with `release-author = "…"`
+        /// in `[workspace.metadata.smart-release]`.
+        #[clap(long, value_name = "IDENTITY", help_heading = Some("CUSTOMIZATION"))]
+        release_author: Option<String>,
+
+        /// Don't run the 'pre-commit', 'commit-msg' and 'post-commit' git hooks when creating the release commit, nor the
+        /// 'pre-push' hook when pushing it.
+        ///
+        /// They run like with 'git commit' and 'git push' by default. Set 'git-hooks = false' in the workspace configuration to
+        /// never run them. Note that this is unrelated to --dangerously-pass-no-verify and to 'no-verify' in
+        /// '[package.metadata.smart-release]', which publish crates without building them first.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        no_verify: bool,
+
+        /// The branch to commit the release to and push, for when HEAD is detached as is common in CI.
+        ///
+        /// The branch is checked out, and created at HEAD if it doesn't exist. If it exists, it must point to HEAD.
+        #[clap(
+            long,
+            value_name = "NAME",
+            conflicts_with_all = ["release_branch", "open_pull_request"],
+            help_heading = Some("CUSTOMIZATION")
+        )]
+        branch: Option<String>,
+
+        /// Open a pull request with the 'gh' program for the release branch once it was pushed, listing the released crates.
+        ///
+        /// Implies --release-branch.
+        #[clap(long, conflicts_with = "no_push", help_heading = Some("CUSTOMIZATION"))]
+        open_pull_request: bool,
+
+        /// Publish only after the pull request of the release was merged.
+        ///
+        /// Only the release branch is pushed and the pull request is opened. Once it's merged, check out the merge
+        /// commit and run again with --resume to publish the crates and tag the merge commit.
+        #[clap(long, requires = "open_pull_request", help_heading = Some("CUSTOMIZATION"))]
+        publish_after_merge: bool,
+
         /// Do not take into consideration any dependencies of the crates to publish.
         ///
         /// This flag is useful when various `--skip-X` are specified in order to bump versions only, without publishing.
@@ -149,26 +498,91 @@ pub enum SubCommands {
         no_dependencies: bool,
 
         /// Alternative registry to publish to.
+        ///
+        /// Individual crates can be published to another registry with `registry = "…"` in their
+        /// `[package.metadata.smart-release]`.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         registry: Option<String>,
 
-        /// Build and package the crate for the given target triple when invoking `cargo publish`.
+        /// Publish each crate to this registry as well, right after publishing it to the main registry.
+        ///
+        /// This is useful to keep an internal mirror up to date along with crates.io. Crates are only published to the
+        /// registries allowed by the `publish` field of their manifest, and the outcome for each registry is listed at the end.
+        /// This can also be set with `mirror-registry = ["…"]` in `[workspace.metadata.smart-release]`.
+        /// This flag can be repeated.
+        #[clap(long, value_name = "REGISTRY", help_heading = Some("CUSTOMIZATION"))]
+        mirror_registry: Vec<String>,
+
+        /// The cargo credential provider to obtain the registry token with when publishing, instead of the one configured for cargo.
+        ///
+        /// Use 'cargo:token-from-stdout <command>' to use short-lived tokens printed by a program, or a provider like
+        /// 'cargo:libsecret', 'cargo:macos-keychain' or 'cargo:wincred' to use the keyring of the operating system.
+        /// This can also be set with `credential-provider = "…"` in `[workspace.metadata.smart-release]`.
+        #[clap(long, value_name = "PROVIDER", help_heading = Some("CUSTOMIZATION"))]
+        credential_provider: Option<String>,
+
+        /// Sign the published crate files and the release tags with a keyless Sigstore signature obtained by 'cosign'.
+        ///
+        /// The identity of the signer is established with OIDC, either interactively or from the ambient credentials of CI.
+        /// The signature bundles are put into 'target/smart-release' and attached to GitHub releases if these are created.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        sigstore: bool,
+
+        /// Build and package the crate for the given target triple when invoking `cargo package`.
         ///
-        /// This flag is forwarded to all `cargo publish` invocations, including dry runs.
+        /// This flag is forwarded to all `cargo package` invocations, including dry runs.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         target: Option<String>,
 
-        /// When invoking `cargo publish`, also mirror feature selection from `package.metadata.docs.rs`.
+        /// When invoking `cargo package` to publish a crate, also mirror feature selection from `package.metadata.docs.rs`.
         ///
         /// Currently this forwards `features`, `all-features`, and `no-default-features`.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         publish_uses_docs_rs_metadata: bool,
 
-        /// Pass --no-verify to 'cargo publish' which should only be a last resort when fixing up packages that
+        /// Pass --no-verify to 'cargo package' which should only be a last resort when fixing up packages that
         /// otherwise wouldn't publish, but need to be publish to resolve the situation.
+        ///
+        /// To always publish individual crates without verification, like those whose build scripts need network access,
+        /// set `no-verify = true` in their `[package.metadata.smart-release]` instead.
         #[clap(long, help_heading = Some("EXPERT"))]
         dangerously_pass_no_verify: bool,
 
+        /// Push the release commit and tags non-atomically, for remotes that don't support atomic pushes.
+        ///
+        /// By default, either all of them are updated on the remote or none, to avoid half-pushed releases.
+        #[clap(long, help_heading = Some("EXPERT"))]
+        no_atomic_push: bool,
+
+        /// Refuse to release crates whose last release tag isn't signed by a key trusted by 'git verify-tag', so a
+        /// release is never based on tampered history. This also applies to releases continued with --resume.
+        ///
+        /// Trusted keys are those of the GPG keyring, or those in 'gpg.ssh.allowedSignersFile' for SSH signatures.
+        /// This can also be enabled for the whole workspace with `verify-tags = true` in `[workspace.metadata.smart-release]`.
+        #[clap(long, help_heading = Some("EXPERT"))]
+        verify_tags: bool,
+
+        /// Replace the release at HEAD, like one with a broken changelog that was pushed but not yet published, by
+        /// amending its commit and re-pointing its tags to the new one.
+        ///
+        /// Both are pushed with '--force-with-lease', so nothing is overwritten if the remote moved on in the meantime.
+        /// Rewriting a public history affects everyone who fetched it, which is why it has to be confirmed by typing
+        /// a code when executing.
+        #[clap(
+            long,
+            conflicts_with_all = ["release_branch", "open_pull_request"],
+            help_heading = Some("EXPERT")
+        )]
+        dangerously_replace_release: bool,
+
+        /// Amend the release commit at HEAD and move its tags instead of creating another release commit, if neither the
+        /// commit nor its tags were pushed and no crate was published, like after the push failed.
+        ///
+        /// Without it, this is offered when running interactively. As the remote is asked which tags it has, this is only
+        /// checked with --execute.
+        #[clap(long, conflicts_with = "dangerously_replace_release", help_heading = Some("EXPERT"))]
+        amend_release: bool,
+
         /// If set it will be allowed to publish crates with cycles to other workspace crates which are not published.
         ///
         /// Doing so causes repeated publishes to never stabilize, as one set of dependencies destabilizes another set.
@@ -181,20 +595,110 @@ pub enum SubCommands {
         #[clap(long, help_heading = Some("CHANGELOG"))]
         capitalize_commit: bool,
 
+        /// Remove a single trailing period from the titles of generated changelog entries.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_strip_trailing_period: bool,
+
+        /// Turn the titles of generated changelog entries into imperative mood, like 'Added foo' or 'Adds foo' into 'Add foo'.
+        ///
+        /// This is a heuristic which only knows common verbs, and leaves titles starting with other words unchanged.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_imperative_mood: bool,
+
+        /// The language of generated changelog headings, 'en', 'de', 'fr' or 'es', or the path to a TOML table of headings.
+        ///
+        /// Tables map git-conventional kinds like 'fix' and 'feat', as well as 'statistics', 'details', 'clippy' and
+        /// 'breaking' for hand-written breaking changes, to their heading, like `fix = "Bug Fixes"`. Unlisted ones keep
+        /// their English heading.
+        /// Headings of the built-in languages and of the given table are understood when parsing existing changelogs.
+        #[clap(long, value_name = "LOCALE", help_heading = Some("CHANGELOG"))]
+        changelog_locale: Option<String>,
+
+        /// Generate changelog entries from the titles of merged pull requests instead of the subjects of the commits they contain.
+        ///
+        /// Only the first parent of each commit is followed, and 'Merge pull request #N' commits use the pull request title.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_pull_request_titles: bool,
+
+        /// Treat '(#123)' references in commit titles as pull requests instead of issues, as is common with squash merges.
+        ///
+        /// References named by 'Pull-Request: #123' or 'PR: #123' trailers are always considered pull requests.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_references_are_pull_requests: bool,
+
+        /// Append the mailmap-resolved name of the commit author to each generated changelog entry, and count the
+        /// distinct authors of each release in its commit statistics.
+        ///
+        /// This can also be enabled for the whole workspace with `changelog-authors = true` in `[workspace.metadata.smart-release]`.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_authors: bool,
+
+        /// Write a commit-graph with 'git' if the repository doesn't have one yet, which makes traversing long histories
+        /// to generate changelogs several times faster.
+        ///
+        /// An existing commit-graph is always used. This can also be enabled for the whole workspace with
+        /// `write-commit-graph = true` in `[workspace.metadata.smart-release]`.
+        #[clap(long, help_heading = Some("EXPERT"))]
+        write_commit_graph: bool,
+
+        /// Store the data used to track generated changelog content in a '.changelog-state.toml' file next to
+        /// each changelog, instead of '<csr-*>' HTML tags in the changelog itself.
+        ///
+        /// This is implied for changelogs that already have such a file.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_sidecar: bool,
+
+        /// Write an HTML anchor like '<a id="v1.2.3"></a>' before the heading of each release, for stable links to versions.
+        ///
+        /// This is implied for changelogs that already contain such anchors.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_anchors: bool,
+
+        /// Only list commit titles in the commit details segment of changelogs, without the commit hashes.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        no_changelog_commit_hashes: bool,
+
+        /// The program to preview changelogs with instead of 'bat', like 'delta', 'glow -p' or 'less -R'.
+        ///
+        /// Arguments are separated by whitespace, and the path to the changelog is passed last.
+        /// Changelogs are printed as is if stdout isn't a terminal.
+        #[clap(long, value_name = "COMMAND", help_heading = Some("CHANGELOG"))]
+        changelog_preview_command: Option<String>,
+
         /// Sign off commit messages.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         signoff: bool,
 
+        /// Sign the release commit and the annotated release tags with `user.signingKey`, or the committer identity.
+        ///
+        /// Commits and tags are also signed if `commit.gpgSign` or `tag.gpgSign` are set in the git configuration.
+        /// Tags are always annotated when signed.
+        /// With `gpg.format = ssh`, `user.signingKey` is the path to a key or the public key of a key in the SSH agent,
+        /// and signatures are verified against `gpg.ssh.allowedSignersFile` if it is set.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        sign: bool,
+
         /// Prefix to add to start of commit messages.
         ///
         /// Useful to enforce commits created for the release are conventional.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         commit_prefix: Option<String>,
+
+        /// Use this message for the release commit instead of the generated one.
+        ///
+        /// `{message}` is replaced with the generated message and `{crates}` with the names and versions of the
+        /// released crates, like `--commit-message "PROJ-123: {message}"`.
+        /// It's also used for the changelog commit of --separate-changelog-commit, and --commit-prefix is still added.
+        #[clap(long, value_name = "MSG", help_heading = Some("CUSTOMIZATION"))]
+        commit_message: Option<String>,
     },
     #[clap(name = "changelog", version = option_env!("CARGO_SMART_RELEASE_VERSION"))]
     /// Generate changelogs from commit histories, non-destructively.
     ///
     /// Use --write to actually write generated changelogs
+    ///
+    /// Defaults for flags can be set in the `[changelog]` table of `~/.config/smart-release/config.toml`, or of
+    /// `$XDG_CONFIG_HOME/smart-release/config.toml`, with keys named like the flags, like `preview-command = "delta"`.
     Changelog {
         /// Actually write the changelog to the respective files
         #[clap(long, short = 'w', help_heading = Some("MAJOR"))]
@@ -204,6 +708,12 @@ pub enum SubCommands {
         #[clap(long, short = 'e', help_heading = Some("MAJOR"))]
         execute: bool,
 
+        /// Fail with the differences if the changelogs aren't what --write would make them, without writing them.
+        ///
+        /// This is useful in CI to assure changelogs are kept up to date with the commit history.
+        #[clap(long, conflicts_with_all = ["write", "execute"], help_heading = Some("MAJOR"))]
+        check: bool,
+
         /// omits these kinds of generated changelog content, values are 'clippy', 'commit-statistics' and 'commit-details'
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         without: Vec<String>,
@@ -215,22 +725,51 @@ pub enum SubCommands {
         #[clap(long, visible_alias = "only", help_heading = Some("CUSTOMIZATION"))]
         no_dependencies: bool,
 
+        /// Only include commits whose git-conventional scope matches this glob pattern, like `parser` in
+        /// `fix(parser): …` or `gix-*`. It can be given multiple times to include commits matching any of them.
+        ///
+        /// Commits without a scope are left out as well, which helps if crates share directories with unrelated
+        /// components.
+        #[clap(long, value_name = "PATTERN", help_heading = Some("CUSTOMIZATION"))]
+        scope: Vec<String>,
+
         /// The name of the crates to generate a changelog for.
         ///
         /// Defaults to the top-level workspace crate if unset.
         crates: Vec<String>,
 
+        #[clap(subcommand)]
+        subcommand: Option<ChangelogSubCommands>,
+
         /// Allow changelog updates to take place on a dirty working tree when --write is set as well.
         ///
         /// For now this is not recommended as changelogs might be damaged beyond repair.
-        #[clap(long, short = 'd', help_heading = Some("EXPERT"))]
-        allow_dirty: bool,
-
-        /// If --write is not set, 'bat' will be used (if available) to print the new changelog to stdout as preview. Use this flag
+        /// With `--allow-dirty=<path>`, only changes at or below the repository-relative path are allowed.
+        #[clap(
+            long,
+            short = 'd',
+            value_name = "PATH",
+            num_args = 0..=1,
+            require_equals = true,
+            action = clap::ArgAction::Append,
+            help_heading = Some("EXPERT")
+        )]
+        allow_dirty: Option<Vec<std::path::PathBuf>>,
+
+        /// If --write is not set, 'bat' or --preview-command will be used (if available) to print the new changelog to stdout as preview. Use this flag
         /// to disable such behaviour.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         no_preview: bool,
 
+        /// Show a progress bar while traversing the history, which is ignored if stderr isn't a terminal.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        progress: bool,
+
+        /// Print the wall-time spent in each phase at the end of the run, like collecting metadata, traversing the
+        /// history and generating the changelog of each crate.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        stats: bool,
+
         /// Do not generate links to commits and issues when writing the changelogs. This currently only works for GitHub.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         no_links: bool,
@@ -238,5 +777,222 @@ pub enum SubCommands {
         /// Capitalize commit messages.
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         capitalize_commit: bool,
+
+        /// Remove a single trailing period from the titles of generated changelog entries.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        strip_trailing_period: bool,
+
+        /// Turn the titles of generated changelog entries into imperative mood, like 'Added foo' or 'Adds foo' into 'Add foo'.
+        ///
+        /// This is a heuristic which only knows common verbs, and leaves titles starting with other words unchanged.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        imperative_mood: bool,
+
+        /// The language of generated changelog headings, 'en', 'de', 'fr' or 'es', or the path to a TOML table of headings.
+        ///
+        /// Tables map git-conventional kinds like 'fix' and 'feat', as well as 'statistics', 'details', 'clippy' and
+        /// 'breaking' for hand-written breaking changes, to their heading, like `fix = "Bug Fixes"`. Unlisted ones keep
+        /// their English heading.
+        /// Headings of the built-in languages and of the given table are understood when parsing existing changelogs.
+        #[clap(long, value_name = "LOCALE", help_heading = Some("CUSTOMIZATION"))]
+        locale: Option<String>,
+
+        /// Generate changelog entries from the titles of merged pull requests instead of the subjects of the commits they contain.
+        ///
+        /// Only the first parent of each commit is followed, and 'Merge pull request #N' commits use the pull request title.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        pull_request_titles: bool,
+
+        /// Treat '(#123)' references in commit titles as pull requests instead of issues, as is common with squash merges.
+        ///
+        /// References named by 'Pull-Request: #123' or 'PR: #123' trailers are always considered pull requests.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        references_are_pull_requests: bool,
+
+        /// Append the mailmap-resolved name of the commit author to each generated changelog entry, and count the
+        /// distinct authors of each release in its commit statistics.
+        ///
+        /// This can also be enabled for the whole workspace with `changelog-authors = true` in `[workspace.metadata.smart-release]`.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        authors: bool,
+
+        /// Write a commit-graph with 'git' if the repository doesn't have one yet, which makes traversing long histories
+        /// to generate changelogs several times faster.
+        ///
+        /// An existing commit-graph is always used. This can also be enabled for the whole workspace with
+        /// `write-commit-graph = true` in `[workspace.metadata.smart-release]`.
+        #[clap(long, help_heading = Some("EXPERT"))]
+        write_commit_graph: bool,
+
+        /// Store the data used to track generated changelog content in a '.changelog-state.toml' file next to
+        /// each changelog, instead of '<csr-*>' HTML tags in the changelog itself.
+        ///
+        /// This is implied for changelogs that already have such a file.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        sidecar: bool,
+
+        /// Write an HTML anchor like '<a id="v1.2.3"></a>' before the heading of each release, for stable links to versions.
+        ///
+        /// This is implied for changelogs that already contain such anchors.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        anchors: bool,
+
+        /// Only list commit titles in the commit details segment, without the commit hashes.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        no_commit_hashes: bool,
+
+        /// The program to preview changelogs with instead of 'bat', like 'delta', 'glow -p' or 'less -R'.
+        ///
+        /// Arguments are separated by whitespace, and the path to the changelog is passed last.
+        /// Changelogs are printed as is if stdout isn't a terminal.
+        #[clap(long, value_name = "COMMAND", help_heading = Some("CUSTOMIZATION"))]
+        preview_command: Option<String>,
+
+        /// The release channel whose tags delimit the changelog sections, like 'nightly', instead of the 'stable' one.
+        #[clap(long, value_name = "CHANNEL", help_heading = Some("CUSTOMIZATION"))]
+        channel: Option<String>,
+
+        /// Show traces of changelog generation, and those of its dependencies as well if passed twice.
+        ///
+        /// Messages can also be filtered by module with `RUST_LOG`, like
+        /// `RUST_LOG=cargo_smart_release::changelog=trace`, which takes precedence.
+        #[clap(long, short = 'v', action = clap::ArgAction::Count, help_heading = Some("CUSTOMIZATION"))]
+        verbose: u8,
+
+        /// Show only warnings and errors, only errors if passed twice, and nothing if passed three times.
+        #[clap(long, short = 'q', action = clap::ArgAction::Count, conflicts_with = "verbose", help_heading = Some("CUSTOMIZATION"))]
+        quiet: u8,
+    },
+}
+
+#[derive(clap::Subcommand)]
+pub enum ChangelogSubCommands {
+    #[clap(name = "show")]
+    /// Print the notes of a single release of a crate from its changelog, like to create a release on GitHub with them.
+    Show {
+        /// The name of the crate to show the release notes of.
+        #[clap(value_name = "CRATE")]
+        crate_name: String,
+
+        /// The version of the release like '1.2.3' or 'v1.2.3', or 'unreleased' for the unreleased changes.
+        version: String,
+
+        /// Do not link commits and issues, leaving it to the hosting platform to auto-link them.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        no_links: bool,
+
+        /// The parts to show along with the notes, 'title' for the heading of the release, 'details' for the commit
+        /// details in collapsible HTML tags and 'html-tags' for the tags tracking generated content.
+        #[clap(long, value_delimiter = ',', default_value = "details", help_heading = Some("CUSTOMIZATION"))]
+        components: Vec<String>,
+    },
+    #[clap(name = "preview")]
+    /// Print the changelog of a crate as it would be written, rendered for the terminal with styled headings, bullets
+    /// and code, without writing it.
+    Preview {
+        /// The name of the crate to preview the changelog of.
+        ///
+        /// Defaults to the top-level workspace crate if unset.
+        #[clap(value_name = "CRATE")]
+        crate_name: Option<String>,
+    },
+}
+
+#[derive(clap::Subcommand)]
+pub enum SmartReleaseSubCommands {
+    #[clap(name = "yank")]
+    /// Yank a published version of a workspace crate and mark it as yanked in its changelog.
+    ///
+    /// Use --execute to actually perform the operation.
+    Yank {
+        /// Actually yank the version and change the changelog. Dry-run mode is the default
+        #[clap(long, short = 'e', help_heading = Some("MAJOR"))]
+        execute: bool,
+
+        /// The name of the crate to yank a version of.
+        #[clap(value_name = "CRATE")]
+        crate_name: String,
+
+        /// The version to yank.
+        version: semver::Version,
+
+        /// Alternative registry to yank from.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        registry: Option<String>,
+
+        /// Don't mark the section of the version as yanked in the changelog of the crate.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        no_changelog: bool,
+
+        /// List the workspace crates that require exactly the yanked version, along with the command to give them a
+        /// patch release once they depend on a fixed version.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        plan_dependents: bool,
+    },
+    #[clap(name = "status")]
+    /// Show the latest release of each workspace crate, the kinds of commits since then, and whether it should be released.
+    Status {
+        /// The name of the crates to show, or all workspace crates if unset.
+        crates: Vec<String>,
+    },
+    #[clap(name = "plan")]
+    /// Print the complete release plan without performing any of it, for CI gates and release dashboards.
+    ///
+    /// The plan lists all crates of the release with their current and next versions, the reason for not publishing
+    /// them or the dependencies whose breaking changes cause their bump, along with the publish order and the tags to
+    /// create. Options of the release go before the subcommand, like in `cargo smart-release --bump minor plan gix`.
+    Plan {
+        /// The format to print the plan in, only `json` is supported.
+        #[clap(long, value_name = "FORMAT", default_value = "json")]
+        format: String,
+
+        /// The name of the crates to plan the release of.
+        crates: Vec<String>,
+    },
+    #[clap(name = "init")]
+    /// Prepare the workspace for releases by creating missing changelogs from the history and adding a starter
+    /// `[workspace.metadata.smart-release]` to the workspace manifest.
+    ///
+    /// Existing changelogs and configuration are kept.
+    Init {
+        /// Actually create the files. Dry-run mode is the default.
+        #[clap(long, short = 'e')]
+        execute: bool,
+
+        /// Merge changelogs by keeping the lines of both sides instead of conflicting, with the `union` merge driver
+        /// configured in `.gitattributes`.
+        ///
+        /// Generated sections are written again with the next release, so only hand-written changes need to be kept.
+        #[clap(long)]
+        merge_driver: bool,
+
+        /// The name of the crates to create changelogs for, or all workspace crates if unset.
+        crates: Vec<String>,
+    },
+    #[clap(name = "undo")]
+    /// Revert the local effects of a release that didn't finish, to start over after it was aborted.
+    ///
+    /// The tags it created are deleted and its commit is reset, restoring the manifests and changelogs, as recorded in
+    /// its journal. This isn't possible once it was pushed or a crate was published, use --resume to finish it then.
+    Undo {
+        /// Actually undo the release. Dry-run mode is the default.
+        #[clap(long, short = 'e')]
+        execute: bool,
+
+        /// The release was performed with --no-publish, so the crates it recorded as published weren't published.
+        ///
+        /// This is implied if `no-publish = true` is set in `[workspace.metadata.smart-release]`.
+        #[clap(long)]
+        no_publish: bool,
+    },
+    #[clap(name = "doctor", visible_alias = "verify")]
+    /// Check everything needed for the release to succeed without changing anything, and print a pass/fail report.
+    ///
+    /// The checks cover the registry token, crate ownership, a clean worktree, a reachable remote, the metadata needed
+    /// to publish, parseable changelogs and existing tags. Options of the release go before the subcommand, like in
+    /// `cargo smart-release --no-push doctor gix`. It fails if one of the checks fails.
+    Doctor {
+        /// The name of the crates to check the release of.
+        crates: Vec<String>,
     },
 }
@@ -0,0 +1,78 @@
+//! Whether to color output like log messages, diffs and changelog previews.
+//!
+//! Like progress, the choice applies to the whole process and is made once with [`set()`], usually by the binary from
+//! `--color`. Until then, output is colored if it goes to a terminal and `NO_COLOR` isn't set.
+use std::{ffi::OsString, io::IsTerminal, sync::OnceLock};
+
+/// When to color output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Color output if it goes to a terminal, unless the `NO_COLOR` environment variable is set to a non-empty value.
+    #[default]
+    Auto,
+    /// Always color output, even if it's captured.
+    Always,
+    /// Never color output.
+    Never,
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+/// Color output according to `mode` from now on, unless a mode was set already.
+pub fn set(mode: Mode) {
+    MODE.get_or_init(|| mode);
+}
+
+/// Return `true` if output written to stdout should be colored.
+pub fn stdout() -> bool {
+    is_enabled(mode(), std::io::stdout().is_terminal(), std::env::var_os("NO_COLOR"))
+}
+
+/// Return `true` if output written to stderr should be colored.
+pub fn stderr() -> bool {
+    is_enabled(mode(), std::io::stderr().is_terminal(), std::env::var_os("NO_COLOR"))
+}
+
+/// Return the `--color` argument to pass to programs like `cargo` or `bat` for their output to stdout or stderr to
+/// match, given `colored`.
+pub(crate) fn arg(colored: bool) -> &'static str {
+    if colored {
+        "--color=always"
+    } else {
+        "--color=never"
+    }
+}
+
+fn mode() -> Mode {
+    MODE.get().copied().unwrap_or_default()
+}
+
+fn is_enabled(mode: Mode, is_terminal: bool, no_color: Option<OsString>) -> bool {
+    match mode {
+        Mode::Always => true,
+        Mode::Never => false,
+        Mode::Auto => is_terminal && no_color.is_none_or(|value| value.is_empty()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_enabled, Mode};
+
+    #[test]
+    fn auto_colors_terminals_unless_no_color_is_set() {
+        assert!(is_enabled(Mode::Auto, true, None));
+        assert!(
+            is_enabled(Mode::Auto, true, Some("".into())),
+            "empty values are ignored"
+        );
+        assert!(!is_enabled(Mode::Auto, true, Some("1".into())));
+        assert!(!is_enabled(Mode::Auto, false, None));
+    }
+
+    #[test]
+    fn explicit_modes_override_terminal_detection_and_no_color() {
+        assert!(is_enabled(Mode::Always, false, Some("1".into())));
+        assert!(!is_enabled(Mode::Never, true, None));
+    }
+}
@@ -2,7 +2,10 @@ use std::io::Write;
 
 use crate::{
     bat,
-    changelog::write::{Components, Linkables},
+    changelog::{
+        write,
+        write::{Components, Linkables},
+    },
     command::changelog::Options,
     git,
     traverse::dependency,
@@ -16,14 +19,44 @@ pub fn changelog(opts: Options, crates: Vec<String>) -> anyhow::Result<()> {
         generator_segments,
         dependencies,
         dry_run,
+        check,
         preview,
+        ref preview_command,
+        render,
         no_links,
         capitalize_commit,
+        strip_trailing_period,
+        imperative_mood,
+        ref locale,
+        pull_request_titles,
+        references_are_pull_requests,
+        authors,
+        write_commit_graph,
+        sidecar,
+        anchors,
+        commit_hashes,
+        ref scopes,
         ..
     } = opts;
     let bump_spec = if dependencies { BumpSpec::Auto } else { BumpSpec::Keep };
-    let force_history_segmentation = false;
-    let ctx = crate::Context::new(crates.clone(), force_history_segmentation, bump_spec, bump_spec)?;
+    let ctx = crate::Context::new(
+        crates.clone(),
+        crate::context::Options {
+            bump: bump_spec,
+            bump_dependencies: bump_spec,
+            history: git::history::Options {
+                pull_request_titles,
+                title_references_are_pull_requests: references_are_pull_requests,
+                authors,
+                write_commit_graph,
+                scopes: scopes.clone(),
+                ..Default::default()
+            },
+            version_scheme: opts.version_scheme.clone(),
+            changelog_locale: locale.clone(),
+            ..Default::default()
+        },
+    )?;
     let crates: Vec<_> = {
         crate::traverse::dependencies(
             &ctx,
@@ -32,14 +65,16 @@ pub fn changelog(opts: Options, crates: Vec<String>) -> anyhow::Result<()> {
                 bump_when_needed: true,
                 isolate_dependencies_from_breaking_changes: true,
                 traverse_graph: dependencies,
+                dependent_bump: None,
+                force: false,
             },
         )?
         .into_iter()
         .filter_map(|d| match d.mode {
             dependency::Mode::ToBePublished { .. } => Some(d.package),
             dependency::Mode::NotForPublishing { .. } => {
-                if crates.contains(&d.package.name) {
-                    log::info!(
+                if ctx.crate_names.contains(&d.package.name) {
+                    tracing::info!(
                         "Skipping '{}' as it won't be published.{}",
                         d.package.name,
                         if !dependencies {
@@ -54,31 +89,47 @@ pub fn changelog(opts: Options, crates: Vec<String>) -> anyhow::Result<()> {
         })
         .collect()
     };
-    assure_working_tree_is_unchanged(opts)?;
-    let history = match git::history::collect(&ctx.repo)? {
+    if !check && !render {
+        assure_working_tree_is_unchanged(&opts)?;
+    }
+    let history = match crate::stats::time("traverse history", || {
+        git::history::collect(&ctx.repo, ctx.history_options.clone())
+    })? {
         None => return Ok(()),
         Some(history) => history,
     };
 
-    let bat = (dry_run && preview).then(bat::Support::new);
+    // Changelogs are checked against what would be written.
+    let simplified = dry_run && !check;
+    let bat = (simplified && preview && !render).then(|| bat::Support::new(preview_command.as_deref()));
 
     let mut pending_changes = Vec::new();
-    let linkables = if dry_run || no_links {
+    let mut pending_tracking_changes = Vec::new();
+    let linkables = if simplified || no_links {
         Linkables::AsText
     } else {
-        git::remote_url(&ctx.repo)?.map_or(Linkables::AsText, |url| Linkables::AsLinks {
+        git::remote_url(&ctx.repo, None)?.map_or(Linkables::AsText, |url| Linkables::AsLinks {
             repository_url: url.into(),
         })
     };
+    let mut out_of_date = Vec::new();
     let mut num_crates = 0;
     for (idx, package) in crates.iter().enumerate() {
         num_crates += 1;
         let crate::changelog::init::Outcome {
-            log, mut lock, state, ..
-        } = ChangeLog::for_package_with_write_lock(package, &history, &ctx, generator_segments)?;
-        log::info!(
-            "{} write {} sections to {} ({})",
-            will(dry_run),
+            log,
+            mut lock,
+            state,
+            tracking_lock,
+            previous_content,
+        } = ChangeLog::for_package_with_write_lock(package, &history, &ctx, generator_segments, sidecar)?;
+        tracing::info!(
+            "{} {} sections to {} ({})",
+            if check {
+                "Comparing".into()
+            } else {
+                format!("{} write", will(dry_run))
+            },
             log.sections.len(),
             lock.resource_path()
                 .strip_prefix(&ctx.root)
@@ -86,33 +137,74 @@ pub fn changelog(opts: Options, crates: Vec<String>) -> anyhow::Result<()> {
                 .display(),
             state.as_str(),
         );
+        let write_options = write::Options {
+            capitalize_commit,
+            locale: locale.clone(),
+            anchors: anchors || previous_content.as_deref().is_some_and(write::has_anchors),
+            omit_commit_hashes: !commit_hashes,
+            strip_trailing_period,
+            imperative_mood,
+        };
         lock.with_mut(|file| {
             let mut buf = String::new();
             log.write_to(
                 &mut buf,
                 &linkables,
-                if dry_run {
+                if simplified {
                     Components::SECTION_TITLE
+                } else if tracking_lock.is_some() {
+                    Components::all() - Components::HTML_TAGS
                 } else {
                     Components::all()
                 },
-                capitalize_commit,
+                &write_options,
             )
             .map_err(std::io::Error::other)?;
             file.write_all(buf.as_bytes())
         })?;
-        if let Some(bat) = bat.as_ref() {
+        if render {
+            let changelog = std::fs::read_to_string(lock.lock_path())?;
+            print!("{}", crate::markdown::render(&changelog, crate::color::stdout()));
+        } else if let Some(bat) = bat.as_ref() {
             bat.display_to_tty(
                 lock.lock_path(),
                 lock.resource_path().strip_prefix(ctx.root.to_path_buf())?,
                 format!("PREVIEW {} / {}, press Ctrl+C to cancel", idx + 1, crates.len()),
             )?;
         }
-        if !dry_run {
+        if check {
+            let changelog = std::fs::read_to_string(lock.lock_path())?;
+            let tracking = tracking_lock
+                .as_ref()
+                .map(|lock| {
+                    let previous = std::fs::read_to_string(lock.resource_path()).ok();
+                    (previous, log.tracking_state(&write_options).to_toml())
+                })
+                .filter(|(previous, current)| previous.as_ref() != Some(current));
+            let path = lock.resource_path();
+            let path = path.strip_prefix(ctx.root.as_std_path()).unwrap_or(&path);
+            if let Some(diff) = crate::diff::unified(
+                &path.display().to_string(),
+                previous_content.as_deref(),
+                &changelog,
+                crate::color::stdout(),
+            )? {
+                out_of_date.push((package.name.as_str(), diff));
+            } else if tracking.is_some() {
+                out_of_date.push((package.name.as_str(), String::new()));
+            }
+        } else if !dry_run {
             pending_changes.push(lock);
+            if let Some(mut tracking_lock) = tracking_lock {
+                tracking_lock.write_all(log.tracking_state(&write_options).to_toml().as_bytes())?;
+                pending_tracking_changes.push(tracking_lock);
+            }
         }
     }
 
+    if check {
+        return report_check(num_crates, &out_of_date);
+    }
     if num_crates == 0 {
         anyhow::bail!(
             "The given crate{} {} didn't change and no changelog could be generated.",
@@ -126,23 +218,51 @@ pub fn changelog(opts: Options, crates: Vec<String>) -> anyhow::Result<()> {
     }
 
     let num_changes = pending_changes.len();
-    for change in pending_changes {
+    for change in pending_changes.into_iter().chain(pending_tracking_changes) {
         change.commit()?;
     }
     if num_changes != 0 {
-        log::info!("Wrote {num_changes} changelogs");
+        tracing::info!("Wrote {num_changes} changelogs");
     }
 
     Ok(())
 }
 
-fn assure_working_tree_is_unchanged(options: Options) -> anyhow::Result<()> {
+/// Print the diffs of the changelogs that are `out_of_date`, given along with the name of their crate, and fail if there
+/// is at least one.
+fn report_check(num_crates: usize, out_of_date: &[(&str, String)]) -> anyhow::Result<()> {
+    if out_of_date.is_empty() {
+        tracing::info!(
+            "{num_crates} changelog{} up to date",
+            if num_crates == 1 { " is" } else { "s are" }
+        );
+        return Ok(());
+    }
+    let mut stdout = std::io::stdout().lock();
+    for (_, diff) in out_of_date {
+        stdout.write_all(diff.as_bytes())?;
+    }
+    anyhow::bail!(
+        "The changelog{} of {} {} out of date, update {} with 'cargo changelog --write {}'",
+        if out_of_date.len() == 1 { "" } else { "s" },
+        out_of_date
+            .iter()
+            .map(|(name, _)| format!("'{name}'"))
+            .collect::<Vec<_>>()
+            .join(", "),
+        if out_of_date.len() == 1 { "is" } else { "are" },
+        if out_of_date.len() == 1 { "it" } else { "them" },
+        out_of_date.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(" ")
+    )
+}
+
+fn assure_working_tree_is_unchanged(options: &Options) -> anyhow::Result<()> {
     if options.allow_dirty {
         Ok(())
     } else {
-        crate::git::assure_clean_working_tree().or_else(|err|
+        crate::git::assure_clean_working_tree(&options.allow_dirty_paths).or_else(|err|
         if options.dry_run {
-            log::warn!("The working tree has changes which will prevent changelog updates with --write unless --allow-dirty is also specified. The latter isn't recommended.");
+            tracing::warn!("The working tree has changes which will prevent changelog updates with --write unless --allow-dirty is also specified. The latter isn't recommended.");
             Ok(())
         } else {
             Err(err)
@@ -0,0 +1,214 @@
+use std::io::Write;
+
+use anyhow::Context as _;
+
+use crate::{
+    changelog::{
+        section::segment,
+        write::{Components, Linkables},
+    },
+    command::init::Options,
+    git,
+    utils::{changelog_path, package_by_name, will},
+    ChangeLog,
+};
+
+/// The block added to the workspace manifest, to show where defaults for the flags of `cargo smart-release` go.
+const WORKSPACE_METADATA: &str = r#"
+[workspace.metadata.smart-release]
+# Defaults for the flags of `cargo smart-release`, named like them, which are used unless the flag is given.
+# no-push = true
+# bump = ["minor"]
+# changelog-without = ["clippy"]
+"#;
+
+/// The name of the merge driver git provides to keep the lines of both sides instead of conflicting.
+const MERGE_DRIVER: &str = "union";
+
+/// Prepare the workspace for releases with `cargo smart-release` by creating the changelogs of `crates`, or of all
+/// workspace crates if empty, that don't have one yet, and by adding `[workspace.metadata.smart-release]` to the
+/// workspace manifest.
+///
+/// With `merge_driver`, changelogs are merged by keeping the lines of both sides, as configured in `.gitattributes`.
+pub fn init(
+    Options {
+        dry_run,
+        merge_driver,
+        write_options,
+        sidecar,
+    }: Options,
+    crates: Vec<String>,
+) -> anyhow::Result<()> {
+    let crates = if crates.is_empty() {
+        cargo_metadata::MetadataCommand::new()
+            .no_deps()
+            .exec()?
+            .workspace_packages()
+            .into_iter()
+            .map(|package| package.name.to_string())
+            .collect()
+    } else {
+        crates
+    };
+    let ctx = crate::Context::new(
+        crates,
+        crate::context::Options {
+            force_history_segmentation: true,
+            changelog_locale: write_options.locale.clone(),
+            ..Default::default()
+        },
+    )?;
+
+    let linkables = git::remote_url(&ctx.repo, None)?.map_or(Linkables::AsText, |url| Linkables::AsLinks {
+        repository_url: url.into(),
+    });
+    let mut changelog_names = Vec::new();
+    for name in &ctx.crate_names {
+        let package = package_by_name(&ctx.meta, name)?;
+        let path = changelog_path(package, &ctx.meta);
+        if let Some(file_name) = path.file_name().map(ToOwned::to_owned) {
+            if !changelog_names.contains(&file_name) {
+                changelog_names.push(file_name);
+            }
+        }
+        let relative_path = path.strip_prefix(&ctx.root).unwrap_or(&path);
+        if path.is_file() {
+            tracing::info!("Keeping the existing changelog at {relative_path}");
+            continue;
+        }
+        let history = ctx
+            .history
+            .as_ref()
+            .context("Did not have access to the Git history - please assure to not be on a detached HEAD")?;
+        let crate::changelog::init::Outcome {
+            log,
+            mut lock,
+            tracking_lock,
+            ..
+        } = ChangeLog::for_package_with_write_lock(package, history, &ctx, segment::Selection::all(), sidecar)?;
+        tracing::info!(
+            "{} create the changelog at {relative_path} with {} sections",
+            will(dry_run),
+            log.sections.len()
+        );
+        if dry_run {
+            continue;
+        }
+        let components = if tracking_lock.is_some() {
+            Components::all() - Components::HTML_TAGS
+        } else {
+            Components::all()
+        };
+        let mut buf = String::new();
+        log.write_to(&mut buf, &linkables, components, &write_options)?;
+        lock.write_all(buf.as_bytes())?;
+        lock.commit()?;
+        if let Some(mut tracking_lock) = tracking_lock {
+            tracking_lock.write_all(log.tracking_state(&write_options).to_toml().as_bytes())?;
+            tracking_lock.commit()?;
+        }
+    }
+
+    add_workspace_metadata(&ctx.root.join("Cargo.toml"), dry_run)?;
+    if merge_driver {
+        let workdir = ctx
+            .repo
+            .workdir()
+            .context("Can only configure merge drivers in non-bare repositories")?;
+        let names: Vec<_> = changelog_names.iter().map(String::as_str).collect();
+        register_merge_driver(&workdir.join(".gitattributes"), &names, dry_run)?;
+    }
+    Ok(())
+}
+
+/// Add [`WORKSPACE_METADATA`] to the workspace manifest at `path` unless it has `[workspace.metadata.smart-release]`.
+fn add_workspace_metadata(path: &cargo_metadata::camino::Utf8Path, dry_run: bool) -> anyhow::Result<()> {
+    let manifest = std::fs::read_to_string(path)?;
+    let doc: toml_edit::DocumentMut = manifest
+        .parse()
+        .with_context(|| format!("Could not parse the workspace manifest at '{path}'"))?;
+    if doc
+        .get("workspace")
+        .and_then(|workspace| workspace.get("metadata"))
+        .and_then(|metadata| metadata.get("smart-release"))
+        .is_some()
+    {
+        tracing::info!("Keeping the existing [workspace.metadata.smart-release] in {path}");
+        return Ok(());
+    }
+    tracing::info!("{} add [workspace.metadata.smart-release] to {path}", will(dry_run));
+    if !dry_run {
+        let separator = if manifest.ends_with('\n') { "" } else { "\n" };
+        std::fs::write(path, format!("{manifest}{separator}{WORKSPACE_METADATA}"))?;
+    }
+    Ok(())
+}
+
+/// Add a line for each of `changelog_names` to the `.gitattributes` file at `path` to merge them with [`MERGE_DRIVER`],
+/// unless it's there already.
+fn register_merge_driver(path: &std::path::Path, changelog_names: &[&str], dry_run: bool) -> anyhow::Result<()> {
+    let attributes = match std::fs::read_to_string(path) {
+        Ok(attributes) => attributes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    let missing = missing_attributes(&attributes, changelog_names);
+    if missing.is_empty() {
+        tracing::info!("Changelogs are merged with the '{MERGE_DRIVER}' merge driver already");
+        return Ok(());
+    }
+    tracing::info!(
+        "{} merge {} with the '{MERGE_DRIVER}' merge driver as configured in {}",
+        will(dry_run),
+        changelog_names.join(", "),
+        path.display()
+    );
+    if !dry_run {
+        let separator = if attributes.is_empty() || attributes.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        std::fs::write(path, format!("{attributes}{separator}{}", missing.concat()))?;
+    }
+    Ok(())
+}
+
+/// Return the lines to add to `attributes` so all files named like one of `changelog_names` are merged with
+/// [`MERGE_DRIVER`].
+fn missing_attributes(attributes: &str, changelog_names: &[&str]) -> Vec<String> {
+    changelog_names
+        .iter()
+        .filter(|name| {
+            !attributes.lines().any(|line| {
+                let mut fields = line.split_whitespace();
+                fields.next() == Some(**name) && fields.any(|field| field == format!("merge={MERGE_DRIVER}"))
+            })
+        })
+        .map(|name| format!("{name} merge={MERGE_DRIVER}\n"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::missing_attributes;
+
+    #[test]
+    fn only_changelogs_without_the_merge_driver_are_added() {
+        assert_eq!(
+            missing_attributes("", &["CHANGELOG.md"]),
+            vec!["CHANGELOG.md merge=union\n".to_owned()]
+        );
+        assert_eq!(
+            missing_attributes(
+                "*.png binary\nCHANGELOG.md merge=union\n",
+                &["CHANGELOG.md", "HISTORY.md"]
+            ),
+            vec!["HISTORY.md merge=union\n".to_owned()]
+        );
+        assert_eq!(
+            missing_attributes("CHANGELOG.md text", &["CHANGELOG.md"]),
+            vec!["CHANGELOG.md merge=union\n".to_owned()]
+        );
+    }
+}
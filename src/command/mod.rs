@@ -1,15 +1,24 @@
 pub mod release {
-    use crate::changelog::section::segment;
+    use crate::changelog::{locale::Locale, section::segment};
 
     #[derive(Debug, Clone)]
     pub struct Options {
         pub dry_run: bool,
         pub allow_dirty: bool,
+        /// Repository-relative paths which may have changes, along with everything below them, unlike the rest of the
+        /// working tree.
+        pub allow_dirty_paths: Vec<std::path::PathBuf>,
+        /// Stash changes in the worktree before the release and restore them afterwards, instead of refusing to release.
+        pub autostash: bool,
         pub ignore_instability: bool,
         pub skip_publish: bool,
+        /// The names of crates which are released without publishing them, unlike the others.
+        pub changelog_only: Vec<String>,
+        /// The file to write a JSON summary of the outcome of the release to once it's done.
+        pub summary: Option<std::path::PathBuf>,
         pub dry_run_cargo_publish: bool,
         pub conservative_pre_release_version_handling: bool,
-        /// Pass --no-verify unconditionally to cargo publish. Really just for fixing things
+        /// Pass --no-verify unconditionally to cargo package. Really just for fixing things
         pub no_verify: bool,
         pub skip_tag: bool,
         pub allow_auto_publish_of_stable_crates: bool,
@@ -17,42 +26,387 @@ pub mod release {
         pub bump_when_needed: bool,
         pub verbose: bool,
         pub skip_push: bool,
+        /// Continue a previous release that didn't finish from where it stopped, as recorded in its journal.
+        pub resume: bool,
+        /// Compute the release plan without accessing the network.
+        pub offline: bool,
+        /// Require the user to type a code before releasing with `--execute`.
+        pub confirm: bool,
+        /// Accept all prompts without asking, and fail instead of waiting for input that would otherwise be required.
+        pub yes: bool,
+        /// Don't package all crates to publish before publishing the first one, nor check that they are owned by the user.
+        pub skip_preflight: bool,
+        /// How long publishing a crate may take, and how to retry it if it fails.
+        pub publish_policy: PublishPolicy,
+        /// How long to wait for published crates to arrive in the index and to be available for download.
+        pub availability_timeout: std::time::Duration,
+        /// Don't download published crates from crates.io to verify they match the uploaded `.crate` files.
+        pub skip_checksum_verification: bool,
         pub dependencies: bool,
         pub isolate_dependencies_from_breaking_changes: bool,
         pub changelog: bool,
         pub preview: bool,
+        /// The program and arguments to preview changelogs with instead of `bat`.
+        pub preview_command: Option<String>,
         pub generator_segments: segment::Selection,
         pub allow_fully_generated_changelogs: bool,
         pub allow_empty_release_message: bool,
         pub changelog_links: bool,
         pub allow_changelog_github_release: bool,
         pub capitalize_commit: bool,
+        /// Remove a single trailing period from generated commit titles.
+        pub changelog_strip_trailing_period: bool,
+        /// Turn generated commit titles starting with known verbs into imperative mood.
+        pub changelog_imperative_mood: bool,
+        /// The language of generated changelog headings.
+        pub changelog_locale: Locale,
+        /// Use the titles of merged pull requests instead of the subjects of the commits they contain.
+        pub changelog_pull_request_titles: bool,
+        /// Link `(#123)` references in commit titles to pull requests instead of issues.
+        pub changelog_references_are_pull_requests: bool,
+        /// Attribute generated changelog entries to their commit author.
+        pub changelog_authors: bool,
+        /// Write a commit-graph if the repository doesn't have one yet, to traverse the history faster.
+        pub write_commit_graph: bool,
+        /// Store changelog tracking data in a sidecar file instead of HTML tags in the changelog.
+        pub changelog_sidecar: bool,
+        /// Write an HTML anchor before each release heading.
+        pub changelog_anchors: bool,
+        /// List commit hashes along with their titles in the commit details segment.
+        pub changelog_commit_hashes: bool,
         pub registry: Option<String>,
+        /// Registries to publish each crate to after publishing it to `registry`, like internal mirrors.
+        pub mirror_registries: Vec<String>,
+        /// The remote to push the release commit and tags to, instead of the push remote of `HEAD`.
+        pub remote: Option<String>,
+        /// Create the release commit on a new branch named after this template and push it instead of the current branch,
+        /// with `{name}` and `{version}` replaced by the crate released last.
+        pub release_branch: Option<String>,
+        /// Push the release commit and tags atomically, so either all of them are updated on the remote or none.
+        pub atomic_push: bool,
+        /// Sign pushes with a push certificate, or `None` to sign them as configured with `push.gpgSign`.
+        pub signed_push: Option<SignedPush>,
+        /// The identity like `Name <email>` to author, commit and tag the release as, instead of the one configured for git.
+        pub release_author: Option<String>,
+        /// Refuse to base a release on tags of previous releases without a signature trusted by `git verify-tag`.
+        pub verify_tags: bool,
+        /// What to do if a tag to create exists already.
+        pub on_existing_tag: TagConflict,
+        /// Run the `pre-commit`, `commit-msg` and `post-commit` git hooks when creating the release commit, and `pre-push`
+        /// when pushing it.
+        pub git_hooks: bool,
+        /// The branch to commit the release to and push, which is checked out and created at `HEAD` if needed.
+        pub branch: Option<String>,
+        /// Open a pull request for the release branch with `gh` once it was pushed.
+        pub open_pull_request: bool,
+        /// Push only the release branch and open the pull request, and publish with `--resume` once it was merged.
+        pub publish_after_merge: bool,
+        /// Replace the tagged release commit at `HEAD` with a new one, re-pointing its tags and force-pushing both.
+        pub replace_release: bool,
+        /// Amend the tagged release commit at `HEAD` and move its tags if neither were pushed, without asking.
+        pub amend_release: bool,
+        /// Commit the changelogs before and separately from the version changes in manifests.
+        pub separate_changelog_commit: bool,
+        /// The refs on the remote to push over with `--force-with-lease`, by the id they are expected to have, or an empty
+        /// string if they are expected not to exist.
+        pub force_with_lease: std::collections::BTreeMap<String, String>,
+        /// The cargo credential provider to obtain the registry token with, overriding the one in the cargo configuration.
+        pub credential_provider: Option<String>,
+        /// Sign published crate files and tags keylessly with `cosign`.
+        pub sigstore: bool,
         pub target: Option<String>,
         pub publish_uses_docs_rs_metadata: bool,
         pub signoff: bool,
+        /// Sign the release commit and tags as configured with `gpg.format`, in addition to signing them if `commit.gpgSign` or
+        /// `tag.gpgSign` are set.
+        pub sign: bool,
         pub commit_prefix: Option<String>,
+        /// The message of the release commit instead of the generated one, with `{message}` replaced by the generated
+        /// message and `{crates}` by the names and versions of the released crates.
+        pub commit_message: Option<String>,
+        /// Validate automatic version bumps with cargo-semver-checks, acting on unmarked breaking changes as configured.
+        pub semver_checks: Option<crate::semver_checks::Policy>,
+        /// Build metadata to append to released versions, with `{commit}` replaced by the short hash of `HEAD`.
+        pub build_metadata: Option<String>,
+        /// Bump specifications for individual crates by name, overriding the ones given for all crates.
+        pub bump_overrides: std::collections::BTreeMap<String, crate::version::BumpSpec>,
+        /// How to bump crates due to breaking changes in their dependencies, or `None` to use the workspace configuration.
+        pub dependent_bump: Option<crate::version::DependentBump>,
+        /// Log which commits and rules lead to the version bump of each crate.
+        pub explain: bool,
+        /// Print the dependency graph of the crates to release along with their publish order, instead of releasing them.
+        pub print_graph: Option<GraphFormat>,
+        /// Let the user toggle crates, adjust their bumps and preview their changelogs before releasing.
+        pub interactive: bool,
+        /// Print the complete release plan in the given format, instead of releasing.
+        pub plan_format: Option<PlanFormat>,
+        /// Check everything needed for the release to succeed and print a report, instead of releasing.
+        pub doctor: bool,
+        /// Release provided crates even if they have no releasable changes since their last release.
+        pub force: bool,
+        /// The names of crates which are never bumped or published, in addition to the ones marked as `frozen` in their manifest.
+        pub pinned: Vec<String>,
+        /// Glob patterns of the names of crates not to select, even if they match the selected crates.
+        pub excluded: Vec<String>,
+        /// The way versions of new releases are computed, and how their tags and changelog sections are named.
+        pub version_scheme: std::rc::Rc<dyn crate::version::VersionScheme>,
+    }
+
+    /// How long publishing a crate may take, and how to retry it if it fails.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PublishPolicy {
+        /// Stop packaging or uploading a crate if it takes longer than this, and consider the attempt failed.
+        pub timeout: Option<std::time::Duration>,
+        /// How often to try publishing a crate again after it failed.
+        pub retries: usize,
+        /// How long to wait before each retry.
+        pub backoff: Backoff,
+    }
+
+    impl Default for PublishPolicy {
+        fn default() -> Self {
+            PublishPolicy {
+                timeout: None,
+                retries: 2,
+                backoff: Backoff::None,
+            }
+        }
+    }
+
+    /// How long to wait before retrying to publish a crate.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Backoff {
+        /// Retry right away.
+        None,
+        /// Always wait the same time.
+        Fixed(std::time::Duration),
+        /// Wait the given time before the first retry, and twice as long before each following one.
+        Exponential(std::time::Duration),
+    }
+
+    impl Backoff {
+        /// Parse a strategy like `none`, `fixed:<seconds>` or `exponential:<seconds>`.
+        pub fn from_spec(spec: &str) -> Option<Self> {
+            let (name, seconds) = match spec.split_once(':') {
+                Some((name, seconds)) => (name, Some(std::time::Duration::from_secs(seconds.parse().ok()?))),
+                None => (spec, None),
+            };
+            Some(match (name, seconds) {
+                ("none", None) => Backoff::None,
+                ("fixed", Some(delay)) => Backoff::Fixed(delay),
+                ("exponential", Some(delay)) => Backoff::Exponential(delay),
+                _unknown => return None,
+            })
+        }
+
+        /// Return how long to wait before the `retry`-th retry, starting at 1.
+        pub fn delay(&self, retry: usize) -> std::time::Duration {
+            match self {
+                Backoff::None => std::time::Duration::ZERO,
+                Backoff::Fixed(delay) => *delay,
+                Backoff::Exponential(delay) => {
+                    delay.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1).try_into().unwrap_or(u32::MAX)))
+                }
+            }
+        }
+    }
+
+    /// What to do if the tag of a release exists already, like after an aborted release.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum TagConflict {
+        /// Abort the release.
+        Fail,
+        /// Keep the existing tag, and don't push it.
+        Skip,
+        /// Replace the existing tag, unless it was pushed to the remote already.
+        ReplaceIfUnpushed,
+    }
+
+    impl TagConflict {
+        /// Parse `fail`, `skip` or `replace-if-unpushed`.
+        pub fn from_name(name: &str) -> Option<Self> {
+            Some(match name {
+                "fail" => TagConflict::Fail,
+                "skip" => TagConflict::Skip,
+                "replace-if-unpushed" => TagConflict::ReplaceIfUnpushed,
+                _unknown => return None,
+            })
+        }
+    }
+
+    /// Whether to sign pushes with a push certificate, for remotes which require certified pushes.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SignedPush {
+        /// Always sign, and fail if the remote doesn't support signed pushes.
+        Always,
+        /// Sign only if the remote supports signed pushes.
+        IfAsked,
+    }
+
+    impl SignedPush {
+        /// Parse `true` or `if-asked`, like the values of `push.gpgSign`.
+        pub fn from_name(name: &str) -> Option<Self> {
+            Some(match name {
+                "true" => SignedPush::Always,
+                "if-asked" => SignedPush::IfAsked,
+                _unknown => return None,
+            })
+        }
+    }
+
+    /// The name of the release branch if none is given, see [`Options::release_branch`].
+    pub const DEFAULT_RELEASE_BRANCH: &str = "release/{name}-{version}";
+
+    /// The format to print the dependency graph of the crates to release in.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum GraphFormat {
+        /// The Graphviz DOT language.
+        Dot,
+        /// A mermaid flowchart.
+        Mermaid,
+    }
+
+    impl GraphFormat {
+        /// Parse `dot` or `mermaid`.
+        pub fn from_name(name: &str) -> Option<Self> {
+            Some(match name {
+                "dot" => GraphFormat::Dot,
+                "mermaid" => GraphFormat::Mermaid,
+                _unknown => return None,
+            })
+        }
+    }
+
+    /// The format to print the release plan in.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum PlanFormat {
+        /// A pretty-printed JSON object.
+        Json,
+    }
+
+    impl PlanFormat {
+        /// Parse `json`.
+        pub fn from_name(name: &str) -> Option<Self> {
+            Some(match name {
+                "json" => PlanFormat::Json,
+                _unknown => return None,
+            })
+        }
     }
 }
 #[path = "release/mod.rs"]
 mod release_impl;
-pub use release_impl::release;
+pub use release_impl::{release, undo};
+
+pub mod undo {
+    #[derive(Debug, Clone)]
+    pub struct Options {
+        pub dry_run: bool,
+        /// The release was performed without publishing, so crates recorded as published weren't actually published.
+        pub skip_publish: bool,
+    }
+}
 
 pub mod changelog {
-    use crate::changelog::section::segment;
+    use crate::changelog::{locale::Locale, section::segment};
 
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone)]
     pub struct Options {
         pub dry_run: bool,
+        /// Fail if the changelogs differ from what would be written, without writing them.
+        pub check: bool,
         pub dependencies: bool,
         pub allow_dirty: bool,
+        /// Repository-relative paths which may have changes, along with everything below them.
+        pub allow_dirty_paths: Vec<std::path::PathBuf>,
         pub preview: bool,
+        /// The program and arguments to preview changelogs with instead of `bat`.
+        pub preview_command: Option<String>,
+        /// Print the changelogs rendered for the terminal instead of previewing them, without writing them.
+        pub render: bool,
         // All the segments to generate
         pub generator_segments: segment::Selection,
         pub no_links: bool,
         pub capitalize_commit: bool,
+        /// Remove a single trailing period from generated commit titles.
+        pub strip_trailing_period: bool,
+        /// Turn generated commit titles starting with known verbs into imperative mood.
+        pub imperative_mood: bool,
+        /// The language of generated changelog headings.
+        pub locale: Locale,
+        /// Use the titles of merged pull requests instead of the subjects of the commits they contain.
+        pub pull_request_titles: bool,
+        /// Link `(#123)` references in commit titles to pull requests instead of issues.
+        pub references_are_pull_requests: bool,
+        /// Attribute generated changelog entries to their commit author.
+        pub authors: bool,
+        /// Write a commit-graph if the repository doesn't have one yet, to traverse the history faster.
+        pub write_commit_graph: bool,
+        /// Store changelog tracking data in a sidecar file instead of HTML tags in the changelog.
+        pub sidecar: bool,
+        /// Write an HTML anchor before each release heading.
+        pub anchors: bool,
+        /// List commit hashes along with their titles in the commit details segment.
+        pub commit_hashes: bool,
+        /// If not empty, only commits with a git-conventional scope matching one of these glob patterns are included.
+        pub scopes: Vec<String>,
+        /// The way versions of new releases are computed, and how their tags and changelog sections are named.
+        pub version_scheme: std::rc::Rc<dyn crate::version::VersionScheme>,
     }
 }
 #[path = "changelog.rs"]
 mod changelog_impl;
 pub use changelog_impl::changelog;
+
+pub mod yank {
+    #[derive(Debug, Clone)]
+    pub struct Options {
+        pub dry_run: bool,
+        /// The registry to yank from instead of crates.io.
+        pub registry: Option<String>,
+        /// Mark the release as yanked in the changelog of the crate.
+        pub changelog: bool,
+        /// Log which workspace crates are pinned to the yanked version and need a follow-up release.
+        pub plan_dependents: bool,
+    }
+}
+#[path = "status.rs"]
+mod status_impl;
+pub use status_impl::status;
+
+pub mod show {
+    use crate::changelog::{locale::Locale, write::Components};
+
+    #[derive(Debug, Clone)]
+    pub struct Options {
+        /// Leave commits and issues unlinked for the hosting platform to link them.
+        pub no_links: bool,
+        /// The parts of the release section to write along with its notes.
+        pub components: Components,
+        /// The language of generated headings.
+        pub locale: Locale,
+    }
+}
+#[path = "show.rs"]
+mod show_impl;
+pub use show_impl::show;
+
+pub mod init {
+    #[derive(Debug, Clone)]
+    pub struct Options {
+        pub dry_run: bool,
+        /// Merge changelogs by keeping the lines of both sides, as configured in `.gitattributes`.
+        pub merge_driver: bool,
+        /// The way changelogs are written, like by `cargo smart-release`.
+        pub write_options: crate::changelog::write::Options,
+        /// Store changelog tracking data in a sidecar file instead of HTML tags in the changelog.
+        pub sidecar: bool,
+    }
+}
+#[path = "init.rs"]
+mod init_impl;
+pub use init_impl::init;
+
+#[path = "yank.rs"]
+mod yank_impl;
+pub use yank_impl::yank;
@@ -0,0 +1,209 @@
+//! Build the binaries of crates for the targets configured by the user, and package them for attachment to releases.
+use std::{io::Read, path::Path, process::Command};
+
+use anyhow::{bail, Context};
+use cargo_metadata::{camino::Utf8PathBuf, Package};
+use flate2::write::GzEncoder;
+use sha2::{Digest, Sha256};
+
+use crate::utils::will;
+
+/// Return the targets to build the binaries of `package` for, as configured with `binary-targets` in
+/// `[package.metadata.smart-release]`, or else in `[workspace.metadata.smart-release]`.
+///
+/// It's empty if `package` has no binaries, or if no targets are configured.
+pub(in crate::command::release_impl) fn binary_targets(
+    package: &Package,
+    ctx: &crate::Context,
+) -> anyhow::Result<Vec<String>> {
+    if !package.targets.iter().any(|target| target.is_bin()) {
+        return Ok(Vec::new());
+    }
+    match targets_in(&package.metadata)? {
+        Some(targets) => Ok(targets),
+        None => Ok(targets_in(&ctx.meta.workspace_metadata)?.unwrap_or_default()),
+    }
+}
+
+/// Build all binaries of `package` in release mode for each of `targets`, and put them into one `.tar.gz` archive per
+/// target, each with a `.sha256` file next to it.
+///
+/// Return the paths to all archives and checksum files, in that order for each target.
+pub(in crate::command::release_impl) fn build(
+    package: &Package,
+    version: &semver::Version,
+    targets: &[String],
+    ctx: &crate::Context,
+    dry_run: bool,
+) -> anyhow::Result<Vec<Utf8PathBuf>> {
+    let binaries: Vec<_> = package
+        .targets
+        .iter()
+        .filter(|target| target.is_bin())
+        .map(|target| target.name.as_str())
+        .collect();
+    let out_dir = ctx.meta.target_directory.join("smart-release");
+    if !dry_run {
+        std::fs::create_dir_all(&out_dir)?;
+    }
+    let mut files = Vec::new();
+    for target in targets {
+        let mut cmd = Command::new("cargo");
+        cmd.args(["build", "--release", "--bins", "--target", target, "--manifest-path"])
+            .arg(ctx.meta.workspace_root.join("Cargo.toml").as_std_path())
+            .args(["--package", package.name.as_str()]);
+        tracing::trace!("{} run {:?}", will(dry_run), cmd);
+        if !dry_run && !cmd.status()?.success() {
+            bail!("Failed to build the binaries of '{}' for '{target}'", package.name);
+        }
+
+        let archive = out_dir.join(archive_name(&package.name, version, target));
+        let bin_dir = ctx.meta.target_directory.join(target).join("release");
+        let file_names: Vec<_> = binaries.iter().map(|name| binary_file_name(name, target)).collect();
+        tracing::trace!(
+            "{} archive {file_names:?} from '{bin_dir}' into '{archive}'",
+            will(dry_run)
+        );
+        if !dry_run {
+            write_archive(archive.as_std_path(), bin_dir.as_std_path(), &file_names)
+                .with_context(|| format!("Failed to create archive at '{archive}'"))?;
+        }
+
+        let checksum_file = Utf8PathBuf::from(format!("{archive}.sha256"));
+        tracing::trace!("{} write SHA-256 checksum to '{checksum_file}'", will(dry_run));
+        if !dry_run {
+            let file =
+                std::fs::File::open(&archive).with_context(|| format!("Could not open '{archive}' for hashing"))?;
+            let file_name = archive.file_name().expect("archives have a file name");
+            std::fs::write(&checksum_file, checksum_line(file, file_name)?)?;
+        }
+        files.push(archive);
+        files.push(checksum_file);
+    }
+    Ok(files)
+}
+
+/// Write the files named `file_names` in `dir` into a gzip-compressed tar archive at `archive`, with their paths
+/// relative to `dir`.
+fn write_archive(archive: &Path, dir: &Path, file_names: &[String]) -> std::io::Result<()> {
+    let out = std::fs::File::create(archive)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(out, flate2::Compression::default()));
+    builder.mode(tar::HeaderMode::Deterministic);
+    for name in file_names {
+        builder.append_path_with_name(dir.join(name), name)?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn targets_in(metadata: &serde_json::Value) -> anyhow::Result<Option<Vec<String>>> {
+    let Some(value) = metadata
+        .get("smart-release")
+        .and_then(|config| config.get("binary-targets"))
+    else {
+        return Ok(None);
+    };
+    value
+        .as_array()
+        .and_then(|targets| {
+            targets
+                .iter()
+                .map(|target| target.as_str().map(ToOwned::to_owned))
+                .collect::<Option<Vec<_>>>()
+        })
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("'binary-targets' must be a list of target triples"))
+}
+
+fn archive_name(crate_name: &str, version: &semver::Version, target: &str) -> String {
+    format!("{crate_name}-v{version}-{target}.tar.gz")
+}
+
+fn binary_file_name(name: &str, target: &str) -> String {
+    if target.contains("windows") {
+        format!("{name}.exe")
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Return the line `sha256sum` would print for the file `file_name` with the contents of `file`, so the checksum file
+/// can be verified with `sha256sum -c`.
+fn checksum_line(file: impl Read, file_name: &str) -> anyhow::Result<String> {
+    Ok(format!("{}  {file_name}\n", sha256_hex(file)?))
+}
+
+/// Return the SHA-256 digest of everything read from `data` as lowercase hex string.
+pub(in crate::command::release_impl) fn sha256_hex(mut data: impl Read) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let bytes_read = data.read(&mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buf[..bytes_read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{archive_name, binary_file_name, checksum_line, targets_in, write_archive};
+
+    #[test]
+    fn targets_are_read_from_the_smart_release_table() {
+        let metadata =
+            json!({"smart-release": {"binary-targets": ["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"]}});
+        assert_eq!(
+            targets_in(&metadata).unwrap().unwrap(),
+            ["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"]
+        );
+        assert_eq!(targets_in(&json!({})).unwrap(), None);
+        assert!(targets_in(&json!({"smart-release": {"binary-targets": "x86_64-unknown-linux-gnu"}})).is_err());
+    }
+
+    #[test]
+    fn archives_are_named_after_crate_version_and_target() {
+        let version = semver::Version::new(1, 2, 3);
+        assert_eq!(
+            archive_name("tool", &version, "x86_64-pc-windows-msvc"),
+            "tool-v1.2.3-x86_64-pc-windows-msvc.tar.gz"
+        );
+        assert_eq!(binary_file_name("tool", "x86_64-pc-windows-msvc"), "tool.exe");
+        assert_eq!(binary_file_name("tool", "x86_64-unknown-linux-gnu"), "tool");
+    }
+
+    #[test]
+    fn archives_contain_the_binaries_by_file_name() {
+        let dir = std::env::temp_dir().join(format!("smart-release-archive-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tool"), b"binary").unwrap();
+        let archive = dir.join("tool.tar.gz");
+        write_archive(&archive, &dir, &["tool".into()]).unwrap();
+
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(std::fs::File::open(&archive).unwrap()));
+        let entries: Vec<_> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut content = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut content).unwrap();
+                (entry.path().unwrap().display().to_string(), content)
+            })
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(entries, [("tool".to_owned(), "binary".to_owned())]);
+    }
+
+    #[test]
+    fn checksums_are_written_like_sha256sum() {
+        assert_eq!(
+            checksum_line(&b"hello\n"[..], "tool.tar.gz").unwrap(),
+            "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03  tool.tar.gz\n"
+        );
+    }
+}
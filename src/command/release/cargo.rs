@@ -1,36 +1,61 @@
-use std::process::Command;
+use std::{
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
 
 use anyhow::bail;
-use cargo_metadata::Package;
+use cargo_metadata::{
+    camino::{Utf8Path, Utf8PathBuf},
+    Package,
+};
 
-use super::Options;
-use crate::utils::will;
+use super::upload::{self, Rejection};
+use crate::{
+    command::release::{Options, PublishPolicy},
+    utils::will,
+};
 
+/// Publish `publishee` at `new_version`, retrying according to the publish policy.
+///
+/// The crate is packaged with `cargo package` and uploaded to the registry by us, so the response of the registry tells
+/// why publishing failed.
 pub(in crate::command::release_impl) fn publish_crate(
     publishee: &Package,
+    new_version: &semver::Version,
     prevent_default_members: bool,
+    ctx: &crate::Context,
     Options {
         skip_publish,
         dry_run,
+        offline,
         dry_run_cargo_publish,
         allow_dirty,
+        allow_dirty_paths,
         no_verify,
         verbose,
         registry,
+        credential_provider,
         target,
         publish_uses_docs_rs_metadata,
+        publish_policy: PublishPolicy {
+            timeout,
+            retries,
+            backoff,
+        },
         ..
     }: Options,
 ) -> anyhow::Result<()> {
     if skip_publish {
         return Ok(());
     }
-    let max_attempts = 3;
-    let uses_cargo_dry_run = dry_run && dry_run_cargo_publish;
-    let cargo_must_run = !dry_run || uses_cargo_dry_run;
-    for attempt in 1..=max_attempts {
+    let packages_in_dry_run = dry_run && dry_run_cargo_publish;
+    let cargo_must_run = !dry_run || packages_in_dry_run;
+    let mut retry = 0;
+    let mut rate_limited_waits = 0;
+    loop {
         let mut c = Command::new("cargo");
-        c.arg("publish");
+        c.arg("package");
 
         if let Some(ref registry) = registry {
             c.arg("--registry").arg(registry);
@@ -42,35 +67,354 @@ pub(in crate::command::release_impl) fn publish_crate(
             c.args(docs_rs_metadata_publish_args(publishee)?);
         }
 
-        if allow_dirty {
+        // Changes to the allowed paths were checked before, but cargo would refuse to package them otherwise.
+        if allow_dirty || !allow_dirty_paths.is_empty() {
             c.arg("--allow-dirty");
         }
-        if no_verify {
+        if no_verify || skips_verification(publishee) {
             c.arg("--no-verify");
         }
-        if uses_cargo_dry_run {
-            c.arg("--dry-run");
-        }
         c.arg("--manifest-path").arg(&publishee.manifest_path);
         if prevent_default_members {
             c.arg("--package").arg(publishee.name.as_str());
         }
         if verbose {
-            log::trace!("{} run {:?}", will(!cargo_must_run), c);
+            tracing::trace!("{} run {:?}", will(!cargo_must_run), c);
         }
-        if !cargo_must_run || c.status()?.success() {
+        if !cargo_must_run {
             break;
-        } else if attempt == max_attempts || dry_run {
-            bail!("Could not successfully execute 'cargo publish'.")
-        } else {
-            log::warn!(
-                "'cargo publish' run {attempt} failed but we retry up to {max_attempts} times to rule out flakiness"
-            );
+        }
+        let rejection = match run_forwarding_stderr(c, timeout)? {
+            Run::Success if packages_in_dry_run => break,
+            Run::Success => {
+                let upload = upload::prepare(
+                    publishee,
+                    new_version,
+                    crate_file(&ctx.meta.target_directory, &publishee.name, new_version).as_std_path(),
+                    registry.as_deref(),
+                    credential_provider.as_deref(),
+                    ctx.root.as_std_path(),
+                )?;
+                match upload.send(timeout) {
+                    Ok(Ok(())) => break,
+                    Ok(Err(rejection)) => {
+                        tracing::warn!(
+                            "The registry refused '{}' (status {}){}",
+                            publishee.name,
+                            rejection.status,
+                            rejection
+                                .errors
+                                .iter()
+                                .map(|error| format!(": {error}"))
+                                .collect::<String>()
+                        );
+                        Some(rejection)
+                    }
+                    Err(err) => {
+                        tracing::warn!("Could not upload '{}': {err:#}", publishee.name);
+                        None
+                    }
+                }
+            }
+            Run::Failure => None,
+            Run::TimedOut => {
+                tracing::warn!(
+                    "'cargo package' of '{}' didn't finish within {}s and was stopped",
+                    publishee.name,
+                    timeout.unwrap_or_default().as_secs()
+                );
+                None
+            }
+        };
+        let published = !offline && registry_has_version(ctx, registry.as_deref(), publishee, new_version);
+        match Failure::classify(published, rejection.as_ref(), jiff::Timestamp::now()) {
+            Failure::AlreadyPublished if !dry_run && retry + rate_limited_waits > 0 => {
+                tracing::info!(
+                    "'{}' was published by a previous attempt even though it seemed to fail",
+                    publishee.name
+                );
+                break;
+            }
+            Failure::AlreadyPublished => bail!(
+                "'{}' was published in this version already. Use --update-crates-index to see the latest versions.",
+                publishee.name
+            ),
+            Failure::Unauthorized => bail!(
+                "The registry didn't accept the credentials to publish '{}'. Log in again with 'cargo login', or check the --credential-provider.",
+                publishee.name
+            ),
+            Failure::RateLimited(delay) if !dry_run && rate_limited_waits < MAX_RATE_LIMITED_WAITS => {
+                rate_limited_waits += 1;
+                tracing::warn!(
+                    "The registry rate-limited publishing '{}', retrying in {}s",
+                    publishee.name,
+                    delay.as_secs()
+                );
+                wait_before_publishing_again(&publishee.name, delay)?;
+            }
+            _ if retry == retries || dry_run => bail!("Could not publish '{}'.", publishee.name),
+            _ => {
+                retry += 1;
+                let delay = backoff.delay(retry);
+                tracing::warn!(
+                    "Publishing failed, but we retry up to {retries} times to rule out flakiness (retry {retry}{})",
+                    if delay.is_zero() {
+                        String::new()
+                    } else {
+                        format!(" in {}s", delay.as_secs())
+                    }
+                );
+                wait_before_publishing_again(&publishee.name, delay)?;
+            }
         }
     }
     Ok(())
 }
 
+/// The outcome of running `cargo package`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Run {
+    Success,
+    Failure,
+    /// It was stopped as it took too long.
+    TimedOut,
+}
+
+/// Why publishing failed, which determines whether it's worth trying again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// The version exists in the registry already.
+    AlreadyPublished,
+    /// The registry didn't accept the token.
+    Unauthorized,
+    /// The registry asks to wait for the given time before publishing again.
+    RateLimited(Duration),
+    /// Anything else, like network errors or crates that fail to build.
+    Other,
+}
+
+impl Failure {
+    /// Classify the failure to publish, knowing if the version to publish is `published` according to the index
+    /// of the registry, and with the `rejection` of the registry if it refused the upload and `now` being the current
+    /// time.
+    fn classify(published: bool, rejection: Option<&Rejection>, now: jiff::Timestamp) -> Self {
+        if published {
+            return Failure::AlreadyPublished;
+        }
+        let Some(rejection) = rejection else {
+            return Failure::Other;
+        };
+        match rejection.status {
+            401 | 403 => Failure::Unauthorized,
+            429 => Failure::RateLimited(rate_limit_delay(rejection.retry_after.as_deref(), now)),
+            _ => Failure::Other,
+        }
+    }
+}
+
+/// Return `true` if `version` of `publishee` is in the index of `registry`, or of crates.io if it's `None`.
+///
+/// If that can't be determined, like for registries with a git index, `false` is returned.
+fn registry_has_version(
+    ctx: &crate::Context,
+    registry: Option<&str>,
+    publishee: &Package,
+    version: &semver::Version,
+) -> bool {
+    let has_version = match registry {
+        None => crate::crates_index::sparse_index_has_version(&publishee.name, version),
+        Some(registry) => match super::mirrors::sparse_index_url(registry, ctx.root.as_std_path()) {
+            Ok(Some(index_url)) => crate::crates_index::index_at_has_version(&index_url, &publishee.name, version),
+            Ok(None) => return false,
+            Err(err) => Err(err),
+        },
+    };
+    has_version.unwrap_or_else(|err| {
+        tracing::warn!(
+            "Could not check if '{} v{version}' is in the index of the registry: {err:#}",
+            publishee.name
+        );
+        false
+    })
+}
+
+/// Package all `publishees` with a single invocation of `cargo package`, which also builds them unless `no_verify` is set,
+/// to find crates that can't be published before any of them is published.
+///
+/// Crates depending on each other are packaged against each other, even though the new versions aren't published yet.
+pub(in crate::command::release_impl) fn verify_packaging(
+    publishees: &[&Package],
+    workspace_root: &Utf8Path,
+    Options {
+        skip_publish,
+        skip_preflight,
+        dry_run,
+        allow_dirty,
+        allow_dirty_paths,
+        no_verify,
+        verbose,
+        registry,
+        target,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    if skip_publish || skip_preflight || publishees.is_empty() {
+        return Ok(());
+    }
+    let mut c = Command::new("cargo");
+    c.arg("package")
+        .arg("--manifest-path")
+        .arg(workspace_root.join("Cargo.toml"));
+    for publishee in publishees {
+        c.arg("--package").arg(publishee.name.as_str());
+    }
+    if let Some(ref registry) = registry {
+        c.arg("--registry").arg(registry);
+    }
+    if let Some(ref target) = target {
+        c.arg("--target").arg(target);
+    }
+    if allow_dirty || !allow_dirty_paths.is_empty() {
+        c.arg("--allow-dirty");
+    }
+    let unverified: Vec<_> = publishees
+        .iter()
+        .filter(|publishee| skips_verification(publishee))
+        .map(|publishee| format!("'{}'", publishee.name))
+        .collect();
+    if no_verify {
+        c.arg("--no-verify");
+    } else if !unverified.is_empty() {
+        // Verification can't be disabled for individual packages, and packaging them separately would prevent them
+        // from being packaged against each other.
+        tracing::info!(
+            "Packaging without building as {} {} configured to be published with --no-verify",
+            unverified.join(", "),
+            if unverified.len() == 1 { "is" } else { "are" }
+        );
+        c.arg("--no-verify");
+    }
+    if verbose {
+        tracing::trace!("{} run {:?}", will(dry_run), c);
+    }
+    if dry_run {
+        return Ok(());
+    }
+    tracing::info!(
+        "Verifying that {} crate(s) can be packaged before publishing",
+        publishees.len()
+    );
+    if !c.status()?.success() {
+        bail!("At least one crate failed to package, so nothing was published. Fix the problem or use --no-preflight to publish anyway.");
+    }
+    Ok(())
+}
+
+/// The file `cargo package` leaves behind in `target_directory` for `package_name` at `version`.
+pub(in crate::command::release_impl) fn crate_file(
+    target_directory: &Utf8Path,
+    package_name: &str,
+    version: &semver::Version,
+) -> Utf8PathBuf {
+    target_directory
+        .join("package")
+        .join(format!("{package_name}-{version}.crate"))
+}
+
+/// Return `true` if `package` is configured with `no-verify = true` in `[package.metadata.smart-release]` to be published
+/// without building it first, as done with `--no-verify`.
+fn skips_verification(package: &Package) -> bool {
+    package
+        .metadata
+        .get("smart-release")
+        .and_then(|config| config.get("no-verify"))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// The amount of times we wait for a rate limit of the registry to pass before giving up on publishing a crate.
+const MAX_RATE_LIMITED_WAITS: usize = 10;
+
+/// Run `cmd` with its stderr shown as part of the progress, and return how it ended.
+///
+/// If it doesn't finish within `timeout`, it is killed.
+fn run_forwarding_stderr(mut cmd: Command, timeout: Option<Duration>) -> anyhow::Result<Run> {
+    cmd.arg(crate::color::arg(crate::color::stderr()));
+    let mut child = cmd.stderr(Stdio::piped()).spawn()?;
+    let child_stderr = child.stderr.take().expect("piped");
+    let forward_stderr = std::thread::spawn(move || -> std::io::Result<()> {
+        let progress = crate::progress::add_child("cargo");
+        for line in BufReader::new(child_stderr).lines() {
+            crate::progress::eprintln(&progress, &line?);
+        }
+        Ok(())
+    });
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let run = loop {
+        if let Some(status) = child.try_wait()? {
+            break if status.success() { Run::Success } else { Run::Failure };
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            child.kill()?;
+            child.wait()?;
+            break Run::TimedOut;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+    // Processes spawned by a killed `cargo` may still hold on to its stderr, so we don't wait for them.
+    if run != Run::TimedOut {
+        forward_stderr.join().expect("no panic while forwarding")?;
+    }
+    Ok(run)
+}
+
+/// Return how long to wait before publishing again after the registry rejected an upload as too many crates were
+/// published in a short time, with `retry_after` being the `Retry-After` header of its response.
+///
+/// The header is either a number of seconds or the date until which to wait, with `now` being the current time.
+/// Without it, a minute is waited.
+fn rate_limit_delay(retry_after: Option<&str>, now: jiff::Timestamp) -> Duration {
+    let delay = retry_after.and_then(|retry_after| match retry_after.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let retry_at = jiff::fmt::rfc2822::parse(retry_after).ok()?;
+            let seconds = retry_at.timestamp().as_second() - now.as_second();
+            Some(Duration::from_secs(seconds.max(0) as u64 + 1))
+        }
+    });
+    delay.unwrap_or(Duration::from_secs(60))
+}
+
+/// Wait for `delay` while showing progress, before publishing `crate_name` again.
+fn wait_before_publishing_again(crate_name: &str, delay: Duration) -> anyhow::Result<()> {
+    let until = Instant::now() + delay;
+    let mut last_report = Instant::now();
+    while let Some(remaining) = until.checked_duration_since(Instant::now()).filter(|d| !d.is_zero()) {
+        if gix::interrupt::is_triggered() {
+            bail!("Interrupted while waiting to publish '{crate_name}' again");
+        }
+        if last_report.elapsed() >= Duration::from_secs(30) {
+            tracing::info!("Publishing '{crate_name}' again in {}s", remaining.as_secs());
+            last_report = Instant::now();
+        }
+        std::thread::sleep(remaining.min(Duration::from_secs(1)));
+    }
+    Ok(())
+}
+
+/// Return the name of the environment variable which configures the credential provider of `registry`, or of crates.io
+/// if it's `None`.
+pub(in crate::command::release_impl) fn credential_provider_env_var(registry: Option<&str>) -> String {
+    match registry {
+        Some(name) => format!(
+            "CARGO_REGISTRIES_{}_CREDENTIAL_PROVIDER",
+            name.to_ascii_uppercase().replace('-', "_")
+        ),
+        None => "CARGO_REGISTRY_CREDENTIAL_PROVIDER".into(),
+    }
+}
+
 pub fn refresh_lock_file() -> anyhow::Result<()> {
     cargo_metadata::MetadataCommand::new().exec()?;
     Ok(())
@@ -162,7 +506,116 @@ fn docs_rs_metadata_publish_args_from_value(
 mod tests {
     use serde_json::json;
 
-    use super::docs_rs_metadata_publish_args_from_value;
+    use std::time::Duration;
+
+    use super::{
+        credential_provider_env_var, docs_rs_metadata_publish_args_from_value, rate_limit_delay, skips_verification,
+        Failure, Rejection,
+    };
+    use crate::command::release::Backoff;
+    use crate::utils::tests::package;
+
+    #[test]
+    fn rate_limits_are_waited_for_as_long_as_the_registry_asks() {
+        let now: jiff::Timestamp = "2024-01-01T12:00:00Z".parse().unwrap();
+        assert_eq!(
+            rate_limit_delay(Some("Mon, 01 Jan 2024 12:10:00 GMT"), now),
+            Duration::from_secs(601)
+        );
+        assert_eq!(rate_limit_delay(Some("120"), now), Duration::from_secs(120));
+        assert_eq!(
+            rate_limit_delay(Some("Mon, 01 Jan 2024 11:00:00 GMT"), now),
+            Duration::from_secs(1),
+            "dates in the past don't need waiting"
+        );
+        assert_eq!(
+            rate_limit_delay(None, now),
+            Duration::from_secs(60),
+            "a default is used if the time to wait is unknown"
+        );
+        assert_eq!(rate_limit_delay(Some("soon"), now), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn failures_are_classified_by_the_index_and_the_response_of_the_registry() {
+        let now: jiff::Timestamp = "2024-01-01T12:00:00Z".parse().unwrap();
+        let rejection = |status: u32, retry_after: Option<&str>| Rejection {
+            status,
+            retry_after: retry_after.map(ToOwned::to_owned),
+            errors: vec!["the details don't matter".into()],
+        };
+        for (published, rejection, expected) in [
+            (false, Some(rejection(403, None)), Failure::Unauthorized),
+            (false, Some(rejection(401, None)), Failure::Unauthorized),
+            (true, Some(rejection(400, None)), Failure::AlreadyPublished),
+            (false, Some(rejection(400, None)), Failure::Other),
+            (
+                false,
+                Some(rejection(429, None)),
+                Failure::RateLimited(Duration::from_secs(60)),
+            ),
+            (
+                false,
+                Some(rejection(429, Some("Mon, 01 Jan 2024 12:00:09 GMT"))),
+                Failure::RateLimited(Duration::from_secs(10)),
+            ),
+            (false, Some(rejection(503, Some("30"))), Failure::Other),
+            (false, None, Failure::Other),
+            (true, None, Failure::AlreadyPublished),
+        ] {
+            assert_eq!(
+                Failure::classify(published, rejection.as_ref(), now),
+                expected,
+                "{rejection:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn verification_is_skipped_if_configured_in_the_manifest() {
+        assert!(skips_verification(&package(
+            "a",
+            &[],
+            json!({"smart-release": {"no-verify": true}})
+        )));
+        assert!(!skips_verification(&package(
+            "a",
+            &[],
+            json!({"smart-release": {"no-verify": false}})
+        )));
+        assert!(!skips_verification(&package("a", &[], json!(null))));
+    }
+
+    #[test]
+    fn backoff_strategies_are_parsed_and_delay_retries() {
+        assert_eq!(Backoff::from_spec("none"), Some(Backoff::None));
+        assert_eq!(
+            Backoff::from_spec("fixed:30"),
+            Some(Backoff::Fixed(Duration::from_secs(30)))
+        );
+        for invalid in ["fixed", "none:1", "exponential:soon", "linear:1"] {
+            assert_eq!(Backoff::from_spec(invalid), None, "{invalid}");
+        }
+
+        let exponential = Backoff::from_spec("exponential:5").unwrap();
+        assert_eq!(
+            (1..=4)
+                .map(|retry| exponential.delay(retry).as_secs())
+                .collect::<Vec<_>>(),
+            [5, 10, 20, 40]
+        );
+        assert_eq!(Backoff::Fixed(Duration::from_secs(7)).delay(3), Duration::from_secs(7));
+        assert_eq!(Backoff::None.delay(1), Duration::ZERO);
+    }
+
+    #[test]
+    fn credential_providers_are_configured_per_registry() {
+        assert_eq!(credential_provider_env_var(None), "CARGO_REGISTRY_CREDENTIAL_PROVIDER");
+        assert_eq!(
+            credential_provider_env_var(Some("my-registry")),
+            "CARGO_REGISTRIES_MY_REGISTRY_CREDENTIAL_PROVIDER"
+        );
+    }
 
     #[test]
     fn docs_rs_metadata_is_ignored_if_absent() {
@@ -0,0 +1,95 @@
+//! Verify that the registry serves exactly the `.crate` files that were uploaded, to notice corruption by the registry or
+//! proxies in between before the release is made public by pushing its tags.
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use cargo_metadata::Package;
+
+use super::{artifacts::sha256_hex, cargo::crate_file, Options};
+
+/// The location crates.io serves the `.crate` files of published crates from.
+const CRATES_IO_DOWNLOAD_URL: &str = "https://static.crates.io/crates";
+
+/// Download `package` at `version` from crates.io once it's available, and fail if it differs from the `.crate` file
+/// that was packaged locally, or if it doesn't become available in time.
+///
+/// Crates published to other registries aren't verified.
+pub(in crate::command::release_impl) fn verify(
+    package: &Package,
+    version: &semver::Version,
+    ctx: &crate::Context,
+    Options {
+        dry_run,
+        dry_run_cargo_publish,
+        skip_publish,
+        registry,
+        availability_timeout: timeout,
+        skip_checksum_verification,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    if dry_run || dry_run_cargo_publish || skip_publish || skip_checksum_verification {
+        return Ok(());
+    }
+    if registry.is_some()
+        || package
+            .publish
+            .as_ref()
+            .is_some_and(|registries| !registries.iter().any(|r| r == "crates-io"))
+    {
+        tracing::info!(
+            "Not verifying the checksum of '{}' as it was published to a registry other than crates.io",
+            package.name
+        );
+        return Ok(());
+    }
+
+    let local = crate_file(&ctx.meta.target_directory, &package.name, version);
+    let expected = sha256_hex(
+        std::fs::File::open(&local).with_context(|| format!("Could not open the packaged crate at '{local}'"))?,
+    )?;
+    let url = download_url(&package.name, version);
+    let start = Instant::now();
+    let published = loop {
+        tracing::trace!("Downloading '{url}'…");
+        match crate::crates_index::http_get(&url, &[])? {
+            (200, body) => break body,
+            // Not published yet or not yet replicated to the CDN.
+            (403 | 404, _) if start.elapsed() < timeout => std::thread::sleep(Duration::from_secs(1)),
+            (403 | 404, _) => bail!(
+                "Could not verify the checksum of '{} v{version}' as it wasn't available for download within {}s. \
+                 Wait longer with --availability-timeout, or skip the verification with --no-verify-checksum.",
+                package.name,
+                timeout.as_secs()
+            ),
+            (code, _) => bail!("Downloading '{url}' failed with unexpected status {code}"),
+        }
+    };
+    let actual = sha256_hex(published.as_slice())?;
+    if actual != expected {
+        bail!(
+            "The published '{} v{version}' has checksum {actual}, but '{local}' which was uploaded has checksum {expected}. \
+             The crate was corrupted by the registry or a proxy, so the tags of the release were not pushed.",
+            package.name
+        );
+    }
+    tracing::info!("Verified checksum of published '{} v{version}'", package.name);
+    Ok(())
+}
+
+fn download_url(name: &str, version: &semver::Version) -> String {
+    format!("{CRATES_IO_DOWNLOAD_URL}/{name}/{name}-{version}.crate")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::download_url;
+
+    #[test]
+    fn crates_are_downloaded_from_the_static_host() {
+        assert_eq!(
+            download_url("gix", &semver::Version::new(0, 85, 0)),
+            "https://static.crates.io/crates/gix/gix-0.85.0.crate"
+        );
+    }
+}
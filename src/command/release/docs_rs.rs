@@ -0,0 +1,148 @@
+//! Validate the `[package.metadata.docs.rs]` configuration of crates, as documentation that fails to build on docs.rs
+//! can only be fixed with another release.
+use anyhow::bail;
+use cargo_metadata::Package;
+
+use super::Options;
+
+/// The keys docs.rs understands in `[package.metadata.docs.rs]`.
+const KNOWN_KEYS: &[&str] = &[
+    "features",
+    "all-features",
+    "no-default-features",
+    "default-target",
+    "targets",
+    "rustc-args",
+    "rustdoc-args",
+    "cargo-args",
+];
+
+/// Fail if the docs.rs configuration of any of `publishees` has values of the wrong type or refers to features that
+/// don't exist, and warn about keys docs.rs doesn't know.
+pub(in crate::command::release_impl) fn verify(
+    publishees: &[&Package],
+    Options {
+        skip_publish,
+        skip_preflight,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    if skip_publish || skip_preflight {
+        return Ok(());
+    }
+    let mut errors = Vec::new();
+    for publishee in publishees {
+        let Some(docs_rs) = publishee.metadata.get("docs").and_then(|docs| docs.get("rs")) else {
+            continue;
+        };
+        let Some(docs_rs) = docs_rs.as_object() else {
+            errors.push(format!(
+                "'{}': package.metadata.docs.rs must be a table",
+                publishee.name
+            ));
+            continue;
+        };
+        for key in docs_rs.keys().filter(|key| !KNOWN_KEYS.contains(&key.as_str())) {
+            tracing::warn!(
+                "'{}': package.metadata.docs.rs.{key} isn't known to docs.rs and will be ignored",
+                publishee.name
+            );
+        }
+        errors.extend(
+            problems(publishee, docs_rs)
+                .into_iter()
+                .map(|problem| format!("'{}': {problem}", publishee.name)),
+        );
+    }
+    if !errors.is_empty() {
+        bail!(
+            "The documentation would likely fail to build on docs.rs, so nothing was changed:\n{}\nFix package.metadata.docs.rs or use --no-preflight to release anyway.",
+            errors.join("\n")
+        );
+    }
+    Ok(())
+}
+
+fn problems(package: &Package, docs_rs: &serde_json::Map<String, serde_json::Value>) -> Vec<String> {
+    let mut out = Vec::new();
+    for key in ["all-features", "no-default-features"] {
+        if docs_rs.get(key).is_some_and(|value| !value.is_boolean()) {
+            out.push(format!("package.metadata.docs.rs.{key} must be a boolean"));
+        }
+    }
+    if docs_rs.get("default-target").is_some_and(|value| !value.is_string()) {
+        out.push("package.metadata.docs.rs.default-target must be a string".into());
+    }
+    for key in ["features", "targets", "rustc-args", "rustdoc-args", "cargo-args"] {
+        let Some(value) = docs_rs.get(key) else { continue };
+        let Some(values) = value
+            .as_array()
+            .and_then(|values| values.iter().map(serde_json::Value::as_str).collect::<Option<Vec<_>>>())
+        else {
+            out.push(format!("package.metadata.docs.rs.{key} must be an array of strings"));
+            continue;
+        };
+        if key == "features" {
+            out.extend(
+                values
+                    .into_iter()
+                    .filter(|feature| !feature_exists(package, feature))
+                    .map(|feature| format!("package.metadata.docs.rs.features refers to unknown feature '{feature}'")),
+            );
+        }
+    }
+    out
+}
+
+/// Return `true` if `feature` can be passed to `--features` when building `package`, either as one of its own features
+/// or as `dependency/feature`.
+fn feature_exists(package: &Package, feature: &str) -> bool {
+    match feature.split_once('/') {
+        Some((dependency, _)) => {
+            let dependency = dependency.strip_suffix('?').unwrap_or(dependency);
+            package
+                .dependencies
+                .iter()
+                .any(|dep| dep.rename.as_deref().unwrap_or(&dep.name) == dependency)
+        }
+        None => package.features.contains_key(feature),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::problems;
+
+    fn package(features: serde_json::Value) -> cargo_metadata::Package {
+        let mut package = crate::utils::tests::package(
+            "a",
+            &[json!({"name": "serde", "req": "^1", "rename": "serde1", "optional": true})],
+            json!(null),
+        );
+        package.features = serde_json::from_value(features).unwrap();
+        package
+    }
+
+    #[test]
+    fn unknown_features_and_wrong_types_are_reported() {
+        let package = package(json!({"std": [], "serde1": ["dep:serde1"]}));
+        let docs_rs = json!({
+            "features": ["std", "serde1", "serde1/derive", "serde1?/rc", "nope", "other/derive"],
+            "all-features": "yes",
+            "targets": ["x86_64-unknown-linux-gnu"],
+            "rustdoc-args": "--cfg docsrs"
+        });
+        assert_eq!(
+            problems(&package, docs_rs.as_object().unwrap()),
+            [
+                "package.metadata.docs.rs.all-features must be a boolean",
+                "package.metadata.docs.rs.features refers to unknown feature 'nope'",
+                "package.metadata.docs.rs.features refers to unknown feature 'other/derive'",
+                "package.metadata.docs.rs.rustdoc-args must be an array of strings",
+            ]
+        );
+        assert!(problems(&package, json!({"features": ["std"]}).as_object().unwrap()).is_empty());
+    }
+}
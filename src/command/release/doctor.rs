@@ -0,0 +1,403 @@
+//! Check everything needed for the release to succeed without changing anything, and report what would prevent it.
+use anyhow::bail;
+use cargo_metadata::Package;
+
+use super::{git, ownership, Context, Options};
+use crate::{
+    command::release::TagConflict,
+    traverse::Dependency,
+    utils::{tag_name, try_to_published_crate_and_new_version},
+};
+
+/// How a check of the release went.
+#[derive(Debug)]
+enum Outcome {
+    /// Nothing prevents the release, as explained.
+    Pass(String),
+    /// The release would fail for the given reason.
+    Fail(String),
+    /// The check couldn't be performed, or isn't needed, for the given reason.
+    Skip(String),
+}
+
+#[derive(Debug)]
+struct Check {
+    name: &'static str,
+    outcome: Outcome,
+}
+
+/// Check that the release of `crates` can be performed and print a report of all checks, failing if one didn't pass.
+pub(in crate::command::release_impl) fn run(
+    ctx: &Context,
+    opts: &Options,
+    crates: &[Dependency<'_>],
+) -> anyhow::Result<()> {
+    let publishees: Vec<_> = crates
+        .iter()
+        .filter_map(try_to_published_crate_and_new_version)
+        .collect();
+    let packages: Vec<_> = publishees
+        .iter()
+        .map(|(package, _)| *package)
+        .filter(|package| !super::is_changelog_only(package, opts))
+        .collect();
+    let checks = [
+        Check {
+            name: "auth token",
+            outcome: auth_token(&packages, ctx, opts),
+        },
+        Check {
+            name: "ownership",
+            outcome: ownership(&packages, ctx, opts),
+        },
+        Check {
+            name: "clean worktree",
+            outcome: clean_worktree(opts),
+        },
+        Check {
+            name: "remote reachable",
+            outcome: remote_reachable(ctx, opts),
+        },
+        Check {
+            name: "manifests well-formed",
+            outcome: manifests(&packages, opts),
+        },
+        Check {
+            name: "changelogs updatable",
+            outcome: changelogs(&packages, ctx, opts),
+        },
+        Check {
+            name: "tags not conflicting",
+            outcome: tags(&publishees, ctx, opts),
+        },
+    ];
+    print!("{}", report(&checks));
+    let failed = checks
+        .iter()
+        .filter(|check| matches!(check.outcome, Outcome::Fail(_)))
+        .count();
+    if failed != 0 {
+        bail!(
+            "{failed} of {} checks failed, so the release would not succeed",
+            checks.len()
+        );
+    }
+    Ok(())
+}
+
+fn report(checks: &[Check]) -> String {
+    checks
+        .iter()
+        .map(|Check { name, outcome }| {
+            let (status, detail) = match outcome {
+                Outcome::Pass(detail) => ("PASS", detail),
+                Outcome::Fail(detail) => ("FAIL", detail),
+                Outcome::Skip(detail) => ("SKIP", detail),
+            };
+            format!("{status} {name}: {detail}\n")
+        })
+        .collect()
+}
+
+fn auth_token(packages: &[&Package], ctx: &Context, opts: &Options) -> Outcome {
+    if opts.skip_publish || packages.is_empty() {
+        return Outcome::Skip("nothing is published".into());
+    }
+    if opts.credential_provider.is_some() {
+        return Outcome::Skip("credentials are obtained from the --credential-provider".into());
+    }
+    let mut registries: Vec<_> = packages.iter().map(|package| ctx.base.registry_of(package)).collect();
+    registries.sort();
+    registries.dedup();
+    let mut missing = Vec::new();
+    for registry in &registries {
+        match ownership::registry_token(*registry) {
+            Ok(Some(_)) => {}
+            Ok(None) => missing.push(format!(
+                "no token for '{}' in {} or in the cargo credentials",
+                registry.unwrap_or("crates-io"),
+                ownership::token_env_var(*registry)
+            )),
+            Err(err) => return failed(err),
+        }
+    }
+    if missing.is_empty() {
+        Outcome::Pass(format!(
+            "found for {}",
+            registries
+                .iter()
+                .map(|registry| format!("'{}'", registry.unwrap_or("crates-io")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    } else {
+        Outcome::Fail(format!("{}, login with 'cargo login'", missing.join(", ")))
+    }
+}
+
+fn ownership(packages: &[&Package], ctx: &Context, opts: &Options) -> Outcome {
+    if opts.skip_publish || packages.is_empty() {
+        return Outcome::Skip("nothing is published".into());
+    }
+    let on_crates_io: Vec<_> = packages
+        .iter()
+        .copied()
+        .filter(|package| ctx.base.registry_of(package).is_none())
+        .collect();
+    if on_crates_io.is_empty() {
+        return Outcome::Skip("ownership can only be checked on crates.io".into());
+    }
+    match ownership::registry_token(None) {
+        Ok(Some(token)) => match ownership::check(&on_crates_io, &token) {
+            Ok(()) => Outcome::Pass(format!("may publish {}", names(&on_crates_io))),
+            Err(err) => failed(err),
+        },
+        Ok(None) => Outcome::Skip("there is no crates.io token to check it with".into()),
+        Err(err) => failed(err),
+    }
+}
+
+fn clean_worktree(opts: &Options) -> Outcome {
+    if opts.allow_dirty {
+        return Outcome::Skip("changes are allowed with --allow-dirty".into());
+    }
+    match crate::git::assure_clean_working_tree(&opts.allow_dirty_paths) {
+        Ok(()) if opts.allow_dirty_paths.is_empty() => Outcome::Pass("no changes".into()),
+        Ok(()) => Outcome::Pass("no changes except for those allowed with --allow-dirty".into()),
+        Err(_) if opts.autostash => Outcome::Pass("changes are stashed with --autostash".into()),
+        Err(err) => failed(err),
+    }
+}
+
+fn remote_reachable(ctx: &Context, opts: &Options) -> Outcome {
+    if opts.skip_push {
+        return Outcome::Skip("nothing is pushed".into());
+    }
+    match git::reachable_push_destination(&ctx.base.repo, opts.remote.clone()) {
+        Ok(remote) => Outcome::Pass(format!("refs of '{remote}' can be listed")),
+        Err(err) => failed(err),
+    }
+}
+
+/// Assure the metadata crates.io requires to publish is present.
+fn manifests(packages: &[&Package], opts: &Options) -> Outcome {
+    if opts.skip_publish || packages.is_empty() {
+        return Outcome::Skip("nothing is published".into());
+    }
+    let problems: Vec<_> = packages
+        .iter()
+        .filter_map(|package| {
+            let mut missing = Vec::new();
+            if package.description.is_none() {
+                missing.push("description");
+            }
+            if package.license.is_none() && package.license_file.is_none() {
+                missing.push("license or license-file");
+            }
+            (!missing.is_empty()).then(|| format!("'{}' lacks {}", package.name, missing.join(" and ")))
+        })
+        .collect();
+    if problems.is_empty() {
+        Outcome::Pass(format!("{} can be published", names(packages)))
+    } else {
+        Outcome::Fail(problems.join(", "))
+    }
+}
+
+/// Assure existing changelogs can be updated, which needs their generated content to be recognized as such.
+fn changelogs(packages: &[&Package], ctx: &Context, opts: &Options) -> Outcome {
+    if !opts.changelog || packages.is_empty() {
+        return Outcome::Skip("no changelog is written".into());
+    }
+    let write_options = crate::changelog::write::Options::from(opts.clone());
+    let mut problems = Vec::new();
+    let mut num_checked = 0;
+    for package in packages {
+        let path = crate::utils::changelog_path(package, &ctx.base.meta);
+        let markdown = match std::fs::read(&path) {
+            Ok(content) => match String::from_utf8(content) {
+                Ok(markdown) => markdown,
+                Err(_) => {
+                    problems.push(format!("'{path}' isn't valid UTF-8"));
+                    continue;
+                }
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                problems.push(format!("'{path}' can't be read: {err}"));
+                continue;
+            }
+        };
+        num_checked += 1;
+        if !unknown_tags_are_balanced(&markdown) {
+            problems.push(format!(
+                "'{path}' has unbalanced {} and {} tags",
+                crate::changelog::Section::UNKNOWN_TAG_START,
+                crate::changelog::Section::UNKNOWN_TAG_END
+            ));
+        } else if !round_trips(
+            &markdown,
+            &ctx.base.changelog_locale,
+            &ctx.changelog_links,
+            &write_options,
+        ) {
+            problems.push(format!("'{path}' changes each time it's parsed and written again"));
+        }
+    }
+    if problems.is_empty() {
+        Outcome::Pass(format!(
+            "{num_checked} changelog{} can be updated, {} will be created",
+            if num_checked == 1 { "" } else { "s" },
+            packages.len() - num_checked
+        ))
+    } else {
+        Outcome::Fail(problems.join(", "))
+    }
+}
+
+/// Return `true` if each tag starting content the parser didn't understand in `markdown` is closed before the next one,
+/// as otherwise everything up to the next closing tag is considered unknown.
+fn unknown_tags_are_balanced(markdown: &str) -> bool {
+    use crate::changelog::Section;
+    let mut is_open = false;
+    for (pos, _) in markdown.match_indices("<csr-unknown") {
+        let is_end = markdown[pos..].starts_with(Section::UNKNOWN_TAG_END);
+        if is_end != is_open {
+            return false;
+        }
+        is_open = !is_end;
+    }
+    !is_open
+}
+
+/// Return `true` if `markdown` parsed with `locale` and written with `links` and `write_options` doesn't change
+/// if it's parsed and written again, so generated content is recognized as such when it's updated.
+fn round_trips(
+    markdown: &str,
+    locale: &crate::changelog::locale::Locale,
+    links: &crate::changelog::write::Linkables,
+    write_options: &crate::changelog::write::Options,
+) -> bool {
+    let write = |markdown: &str| {
+        let mut out = String::new();
+        crate::ChangeLog::from_markdown(markdown, locale)
+            .write_to(
+                &mut out,
+                links,
+                crate::changelog::write::Components::all(),
+                write_options,
+            )
+            .map(|()| out)
+    };
+    match write(markdown) {
+        Ok(once) => write(&once).is_ok_and(|twice| twice == once),
+        Err(_) => false,
+    }
+}
+
+fn tags(publishees: &[(&Package, &semver::Version)], ctx: &Context, opts: &Options) -> Outcome {
+    if opts.skip_tag || publishees.is_empty() {
+        return Outcome::Skip("no tag is created".into());
+    }
+    let mut existing = Vec::new();
+    for (package, version) in publishees {
+        let tag_name = tag_name(package, version, &ctx.base);
+        match ctx
+            .base
+            .repo
+            .try_find_reference(format!("refs/tags/{tag_name}").as_str())
+        {
+            Ok(Some(_)) => existing.push(tag_name),
+            Ok(None) => {}
+            Err(err) => return failed(err.into()),
+        }
+    }
+    let exist = if existing.len() == 1 { "exists" } else { "exist" };
+    match (existing.is_empty(), opts.on_existing_tag) {
+        (true, _) => Outcome::Pass(format!(
+            "{} tag{} can be created",
+            publishees.len(),
+            if publishees.len() == 1 { "" } else { "s" }
+        )),
+        (false, TagConflict::Fail) => Outcome::Fail(format!(
+            "{} {exist} already, delete or choose what to do with --on-existing-tag",
+            existing.join(", ")
+        )),
+        (false, _) => Outcome::Pass(format!(
+            "{} {exist} already, which is handled as chosen with --on-existing-tag",
+            existing.join(", ")
+        )),
+    }
+}
+
+/// Describe `err` with all of its causes on a single line.
+fn failed(err: anyhow::Error) -> Outcome {
+    Outcome::Fail(
+        format!("{err:#}")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+fn names(packages: &[&Package]) -> String {
+    packages
+        .iter()
+        .map(|package| format!("'{}'", package.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{report, round_trips, unknown_tags_are_balanced, Check, Outcome};
+    use crate::changelog::write::Linkables;
+
+    #[test]
+    fn unknown_tags_must_be_closed_before_the_next_one() {
+        assert!(unknown_tags_are_balanced(
+            "## v1.0.0\n\n<csr-unknown>\nstuff\n<csr-unknown/>\n"
+        ));
+        assert!(unknown_tags_are_balanced("## v1.0.0\n\nNo tags.\n"));
+        assert!(!unknown_tags_are_balanced("## v1.0.0\n\n<csr-unknown>\nstuff\n"));
+        assert!(!unknown_tags_are_balanced(
+            "<csr-unknown>\n<csr-unknown>\n<csr-unknown/>\n"
+        ));
+        assert!(!unknown_tags_are_balanced("stuff\n<csr-unknown/>\n"));
+    }
+
+    #[test]
+    fn changelogs_written_by_us_round_trip() {
+        let markdown = "# Changelog\n\n## Unreleased\n\n### Bug Fixes\n\n - a bug\n\n## v1.0.0 (2024-01-01)\n\nThe first release.\n";
+        assert!(round_trips(
+            markdown,
+            &Default::default(),
+            &Linkables::AsText,
+            &Default::default()
+        ));
+    }
+
+    #[test]
+    fn each_check_is_reported_on_its_own_line() {
+        let checks = [
+            Check {
+                name: "auth token",
+                outcome: Outcome::Pass("found for 'crates-io'".into()),
+            },
+            Check {
+                name: "clean worktree",
+                outcome: Outcome::Fail("changed".into()),
+            },
+            Check {
+                name: "remote reachable",
+                outcome: Outcome::Skip("nothing is pushed".into()),
+            },
+        ];
+        assert_eq!(
+            report(&checks),
+            "PASS auth token: found for 'crates-io'\nFAIL clean worktree: changed\nSKIP remote reachable: nothing is pushed\n"
+        );
+    }
+}
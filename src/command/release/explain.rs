@@ -0,0 +1,170 @@
+use std::fmt::Write;
+
+use crate::{
+    commit::history::Item,
+    git::history::{crate_ref_segments, SegmentScope},
+    traverse::{dependency, dependency::VersionAdjustment, Dependency},
+    version,
+    version::{BumpSpec, Change},
+};
+
+/// Log, for each crate with a version change, the commits that lead to it along with the rule that was applied.
+pub(super) fn bumps(crates: &[Dependency<'_>], ctx: &crate::Context) -> anyhow::Result<()> {
+    for dep in crates {
+        let Some(bump) = dep.mode.version_adjustment_bump() else {
+            continue;
+        };
+        if bump.next_release == dep.package.version {
+            continue;
+        }
+        let mut out = format!(
+            "Why '{}' goes from {} to {}:",
+            dep.package.name, dep.package.version, bump.next_release
+        );
+        let spec = version::select_publishee_bump_spec(&dep.package.name, ctx);
+        let origin = if ctx.bump_overrides.contains_key(dep.package.name.as_str()) {
+            "set for this crate"
+        } else if matches!(dep.kind, dependency::Kind::UserSelection) {
+            "set for provided crates"
+        } else {
+            "set for dependencies"
+        };
+        writeln!(out)?;
+        write!(out, "  bump specification '{spec}' {origin}")?;
+
+        match &dep.mode {
+            dependency::Mode::ToBePublished {
+                adjustment:
+                    VersionAdjustment::Breakage {
+                        causing_dependency_names,
+                        ..
+                    },
+            }
+            | dependency::Mode::NotForPublishing {
+                adjustment:
+                    Some(dependency::ManifestAdjustment::Version(VersionAdjustment::Breakage {
+                        causing_dependency_names,
+                        ..
+                    })),
+                ..
+            } => {
+                writeln!(out)?;
+                write!(
+                    out,
+                    "  rule: dependencies with breaking changes ({}) require a bump for safety",
+                    causing_dependency_names
+                        .iter()
+                        .map(|n| format!("'{n}'"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )?;
+            }
+            _ => {}
+        }
+
+        if matches!(spec, BumpSpec::Auto | BumpSpec::PreRelease(_)) {
+            if let Some(history) = ctx.history.as_ref() {
+                let segments = crate_ref_segments(dep.package, ctx, history, SegmentScope::Unreleased)?;
+                let unreleased: &[&Item] = segments.first().map_or(&[], |s| s.history.as_slice());
+                let documented = version::unreleased_changelog_change(dep.package, ctx)?;
+                let dependencies = version::incompatibly_changed_dependencies(dep.package, ctx)?;
+                write_commit_rule(&mut out, unreleased, documented, &dependencies, ctx, dep)?;
+            }
+        }
+        if bump.next_release != bump.desired_release {
+            writeln!(out)?;
+            write!(
+                out,
+                "  note: the computed version {} was not used as the manifest version is sufficient",
+                bump.desired_release
+            )?;
+        }
+        tracing::info!("{out}");
+    }
+    Ok(())
+}
+
+fn write_commit_rule(
+    out: &mut String,
+    unreleased: &[&Item],
+    documented: Option<Change>,
+    dependencies: &[String],
+    ctx: &crate::Context,
+    dep: &Dependency<'_>,
+) -> std::fmt::Result {
+    let is_zero_version = version::is_pre_release(&dep.package.version);
+    let breaking: Vec<_> = unreleased.iter().filter(|item| item.message.breaking).collect();
+    let features: Vec<_> = unreleased
+        .iter()
+        .filter(|item| !item.message.breaking && item.message.kind == Some("feat"))
+        .collect();
+    let others = unreleased.len() - breaking.len() - features.len();
+    let api_is_breaking = ctx
+        .semver_checks
+        .as_ref()
+        .and_then(|checks| checks.found_breaking_changes(&dep.package.name))
+        .unwrap_or(false);
+
+    writeln!(out)?;
+    let rule = if unreleased.is_empty() {
+        "no commits since the last release, so the version is kept".into()
+    } else if !breaking.is_empty() {
+        format!(
+            "breaking changes require a {} bump",
+            if is_zero_version { "minor (pre-1.0)" } else { "major" }
+        )
+    } else if api_is_breaking {
+        format!(
+            "cargo-semver-checks found breaking API changes which require a {} bump",
+            if is_zero_version { "minor (pre-1.0)" } else { "major" }
+        )
+    } else if documented == Some(Change::Breaking) {
+        format!(
+            "breaking changes written by hand into the 'Unreleased' changelog section require a {} bump",
+            if is_zero_version { "minor (pre-1.0)" } else { "major" }
+        )
+    } else if !dependencies.is_empty() {
+        format!(
+            "incompatible version requirements of public dependencies ({}) require a {} bump",
+            dependencies
+                .iter()
+                .map(|n| format!("'{n}'"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if is_zero_version { "minor (pre-1.0)" } else { "major" }
+        )
+    } else if !features.is_empty() || documented == Some(Change::Feature) {
+        format!(
+            "new features{} require a {} bump",
+            if features.is_empty() {
+                " written by hand into the 'Unreleased' changelog section"
+            } else {
+                ""
+            },
+            if is_zero_version { "patch (pre-1.0)" } else { "minor" }
+        )
+    } else {
+        "fixes and other changes require a patch bump".into()
+    };
+    write!(out, "  rule: {rule}")?;
+    for (title, items) in [("breaking", &breaking), ("feature", &features)] {
+        for item in items.iter() {
+            writeln!(out)?;
+            write!(
+                out,
+                "    {title}: {} {}",
+                item.id.to_hex_with_len(7),
+                item.message.title
+            )?;
+        }
+    }
+    if others != 0 {
+        writeln!(out)?;
+        write!(
+            out,
+            "    and {others} other commit{}",
+            if others == 1 { "" } else { "s" }
+        )?;
+    }
+    Ok(())
+}
@@ -1,85 +1,542 @@
-use std::{convert::TryInto, path::Path, process::Command};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryInto,
+    io::Read,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
 
 use anyhow::{anyhow, bail, Context};
 use cargo_metadata::Package;
-use gix::{bstr::ByteSlice, refs, refs::transaction::PreviousValue, Id};
+use gix::{
+    bstr::{BString, ByteSlice},
+    prelude::ObjectIdExt,
+    refs,
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        Target,
+    },
+    Id,
+};
 
-use super::{tag_name, Options};
-use crate::utils::will;
+use super::{
+    git_hooks::{self, Hook as GitHook},
+    signing::{self, Signer},
+    tag_name, Options,
+};
+use crate::{
+    command::release::{SignedPush, TagConflict},
+    event::{self, Step},
+    git::push::{self, PushStatus, PushedRef},
+    utils::will,
+};
 
+/// Commit all changes to tracked files along with the files at `paths`, like `git commit -a` would after adding them.
+///
+/// `paths` are the files changed by the release, which are committed even if they aren't tracked yet, like new
+/// changelogs. The index is updated to match the new commit.
+///
+/// The worktree, index and `HEAD` are those of the worktree `repo` was opened in, which may be a linked worktree.
+/// Unless `git_hooks` is disabled, the `pre-commit`, `commit-msg` and `post-commit` hooks run like with `git commit`.
+///
+/// If `only_paths` is set, other changes to tracked files aren't committed, so they can be committed separately.
+///
+/// With `dry_run`, the files aren't changed yet, so their new content is taken from `paths` instead. The commit is then
+/// created in memory only to report its id, without running hooks or changing any reference. It's created on top of
+/// `dry_run_parent` if set, a commit created in memory before, instead of `HEAD`.
+#[allow(clippy::too_many_arguments)]
 pub(in crate::command::release_impl) fn commit_changes<'a>(
     message: impl AsRef<str>,
-    dry_run: bool,
     empty_commit_possible: bool,
-    signoff: bool,
-    changelog_paths: &[impl AsRef<Path>],
+    paths: &[(PathBuf, Option<Vec<u8>>)],
+    only_paths: bool,
+    dry_run_parent: Option<gix::ObjectId>,
     repo: &'a gix::Repository,
+    Options {
+        dry_run,
+        signoff,
+        sign,
+        git_hooks,
+        replace_release,
+        release_author,
+        allow_dirty_paths,
+        ..
+    }: Options,
 ) -> anyhow::Result<Option<Id<'a>>> {
     let workdir = repo.workdir().context("Can only work in non-bare repositories")?;
-    // Add changelog files that are not yet tracked in git index.
-    // `git commit -am` only stages tracked files, so we need to explicitly add new ones.
-    if !changelog_paths.is_empty() {
-        let index = repo.index_or_empty()?;
+    // Paths reported by cargo may reach the worktree through symlinks, or be relative to the current directory.
+    let canonical_workdir = gix::path::realpath(workdir)?;
+    let mut rela_paths = BTreeSet::<BString>::new();
+    let mut dry_run_contents = BTreeMap::<BString, &[u8]>::new();
+    for (path, content) in paths {
+        let canonical_path = match path.parent().map(gix::path::realpath).transpose()? {
+            Some(dir) => dir.join(path.file_name().context("paths to commit are files")?),
+            None => path.to_owned(),
+        };
+        let rela_path = canonical_path.strip_prefix(&canonical_workdir).with_context(|| {
+            format!(
+                "'{}' is outside of the worktree at '{}' and can't be committed",
+                path.display(),
+                canonical_workdir.display()
+            )
+        })?;
+        let rela_path = gix::path::to_unix_separators(gix::path::into_bstr(rela_path)).into_owned();
+        if let Some(content) = content.as_deref().filter(|_| dry_run) {
+            dry_run_contents.insert(rela_path.clone(), content);
+        }
+        rela_paths.insert(rela_path);
+    }
+    if !only_paths {
+        // Changes that are allowed to be there stay in the worktree instead of being released.
+        rela_paths.extend(crate::git::tracked_modifications(repo)?.into_iter().filter(|path| {
+            !allow_dirty_paths
+                .iter()
+                .any(|allowed| std::path::Path::new(&*path.to_str_lossy()).starts_with(allowed))
+        }));
+    }
 
-        let untracked_paths: Vec<_> = changelog_paths
+    let author = as_release_author(
+        repo.author()
+            .context("An author must be configured to commit")??
+            .to_owned()?,
+        release_author.as_deref(),
+    )?;
+    let committer = as_release_author(
+        repo.committer()
+            .context("A committer must be configured to commit")??
+            .to_owned()?,
+        release_author.as_deref(),
+    )?;
+    let mut message = message.as_ref().to_owned();
+    if signoff {
+        message = with_signoff(
+            &message,
+            &committer.name.to_str_lossy(),
+            &committer.email.to_str_lossy(),
+        );
+    }
+    let signer = Signer::from_config(repo, sign, "commit.gpgSign")?;
+    tracing::trace!(
+        "{} commit {:?}{} with changes to {}",
+        will(dry_run),
+        message,
+        signer
+            .as_ref()
+            .map(|signer| format!(" signed by '{}'", signer.key()))
+            .unwrap_or_default(),
+        rela_paths
             .iter()
-            .filter_map(|path| {
-                // Convert absolute path to worktree-relative path with forward slashes
-                path.as_ref().strip_prefix(workdir).ok().and_then(|relative_path| {
-                    let relative_path_unix = gix::path::to_unix_separators(gix::path::into_bstr(relative_path));
-                    // Check if the path is NOT tracked in the git index
-                    index
-                        .entry_by_path(relative_path_unix.as_bstr())
-                        .is_none()
-                        .then_some(relative_path)
-                })
-            })
-            .collect();
-
-        if !untracked_paths.is_empty() {
-            let mut git_add = Command::new(gix::path::env::exe_invocation());
-            git_add.args(["add", "--"]);
-            for path in &untracked_paths {
-                git_add.arg(path);
+            .map(|path| format!("'{path}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if dry_run && git_hooks {
+        for hook in [GitHook::PreCommit, GitHook::CommitMsg, GitHook::PostCommit] {
+            git_hooks::run(hook, String::new(), &repo.index_path(), dry_run, repo)?;
+        }
+    }
+    // Objects of a dry-run are only kept in memory, so nothing is left behind in the object database.
+    let memory_repo;
+    let objects = if dry_run {
+        memory_repo = repo.clone().with_object_memory();
+        &memory_repo
+    } else {
+        repo
+    };
+
+    let mut index = repo
+        .open_index()
+        .context("The index of the worktree is needed to commit the release, check it out first")?;
+    let (mut pipeline, _) = repo.filter_pipeline(None)?;
+    for rela_path in &rela_paths {
+        let path = workdir.join(gix::path::from_bstr(rela_path.as_bstr()));
+        if let Some(content) = dry_run_contents.get(rela_path) {
+            let mut data = Vec::new();
+            pipeline
+                .convert_to_git(*content, gix::path::from_bstr(rela_path.as_bstr()).as_ref(), &index)?
+                .read_to_end(&mut data)?;
+            let id = objects.write_blob(&data)?.detach();
+            match index.entry_mut_by_path_and_stage(rela_path.as_bstr(), gix::index::entry::Stage::Unconflicted) {
+                Some(entry) => entry.id = id,
+                None => index.dangerously_push_entry(
+                    Default::default(),
+                    id,
+                    gix::index::entry::Flags::empty(),
+                    gix::index::entry::Mode::FILE,
+                    rela_path.as_bstr(),
+                ),
             }
-            log::trace!("{} run {:?}", will(dry_run), git_add);
-            if !dry_run {
-                git_add.current_dir(workdir);
-                let output = git_add.output()?;
-                if !output.status.success() {
-                    let paths: Vec<_> = untracked_paths.iter().map(|p| p.to_string_lossy()).collect();
-                    bail!(
-                        "Failed to add new changelog files to git: {}: {err}",
-                        paths.join(", "),
-                        err = output.stderr.to_str_lossy()
-                    );
-                }
+            continue;
+        }
+        let metadata = match gix::index::fs::Metadata::from_path_no_follow(&path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                // Files outside the sparse checkout are missing, but not deleted.
+                index.remove_entries(|_, entry_path, entry| {
+                    entry_path == rela_path.as_bstr() && !entry.flags.contains(gix::index::entry::Flags::SKIP_WORKTREE)
+                });
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let (data, mode) = if metadata.is_symlink() {
+            let target = std::fs::read_link(&path)?;
+            (
+                gix::path::into_bstr(target).into_owned().into(),
+                gix::index::entry::Mode::SYMLINK,
+            )
+        } else {
+            let mut data = Vec::new();
+            pipeline
+                .convert_to_git(
+                    std::fs::File::open(&path)?,
+                    gix::path::from_bstr(rela_path.as_bstr()).as_ref(),
+                    &index,
+                )?
+                .read_to_end(&mut data)?;
+            let mode = if metadata.is_executable() {
+                gix::index::entry::Mode::FILE_EXECUTABLE
+            } else {
+                gix::index::entry::Mode::FILE
+            };
+            (data, mode)
+        };
+        let id = objects.write_blob(&data)?.detach();
+        let stat = gix::index::entry::Stat::from_fs(&metadata)?;
+        match index.entry_mut_by_path_and_stage(rela_path.as_bstr(), gix::index::entry::Stage::Unconflicted) {
+            Some(entry) => {
+                entry.id = id;
+                entry.mode = mode;
+                entry.stat = stat;
+                // Like `git add --sparse`, files written outside the sparse checkout become part of it.
+                entry.flags.remove(gix::index::entry::Flags::SKIP_WORKTREE);
+            }
+            None => {
+                index.dangerously_push_entry(stat, id, gix::index::entry::Flags::empty(), mode, rela_path.as_bstr())
             }
         }
     }
+    index.sort_entries();
+    index.remove_tree();
+    if git_hooks && !dry_run {
+        // Like `git commit -a`, let the hooks see the changes to commit in a temporary index, which only replaces the
+        // actual index once the commit was created. Changes the hooks make to it are committed.
+        let tmp_index_path = repo.git_dir().join("smart-release-index");
+        index.set_path(&tmp_index_path);
+        index.write(Default::default())?;
+        let res = git_hooks::run(GitHook::PreCommit, String::new(), &tmp_index_path, dry_run, repo)
+            .and_then(|_| git_hooks::run(GitHook::CommitMsg, message, &tmp_index_path, dry_run, repo))
+            .and_then(|edited_message| {
+                let tmp_index = gix::index::File::at(&tmp_index_path, repo.object_hash(), false, Default::default())?;
+                Ok((edited_message, tmp_index))
+            });
+        std::fs::remove_file(&tmp_index_path).ok();
+        let (edited_message, tmp_index) = res?;
+        message = edited_message;
+        index = tmp_index;
+        index.set_path(repo.index_path());
+        index.remove_tree();
+    }
 
-    let mut cmd = Command::new(gix::path::env::exe_invocation());
-    cmd.arg("commit").arg("-am").arg(message.as_ref());
-    if empty_commit_possible {
-        cmd.arg("--allow-empty");
+    let mut tree = objects.edit_tree(gix::ObjectId::empty_tree(repo.object_hash()))?;
+    for entry in index.entries() {
+        if entry.stage() != gix::index::entry::Stage::Unconflicted {
+            bail!("Cannot commit the release with unresolved conflicts in the index");
+        }
+        let Some(mode) = entry.mode.to_tree_entry_mode() else {
+            continue;
+        };
+        tree.upsert(entry.path(&index), mode.kind(), entry.id)?;
     }
-    if signoff {
-        cmd.arg("--signoff");
+    let tree_id = tree.write()?.detach();
+
+    let head = repo.head_commit()?;
+    if head.tree_id()? == tree_id && !empty_commit_possible && !replace_release && dry_run_parent.is_none() {
+        tracing::info!("No tracked or staged changes remained to commit; assuming the release commit already exists.");
+        return Ok(Some(head.id()));
     }
-    log::trace!("{} run {:?}", will(dry_run), cmd);
+    // A release that is replaced is amended, so its commit is replaced as well.
+    let (parents, reflog_prefix) = if let Some(parent) = dry_run_parent.filter(|_| dry_run) {
+        (Some(parent).into_iter().collect(), "commit")
+    } else if replace_release {
+        (head.parent_ids().map(|id| id.detach()).collect(), "commit (amend)")
+    } else {
+        (Some(head.id).into_iter().collect(), "commit")
+    };
+    let title = message.lines().next().unwrap_or_default().to_owned();
+    let reflog_message = format!("{reflog_prefix}: {title}");
+    let commit = gix::objs::Commit {
+        tree: tree_id,
+        parents,
+        author,
+        committer,
+        encoding: None,
+        message: message.into(),
+        extra_headers: Vec::new(),
+    };
+    if dry_run {
+        let id = objects.write_object(&commit)?.detach();
+        match signer {
+            Some(signer) => tracing::info!(
+                "WOULD create the commit '{title}' signed with key '{}', which would be {id} if it wasn't signed",
+                signer.key()
+            ),
+            None => tracing::info!("WOULD create the commit '{title}' as {id}"),
+        }
+        return Ok(Some(id.attach(repo)));
+    }
+    let id = match signer {
+        None => repo.write_object(&commit)?,
+        Some(signer) => signing::write_signed_commit(repo, commit, &signer)?,
+    };
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: reflog_message.into(),
+            },
+            expected: PreviousValue::MustExistAndMatch(Target::Object(head.id)),
+            new: Target::Object(id.detach()),
+        },
+        name: "HEAD".try_into()?,
+        deref: true,
+    })?;
+    index.write(Default::default())?;
+    if git_hooks {
+        git_hooks::run(GitHook::PostCommit, String::new(), &repo.index_path(), dry_run, repo)?;
+    }
+    Ok(Some(id))
+}
+
+/// Parse `identity` like `Name <email>` as given with `--release-author`.
+pub(in crate::command::release_impl) fn parse_release_author(identity: &str) -> anyhow::Result<gix::actor::Identity> {
+    let parsed = gix::actor::IdentityRef::from_bytes(identity.as_bytes())
+        .ok()
+        .map(|parsed| parsed.trim())
+        .filter(|parsed| !parsed.name.is_empty() && !parsed.email.is_empty())
+        .with_context(|| format!("The release author '{identity}' must be given like 'Name <email>'"))?;
+    Ok(parsed.into())
+}
+
+/// Return `signature` with the name and email of `release_author` if it's set, at the same time.
+fn as_release_author(
+    signature: gix::actor::Signature,
+    release_author: Option<&str>,
+) -> anyhow::Result<gix::actor::Signature> {
+    let Some(identity) = release_author else {
+        return Ok(signature);
+    };
+    let identity = parse_release_author(identity)?;
+    Ok(gix::actor::Signature {
+        name: identity.name,
+        email: identity.email,
+        time: signature.time,
+    })
+}
+
+/// Stash all changes in the worktree of `repo` including untracked files, like `git stash push --include-untracked`,
+/// and return the id of the stash to restore them from once the release is done, or `None` if there were none.
+pub(in crate::command::release_impl) fn stash_changes(
+    repo: &gix::Repository,
+    dry_run: bool,
+) -> anyhow::Result<Option<gix::ObjectId>> {
+    if crate::git::assure_clean_working_tree(&[]).is_ok() {
+        return Ok(None);
+    }
+    tracing::info!(
+        "{} stash the changes in the worktree and restore them after the release",
+        will(dry_run)
+    );
     if dry_run {
         return Ok(None);
     }
-    cmd.current_dir(workdir);
+    let workdir = repo
+        .workdir()
+        .context("Can only stash changes in non-bare repositories")?;
+    let status = Command::new(gix::path::env::exe_invocation())
+        .current_dir(workdir)
+        .args([
+            "stash",
+            "push",
+            "--quiet",
+            "--include-untracked",
+            "--message",
+            "cargo smart-release autostash",
+        ])
+        .status()?;
+    if !status.success() {
+        bail!("Could not stash the changes in the worktree, commit or stash them yourself and try again");
+    }
+    let id = repo.find_reference("refs/stash")?.peel_to_id()?.detach();
+    tracing::debug!("Stashed the changes in the worktree as {id}");
+    Ok(Some(id))
+}
 
-    let output = cmd.output()?;
-    if !output.status.success() {
-        if crate::git::has_tracked_modifications(repo)? || crate::git::has_staged_changes(repo)? {
-            bail!("Failed to commit changed manifests");
+/// Apply the stash `id` made by [`stash_changes()`] to the worktree of `repo` and drop it, or keep it and tell the user
+/// how to restore it if it can't be applied cleanly, like after `git pull --autostash`.
+pub(in crate::command::release_impl) fn restore_stash(repo: &gix::Repository, id: gix::ObjectId) {
+    let Some(workdir) = repo.workdir() else {
+        return;
+    };
+    let git = || {
+        let mut cmd = Command::new(gix::path::env::exe_invocation());
+        cmd.current_dir(workdir);
+        cmd
+    };
+    let applied = git()
+        .args(["stash", "apply", "--quiet", &id.to_string()])
+        .status()
+        .is_ok_and(|status| status.success());
+    if !applied {
+        tracing::warn!(
+            "Applying the autostash {id} resulted in conflicts. The changes are safe in the stash, resolve the conflicts and drop it with 'git stash drop', or reset the worktree and apply it again with 'git stash pop'."
+        );
+        return;
+    }
+    // The stash is most likely still the latest one, but must not be dropped if it isn't anymore.
+    let is_latest = git()
+        .args(["rev-parse", "--verify", "--quiet", "stash@{0}"])
+        .output()
+        .is_ok_and(|out| out.stdout.trim() == id.to_string().as_bytes());
+    let dropped = is_latest
+        && git()
+            .args(["stash", "drop", "--quiet", "stash@{0}"])
+            .status()
+            .is_ok_and(|status| status.success());
+    if dropped {
+        tracing::info!("Restored the stashed changes in the worktree");
+    } else {
+        tracing::warn!("Restored the stashed changes in the worktree, but couldn't drop their stash {id}");
+    }
+}
+
+/// Create the branch `name` at `HEAD` and check it out, so the release commit is created on it like after
+/// `git switch -c <name>`. The working tree and index remain unchanged as both point to the same commit.
+pub(in crate::command::release_impl) fn create_release_branch(
+    name: &str,
+    dry_run: bool,
+    repo: &gix::Repository,
+) -> anyhow::Result<()> {
+    let full_name = branch_name(name)?;
+    if repo.try_find_reference(full_name.as_ref())?.is_some() {
+        bail!("The release branch '{name}' exists already. Delete it or choose another name with --release-branch.");
+    }
+    tracing::info!("{} create and check out the release branch '{name}'", will(dry_run));
+    if dry_run {
+        return Ok(());
+    }
+    check_out_branch_at_head(full_name, true, repo)
+}
+
+/// Check out the branch `name` to commit the release to, which is created at `HEAD` if it doesn't exist yet, like
+/// after `git switch <name>` from a detached `HEAD` at the commit of the branch.
+pub(in crate::command::release_impl) fn check_out_branch(
+    name: &str,
+    dry_run: bool,
+    repo: &gix::Repository,
+) -> anyhow::Result<()> {
+    let full_name = branch_name(name)?;
+    let head = repo.head()?;
+    if head.referent_name() == Some(full_name.as_ref()) {
+        return Ok(());
+    }
+    let head_id = head
+        .id()
+        .context("Cannot commit the release to a branch in an empty repository")?;
+    let create = match repo.try_find_reference(full_name.as_ref())? {
+        Some(mut branch) => {
+            let branch_id = branch.peel_to_id()?;
+            if branch_id != head_id {
+                bail!("The branch '{name}' is at {branch_id}, but HEAD is at {head_id}. Check out the commit of the branch to release it, or choose another branch with --branch.");
+            }
+            false
         }
-        log::info!("No tracked or staged changes remained to commit; assuming the release commit already exists.");
+        None => true,
+    };
+    tracing::info!(
+        "{} {} the branch '{name}' to commit the release to",
+        will(dry_run),
+        if create { "create and check out" } else { "check out" }
+    );
+    if dry_run {
+        return Ok(());
+    }
+    check_out_branch_at_head(full_name, create, repo)
+}
+
+fn branch_name(name: &str) -> anyhow::Result<refs::FullName> {
+    format!("refs/heads/{name}")
+        .try_into()
+        .with_context(|| format!("'{name}' is not a valid branch name"))
+}
+
+/// Point `HEAD` to the branch `full_name`, after creating it at `HEAD` if `create` is set.
+fn check_out_branch_at_head(full_name: refs::FullName, create: bool, repo: &gix::Repository) -> anyhow::Result<()> {
+    let head = repo.head()?;
+    let previous = head
+        .referent_name()
+        .map(|name| name.shorten().to_string())
+        .or_else(|| head.id().map(|id| id.to_string()))
+        .unwrap_or_else(|| "HEAD".into());
+    let head_id = head.id().context("Cannot check out a branch in an empty repository")?;
+    let mut edits = Vec::new();
+    if create {
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "branch: Created from HEAD".into(),
+                },
+                expected: PreviousValue::MustNotExist,
+                new: Target::Object(head_id.detach()),
+            },
+            name: full_name.clone(),
+            deref: false,
+        });
     }
-    Ok(Some(repo.find_reference("HEAD")?.peel_to_id()?))
+    edits.push(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: format!("checkout: moving from {previous} to {}", full_name.shorten()).into(),
+            },
+            expected: PreviousValue::Any,
+            new: Target::Symbolic(full_name),
+        },
+        name: "HEAD".try_into()?,
+        deref: false,
+    });
+    repo.edit_references(edits)?;
+    Ok(())
+}
+
+/// Return the name of the release branch from `template`, with `{name}` and `{version}` replaced by those of the crate.
+pub(in crate::command::release_impl) fn release_branch_name(
+    template: &str,
+    name: &str,
+    version: &semver::Version,
+) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{version}", &version.to_string())
+}
+
+/// Return `message` with a `Signed-off-by` trailer for the committer named `name` with `email`, unless it has one already.
+fn with_signoff(message: &str, name: &str, email: &str) -> String {
+    let trailer = format!("Signed-off-by: {name} <{email}>");
+    if message.lines().any(|line| line == trailer) {
+        return message.to_owned();
+    }
+    let has_trailers = message
+        .trim_end()
+        .lines()
+        .last()
+        .is_some_and(|line| line.starts_with("Signed-off-by: "));
+    let separator = if has_trailers { "\n" } else { "\n\n" };
+    format!("{}{separator}{trailer}\n", message.trim_end())
 }
 
 pub(in crate::command::release_impl) fn create_version_tag<'repo>(
@@ -88,84 +545,615 @@ pub(in crate::command::release_impl) fn create_version_tag<'repo>(
     commit_id: Option<Id<'repo>>,
     tag_message: Option<String>,
     ctx: &'repo crate::Context,
-    Options { dry_run, skip_tag, .. }: Options,
+    Options {
+        dry_run,
+        offline,
+        skip_tag,
+        sign,
+        on_existing_tag,
+        remote,
+        force_with_lease,
+        release_author,
+        ..
+    }: Options,
 ) -> anyhow::Result<Option<refs::FullName>> {
     if skip_tag {
         return Ok(None);
     }
-    let tag_name = tag_name(publishee, new_version, &ctx.repo);
-    if dry_run {
-        match tag_message {
-            Some(message) => {
-                log::trace!(
-                    "WOULD create tag object {} with changelog message, first line is: '{}'",
-                    tag_name,
-                    message.lines().next().unwrap_or("")
+    let tag_name = tag_name(publishee, new_version, ctx);
+    let full_name = format!("refs/tags/{tag_name}");
+    let constraint = match ctx.repo.try_find_reference(full_name.as_str())? {
+        None => PreviousValue::MustNotExist,
+        Some(mut existing) => {
+            // Peeling changes the target to the commit, but annotated tags must be replaced by the object they point to.
+            let previous_target = existing.inner.target.clone();
+            let existing_target = existing.peel_to_id()?;
+            if commit_id == Some(existing_target) {
+                tracing::info!("Tag {tag_name} exists already for the release commit");
+                return Ok(Some(full_name.try_into()?));
+            }
+            if force_with_lease.contains_key(&full_name) {
+                // Only seen with --dry-run, as the tags of a replaced release are deleted before planning it otherwise.
+                tracing::warn!(
+                    "{} re-point the tag {tag_name} of the replaced release at {existing_target}",
+                    will(dry_run)
                 );
+                PreviousValue::MustExistAndMatch(previous_target)
+            } else {
+                match on_existing_tag {
+                TagConflict::Fail => bail!(
+                    "Tag {tag_name} exists already at {existing_target}. Delete it, or choose what to do with --on-existing-tag skip|replace-if-unpushed."
+                ),
+                TagConflict::Skip => {
+                    tracing::warn!(
+                        "{} keep the existing tag {tag_name} at {existing_target} and not push it",
+                        will(dry_run)
+                    );
+                    return Ok(None);
+                }
+                TagConflict::ReplaceIfUnpushed if offline => bail!(
+                    "Tag {tag_name} exists already at {existing_target}, and with --offline the remote can't be asked if it was pushed. Choose what to do with --on-existing-tag fail|skip."
+                ),
+                TagConflict::ReplaceIfUnpushed if dry_run => {
+                    tracing::warn!(
+                        "WOULD check if the existing tag {tag_name} at {existing_target} was pushed, and replace it if it wasn't"
+                    );
+                    PreviousValue::MustExistAndMatch(previous_target)
+                }
+                TagConflict::ReplaceIfUnpushed => {
+                    if tag_was_pushed(&ctx.repo, &full_name, remote)? {
+                        bail!("Tag {tag_name} exists already at {existing_target} and was pushed, so it can't be replaced. Delete it everywhere, or keep it with --on-existing-tag skip.");
+                    }
+                    tracing::warn!(
+                        "Will replace the existing tag {tag_name} at {existing_target} as it wasn't pushed"
+                    );
+                    PreviousValue::MustExistAndMatch(previous_target)
+                }
             }
-            None => {
-                log::trace!("WOULD create tag {tag_name}");
             }
         }
-        Ok(Some(format!("refs/tags/{tag_name}").try_into()?))
-    } else {
-        let target = commit_id.expect("set in --execute mode");
-        let constraint = PreviousValue::Any;
-        let tag = match tag_message {
-            Some(message) => {
-                let tag = ctx.repo.tag(
-                    tag_name,
-                    target,
-                    gix::objs::Kind::Commit,
-                    Some(crate::git::author()?.to_ref(&mut Default::default())),
-                    message,
-                    constraint,
-                )?;
-                log::info!("Created tag object {} with release notes.", tag.name().as_bstr());
-                tag
-            }
-            None => {
-                let tag = ctx.repo.tag_reference(tag_name, target, constraint)?;
-                log::info!("Created tag {}", tag.name().as_bstr());
-                tag
+    };
+    let signer = Signer::from_config(&ctx.repo, sign, "tag.gpgSign")?;
+    let Some(target) = commit_id else {
+        assert!(dry_run, "the release commit is known in --execute mode");
+        tracing::info!("WOULD create tag {tag_name}");
+        return Ok(Some(full_name.try_into()?));
+    };
+    // Only annotated tags can be signed, so the tag name serves as message if there are no release notes.
+    let tag_object = match (tag_message, &signer) {
+        (None, None) => None,
+        (message, _) => Some(gix::objs::Tag {
+            target: target.detach(),
+            target_kind: gix::objs::Kind::Commit,
+            name: tag_name.as_str().into(),
+            tagger: Some(as_release_author(crate::git::author()?, release_author.as_deref())?),
+            message: message.unwrap_or_else(|| tag_name.clone()).into(),
+            pgp_signature: None,
+        }),
+    };
+    if dry_run {
+        match (tag_object, signer) {
+            (Some(_), Some(signer)) => tracing::info!(
+                "WOULD create tag object {tag_name} for {target} signed with key '{}'",
+                signer.key()
+            ),
+            (Some(tag), None) => {
+                let id = ctx.repo.clone().with_object_memory().write_object(&tag)?.detach();
+                tracing::info!(
+                    "WOULD create tag object {tag_name} {id} for {target}, first line of its message is: '{}'",
+                    tag.message.lines().next().unwrap_or_default().as_bstr()
+                );
             }
-        };
-        Ok(Some(tag.inner.name))
+            (None, _) => tracing::info!("WOULD create tag {tag_name} for {target}"),
+        }
+        return Ok(Some(full_name.try_into()?));
     }
+    let tag = match (tag_object, signer) {
+        (Some(tag), Some(signer)) => {
+            let id = signing::write_signed_tag(&ctx.repo, tag, &signer)?;
+            let tag = ctx.repo.tag_reference(tag_name, id, constraint)?;
+            tracing::info!("Created signed tag object {}.", tag.name().as_bstr());
+            tag
+        }
+        (Some(tag), None) => {
+            let id = ctx.repo.write_object(&tag)?;
+            let tag = ctx.repo.tag_reference(tag_name, id, constraint)?;
+            tracing::info!("Created tag object {} with release notes.", tag.name().as_bstr());
+            tag
+        }
+        (None, _) => {
+            let tag = ctx.repo.tag_reference(tag_name, target, constraint)?;
+            tracing::info!("Created tag {}", tag.name().as_bstr());
+            tag
+        }
+    };
+    Ok(Some(tag.inner.name))
 }
 
-// TODO: Use gitoxide here
+/// Push `HEAD` and `tag_names` unless pushing is skipped or there are no tags to push.
 pub fn push_tags_and_head(
     repo: &gix::Repository,
     tag_names: &[refs::FullName],
-    Options { dry_run, skip_push, .. }: Options,
+    options: Options,
 ) -> anyhow::Result<()> {
-    if skip_push || tag_names.is_empty() {
+    if options.skip_push || tag_names.is_empty() {
         return Ok(());
     }
+    push(repo, tag_names, options)
+}
 
-    let mut cmd = Command::new(gix::path::env::exe_invocation());
-    cmd.arg("push")
-        .arg({
-            let remote = repo
-                .head()?
-                .into_remote(gix::remote::Direction::Push)
-                .ok_or_else(|| anyhow!("Cannot push in uninitialized repo"))??;
+/// Push only `HEAD`, like the release branch before its pull request is merged.
+pub fn push_head(repo: &gix::Repository, options: Options) -> anyhow::Result<()> {
+    push(repo, &[], options)
+}
+
+/// Push `HEAD` and `tag_names`, atomically unless `atomic_push` is disabled so either all refs are updated or none.
+///
+/// `HEAD` is pushed to `branch`, or to the branch of the same name as the one that is checked out.
+fn push(
+    repo: &gix::Repository,
+    tag_names: &[refs::FullName],
+    Options {
+        dry_run,
+        remote,
+        branch,
+        atomic_push,
+        force_with_lease,
+        signed_push,
+        git_hooks,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    let remote = find_push_remote(repo, remote)?;
+    let remote_name = remote.name().map_or_else(
+        || {
             remote
-                .name()
-                .map(|name| name.as_bstr().to_string())
-                .or_else(|| remote.url(gix::remote::Direction::Push).map(|url| url.to_string()))
-                .context("Couldn't find push-remote of HEAD reference")?
-        })
-        .arg("HEAD");
+                .url(gix::remote::Direction::Push)
+                .map(|url| url.to_bstring().to_string())
+                .unwrap_or_default()
+        },
+        |name| name.as_bstr().to_string(),
+    );
+    // An explicit destination also works if HEAD was detached again, like when resuming the release.
+    let branch = match branch {
+        Some(branch) => Some(branch_name(&branch)?),
+        None => repo.head_name()?,
+    };
+
+    event::step_started(Step::Push, None, dry_run);
+    tracing::trace!(
+        "{} push HEAD to '{}' and {} to '{remote_name}'",
+        will(dry_run),
+        branch
+            .as_ref()
+            .map_or_else(|| "HEAD".into(), |name| name.as_bstr().to_string()),
+        tag_names
+            .iter()
+            .map(|name| name.as_bstr().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if dry_run {
+        event::push_done(tag_names, dry_run);
+        return Ok(());
+    }
+    let branch = branch.context("HEAD is detached, so choose the branch to push it to with --branch <name>")?;
+    let mut updates = vec![push::Update {
+        local_ref: "HEAD".into(),
+        id: repo.head_id()?.detach(),
+        remote_ref: branch.as_bstr().to_string(),
+        lease: None,
+    }];
     for tag_name in tag_names {
-        cmd.arg(tag_name.as_bstr().to_str()?);
+        updates.push(push::Update {
+            local_ref: tag_name.as_bstr().to_string(),
+            id: repo.find_reference(tag_name.as_ref())?.id().detach(),
+            remote_ref: tag_name.as_bstr().to_string(),
+            lease: None,
+        });
     }
-
-    log::trace!("{} run {:?}", will(dry_run), cmd);
-    if dry_run || cmd.status()?.success() {
+    for update in &mut updates {
+        update.lease = match force_with_lease.get(&update.remote_ref) {
+            Some(expected) if expected.is_empty() => Some(gix::ObjectId::null(repo.object_hash())),
+            Some(expected) => Some(gix::ObjectId::from_hex(expected.as_bytes())?),
+            None => None,
+        };
+    }
+    let signed_push = signed_push.or_else(|| configured_signed_push(repo));
+    let signer = match signed_push {
+        Some(_) => Signer::from_config(repo, true, "push.gpgSign")?,
+        None => None,
+    };
+    let sign = |data: &[u8]| signer.as_ref().expect("only used when signing").sign(data);
+    let certificate = match (signed_push, &signer) {
+        (Some(mode), Some(_)) => Some(push::Certificate {
+            required: mode == SignedPush::Always,
+            pusher: repo
+                .committer()
+                .transpose()?
+                .context("Couldn't find the committer to sign the push as, configure 'user.name' and 'user.email'")?
+                .to_owned()?,
+            sign: &sign,
+        }),
+        _ => None,
+    };
+    let url = remote
+        .url(gix::remote::Direction::Push)
+        .map(|url| url.to_bstring().to_string())
+        .unwrap_or_default();
+    let before_sending = |commands: &[push::Command]| {
+        if git_hooks {
+            git_hooks::run_pre_push(&remote_name, &url, commands, repo)?;
+        }
         Ok(())
+    };
+    let outcome = push::push(
+        repo,
+        &remote,
+        updates,
+        push::Options {
+            atomic: atomic_push,
+            certificate,
+            before_sending: &before_sending,
+        },
+    )?;
+    if signed_push.is_some() {
+        report_push_certificate(outcome.signed, &outcome.remote_messages);
+    }
+    for PushedRef {
+        local_ref,
+        remote_ref,
+        status,
+        summary,
+    } in &outcome.refs
+    {
+        match status {
+            PushStatus::Updated => tracing::info!("Pushed '{local_ref}' to '{remote_ref}' ({summary})"),
+            PushStatus::UpToDate => tracing::info!("'{remote_ref}' was up to date"),
+            PushStatus::Rejected => tracing::warn!("Pushing '{local_ref}' to '{remote_ref}' was rejected: {summary}"),
+        }
+    }
+    let rejected = rejected_refs(&outcome.refs);
+    if rejected.is_empty() {
+        event::push_done(tag_names, dry_run);
+        return Ok(());
+    }
+    if atomic_push {
+        bail!(
+            "The remote rejected {}, so nothing was pushed. Fix the problem and run again with --resume to push again, or push manually and resume with --no-push.",
+            rejected.join(", ")
+        );
+    }
+    bail!(
+        "The remote rejected {}. Push them manually and run again with --resume to finish the release, possibly with --no-push.",
+        rejected.join(", ")
+    );
+}
+
+/// Return the remote named `remote`, or the remote to push `HEAD` to if `None`.
+fn find_push_remote(repo: &gix::Repository, remote: Option<String>) -> anyhow::Result<gix::Remote<'_>> {
+    match remote {
+        Some(remote) => Ok(repo.find_remote(remote.as_str())?),
+        None => crate::git::push_remote(repo)?
+            .ok_or_else(|| anyhow!("Couldn't find a remote to push to, configure one or pass --remote")),
+    }
+}
+
+/// Return how pushes are signed as configured with `push.gpgSign`, or `None` if they aren't.
+fn configured_signed_push(repo: &gix::Repository) -> Option<SignedPush> {
+    let config = repo.config_snapshot();
+    if config
+        .string("push.gpgSign")
+        .is_some_and(|value| value.eq_ignore_ascii_case(b"if-asked"))
+    {
+        return Some(SignedPush::IfAsked);
+    }
+    config
+        .boolean("push.gpgSign")
+        .unwrap_or(false)
+        .then_some(SignedPush::Always)
+}
+
+/// Log what the remote said about the push certificate of a push which was `signed` or not, as seen in the `messages`
+/// of the remote.
+fn report_push_certificate(signed: bool, messages: &str) {
+    for line in certificate_lines(messages) {
+        tracing::info!("The remote reported on the push certificate: {line}");
+    }
+    if signed {
+        tracing::info!("The remote received the signed push");
     } else {
-        bail!("'git push' invocation failed. Try to push manually and repeat the smart-release invocation to resume, possibly with --skip-push.");
+        tracing::info!("The push wasn't signed as the remote doesn't support signed pushes");
+    }
+}
+
+/// Return the lines in the `messages` of the remote which are about push certificates, as printed by its hooks.
+fn certificate_lines(messages: &str) -> impl Iterator<Item = &str> {
+    messages
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.to_ascii_lowercase().contains("cert"))
+}
+
+/// Return the name or URL of `remote`, or of the remote to push `HEAD` to if `None`, or `None` if there is no remote.
+fn push_destination(repo: &gix::Repository, remote: Option<String>) -> anyhow::Result<Option<String>> {
+    if let Some(remote) = remote {
+        return Ok(Some(remote));
+    }
+    let Some(remote) = crate::git::push_remote(repo)? else {
+        return Ok(None);
+    };
+    Ok(Some(
+        remote
+            .name()
+            .map(|name| name.as_bstr().to_string())
+            .or_else(|| remote.url(gix::remote::Direction::Push).map(|url| url.to_string()))
+            .context("Couldn't find push-remote of HEAD reference")?,
+    ))
+}
+
+/// Return the remote the release would be pushed to, after assuring its refs can be listed.
+pub(in crate::command::release_impl) fn reachable_push_destination(
+    repo: &gix::Repository,
+    remote: Option<String>,
+) -> anyhow::Result<String> {
+    let remote = push_destination(repo, remote)?
+        .ok_or_else(|| anyhow!("Couldn't find a remote to push to, configure one or pass --remote"))?;
+    remote_ids(repo, &["HEAD"], Some(remote.clone()))?;
+    Ok(remote)
+}
+
+/// Return `true` if the tag `full_name` exists on the remote the release would be pushed to.
+fn tag_was_pushed(repo: &gix::Repository, full_name: &str, remote: Option<String>) -> anyhow::Result<bool> {
+    Ok(!remote_ids(repo, &[full_name], remote)?.is_empty())
+}
+
+/// Return the ids of those of `full_names` which exist on the remote the release would be pushed to.
+fn remote_ids(
+    repo: &gix::Repository,
+    full_names: &[&str],
+    remote: Option<String>,
+) -> anyhow::Result<BTreeMap<String, gix::ObjectId>> {
+    let Some(remote) = push_destination(repo, remote)? else {
+        return Ok(BTreeMap::new());
+    };
+    let mut cmd = Command::new(gix::path::env::exe_invocation());
+    cmd.arg("ls-remote").arg(&remote).args(full_names);
+    tracing::trace!("run {:?}", cmd);
+    let out = cmd.stderr(Stdio::inherit()).output()?;
+    if !out.status.success() {
+        bail!("Could not list the refs on '{remote}'");
+    }
+    let mut ids = BTreeMap::new();
+    for line in out.stdout.lines() {
+        let Some((id, name)) = line.to_str()?.split_once('\t') else {
+            continue;
+        };
+        // Patterns also match refs with the same suffix, and peeled tags.
+        if full_names.contains(&name) {
+            ids.insert(name.to_owned(), gix::ObjectId::from_hex(id.as_bytes())?);
+        }
+    }
+    Ok(ids)
+}
+
+/// The release at `HEAD` that is replaced with `--dangerously-replace-release`, or amended as it wasn't pushed.
+pub(in crate::command::release_impl) struct ReleaseToReplace {
+    /// The release commit.
+    pub commit: gix::ObjectId,
+    /// The local tags pointing to the release commit, along with their direct target.
+    pub tags: Vec<(refs::FullName, gix::ObjectId)>,
+    /// The ids the branch and the tags must have on the remote to be replaced, or an empty string if they must not exist.
+    pub leases: BTreeMap<String, String>,
+}
+
+/// Return the release at `HEAD` of the branch `branch`, or of the checked out branch if `None`, to replace it.
+///
+/// Fail if `HEAD` isn't tagged, or if the remote branch moved on from the release commit so replacing it would lose
+/// commits of others.
+pub(in crate::command::release_impl) fn release_to_replace(
+    repo: &gix::Repository,
+    branch: Option<&str>,
+    remote: Option<String>,
+) -> anyhow::Result<ReleaseToReplace> {
+    let head = repo.head_commit()?;
+    let branch = match branch {
+        Some(branch) => branch_name(branch)?,
+        None => repo
+            .head_name()?
+            .context("HEAD is detached, so choose the branch of the release to replace with --branch <name>")?,
+    };
+    let tags = tags_pointing_to(repo, head.id)?;
+    if tags.is_empty() {
+        bail!("HEAD at {} isn't tagged, so there is no release to replace", head.id);
+    }
+
+    let names: Vec<_> = Some(branch.as_bstr().to_str()?)
+        .into_iter()
+        .chain(
+            tags.iter()
+                .map(|(name, _)| name.as_bstr().to_str().expect("valid UTF-8 tag names")),
+        )
+        .collect();
+    let on_remote = remote_ids(repo, &names, remote)?;
+    if let Some(remote_branch) = on_remote.get(names[0]) {
+        if *remote_branch != head.id && !head.parent_ids().any(|parent| parent == *remote_branch) {
+            bail!(
+                "The remote branch '{}' is at {remote_branch} and moved on from the release commit {}, so the release can't be replaced without losing commits",
+                branch.shorten(),
+                head.id
+            );
+        }
+    }
+    let leases = names
+        .iter()
+        .map(|name| {
+            (
+                name.to_string(),
+                on_remote.get(*name).map(ToString::to_string).unwrap_or_default(),
+            )
+        })
+        .collect();
+    Ok(ReleaseToReplace {
+        commit: head.id,
+        tags,
+        leases,
+    })
+}
+
+/// Return the release at `HEAD` of the branch `branch`, or of the checked out branch if `None`, if neither its commit
+/// nor any of its tags are on the remote, so it can be amended instead of creating another release commit on top.
+///
+/// Return `None` if `HEAD` isn't tagged, is detached without `branch` being set, or if the release was pushed.
+pub(in crate::command::release_impl) fn unpushed_release(
+    repo: &gix::Repository,
+    branch: Option<&str>,
+    remote: Option<String>,
+) -> anyhow::Result<Option<ReleaseToReplace>> {
+    let head_id = repo.head_id()?.detach();
+    let tags = tags_pointing_to(repo, head_id)?;
+    if tags.is_empty() {
+        return Ok(None);
+    }
+    let branch = match branch {
+        Some(branch) => branch_name(branch)?,
+        None => match repo.head_name()? {
+            Some(name) => name,
+            None => return Ok(None),
+        },
+    };
+    let names: Vec<_> = Some(branch.as_bstr().to_str()?)
+        .into_iter()
+        .chain(
+            tags.iter()
+                .map(|(name, _)| name.as_bstr().to_str().expect("valid UTF-8 tag names")),
+        )
+        .collect();
+    let on_remote = remote_ids(repo, &names, remote)?;
+    let pushed =
+        on_remote.get(names[0]) == Some(&head_id) || names[1..].iter().any(|name| on_remote.contains_key(*name));
+    Ok((!pushed).then_some(ReleaseToReplace {
+        commit: head_id,
+        tags,
+        leases: Default::default(),
+    }))
+}
+
+/// Return the tags pointing to the commit `id`, along with their direct target.
+fn tags_pointing_to(repo: &gix::Repository, id: gix::ObjectId) -> anyhow::Result<Vec<(refs::FullName, gix::ObjectId)>> {
+    let mut tags = Vec::new();
+    for tag in repo.references()?.prefixed("refs/tags/")? {
+        let mut tag = tag.map_err(|err| anyhow!(err))?;
+        let Some(direct_target) = tag.target().try_id().map(ToOwned::to_owned) else {
+            continue;
+        };
+        if tag.peel_to_id()? == id {
+            tags.push((tag.inner.name.clone(), direct_target));
+        }
+    }
+    Ok(tags)
+}
+
+/// Delete the local `tags` of a release to replace, so they can be created again for the replacement.
+pub(in crate::command::release_impl) fn delete_tags(
+    repo: &gix::Repository,
+    tags: &[(refs::FullName, gix::ObjectId)],
+) -> anyhow::Result<()> {
+    repo.edit_references(tags.iter().map(|(name, target)| RefEdit {
+        change: Change::Delete {
+            expected: PreviousValue::MustExistAndMatch(Target::Object(*target)),
+            log: RefLog::AndReference,
+        },
+        name: name.clone(),
+        deref: false,
+    }))?;
+    Ok(())
+}
+
+/// Return the quoted names of the remote refs that were rejected, leaving out those that were only rejected because
+/// another ref of an atomic push was.
+fn rejected_refs(pushed: &[PushedRef]) -> Vec<String> {
+    pushed
+        .iter()
+        .filter(|pushed| pushed.status == PushStatus::Rejected && !pushed.summary.contains("(atomic push failed)"))
+        .map(|pushed| format!("'{}'", pushed.remote_ref))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        certificate_lines, parse_release_author, rejected_refs, release_branch_name, with_signoff, PushStatus,
+        PushedRef,
+    };
+
+    #[test]
+    fn release_branch_names_are_filled_in_from_the_template() {
+        let version = semver::Version::new(0, 60, 0);
+        assert_eq!(
+            release_branch_name("release/{name}-{version}", "gix", &version),
+            "release/gix-0.60.0"
+        );
+        assert_eq!(
+            release_branch_name("release-v{version}", "gix", &version),
+            "release-v0.60.0"
+        );
+    }
+
+    #[test]
+    fn release_authors_need_a_name_and_email() {
+        let identity = parse_release_author(" Release Bot <bot@example.com> ").unwrap();
+        assert_eq!(identity.name, "Release Bot");
+        assert_eq!(identity.email, "bot@example.com");
+        assert!(parse_release_author("Release Bot").is_err());
+        assert!(parse_release_author("<bot@example.com>").is_err());
+    }
+
+    #[test]
+    fn certificate_results_are_taken_from_remote_lines() {
+        let messages = "Push certificate status: G\nResolving deltas: 100%\n  cert nonce OK  \n";
+        assert_eq!(
+            certificate_lines(messages).collect::<Vec<_>>(),
+            ["Push certificate status: G", "cert nonce OK"]
+        );
+    }
+
+    #[test]
+    fn signoff_trailers_are_appended_once() {
+        let signed = with_signoff("Release a v1.0.0", "A U Thor", "author@example.com");
+        assert_eq!(
+            signed,
+            "Release a v1.0.0\n\nSigned-off-by: A U Thor <author@example.com>\n"
+        );
+        assert_eq!(with_signoff(&signed, "A U Thor", "author@example.com"), signed);
+        assert_eq!(
+            with_signoff(&signed, "Other", "other@example.com"),
+            "Release a v1.0.0\n\nSigned-off-by: A U Thor <author@example.com>\nSigned-off-by: Other <other@example.com>\n"
+        );
+    }
+
+    #[test]
+    fn refs_rejected_only_due_to_atomic_pushes_are_not_reported() {
+        let pushed = [
+            PushedRef {
+                local_ref: "HEAD".into(),
+                remote_ref: "refs/heads/main".into(),
+                status: PushStatus::Rejected,
+                summary: "[rejected] (atomic push failed)".into(),
+            },
+            PushedRef {
+                local_ref: "refs/tags/b-v1.0.0".into(),
+                remote_ref: "refs/tags/b-v1.0.0".into(),
+                status: PushStatus::UpToDate,
+                summary: "[up to date]".into(),
+            },
+            PushedRef {
+                local_ref: "refs/tags/c-v1.0.0".into(),
+                remote_ref: "refs/tags/c-v1.0.0".into(),
+                status: PushStatus::Rejected,
+                summary: "[rejected] (already exists)".into(),
+            },
+        ];
+        assert_eq!(rejected_refs(&pushed), ["'refs/tags/c-v1.0.0'"]);
     }
 }
@@ -0,0 +1,161 @@
+//! Run the hooks of the repository for the release commit like `git commit` would, and before pushing like `git push`
+//! would, as both happen without `git`.
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context};
+
+use crate::utils::will;
+
+/// How to skip the hooks, for the errors of those that fail.
+const SKIP_HINT: &str = "Use --no-verify or set 'git-hooks = false' in the workspace configuration to skip the hooks.";
+
+/// A git hook that is run when committing or pushing.
+#[derive(Debug, Clone, Copy)]
+pub(in crate::command::release_impl) enum Hook {
+    /// Run before the commit is created, and able to prevent it.
+    PreCommit,
+    /// Run with the path to a file with the commit message, and able to change it or to prevent the commit.
+    CommitMsg,
+    /// Run after the commit was created.
+    PostCommit,
+    /// Run with the remote and the refs to update before pushing, and able to prevent the push.
+    PrePush,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::PreCommit => "pre-commit",
+            Hook::CommitMsg => "commit-msg",
+            Hook::PostCommit => "post-commit",
+            Hook::PrePush => "pre-push",
+        }
+    }
+}
+
+/// Run `hook` of `repo` in its worktree with the index at `index_path` if it's installed, and fail if it fails unless
+/// it's the `post-commit` hook.
+///
+/// `message` is passed to the `commit-msg` hook in a file and returned as it was possibly edited by the hook.
+pub(in crate::command::release_impl) fn run(
+    hook: Hook,
+    message: String,
+    index_path: &Path,
+    dry_run: bool,
+    repo: &gix::Repository,
+) -> anyhow::Result<String> {
+    let Some(path) = find(repo, hook)? else {
+        return Ok(message);
+    };
+    tracing::trace!("{} run the git hook '{}'", will(dry_run), path.display());
+    if dry_run {
+        return Ok(message);
+    }
+    let workdir = repo.workdir().context("Can only run hooks in non-bare repositories")?;
+    let mut cmd = command(&path);
+    cmd.current_dir(workdir).env("GIT_INDEX_FILE", index_path);
+    let message_path = repo.git_dir().join("COMMIT_EDITMSG");
+    if let Hook::CommitMsg = hook {
+        // Hooks which append trailers expect the message to end with a newline, like it does when `git` writes it.
+        let newline = if message.ends_with('\n') { "" } else { "\n" };
+        std::fs::write(&message_path, format!("{message}{newline}"))?;
+        cmd.arg(&message_path);
+    }
+    let status = cmd
+        .status()
+        .with_context(|| format!("Could not run the git hook '{}'", path.display()))?;
+    if !status.success() {
+        match hook {
+            Hook::PostCommit => tracing::warn!("The git hook '{}' failed", path.display()),
+            Hook::PreCommit | Hook::CommitMsg | Hook::PrePush => bail!(
+                "The git hook '{}' failed, so the release wasn't committed. {SKIP_HINT}",
+                path.display()
+            ),
+        }
+    }
+    Ok(match hook {
+        Hook::CommitMsg => std::fs::read_to_string(&message_path)?,
+        Hook::PreCommit | Hook::PostCommit | Hook::PrePush => message,
+    })
+}
+
+/// Run the `pre-push` hook of `repo` if it's installed with the `remote` name, or its `url` if it has none, and
+/// `commands` to push, and fail if it fails.
+pub(in crate::command::release_impl) fn run_pre_push(
+    remote: &str,
+    url: &str,
+    commands: &[crate::git::push::Command],
+    repo: &gix::Repository,
+) -> anyhow::Result<()> {
+    let Some(path) = find(repo, Hook::PrePush)? else {
+        return Ok(());
+    };
+    tracing::trace!("run the git hook '{}'", path.display());
+    let mut cmd = command(&path);
+    cmd.args([remote, url]).stdin(Stdio::piped());
+    if let Some(workdir) = repo.workdir() {
+        cmd.current_dir(workdir);
+    }
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Could not run the git hook '{}'", path.display()))?;
+    let mut stdin = child.stdin.take().expect("configured");
+    for command in commands {
+        let line = format!(
+            "{} {} {} {}\n",
+            command.local_ref, command.new, command.remote_ref, command.old
+        );
+        // The hook may exit without reading what's pushed, which is fine.
+        if stdin.write_all(line.as_bytes()).is_err() {
+            break;
+        }
+    }
+    drop(stdin);
+    if !child.wait()?.success() {
+        bail!(
+            "The git hook '{}' failed, so nothing was pushed. {SKIP_HINT}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Return the path to `hook` in the hooks directory of `repo`, which is `core.hooksPath` if set, if it's executable.
+fn find(repo: &gix::Repository, hook: Hook) -> anyhow::Result<Option<PathBuf>> {
+    let dir = match repo.config_snapshot().trusted_path("core.hooksPath").transpose()? {
+        // Relative paths are relative to the worktree where the hooks run.
+        Some(dir) => match repo.workdir() {
+            Some(workdir) => workdir.join(dir),
+            None => dir.into_owned(),
+        },
+        None => repo.common_dir().join("hooks"),
+    };
+    let path = dir.join(hook.name());
+    Ok(is_executable(&path).then_some(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+fn command(path: &Path) -> Command {
+    if cfg!(windows) {
+        // Hooks are shell scripts, which need the shell that comes with git.
+        let mut cmd = Command::new("sh");
+        cmd.arg(path);
+        cmd
+    } else {
+        Command::new(path)
+    }
+}
@@ -1,7 +1,11 @@
 #![allow(dead_code)]
 
-use std::{borrow::Cow, process::Command};
+use std::{
+    borrow::Cow,
+    process::{Command, Stdio},
+};
 
+use anyhow::{bail, Context as _};
 use cargo_metadata::Package;
 
 use crate::{
@@ -35,21 +39,21 @@ pub fn create_release(
     Options { dry_run, .. }: Options,
     ctx: &Context,
 ) -> anyhow::Result<()> {
-    let tag_name = crate::utils::tag_name(publishee, new_version, &ctx.repo);
+    let tag_name = crate::utils::tag_name(publishee, new_version, ctx);
     let mut cmd = Command::new("gh");
     cmd.args(["release", "create"])
         .arg(&tag_name)
         .arg("--title")
         .arg(format!(
             "{}v{}",
-            match crate::utils::tag_prefix(publishee, &ctx.repo) {
+            match crate::utils::tag_prefix(publishee, ctx.repo_of(publishee)) {
                 Some(prefix) => Cow::Owned(format!("{prefix} ")),
                 None => "".into(),
             },
             new_version
         ))
         .arg("--notes");
-    log::trace!(
+    tracing::trace!(
         "{} run {:?} \"{}…\" [note truncated]",
         will(dry_run),
         cmd,
@@ -63,9 +67,121 @@ pub fn create_release(
 
     cmd.arg(notes);
     if !dry_run && !cmd.status()?.success() {
-        log::warn!(
+        tracing::warn!(
             "'gh' tool execution failed - considering this non-critical, and you may try to create the release with: {cmd:?}"
         );
     }
     Ok(())
 }
+
+/// Attach `files` to the existing GitHub release of `publishee` at `new_version`, replacing assets of the same name.
+pub fn upload_assets(
+    publishee: &Package,
+    new_version: &semver::Version,
+    files: &[cargo_metadata::camino::Utf8PathBuf],
+    Options { dry_run, .. }: Options,
+    ctx: &Context,
+) -> anyhow::Result<()> {
+    let tag_name = crate::utils::tag_name(publishee, new_version, ctx);
+    let mut cmd = Command::new("gh");
+    cmd.args(["release", "upload", "--clobber"])
+        .arg(&tag_name)
+        .args(files.iter().map(|file| file.as_std_path()));
+    tracing::trace!("{} run {:?}", will(dry_run), cmd);
+    if !dry_run && !cmd.status()?.success() {
+        tracing::warn!(
+            "'gh' tool execution failed - considering this non-critical, and you may try to upload the release assets with: {cmd:?}"
+        );
+    }
+    Ok(())
+}
+
+/// Open a pull request for the checked out release branch, which must have been pushed, and return its URL.
+///
+/// Its body lists `publishees`, which are published after it was merged if `publish_after_merge` is set.
+pub fn open_pull_request(
+    publishees: &[(&Package, &semver::Version)],
+    Options {
+        dry_run,
+        publish_after_merge,
+        ..
+    }: Options,
+) -> anyhow::Result<Option<String>> {
+    let mut cmd = Command::new("gh");
+    cmd.args(["pr", "create", "--title"])
+        .arg(format!("Release {}", crate::utils::names_and_versions(publishees)))
+        .arg("--body")
+        .arg(pull_request_body(publishees, publish_after_merge));
+    tracing::trace!("{} run {:?}", will(dry_run), cmd);
+    if dry_run {
+        return Ok(None);
+    }
+    let out = cmd.stderr(Stdio::inherit()).output()?;
+    if !out.status.success() {
+        bail!("'gh' failed to open a pull request for the release branch, which was pushed already: {cmd:?}");
+    }
+    let url = String::from_utf8(out.stdout)?
+        .lines()
+        .last()
+        .map(|line| line.trim().to_owned())
+        .context("'gh' didn't print the URL of the new pull request")?;
+    tracing::info!("Opened pull request {url}");
+    Ok(Some(url))
+}
+
+/// Return the id of the commit the pull request at `url` was merged as, or `None` if it's still open.
+pub fn merge_commit(url: &str) -> anyhow::Result<Option<String>> {
+    let mut cmd = Command::new("gh");
+    cmd.args(["pr", "view", url, "--json", "state,mergeCommit"]);
+    tracing::trace!("run {:?}", cmd);
+    let out = cmd.stderr(Stdio::inherit()).output()?;
+    if !out.status.success() {
+        bail!("'gh' failed to query the state of the pull request at {url}");
+    }
+    let pr: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    match pr["state"].as_str() {
+        Some("MERGED") => Ok(Some(
+            pr["mergeCommit"]["oid"]
+                .as_str()
+                .context("A merged pull request has a merge commit")?
+                .to_owned(),
+        )),
+        Some("OPEN") => Ok(None),
+        state => bail!(
+            "The pull request at {url} was {}, so the release can't be published",
+            state.unwrap_or("unknown").to_lowercase()
+        ),
+    }
+}
+
+fn pull_request_body(publishees: &[(&Package, &semver::Version)], publish_after_merge: bool) -> String {
+    let mut body = String::from("This releases the following crates:\n\n");
+    for (publishee, version) in publishees {
+        body.push_str(&format!("- `{}` v{version}\n", publishee.name));
+    }
+    body.push('\n');
+    body.push_str(if publish_after_merge {
+        "They will be published once this pull request is merged."
+    } else {
+        "They were published already, from the commit of this pull request."
+    });
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::pull_request_body;
+    use crate::utils::tests::package;
+
+    #[test]
+    fn pull_request_bodies_list_the_released_crates() {
+        let (a, b) = (package("a", &[], json!(null)), package("b", &[], json!(null)));
+        let version = semver::Version::new(0, 2, 0);
+        assert_eq!(
+            pull_request_body(&[(&a, &version), (&b, &version)], true),
+            "This releases the following crates:\n\n- `a` v0.2.0\n- `b` v0.2.0\n\nThey will be published once this pull request is merged."
+        );
+    }
+}
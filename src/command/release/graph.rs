@@ -0,0 +1,182 @@
+//! Render the crates of a release as a graph of their dependencies, annotated with the order they are published in.
+use std::fmt::Write;
+
+use crate::{
+    command::release::GraphFormat,
+    traverse::{dependency, Dependency},
+    utils::package_eq_dependency_ignore_dev_without_version,
+};
+
+struct Node<'a> {
+    name: &'a str,
+    label: String,
+    published: bool,
+}
+
+/// Return `crates` as graph in `format`, with an edge from each crate to each of its dependencies among `crates`.
+///
+/// Crates to be published are labelled with their position in the publish order and their version change, all others
+/// with the reason for not publishing them.
+pub(in crate::command::release_impl) fn render(crates: &[Dependency<'_>], format: GraphFormat) -> String {
+    let mut publish_order = 0;
+    let nodes: Vec<_> = crates
+        .iter()
+        .map(|dep| {
+            let name = dep.package.name.as_str();
+            match &dep.mode {
+                dependency::Mode::ToBePublished { adjustment } => {
+                    publish_order += 1;
+                    Node {
+                        name,
+                        label: format!(
+                            "{publish_order}. {name} v{} -> v{}",
+                            dep.package.version,
+                            adjustment.bump().next_release
+                        ),
+                        published: true,
+                    }
+                }
+                dependency::Mode::NotForPublishing { reason, .. } => Node {
+                    name,
+                    label: format!("{name} v{} ({reason})", dep.package.version),
+                    published: false,
+                },
+            }
+        })
+        .collect();
+    let edges: Vec<_> = crates
+        .iter()
+        .enumerate()
+        .flat_map(|(from, dep)| {
+            crates.iter().enumerate().filter_map(move |(to, other)| {
+                dep.package
+                    .dependencies
+                    .iter()
+                    .any(|d| package_eq_dependency_ignore_dev_without_version(other.package, d))
+                    .then_some((from, to))
+            })
+        })
+        .collect();
+
+    match format {
+        GraphFormat::Dot => dot(&nodes, &edges),
+        GraphFormat::Mermaid => mermaid(&nodes, &edges),
+    }
+}
+
+fn dot(nodes: &[Node<'_>], edges: &[(usize, usize)]) -> String {
+    let mut out = String::from("digraph release {\n    rankdir = BT;\n");
+    for node in nodes {
+        let style = if node.published { "" } else { ", style = dashed" };
+        writeln!(out, "    \"{}\" [label = \"{}\"{style}];", node.name, node.label).ok();
+    }
+    for (from, to) in edges {
+        writeln!(out, "    \"{}\" -> \"{}\";", nodes[*from].name, nodes[*to].name).ok();
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn mermaid(nodes: &[Node<'_>], edges: &[(usize, usize)]) -> String {
+    let mut out = String::from("flowchart BT\n");
+    for (id, node) in nodes.iter().enumerate() {
+        writeln!(out, "    n{id}[\"{}\"]", node.label).ok();
+    }
+    for (from, to) in edges {
+        writeln!(out, "    n{from} --> n{to}").ok();
+    }
+    let skipped: Vec<_> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| !node.published)
+        .map(|(id, _)| format!("n{id}"))
+        .collect();
+    if !skipped.is_empty() {
+        out.push_str("    classDef skipped stroke-dasharray: 5 5\n");
+        writeln!(out, "    class {} skipped", skipped.join(",")).ok();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::render;
+    use crate::{
+        command::release::GraphFormat,
+        traverse::{dependency, Dependency},
+        version::Bump,
+    };
+
+    fn package(name: &str, dependencies: &[&str]) -> cargo_metadata::Package {
+        let dependencies: Vec<_> = dependencies.iter().map(|dep| json!({ "name": dep })).collect();
+        crate::utils::tests::package(name, &dependencies, json!(null))
+    }
+
+    fn published(package: &cargo_metadata::Package) -> Dependency<'_> {
+        let next_release = semver::Version::new(0, 2, 0);
+        Dependency {
+            package,
+            kind: dependency::Kind::UserSelection,
+            mode: dependency::Mode::ToBePublished {
+                adjustment: dependency::VersionAdjustment::Changed {
+                    change: None,
+                    bump: Bump {
+                        next_release: next_release.clone(),
+                        package_version: package.version.clone(),
+                        latest_release: None,
+                        desired_release: next_release,
+                    },
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn crates_are_rendered_with_their_dependencies_and_publish_order() {
+        let (a, b, c) = (
+            package("a", &[]),
+            package("b", &["a", "serde"]),
+            package("c", &["a", "b"]),
+        );
+        let crates = [
+            published(&a),
+            Dependency {
+                package: &b,
+                kind: dependency::Kind::DependencyOrDependentOfUserSelection,
+                mode: dependency::Mode::NotForPublishing {
+                    reason: dependency::NoPublishReason::Unchanged,
+                    adjustment: None,
+                },
+            },
+            published(&c),
+        ];
+        assert_eq!(
+            render(&crates, GraphFormat::Dot),
+            r#"digraph release {
+    rankdir = BT;
+    "a" [label = "1. a v0.1.0 -> v0.2.0"];
+    "b" [label = "b v0.1.0 (unchanged)", style = dashed];
+    "c" [label = "2. c v0.1.0 -> v0.2.0"];
+    "b" -> "a";
+    "c" -> "a";
+    "c" -> "b";
+}
+"#
+        );
+        assert_eq!(
+            render(&crates, GraphFormat::Mermaid),
+            r#"flowchart BT
+    n0["1. a v0.1.0 -> v0.2.0"]
+    n1["b v0.1.0 (unchanged)"]
+    n2["2. c v0.1.0 -> v0.2.0"]
+    n1 --> n0
+    n2 --> n0
+    n2 --> n1
+    classDef skipped stroke-dasharray: 5 5
+    class n1 skipped
+"#
+        );
+    }
+}
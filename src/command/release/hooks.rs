@@ -0,0 +1,133 @@
+//! Commands configured by the user to run at certain steps of a release, like to generate code or to make announcements.
+use std::process::Command;
+
+use anyhow::bail;
+use cargo_metadata::Package;
+
+use crate::utils::will;
+
+/// The steps of a release that hooks can run at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(in crate::command::release_impl) enum Hook {
+    /// Before manifests and changelogs are changed.
+    PreBump,
+    /// After manifests and changelogs were changed, but before they are committed.
+    PostBump,
+    /// Before the crate is published.
+    PrePublish,
+    /// After the crate was published.
+    PostPublish,
+    /// After the release commit and all tags were pushed.
+    PostPush,
+}
+
+impl Hook {
+    fn name(&self) -> &'static str {
+        match self {
+            Hook::PreBump => "pre-bump",
+            Hook::PostBump => "post-bump",
+            Hook::PrePublish => "pre-publish",
+            Hook::PostPublish => "post-publish",
+            Hook::PostPush => "post-push",
+        }
+    }
+}
+
+/// Run the commands configured for `hook` in `[workspace.metadata.smart-release.hooks]` and then those in
+/// `[package.metadata.smart-release.hooks]` of `package`, from the workspace root.
+///
+/// They are run by the shell with the crate name and `version` in the `SMART_RELEASE_CRATE` and `SMART_RELEASE_VERSION`
+/// environment variables, and the first failing one aborts the release.
+pub(in crate::command::release_impl) fn run(
+    hook: Hook,
+    package: &Package,
+    version: &semver::Version,
+    ctx: &crate::Context,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let commands = commands(&ctx.meta.workspace_metadata, hook)?
+        .into_iter()
+        .chain(commands(&package.metadata, hook)?);
+    for command in commands {
+        let mut cmd = shell(&command);
+        cmd.current_dir(&ctx.root)
+            .env("SMART_RELEASE_HOOK", hook.name())
+            .env("SMART_RELEASE_CRATE", package.name.as_str())
+            .env("SMART_RELEASE_VERSION", version.to_string());
+        tracing::trace!(
+            "{} run {} hook for '{} v{version}': {command}",
+            will(dry_run),
+            hook.name(),
+            package.name
+        );
+        if !dry_run && !cmd.status()?.success() {
+            bail!(
+                "The {} hook '{command}' failed for '{} v{version}'",
+                hook.name(),
+                package.name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Read the commands of `hook` from the `hooks` table of the `smart-release` table in `metadata`, which can be a single
+/// command or a list of them.
+fn commands(metadata: &serde_json::Value, hook: Hook) -> anyhow::Result<Vec<String>> {
+    let Some(value) = metadata
+        .get("smart-release")
+        .and_then(|config| config.get("hooks"))
+        .and_then(|hooks| hooks.get(hook.name()))
+    else {
+        return Ok(Vec::new());
+    };
+    match value {
+        serde_json::Value::String(command) => Ok(vec![command.clone()]),
+        serde_json::Value::Array(commands) => commands
+            .iter()
+            .map(|command| command.as_str().map(ToOwned::to_owned))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow::anyhow!("The '{}' hook must be a command or a list of commands", hook.name())),
+        _ => bail!("The '{}' hook must be a command or a list of commands", hook.name()),
+    }
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{commands, Hook};
+
+    #[test]
+    fn hooks_are_single_commands_or_lists() {
+        let metadata = json!({
+            "smart-release": {
+                "hooks": {
+                    "pre-publish": "cargo xtask codegen",
+                    "post-push": ["./announce.sh", "cargo sync-mirror"],
+                    "post-bump": 42
+                }
+            }
+        });
+        assert_eq!(commands(&metadata, Hook::PrePublish).unwrap(), ["cargo xtask codegen"]);
+        assert_eq!(
+            commands(&metadata, Hook::PostPush).unwrap(),
+            ["./announce.sh", "cargo sync-mirror"]
+        );
+        assert!(commands(&metadata, Hook::PreBump).unwrap().is_empty());
+        assert!(commands(&metadata, Hook::PostBump).is_err());
+        assert!(commands(&json!({}), Hook::PostPublish).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! Let the user adjust which crates are released and how they are bumped before the release is performed.
+use std::io::{IsTerminal, Write};
+
+use anyhow::bail;
+
+use super::{Context, Options};
+use crate::{
+    changelog::write::Components,
+    traverse::{self, dependency},
+    version::BumpSpec,
+    ChangeLog,
+};
+
+/// What the user wants to do next.
+#[derive(Debug)]
+enum Command {
+    /// Select the crate at the index if it isn't selected, or deselect it otherwise.
+    Toggle(usize),
+    /// Bump the crate at the index as specified, instead of how it would be bumped otherwise.
+    Bump(usize, BumpSpec),
+    /// Show the unreleased changes of the crate at the index as they would appear in its changelog.
+    Preview(usize),
+    /// Release the crates as listed.
+    Proceed,
+    /// Don't release anything.
+    Abort,
+}
+
+/// Show the crates of the release along with their version changes, and let the user toggle crates, adjust their bumps
+/// and preview their changelogs until they proceed, which is when `true` is returned.
+///
+/// The selection and bumps are changed in `ctx`, so the release is computed from them afterwards.
+pub(in crate::command::release_impl) fn adjust(ctx: &mut Context, opts: &Options) -> anyhow::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        bail!("--interactive needs a terminal to read the selection from");
+    }
+    loop {
+        let names = list(ctx, opts)?;
+        print!(
+            "Enter a number to toggle a crate, '<number> major|minor|patch|keep|auto' to change its bump, \
+             'p <number>' to preview its changelog, 'y' to proceed or 'q' to abort: "
+        );
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            return Ok(false);
+        }
+        let command = match parse(&line, names.len()) {
+            Ok(command) => command,
+            Err(err) => {
+                println!("{err}");
+                continue;
+            }
+        };
+        match command {
+            Command::Toggle(idx) => {
+                let name = &names[idx];
+                let crate_names = &mut ctx.base.crate_names;
+                match crate_names.iter().position(|selected| selected == name) {
+                    Some(_) if crate_names.len() == 1 => println!("At least one crate must remain selected"),
+                    Some(pos) => {
+                        crate_names.remove(pos);
+                    }
+                    None => crate_names.push(name.clone()),
+                }
+            }
+            Command::Bump(idx, spec) => {
+                ctx.base.bump_overrides.insert(names[idx].clone(), spec);
+            }
+            Command::Preview(idx) => preview(ctx, opts, &names[idx])?,
+            Command::Proceed => return Ok(true),
+            Command::Abort => return Ok(false),
+        }
+    }
+}
+
+/// Print the crates of the release as currently selected, and return their names in the order they are listed in.
+fn list(ctx: &Context, opts: &Options) -> anyhow::Result<Vec<String>> {
+    let crates = traverse::dependencies(&ctx.base, opts.clone().into())?;
+    println!();
+    let mut names = Vec::with_capacity(crates.len());
+    for (idx, dep) in crates.iter().enumerate() {
+        let selected = if matches!(dep.kind, dependency::Kind::UserSelection) {
+            "x"
+        } else {
+            " "
+        };
+        let change = match &dep.mode {
+            dependency::Mode::ToBePublished { adjustment } => {
+                format!("v{} -> v{}", dep.package.version, adjustment.bump().next_release)
+            }
+            dependency::Mode::NotForPublishing { reason, .. } => format!("v{} ({reason})", dep.package.version),
+        };
+        println!("{:>3}. [{selected}] {} {change}", idx + 1, dep.package.name);
+        names.push(dep.package.name.to_string());
+    }
+    Ok(names)
+}
+
+fn parse(line: &str, num_crates: usize) -> anyhow::Result<Command> {
+    let index = |number: &str| -> anyhow::Result<usize> {
+        match number.parse::<usize>() {
+            Ok(number) if (1..=num_crates).contains(&number) => Ok(number - 1),
+            _ => bail!("'{number}' isn't the number of a listed crate"),
+        }
+    };
+    let words: Vec<_> = line.split_whitespace().collect();
+    Ok(match words.as_slice() {
+        ["y" | "yes"] => Command::Proceed,
+        ["q" | "quit"] => Command::Abort,
+        ["p" | "preview", number] => Command::Preview(index(number)?),
+        [number] => Command::Toggle(index(number)?),
+        [number, spec] => Command::Bump(index(number)?, spec.parse()?),
+        _ => bail!("Didn't understand '{}'", line.trim()),
+    })
+}
+
+/// Print the unreleased section that would be added to the changelog of the crate named `name`.
+fn preview(ctx: &Context, opts: &Options, name: &str) -> anyhow::Result<()> {
+    let Some(history) = ctx.base.history.as_ref() else {
+        println!("The changelog can't be previewed as the history wasn't collected");
+        return Ok(());
+    };
+    let package = crate::utils::package_by_name(&ctx.base.meta, name)?;
+    let segments = crate::git::history::crate_ref_segments(
+        package,
+        &ctx.base,
+        history,
+        crate::git::history::SegmentScope::Unreleased,
+    )?;
+    let log = ChangeLog::from_history_segments(
+        package,
+        &segments,
+        ctx.base.repo_of(package),
+        ctx.base.version_scheme.as_ref(),
+        opts.generator_segments,
+        ctx.base.history_options.authors,
+    );
+    let mut markdown = String::new();
+    log.write_to(
+        &mut markdown,
+        &ctx.changelog_links,
+        Components::all(),
+        &opts.clone().into(),
+    )?;
+    println!("\n{markdown}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Command};
+
+    #[test]
+    fn commands_refer_to_crates_by_their_listed_number() {
+        assert!(matches!(parse("2\n", 3).unwrap(), Command::Toggle(1)));
+        assert!(matches!(parse("p 3", 3).unwrap(), Command::Preview(2)));
+        assert!(matches!(
+            parse("1 minor", 3).unwrap(),
+            Command::Bump(0, crate::version::BumpSpec::Minor)
+        ));
+        assert!(matches!(parse("y", 3).unwrap(), Command::Proceed));
+        assert!(matches!(parse("q", 3).unwrap(), Command::Abort));
+        assert!(parse("4", 3).is_err());
+        assert!(parse("0", 3).is_err());
+        assert!(parse("1 huge", 3).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! A record of the steps of a release that were performed already, to be able to resume it if it fails midway.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use cargo_metadata::camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// The state of a release performed with `--execute`, persisted in the `.git` directory after each step.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(in crate::command::release_impl) struct Journal {
+    /// The crates to publish along with their new version, in the order they are published in.
+    pub crates: Vec<Release>,
+    /// The commit with the adjusted manifests and changelogs, if one was made.
+    pub commit: Option<String>,
+    /// The names of the crates which were published successfully.
+    pub published: BTreeSet<String>,
+    /// The full names of the tags that were created, by the name of the crate they were created for.
+    pub tags: BTreeMap<String, String>,
+    /// If `true`, the tags and the release commit were pushed.
+    pub pushed: bool,
+    /// The names of the crates for which a GitHub release was created.
+    pub github_releases: BTreeSet<String>,
+    /// The paths to the Sigstore bundles of the signed crates and their tags, by the name of the crate.
+    pub signatures: BTreeMap<String, Vec<Utf8PathBuf>>,
+    /// The names of the crates whose downloaded `.crate` file was checked to match the one that was uploaded.
+    pub verified: BTreeSet<String>,
+    /// The names of the crates published to each of the additional registries, by the name of the registry.
+    pub mirrored: BTreeMap<String, BTreeSet<String>>,
+    /// The URL of the pull request opened for the release branch.
+    pub pull_request: Option<String>,
+    /// If `true`, publishing waits until the pull request is merged, and the tags are created for its merge commit.
+    #[serde(default)]
+    pub publish_after_merge: bool,
+    /// The ids the refs of a replaced release must have on the remote to be pushed over, by their full name.
+    #[serde(default)]
+    pub force_with_lease: BTreeMap<String, String>,
+}
+
+/// A crate to release.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(in crate::command::release_impl) struct Release {
+    pub name: String,
+    pub version: semver::Version,
+}
+
+impl Journal {
+    /// The journal is private to the worktree `repo` was opened in, so releases in other worktrees don't see it.
+    fn path(repo: &gix::Repository) -> PathBuf {
+        repo.git_dir().join("smart-release-state.json")
+    }
+
+    /// Read the journal of a previous release that didn't finish, if there is one.
+    pub fn load(repo: &gix::Repository) -> anyhow::Result<Option<Self>> {
+        let path = Self::path(repo);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Could not parse release journal at '{}'", path.display()))
+            .map(Some)
+    }
+
+    /// Write the journal, replacing the previous one atomically.
+    pub fn save(&self, repo: &gix::Repository) -> anyhow::Result<()> {
+        let path = Self::path(repo);
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Could not write release journal to '{}'", path.display()))
+    }
+
+    /// Delete the journal once the release is complete.
+    pub fn remove(repo: &gix::Repository) -> anyhow::Result<()> {
+        match std::fs::remove_file(Self::path(repo)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
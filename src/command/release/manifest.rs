@@ -1,7 +1,7 @@
 use std::{
     borrow::Cow,
     collections::{btree_map::Entry, BTreeMap},
-    io::Write,
+    io::{IsTerminal, Write},
     str::FromStr,
 };
 
@@ -10,10 +10,14 @@ use cargo_metadata::{camino::Utf8PathBuf, Package};
 use gix::{lock::File, Id};
 use semver::{Version, VersionReq};
 
-use super::{cargo, git, Context, Options};
+use super::{
+    cargo, git,
+    hooks::{self, Hook},
+    Context, Options,
+};
 use crate::{
     changelog,
-    changelog::{write::Linkables, Section},
+    changelog::Section,
     traverse::Dependency,
     utils::{names_and_versions, try_to_published_crate_and_new_version, version_req_unset_or_default, will},
     version, ChangeLog,
@@ -36,6 +40,8 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates_
         .collect();
     let GatherOutcome {
         pending_changelogs,
+        tracking_locks_by_changelog_id,
+        changelog_diffs,
         mut locks_by_manifest_path,
         changelog_ids_with_statistical_segments_only,
         changelog_ids_probably_lacking_user_edits,
@@ -45,18 +51,23 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates_
         .then(|| gather_changelog_data(ctx, &crates_and_versions_to_be_published, opts.clone()))
         .transpose()?
         .unwrap_or_default();
+    let changelogs_changed = made_change;
 
     let crates_with_version_change: Vec<_> = crates
         .iter()
         .filter_map(|c| c.mode.version_adjustment_bump().map(|b| (c.package, &b.next_release)))
         .collect();
+    let workspace_version = crates_with_version_change
+        .iter()
+        .find_map(|(package, version)| ctx.base.inherits_workspace_version(package).then_some(*version));
+    let workspace_manifest_path = ctx.base.root.join("Cargo.toml");
     for (package, possibly_new_version) in crates
         .iter()
         .filter(|c| c.mode.manifest_will_change())
         .map(|c| (c.package, c.mode.version_adjustment_bump().map(|b| &b.next_release)))
     {
         let mut entry_store;
-        let lock = match locks_by_manifest_path.entry(&package.manifest_path) {
+        let lock = match locks_by_manifest_path.entry(package.manifest_path.clone()) {
             Entry::Occupied(entry) => {
                 entry_store = entry;
                 entry_store.get_mut()
@@ -78,11 +89,25 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates_
         made_change |= set_version_and_update_package_dependency(
             package,
             possibly_new_version,
+            workspace_version.filter(|_| package.manifest_path == workspace_manifest_path),
             &crates_with_version_change,
             lock,
             opts.clone(),
         )?;
     }
+    if let Some(version) = workspace_version.filter(|_| !locks_by_manifest_path.contains_key(&workspace_manifest_path))
+    {
+        let mut lock = gix::lock::File::acquire_to_update_resource(
+            &workspace_manifest_path,
+            gix::lock::acquire::Fail::Immediately,
+            None,
+        )
+        .with_context(|| {
+            format!("While locking workspace manifest '{workspace_manifest_path}' to update the version of its members")
+        })?;
+        made_change |= set_workspace_version(&workspace_manifest_path, version, &mut lock)?;
+        locks_by_manifest_path.insert(workspace_manifest_path, lock);
+    }
 
     let would_stop_release = (!changelog_ids_with_statistical_segments_only.is_empty()
         && !opts.allow_fully_generated_changelogs)
@@ -100,31 +125,92 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates_
         opts.clone(),
     );
 
-    preview_changelogs(ctx, &pending_changelogs, opts.clone())?;
+    preview_changelogs(ctx, &pending_changelogs, &changelog_diffs, opts.clone())?;
 
-    // Collect all changelog paths before they are consumed by commit_locks_and_generate_bail_message
-    let changelog_paths: Vec<std::path::PathBuf> = pending_changelogs
+    // Collect all paths before they are consumed by commit_locks_and_generate_bail_message, along with their new
+    // content in dry-run mode as the locks are dropped without changing the files. Changelogs come first.
+    let num_changelog_paths = pending_changelogs.len() + tracking_locks_by_changelog_id.len();
+    let mut changed_paths = pending_changelogs
         .iter()
-        .map(|(_, _, lock)| lock.resource_path().to_owned())
-        .collect();
+        .map(|(_, _, lock)| lock)
+        .chain(tracking_locks_by_changelog_id.values())
+        .chain(locks_by_manifest_path.values())
+        .map(|lock| {
+            let content = dry_run.then(|| std::fs::read(lock.lock_path())).transpose()?;
+            Ok((lock.resource_path(), content))
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    if dry_run {
+        print_manifest_diffs(ctx, &changed_paths[num_changelog_paths..])?;
+        // Cargo only updates the lock file once the manifests are written, so predict its changes.
+        let lock_file_path = ctx.base.root.join("Cargo.lock").into_std_path_buf();
+        if let Some(content) = lock_file_with_new_versions(&lock_file_path, &crates_with_version_change)? {
+            if crate::git::is_tracked(&ctx.base.repo, &lock_file_path)? {
+                changed_paths.push((lock_file_path, Some(content.into_bytes())));
+            }
+        }
+    }
 
     let bail_message = commit_locks_and_generate_bail_message(
         ctx,
         pending_changelogs,
+        tracking_locks_by_changelog_id,
         locks_by_manifest_path,
         changelog_ids_with_statistical_segments_only,
         changelog_ids_probably_lacking_user_edits,
         opts.clone(),
     )?;
 
-    let res = git::commit_changes(
-        commit_message,
-        dry_run,
-        !made_change,
-        opts.signoff,
-        &changelog_paths,
-        &ctx.base.repo,
-    )?;
+    for (package, version) in &crates_with_version_change {
+        hooks::run(Hook::PostBump, package, version, &ctx.base, dry_run)?;
+    }
+    if let Some(template) = &opts.release_branch {
+        let (package, version) = crates_and_versions_to_be_published
+            .last()
+            .copied()
+            .or_else(|| crates_with_version_change.last().copied())
+            .context("There must be a crate with a version change to name the release branch after")?;
+        let name = git::release_branch_name(template, &package.name, version);
+        git::create_release_branch(&name, dry_run, &ctx.base.repo)?;
+    } else if let Some(branch) = &opts.branch {
+        git::check_out_branch(branch, dry_run, &ctx.base.repo)?;
+    }
+    let res = if opts.separate_changelog_commit && changelogs_changed {
+        let changelog_commit = git::commit_changes(
+            changelog_commit_message(&crates_and_versions_to_be_published, opts.clone()),
+            false,
+            &changed_paths[..num_changelog_paths],
+            true,
+            None,
+            &ctx.base.repo,
+            opts.clone(),
+        )?;
+        // In dry-run mode the changelogs aren't written, so they are passed on to be part of the tree of the next commit.
+        let (paths, dry_run_parent) = if dry_run {
+            (&changed_paths[..], changelog_commit.map(|id| id.detach()))
+        } else {
+            (&changed_paths[num_changelog_paths..], None)
+        };
+        git::commit_changes(
+            commit_message,
+            !made_change,
+            paths,
+            false,
+            dry_run_parent,
+            &ctx.base.repo,
+            opts.clone(),
+        )?
+    } else {
+        git::commit_changes(
+            commit_message,
+            !made_change,
+            &changed_paths,
+            false,
+            None,
+            &ctx.base.repo,
+            opts.clone(),
+        )?
+    };
     if let Some(bail_message) = bail_message {
         bail!(bail_message);
     } else {
@@ -138,7 +224,8 @@ pub(in crate::command::release_impl) fn edit_version_and_fixup_dependent_crates_
 fn commit_locks_and_generate_bail_message(
     ctx: &Context,
     pending_changelogs: Vec<(&Package, bool, File)>,
-    locks_by_manifest_path: BTreeMap<&Utf8PathBuf, File>,
+    mut tracking_locks_by_changelog_id: BTreeMap<usize, File>,
+    locks_by_manifest_path: BTreeMap<Utf8PathBuf, File>,
     changelog_ids_with_statistical_segments_only: Vec<usize>,
     changelog_ids_probably_lacking_user_edits: Vec<usize>,
     Options {
@@ -157,6 +244,9 @@ fn commit_locks_and_generate_bail_message(
                 || changelog_ids_with_statistical_segments_only.contains(&idx)
             {
                 lock.commit()?;
+                if let Some(tracking_lock) = tracking_locks_by_changelog_id.remove(&idx) {
+                    tracking_lock.commit()?;
+                }
                 if !allow_fully_generated_changelogs && !changelog_ids_with_statistical_segments_only.is_empty() {
                     packages_whose_changelogs_need_edits
                         .get_or_insert_with(Vec::new)
@@ -182,7 +272,7 @@ fn commit_locks_and_generate_bail_message(
             let names_of_crates_in_need_of_changelog_entry =
                 logs.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", ");
             if skip_publish {
-                log::warn!(
+                tracing::warn!(
                     "Please consider creating changelog entries for crate{}: {}",
                     if logs.len() == 1 { "" } else { "s" },
                     names_of_crates_in_need_of_changelog_entry
@@ -227,7 +317,7 @@ fn commit_locks_and_generate_bail_message(
         if !changelog_ids_with_statistical_segments_only.is_empty() {
             let crate_names = crate_names(&changelog_ids_with_statistical_segments_only);
             let names_of_crates_that_would_need_review = crate_names.join(", ");
-            log::warn!(
+            tracing::warn!(
                 "WOULD {message}{plural_s}: {crates}",
                 crates = names_of_crates_that_would_need_review,
                 plural_s = if changelog_ids_with_statistical_segments_only.len() == 1 {
@@ -244,7 +334,7 @@ fn commit_locks_and_generate_bail_message(
                 },
             );
             if !allow_empty_release_message {
-                log::warn!(
+                tracing::warn!(
                     "To fix the changelog manually, run: cargo changelog --write {}",
                     ctx.base.crate_names.join(" ")
                 );
@@ -252,7 +342,7 @@ fn commit_locks_and_generate_bail_message(
         }
         if !changelog_ids_probably_lacking_user_edits.is_empty() {
             let crate_names = crate_names(&changelog_ids_probably_lacking_user_edits);
-            log::warn!(
+            tracing::warn!(
                 "{} likely to be fully generated from commit history or contain lower-case git-conventional headlines: {}{}",
                 if changelog_ids_probably_lacking_user_edits.len() == 1 {
                     "This changelog is"
@@ -268,7 +358,7 @@ fn commit_locks_and_generate_bail_message(
             );
             if !allow_fully_generated_changelogs {
                 for crate_name in crate_names {
-                    log::warn!("{fix_preamble} {crate_name}");
+                    tracing::warn!("{fix_preamble} {crate_name}");
                 }
             }
         }
@@ -280,7 +370,14 @@ fn commit_locks_and_generate_bail_message(
 fn preview_changelogs(
     ctx: &Context,
     pending_changelogs: &[(&Package, bool, File)],
-    Options { dry_run, preview, .. }: Options,
+    changelog_diffs: &[String],
+    Options {
+        dry_run,
+        preview,
+        preview_command,
+        yes,
+        ..
+    }: Options,
 ) -> anyhow::Result<()> {
     if !pending_changelogs.is_empty() && preview && !dry_run {
         let additional_info =
@@ -289,13 +386,14 @@ fn preview_changelogs(
             .iter()
             .filter_map(|(_, has_changes, lock)| (*has_changes).then_some(lock))
             .collect::<Vec<_>>();
-        log::info!(
+        tracing::info!(
             "About to preview {} pending changelog(s), {}",
             changelogs_with_changes.len(),
             additional_info
         );
 
-        let bat = crate::bat::Support::new();
+        let bat = crate::bat::Support::new(preview_command.as_deref());
+        let bat = if yes { bat.non_interactive() } else { bat };
         for (idx, lock) in changelogs_with_changes.iter().enumerate() {
             let additional_info = format!(
                 "PREVIEW {} / {}, {}{}",
@@ -311,7 +409,18 @@ fn preview_changelogs(
             )?;
         }
     } else if !pending_changelogs.is_empty() && preview {
-        log::info!(
+        if !changelog_diffs.is_empty() {
+            tracing::info!(
+                "Showing the changes to {} changelog{} that would be written with --execute.",
+                changelog_diffs.len(),
+                if changelog_diffs.len() == 1 { "" } else { "s" }
+            );
+            let mut stdout = std::io::stdout().lock();
+            for diff in changelog_diffs {
+                stdout.write_all(diff.as_bytes())?;
+            }
+        }
+        tracing::info!(
             "Up to {} changelog{} would be previewed if the --execute is set and --no-changelog-preview is unset.",
             pending_changelogs.len(),
             if pending_changelogs.len() == 1 { "" } else { "s" }
@@ -320,6 +429,38 @@ fn preview_changelogs(
     Ok(())
 }
 
+/// Print unified diffs of the `manifests` with the content they would have in dry-run mode, next to their path.
+fn print_manifest_diffs(ctx: &Context, manifests: &[(std::path::PathBuf, Option<Vec<u8>>)]) -> anyhow::Result<()> {
+    let color = crate::color::stdout();
+    let mut diffs = Vec::new();
+    for (path, content) in manifests {
+        let Some(content) = content else { continue };
+        let before = std::fs::read_to_string(path)?;
+        let relative_path = path.strip_prefix(ctx.base.root.as_std_path()).unwrap_or(path);
+        if let Some(diff) = crate::diff::unified(
+            &relative_path.display().to_string(),
+            Some(&before),
+            std::str::from_utf8(content)?,
+            color,
+        )? {
+            diffs.push(diff);
+        }
+    }
+    if diffs.is_empty() {
+        return Ok(());
+    }
+    tracing::info!(
+        "Showing the changes to {} manifest{} that would be written with --execute.",
+        diffs.len(),
+        if diffs.len() == 1 { "" } else { "s" }
+    );
+    let mut stdout = std::io::stdout().lock();
+    for diff in diffs {
+        stdout.write_all(diff.as_bytes())?;
+    }
+    Ok(())
+}
+
 fn generate_commit_message(
     crates_and_versions_to_be_published: &[(&Package, &Version)],
     safety_bumped_packages: &[(&Package, &Version)],
@@ -330,6 +471,7 @@ fn generate_commit_message(
         skip_publish,
         dry_run,
         commit_prefix,
+        commit_message,
         ..
     }: Options,
 ) -> String {
@@ -357,11 +499,18 @@ fn generate_commit_message(
             }
         }
     );
+    if let Some(template) = commit_message {
+        message = message_from_template(
+            &template,
+            &message,
+            &names_and_versions(crates_and_versions_to_be_published),
+        );
+    }
     if let Some(prefix) = commit_prefix {
         message = format!("{prefix} {message}");
     }
 
-    log::trace!(
+    tracing::trace!(
         "{} persist changes to {} manifests {}with: {:?}",
         will(dry_run),
         num_locks,
@@ -388,10 +537,42 @@ fn generate_commit_message(
     message
 }
 
+/// The message of the commit with only the changelogs of `crates_and_versions_to_be_published`, made before the one
+/// with their version changes if they are committed separately.
+fn changelog_commit_message(
+    crates_and_versions_to_be_published: &[(&Package, &Version)],
+    Options {
+        commit_prefix,
+        commit_message,
+        ..
+    }: Options,
+) -> String {
+    let crates = names_and_versions(crates_and_versions_to_be_published);
+    let mut message = format!("Update changelogs prior to release of {crates}");
+    if let Some(template) = commit_message {
+        message = message_from_template(&template, &message, &crates);
+    }
+    match commit_prefix {
+        Some(prefix) => format!("{prefix} {message}"),
+        None => message,
+    }
+}
+
+/// Turn `template` into a commit message, replacing `{message}` with the generated `message` and `{crates}` with the
+/// names and versions of the released `crates`.
+fn message_from_template(template: &str, message: &str, crates: &str) -> String {
+    template.replace("{message}", message).replace("{crates}", crates)
+}
+
 #[derive(Default)]
 pub struct GatherOutcome<'meta> {
     pending_changelogs: Vec<(&'meta Package, bool, File)>,
-    locks_by_manifest_path: BTreeMap<&'meta Utf8PathBuf, File>,
+    /// Locks on sidecar files with tracking data, by index into `pending_changelogs`
+    tracking_locks_by_changelog_id: BTreeMap<usize, File>,
+    /// Colored unified diffs of what would change in each changelog, only computed for previews in dry-run mode
+    /// when attached to a terminal.
+    changelog_diffs: Vec<String>,
+    locks_by_manifest_path: BTreeMap<Utf8PathBuf, File>,
     /// Ids into `pending_changelogs`
     changelog_ids_with_statistical_segments_only: Vec<usize>,
     changelog_ids_probably_lacking_user_edits: Vec<usize>,
@@ -402,16 +583,21 @@ pub struct GatherOutcome<'meta> {
 fn gather_changelog_data<'meta>(
     ctx: &Context,
     crates_and_versions_to_be_published: &[(&'meta Package, &Version)],
-    Options {
+    opts: Options,
+) -> anyhow::Result<GatherOutcome<'meta>> {
+    let Options {
         dry_run,
+        preview,
         generator_segments,
-        capitalize_commit,
+        changelog_sidecar,
         ..
-    }: Options,
-) -> anyhow::Result<GatherOutcome<'meta>> {
+    } = opts;
+    let write_options: changelog::write::Options = opts.into();
     let mut out = GatherOutcome::default();
     let GatherOutcome {
         pending_changelogs,
+        tracking_locks_by_changelog_id,
+        changelog_diffs,
         locks_by_manifest_path,
         changelog_ids_with_statistical_segments_only,
         changelog_ids_probably_lacking_user_edits,
@@ -431,7 +617,7 @@ fn gather_changelog_data<'meta>(
                 publishee.manifest_path, publishee.name
             )
         })?;
-        let previous = locks_by_manifest_path.insert(&publishee.manifest_path, lock);
+        let previous = locks_by_manifest_path.insert(publishee.manifest_path.clone(), lock);
         assert!(previous.is_none(), "publishees are unique so insertion always happens");
         if let Some(history) = ctx.base.history.as_ref() {
             let changelog::init::Outcome {
@@ -439,9 +625,16 @@ fn gather_changelog_data<'meta>(
                 state: log_init_state,
                 previous_content,
                 mut lock,
-            } = ChangeLog::for_package_with_write_lock(publishee, history, &ctx.base, generator_segments)?;
+                tracking_lock,
+            } = ChangeLog::for_package_with_write_lock(
+                publishee,
+                history,
+                &ctx.base,
+                generator_segments,
+                changelog_sidecar,
+            )?;
 
-            log::info!(
+            tracing::info!(
                 "{} {} changelog for '{}'.",
                 will(dry_run),
                 match log_init_state {
@@ -460,7 +653,7 @@ fn gather_changelog_data<'meta>(
                     ..
                 } => {
                     if !log_init_state.is_modified() {
-                        log::info!(
+                        tracing::info!(
                             "{}: {} only change headline from 'Unreleased' to '{}'",
                             publishee.name,
                             will(dry_run),
@@ -506,23 +699,41 @@ fn gather_changelog_data<'meta>(
                     changelog_ids_probably_lacking_user_edits.push(pending_changelogs.len());
                 }
             }
+            let write_options = changelog::write::Options {
+                anchors: write_options.anchors
+                    || previous_content.as_deref().is_some_and(changelog::write::has_anchors),
+                ..write_options.clone()
+            };
+            // Even in dry-run mode, the lock receives what would be written so the release commit can be created from it.
             let mut write_buf = String::new();
             log.write_to(
                 &mut write_buf,
-                if dry_run {
-                    &Linkables::AsText
-                } else {
-                    &ctx.changelog_links
-                },
-                if dry_run {
-                    changelog::write::Components::SECTION_TITLE
+                &ctx.changelog_links,
+                if tracking_lock.is_some() {
+                    changelog::write::Components::all() - changelog::write::Components::HTML_TAGS
                 } else {
                     changelog::write::Components::all()
                 },
-                capitalize_commit,
+                &write_options,
             )?;
             lock.with_mut(|file| file.write_all(write_buf.as_bytes()))?;
+            if dry_run && preview && std::io::stdout().is_terminal() {
+                let path = lock.resource_path();
+                let path = path.strip_prefix(ctx.base.root.as_std_path()).unwrap_or(&path);
+                if let Some(diff) = crate::diff::unified(
+                    &path.display().to_string(),
+                    previous_content.as_deref(),
+                    &write_buf,
+                    crate::color::stdout(),
+                )? {
+                    changelog_diffs.push(diff);
+                }
+            }
             *made_change |= previous_content != Some(write_buf);
+            if let Some(mut tracking_lock) = tracking_lock {
+                tracking_lock.write_all(log.tracking_state(&write_options).to_toml().as_bytes())?;
+                tracking_locks_by_changelog_id.insert(pending_changelogs.len(), tracking_lock);
+            }
             pending_changelogs.push((publishee, log_init_state.is_modified(), lock));
             release_section_by_publishee.insert(publishee.name.as_str(), log.take_recent_release_section());
         }
@@ -530,9 +741,72 @@ fn gather_changelog_data<'meta>(
     Ok(out)
 }
 
+/// Return the content of the lock file at `path` with the versions of the workspace members in `crates` changed like
+/// cargo would after their manifests were updated, or `None` if there is no lock file or it wouldn't change.
+fn lock_file_with_new_versions(
+    path: &std::path::Path,
+    crates: &[(&Package, &Version)],
+) -> anyhow::Result<Option<String>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut doc = toml_edit::DocumentMut::from_str(&content)?;
+    let Some(packages) = doc.get_mut("package").and_then(|item| item.as_array_of_tables_mut()) else {
+        return Ok(None);
+    };
+    for entry in packages.iter_mut().filter(|entry| !entry.contains_key("source")) {
+        let field = |key| entry.get(key).and_then(|item| item.as_str()).unwrap_or_default();
+        let new_version = crates.iter().find_map(|(package, new_version)| {
+            (package.name == field("name") && package.version.to_string() == field("version")).then_some(new_version)
+        });
+        if let Some(new_version) = new_version {
+            entry["version"] = toml_edit::value(new_version.to_string());
+        }
+    }
+    let new_content = doc.to_string();
+    Ok((new_content != content).then_some(new_content))
+}
+
+/// Set the version in `[workspace.package]` of the workspace manifest at `manifest_path` to `new_version` and write
+/// the result to `out`, returning `true` if it changed.
+fn set_workspace_version(
+    manifest_path: &Utf8PathBuf,
+    new_version: &semver::Version,
+    mut out: impl std::io::Write,
+) -> anyhow::Result<bool> {
+    let manifest = std::fs::read_to_string(manifest_path)?;
+    let mut doc = toml_edit::DocumentMut::from_str(&manifest)?;
+    set_workspace_package_version(&mut doc, new_version)?;
+    let new_manifest = doc.to_string();
+    out.write_all(new_manifest.as_bytes())?;
+    Ok(manifest != new_manifest)
+}
+
+fn set_workspace_package_version(
+    doc: &mut toml_edit::DocumentMut,
+    new_version: &semver::Version,
+) -> anyhow::Result<()> {
+    let Some(version) = doc
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.get_mut("package"))
+        .and_then(|package| package.get_mut("version"))
+    else {
+        bail!("Workspace members inherit the version, but there is no `version` in `[workspace.package]`")
+    };
+    let new_version = new_version.to_string();
+    if version.as_str() != Some(new_version.as_str()) {
+        tracing::trace!("Pending workspace manifest version update: \"{new_version}\"");
+        *version = toml_edit::value(new_version);
+    }
+    Ok(())
+}
+
 fn set_version_and_update_package_dependency(
     package_to_update: &Package,
     new_package_version: Option<&semver::Version>,
+    new_workspace_version: Option<&semver::Version>,
     crates: &[(&Package, &semver::Version)],
     mut out: impl std::io::Write,
     Options {
@@ -543,10 +817,13 @@ fn set_version_and_update_package_dependency(
     let manifest = std::fs::read_to_string(&package_to_update.manifest_path)?;
     let mut doc = toml_edit::DocumentMut::from_str(&manifest)?;
 
-    if let Some(new_version) = new_package_version {
+    if let Some(new_version) = new_workspace_version {
+        set_workspace_package_version(&mut doc, new_version)?;
+    }
+    if let Some(new_version) = new_package_version.filter(|_| !crate::utils::inherits_workspace_version(&doc)) {
         let new_version = new_version.to_string();
         if doc["package"]["version"].as_str() != Some(new_version.as_str()) {
-            log::trace!(
+            tracing::trace!(
                 "Pending '{}' manifest version update: \"{}\"",
                 package_to_update.name,
                 new_version
@@ -569,7 +846,7 @@ fn set_version_and_update_package_dependency(
                     continue;
                 };
                 if name_table.get("path").is_none() {
-                    log::trace!(
+                    tracing::trace!(
                         "Skipping '{}' manifest {} update due as it's no local dependency: '{} = \"{}\"'",
                         package_to_update.name,
                         dep_type,
@@ -580,10 +857,14 @@ fn set_version_and_update_package_dependency(
                 }
                 if let Some(current_version_req) = name_table.get_mut("version") {
                     let version_req = VersionReq::parse(current_version_req.as_str().expect("versions are strings"))?;
-                    let force_update = conservative_pre_release_version_handling
+                    let force_update = (conservative_pre_release_version_handling
                         && version::is_pre_release(new_version) // setting the lower bound unnecessarily can be harmful
                         // don't claim to be conservative if this is necessary anyway
-                        && req_as_version(&version_req).is_some_and(|req_version| !version::rhs_is_breaking_bump_for_lhs(&req_version, new_version));
+                        && req_as_version(&version_req).is_some_and(|req_version| !version::rhs_is_breaking_bump_for_lhs(&req_version, new_version)))
+                        // pre-releases may change in incompatible ways, so depend on the latest one, and on the final
+                        // version once a pre-release graduates
+                        || !new_version.pre.is_empty()
+                        || req_as_version(&version_req).is_some_and(|req_version| !req_version.pre.is_empty());
                     if !version_req.matches(new_version) || force_update {
                         if !version_req_unset_or_default(&version_req) {
                             bail!(
@@ -593,9 +874,16 @@ fn set_version_and_update_package_dependency(
                                 current_version_req
                             );
                         }
-                        let new_version = format!("^{new_version}");
+                        // build metadata isn't allowed in version requirements
+                        let new_version = format!(
+                            "^{}",
+                            Version {
+                                build: semver::BuildMetadata::EMPTY,
+                                ..(*new_version).clone()
+                            }
+                        );
                         if version_req.to_string() != new_version {
-                            log::trace!(
+                            tracing::trace!(
                                 "Pending '{}' {}manifest {} update: '{} = \"{}\"' (from {})",
                                 package_to_update.name,
                                 if force_update { "conservative " } else { "" },
@@ -665,3 +953,28 @@ fn req_as_version(req: &VersionReq) -> Option<Version> {
         build: Default::default(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::message_from_template;
+
+    #[test]
+    fn commit_messages_are_filled_in_from_the_template() {
+        assert_eq!(
+            message_from_template("PROJ-123: {message}", "Release a v1.0.0", "a v1.0.0"),
+            "PROJ-123: Release a v1.0.0"
+        );
+        assert_eq!(
+            message_from_template(
+                "[PROJ-123] release {crates}",
+                "Release a v1.0.0, b v0.2.0",
+                "a v1.0.0, b v0.2.0"
+            ),
+            "[PROJ-123] release a v1.0.0, b v0.2.0"
+        );
+        assert_eq!(
+            message_from_template("chore: release", "Release a v1.0.0", "a v1.0.0"),
+            "chore: release"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Publish crates to registries in addition to the main one, like internal mirrors of crates.io.
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use cargo_metadata::Package;
+
+use super::{cargo, Options};
+
+/// What happened when publishing a crate to a registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(in crate::command::release_impl) enum Outcome {
+    /// The crate was published, and it showed up in the index of the registry if that could be checked.
+    Published,
+    /// The crate was published, but didn't show up in the index of the registry in time.
+    Unconfirmed,
+    /// The `publish` field in the manifest of the crate doesn't list the registry.
+    NotAllowed,
+    /// Publishing to the registry was skipped as a crate published before failed to publish to it.
+    Skipped,
+    /// Publishing to the registry failed.
+    Failed,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Outcome::Published => "published",
+            Outcome::Unconfirmed => "published but not yet in its index",
+            Outcome::NotAllowed => "not allowed by manifest",
+            Outcome::Skipped => "skipped",
+            Outcome::Failed => "failed",
+        })
+    }
+}
+
+/// Publish `publishee` at `version` to `registry`, and wait for it to show up in the index of `registry` so crates that
+/// depend on it can be published there next.
+pub(in crate::command::release_impl) fn publish(
+    publishee: &Package,
+    version: &semver::Version,
+    registry: &str,
+    prevent_default_members: bool,
+    ctx: &crate::Context,
+    options: Options,
+) -> anyhow::Result<Outcome> {
+    if publishee
+        .publish
+        .as_ref()
+        .is_some_and(|registries| !registries.iter().any(|r| r == registry))
+    {
+        tracing::info!(
+            "Not publishing '{}' to '{registry}' as its manifest doesn't allow it",
+            publishee.name
+        );
+        return Ok(Outcome::NotAllowed);
+    }
+    let skip_waiting = options.skip_publish || options.dry_run || options.dry_run_cargo_publish;
+    cargo::publish_crate(
+        publishee,
+        version,
+        prevent_default_members,
+        ctx,
+        Options {
+            registry: Some(registry.to_owned()),
+            // The provider is meant for the main registry, cargo knows how to authenticate with the others.
+            credential_provider: None,
+            ..options
+        },
+    )?;
+    if skip_waiting {
+        return Ok(Outcome::Published);
+    }
+
+    let Some(index_url) = sparse_index_url(registry, ctx.root.as_std_path())? else {
+        tracing::info!(
+            "Not waiting for '{}' to arrive in '{registry}' as it doesn't use a sparse index",
+            publishee.name
+        );
+        return Ok(Outcome::Published);
+    };
+    tracing::info!(
+        "Waiting for '{} v{version}' to arrive in the index of '{registry}'…",
+        publishee.name
+    );
+    let timeout = Duration::from_secs(60);
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if crate::crates_index::index_at_has_version(&index_url, &publishee.name, version)? {
+            return Ok(Outcome::Published);
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+    tracing::warn!(
+        "'{} v{version}' didn't arrive in the index of '{registry}' within {}s",
+        publishee.name,
+        timeout.as_secs()
+    );
+    Ok(Outcome::Unconfirmed)
+}
+
+/// Log the outcome of publishing each crate to each registry, given as `(crate, version, registry, outcome)` in the
+/// order crates were published in.
+pub(in crate::command::release_impl) fn report(results: &[(&Package, &semver::Version, &str, Outcome)]) {
+    tracing::info!("Publishing results by registry:\n{}", summary(results));
+}
+
+fn summary(results: &[(&Package, &semver::Version, &str, Outcome)]) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut previous = None;
+    for (publishee, version, registry, outcome) in results {
+        if previous == Some(publishee.name.as_str()) {
+            let line = lines.last_mut().expect("a line per crate");
+            line.push_str(&format!(", {registry} {outcome}"));
+        } else {
+            lines.push(format!("  '{} v{version}': {registry} {outcome}", publishee.name));
+        }
+        previous = Some(publishee.name.as_str());
+    }
+    lines.join("\n")
+}
+
+/// Return the URL of the sparse index of `registry` as configured for cargo, or `None` if it uses a git index.
+pub(in crate::command::release_impl) fn sparse_index_url(
+    registry: &str,
+    workspace_root: &Path,
+) -> anyhow::Result<Option<String>> {
+    Ok(index_url(registry, workspace_root)?.and_then(|url| url.strip_prefix("sparse+").map(ToOwned::to_owned)))
+}
+
+/// Return the URL of the index of `registry` as configured for cargo, with the `sparse+` prefix of sparse indices.
+pub(in crate::command::release_impl) fn index_url(
+    registry: &str,
+    workspace_root: &Path,
+) -> anyhow::Result<Option<String>> {
+    let env_var = format!(
+        "CARGO_REGISTRIES_{}_INDEX",
+        registry.to_ascii_uppercase().replace('-', "_")
+    );
+    if let Ok(url) = std::env::var(env_var) {
+        return Ok(Some(url));
+    }
+    for path in config_files(workspace_root)? {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let url = index_url_in(&content, registry)
+            .with_context(|| format!("Could not parse cargo configuration at '{}'", path.display()))?;
+        if url.is_some() {
+            return Ok(url);
+        }
+    }
+    Ok(None)
+}
+
+/// The cargo configuration files in the order of their precedence, as seen from `workspace_root`.
+pub(in crate::command::release_impl) fn config_files(workspace_root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let cargo_home = home::cargo_home()?;
+    Ok(workspace_root
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .chain(Some(cargo_home))
+        .flat_map(|dir| [dir.join("config.toml"), dir.join("config")])
+        .collect())
+}
+
+fn index_url_in(config: &str, registry: &str) -> anyhow::Result<Option<String>> {
+    let config: toml_edit::DocumentMut = config.parse()?;
+    Ok(config
+        .get("registries")
+        .and_then(|registries| registries.get(registry))
+        .and_then(|registry| registry.get("index"))
+        .and_then(|index| index.as_str())
+        .map(ToOwned::to_owned))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{index_url_in, summary, Outcome};
+    use crate::utils::tests::package;
+
+    #[test]
+    fn index_urls_are_read_from_the_registries_table() {
+        let config = "[registries.internal]\nindex = \"sparse+https://crates.example.com/index/\"\n";
+        assert_eq!(
+            index_url_in(config, "internal").unwrap().as_deref(),
+            Some("sparse+https://crates.example.com/index/")
+        );
+        assert_eq!(index_url_in(config, "other").unwrap(), None);
+        assert!(index_url_in("registries = ", "internal").is_err());
+    }
+
+    #[test]
+    fn results_are_summarized_per_crate() {
+        let (a, b) = (package("a", &[], json!(null)), package("b", &[], json!(null)));
+        let version = semver::Version::new(1, 1, 0);
+        assert_eq!(
+            summary(&[
+                (&a, &version, "crates-io", Outcome::Published),
+                (&a, &version, "internal", Outcome::Failed),
+                (&b, &version, "crates-io", Outcome::Published),
+                (&b, &version, "internal", Outcome::Skipped),
+            ]),
+            "  'a v1.1.0': crates-io published, internal failed\n  'b v1.1.0': crates-io published, internal skipped"
+        );
+    }
+}
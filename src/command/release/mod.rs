@@ -1,11 +1,16 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::IsTerminal,
+};
 
 use anyhow::bail;
+use prodash::{Count, NestedProgress, Progress};
 
 use crate::{
     changelog,
     changelog::{write::Linkables, Section},
     command::release::Options,
+    event::{self, Step},
     traverse::{
         self, dependency,
         dependency::{ManifestAdjustment, VersionAdjustment},
@@ -16,10 +21,35 @@ use crate::{
     version::BumpSpec,
 };
 
+mod artifacts;
 mod cargo;
+mod checksum;
+mod docs_rs;
+mod doctor;
+mod explain;
 mod git;
+mod git_hooks;
 mod github;
+mod graph;
+mod hooks;
+mod interactive;
+mod journal;
 mod manifest;
+mod mirrors;
+mod ownership;
+mod package_contents;
+mod plan;
+mod protected_branch;
+mod signing;
+mod sigstore;
+mod summary;
+mod undo;
+mod upload;
+
+pub use undo::undo;
+
+use hooks::Hook;
+use journal::Journal;
 
 pub(crate) struct Context {
     base: crate::Context,
@@ -32,12 +62,42 @@ impl Context {
         bump: BumpSpec,
         bump_dependencies: BumpSpec,
         changelog: bool,
-        changelog_links: bool,
+        opts: &Options,
     ) -> anyhow::Result<Self> {
-        let base = crate::Context::new(crate_names, changelog, bump, bump_dependencies)?;
-        let changelog_links = if changelog_links {
-            crate::git::remote_url(&base.repo)?.map_or(Linkables::AsText, |url| Linkables::AsLinks {
-                repository_url: url.into(),
+        let base = crate::Context::new(
+            crate_names,
+            crate::context::Options {
+                force_history_segmentation: changelog,
+                bump,
+                bump_dependencies,
+                history: crate::git::history::Options {
+                    pull_request_titles: opts.changelog_pull_request_titles,
+                    title_references_are_pull_requests: opts.changelog_references_are_pull_requests,
+                    authors: opts.changelog_authors,
+                    write_commit_graph: opts.write_commit_graph,
+                    ..Default::default()
+                },
+                semver_checks: opts.semver_checks,
+                build_metadata: opts.build_metadata.clone(),
+                bump_overrides: opts.bump_overrides.clone(),
+                pinned: opts.pinned.clone(),
+                excluded: opts.excluded.clone(),
+                version_scheme: opts.version_scheme.clone(),
+                registry: opts.registry.clone(),
+                verify_tags: opts.verify_tags,
+                changelog_locale: opts.changelog_locale.clone(),
+            },
+        )?;
+        if let Some(name) = &opts.remote {
+            base.repo
+                .find_remote(name.as_str())
+                .map_err(|err| anyhow::anyhow!("Cannot push to the remote given with --remote: {err}"))?;
+        }
+        let changelog_links = if opts.changelog_links {
+            crate::git::remote_url(&base.repo, opts.remote.as_deref())?.map_or(Linkables::AsText, |url| {
+                Linkables::AsLinks {
+                    repository_url: url.into(),
+                }
             })
         } else {
             Linkables::AsText
@@ -52,8 +112,47 @@ pub fn release(opts: Options, crates: Vec<String>, bump: BumpSpec, bump_dependen
     if opts.dry_run_cargo_publish && !opts.dry_run {
         bail!("The --no-dry-run-cargo-publish flag is only effective without --execute")
     }
+    if opts.sigstore && !opts.skip_publish && !Program::named("cosign").found {
+        bail!("To sign releases with --sigstore, please install the 'cosign' program and try again");
+    }
+    if opts.offline {
+        if !opts.dry_run {
+            bail!("With --offline only the release plan is computed, so it can't be combined with --execute");
+        }
+        if opts.update_crates_index {
+            bail!("The crates index can't be updated with --offline");
+        }
+        if opts.semver_checks.is_some() {
+            bail!("--semver-checks downloads the released crates to compare against and can't be used with --offline");
+        }
+        // Have cargo itself use only what's available locally too, whenever we invoke it.
+        std::env::set_var("CARGO_NET_OFFLINE", "true");
+    }
+    if opts.yes {
+        // Fail instead of hanging while git or gh wait for credentials that nobody will type.
+        std::env::set_var("GIT_TERMINAL_PROMPT", "0");
+        std::env::set_var("GH_PROMPT_DISABLED", "1");
+    }
+    if opts.open_pull_request && !Program::named("gh").found {
+        bail!("To open a pull request with --open-pull-request, please install the 'gh' program and try again");
+    }
+    if opts.resume {
+        return resume(opts);
+    }
+    let opts = if opts.replace_release {
+        unwind_release_to_replace(opts)?
+    } else if !opts.dry_run
+        && !opts.skip_push
+        && !opts.separate_changelog_commit
+        && (opts.amend_release || std::io::stdin().is_terminal())
+    {
+        // A release with separate commits can't be amended as a whole.
+        amend_unpushed_release(opts)?
+    } else {
+        opts
+    };
     let allow_changelog = if opts.changelog && opts.skip_tag {
-        log::warn!("With --no-tag enabled, changelog generation will be disabled as it relies on tags to segment commit history.");
+        tracing::warn!("With --no-tag enabled, changelog generation will be disabled as it relies on tags to segment commit history.");
         false
     } else {
         opts.changelog
@@ -61,17 +160,49 @@ pub fn release(opts: Options, crates: Vec<String>, bump: BumpSpec, bump_dependen
 
     if should_update_crates_index(&opts) {
         // Do this before creating our context to pick up a possibly newly fetched/created index.
-        log::info!("Updating crates-io index",);
+        tracing::info!("Updating crates-io index",);
         crates_index::GitIndex::new_cargo_default()?.update()?;
-    } else if opts.bump_when_needed {
-        log::warn!(
+    } else if opts.bump_when_needed && !opts.offline {
+        tracing::warn!(
             "Consider running with --update-crates-index to assure bumping on demand uses the latest information"
         );
     }
 
-    let ctx = Context::new(crates, bump, bump_dependencies, allow_changelog, opts.changelog_links)?;
+    // Stash before the workspace is read, so the release is planned without the stashed changes.
+    let stash = if opts.autostash {
+        let repo = gix::discover(".")?;
+        git::stash_changes(&repo, opts.dry_run)?.map(|id| (repo, id))
+    } else {
+        None
+    };
+    let res = plan_and_release(crates, bump, bump_dependencies, allow_changelog, opts);
+    if let Some((repo, id)) = stash {
+        git::restore_stash(&repo, id);
+    }
+    res
+}
+
+fn plan_and_release(
+    crates: Vec<String>,
+    bump: BumpSpec,
+    bump_dependencies: BumpSpec,
+    allow_changelog: bool,
+    opts: Options,
+) -> anyhow::Result<()> {
+    let ctx = Context::new(crates, bump, bump_dependencies, allow_changelog, &opts)?;
+    for name in &opts.changelog_only {
+        crate::utils::package_by_name(&ctx.base.meta, name)
+            .map_err(|_| anyhow::anyhow!("'{name}' passed to --changelog-only isn't a crate of the workspace"))?;
+    }
+    if ctx.base.repo.head()?.is_detached() && !opts.skip_push && opts.branch.is_none() && opts.release_branch.is_none()
+    {
+        bail!("HEAD is detached, so there is no branch to commit the release to and push. Choose one with --branch <name>.");
+    }
     if !ctx.base.crates_index.exists() {
-        log::warn!("Crates.io index doesn't exist. Consider using --update-crates-index to help determining if release versions are published already");
+        tracing::warn!("Crates.io index doesn't exist. Consider using --update-crates-index to help determining if release versions are published already");
+    }
+    if let Some(identity) = &opts.release_author {
+        git::parse_release_author(identity)?;
     }
 
     release_depth_first(ctx, opts)?;
@@ -82,6 +213,19 @@ fn should_update_crates_index(opts: &Options) -> bool {
     opts.update_crates_index || (!opts.dry_run && !opts.skip_publish && opts.registry.is_none())
 }
 
+impl From<Options> for changelog::write::Options {
+    fn from(v: Options) -> Self {
+        Self {
+            capitalize_commit: v.capitalize_commit,
+            locale: v.changelog_locale,
+            anchors: v.changelog_anchors,
+            omit_commit_hashes: !v.changelog_commit_hashes,
+            strip_trailing_period: v.changelog_strip_trailing_period,
+            imperative_mood: v.changelog_imperative_mood,
+        }
+    }
+}
+
 impl From<Options> for traverse::Options {
     fn from(v: Options) -> Self {
         Self {
@@ -89,11 +233,17 @@ impl From<Options> for traverse::Options {
             bump_when_needed: v.bump_when_needed,
             isolate_dependencies_from_breaking_changes: v.isolate_dependencies_from_breaking_changes,
             traverse_graph: v.dependencies,
+            dependent_bump: v.dependent_bump,
+            force: v.force,
         }
     }
 }
 
-fn release_depth_first(ctx: Context, opts: Options) -> anyhow::Result<()> {
+fn release_depth_first(mut ctx: Context, opts: Options) -> anyhow::Result<()> {
+    if opts.interactive && !interactive::adjust(&mut ctx, &opts)? {
+        tracing::info!("Aborted the release as requested");
+        return Ok(());
+    }
     let crates = {
         traverse::dependencies(&ctx.base, opts.clone().into())
             .and_then(|crates| assure_crates_index_is_uptodate(crates, &ctx.base, opts.clone().into()))
@@ -101,6 +251,23 @@ fn release_depth_first(ctx: Context, opts: Options) -> anyhow::Result<()> {
                 present_and_validate_dependencies(&crates, &ctx, opts.verbose, opts.dry_run).map(|_| crates)
             })?
     };
+    if opts.explain {
+        explain::bumps(&crates, &ctx.base)?;
+    }
+    if let Some(format) = opts.print_graph {
+        print!("{}", graph::render(&crates, format));
+        return Ok(());
+    }
+    if let Some(format) = opts.plan_format {
+        let plan = plan::render(&crates, format, |package, version| {
+            (!opts.skip_tag).then(|| tag_name(package, version, &ctx.base))
+        })?;
+        print!("{plan}");
+        return Ok(());
+    }
+    if opts.doctor {
+        return doctor::run(&ctx, &opts, &crates);
+    }
 
     assure_working_tree_is_unchanged(opts.clone())?;
     perform_release(&ctx, opts, &crates)?;
@@ -124,7 +291,7 @@ fn assure_crates_index_is_uptodate<'meta>(
     {
         let mut index = crate::crates_index::Index::new_cargo_default()?;
         if index.exists() {
-            log::warn!("Crate '{}' computed version not greater than the current package version. Updating crates index to assure correct results.", dep.package.name);
+            tracing::warn!("Crate '{}' computed version not greater than the current package version. Updating crates index to assure correct results.", dep.package.name);
             index.update()?;
             return traverse::dependencies(ctx, opts);
         }
@@ -143,28 +310,44 @@ fn present_and_validate_dependencies(
         .iter()
         .filter_map(|dep| match &dep.mode {
             dependency::Mode::NotForPublishing { reason, adjustment } => {
-                Some((dep.package.name.as_str(), adjustment.is_some(), *reason))
+                Some((dep.package, adjustment.is_some(), *reason))
             }
             _ => None,
         })
         .collect();
     let mut num_refused = 0;
-    for (refused_crate, has_adjustment, _) in all_skipped
+    for (refused_crate, has_adjustment, reason) in all_skipped
         .iter()
-        .filter(|(name, _, _)| ctx.base.crate_names.iter().any(|n| n == *name))
+        .filter(|(package, _, _)| ctx.base.crate_names.iter().any(|n| *n == package.name.as_str()))
     {
         num_refused += 1;
-        log::warn!(
+        tracing::warn!(
             "Refused to publish '{}' as {}.",
-            refused_crate,
-            has_adjustment
-                .then(|| "only a manifest change is needed")
-                .unwrap_or("as it didn't change")
+            refused_crate.name,
+            match reason {
+                dependency::NoPublishReason::Frozen => String::from("it is frozen or pinned"),
+                dependency::NoPublishReason::PublishDisabledInManifest =>
+                    "publishing it is disabled in its manifest".into(),
+                dependency::NoPublishReason::RestrictedToOtherRegistries => format!(
+                    "its manifest only allows publishing it to {}, not to '{}'",
+                    refused_crate
+                        .publish
+                        .iter()
+                        .flatten()
+                        .map(|r| format!("'{r}'"))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    ctx.base.registry_of(refused_crate).unwrap_or("crates-io")
+                ),
+                _ if *has_adjustment => "only a manifest change is needed".into(),
+                _ => "it has no releasable changes since its last release - use --force to release it anyway".into(),
+            }
         );
     }
 
     let no_requested_crate_will_publish = num_refused == ctx.base.crate_names.len();
-    let no_crate_being_published_message = "No provided crate is actually eligible for publishing.";
+    let no_crate_being_published_message =
+        "No provided crate is actually eligible for publishing, use --force to release unchanged crates anyway.";
     if no_requested_crate_will_publish && !verbose {
         bail!(
             "{} Use --verbose to see the release plan nonetheless.",
@@ -174,7 +357,7 @@ fn present_and_validate_dependencies(
 
     let skipped = all_skipped
         .iter()
-        .filter_map(|(name, has_adjustment, reason)| (!has_adjustment).then_some((*name, reason)))
+        .filter_map(|(package, has_adjustment, reason)| (!has_adjustment).then_some((package.name.as_str(), reason)))
         .collect::<Vec<_>>();
     if !skipped.is_empty() {
         let skipped_len = skipped.len();
@@ -188,7 +371,7 @@ fn present_and_validate_dependencies(
             .collect();
         crates_by_reason.sort_by_key(|(k, _)| *k);
 
-        log::info!(
+        tracing::info!(
             "Will not publish or alter {} dependent crate{}: {}",
             skipped_len,
             if skipped_len != 1 { "s" } else { "" },
@@ -210,9 +393,10 @@ fn present_and_validate_dependencies(
 
     let mut error = false;
     for dep in crates {
-        let (bump_spec, kind) = match dep.kind {
-            Kind::UserSelection => (ctx.base.bump, "provided"),
-            Kind::DependencyOrDependentOfUserSelection => (ctx.base.bump_dependencies, "dependent"),
+        let bump_spec = version::select_publishee_bump_spec(&dep.package.name, &ctx.base);
+        let kind = match dep.kind {
+            Kind::UserSelection => "provided",
+            Kind::DependencyOrDependentOfUserSelection => "dependent",
         };
         match &dep.mode {
             dependency::Mode::ToBePublished { adjustment } => {
@@ -234,13 +418,13 @@ fn present_and_validate_dependencies(
                         Kind::DependencyOrDependentOfUserSelection => "--bump-dependencies <level>",
                     };
                     if bump.next_release == bump.package_version {
-                        log::error!(
+                        tracing::error!(
                             "'{}' is unchanged. Consider using {} along with --no-bump-on-demand to force a version change.",
                             dep.package.name,
                             bump_flag
                         );
                     } else {
-                        log::error!(
+                        tracing::error!(
                             "Latest published version of '{}' is {}, the new version is {}. Consider using {} or update the index with --update-crates-index.",
                             dep.package.name,
                             latest_release,
@@ -251,7 +435,7 @@ fn present_and_validate_dependencies(
                     error = true;
                 }
                 if bump.next_release > dep.package.version {
-                    log::info!(
+                    tracing::info!(
                         "{} {}-bump {} package '{}' from {} to {} for publishing{}{}{}",
                         will(dry_run),
                         bump_spec,
@@ -280,7 +464,7 @@ fn present_and_validate_dependencies(
                         },
                     );
                 } else if bump.desired_release != dep.package.version {
-                    log::info!(
+                    tracing::info!(
                         "Manifest version of {} package '{}' at {} is sufficient{}, ignoring computed version {}",
                         kind,
                         dep.package.name,
@@ -334,7 +518,7 @@ fn present_and_validate_dependencies(
             );
         for (cause, deps_and_bumps) in affected_crates_by_cause {
             let plural_s = if deps_and_bumps.len() != 1 { "s" } else { "" };
-            log::info!(
+            tracing::info!(
                 "{} adjust {} manifest version{} due to breaking change in '{}': {}",
                 will(dry_run),
                 deps_and_bumps.len(),
@@ -349,6 +533,37 @@ fn present_and_validate_dependencies(
         }
     }
 
+    {
+        let crates_following_workspace_version: Vec<_> = crates
+            .iter()
+            .filter_map(|dep| match &dep.mode {
+                dependency::Mode::NotForPublishing {
+                    adjustment: Some(ManifestAdjustment::Version(VersionAdjustment::Changed { bump, .. })),
+                    ..
+                } => Some((dep.package.name.as_str(), bump)),
+                _ => None,
+            })
+            .collect();
+        if !crates_following_workspace_version.is_empty() {
+            let plural_s = if crates_following_workspace_version.len() != 1 {
+                "s"
+            } else {
+                ""
+            };
+            tracing::info!(
+                "{} adjust {} manifest version{} as they inherit the workspace version: {}",
+                will(dry_run),
+                crates_following_workspace_version.len(),
+                plural_s,
+                crates_following_workspace_version
+                    .into_iter()
+                    .map(|(dep_name, bump)| format!("'{}' {} ➡ {}", dep_name, bump.package_version, bump.next_release))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
     {
         let crate_names_for_manifest_updates = crates
             .iter()
@@ -369,7 +584,7 @@ fn present_and_validate_dependencies(
             } else {
                 Default::default()
             };
-            log::info!(
+            tracing::info!(
                 "{} adjust version constraints in manifest{} of {} package{} as direct dependencies are changing: {}",
                 will(dry_run),
                 plural_s,
@@ -391,10 +606,12 @@ fn present_and_validate_dependencies(
 }
 
 fn assure_working_tree_is_unchanged(options: Options) -> anyhow::Result<()> {
-    if !options.allow_dirty {
-        if let Err(err) = crate::git::assure_clean_working_tree() {
+    // In dry-run mode, changes that would be stashed are still there.
+    let would_be_stashed = options.autostash && options.dry_run;
+    if !options.allow_dirty && !would_be_stashed {
+        if let Err(err) = crate::git::assure_clean_working_tree(&options.allow_dirty_paths) {
             if options.dry_run {
-                log::warn!("The working tree has changes which will prevent a release with --execute unless --allow-dirty is also specified. The latter isn't recommended.")
+                tracing::warn!("The working tree has changes which will prevent a release with --execute unless --allow-dirty is also specified. The latter isn't recommended.")
             } else {
                 return Err(err);
             }
@@ -404,66 +621,692 @@ fn assure_working_tree_is_unchanged(options: Options) -> anyhow::Result<()> {
 }
 
 fn perform_release(ctx: &Context, options: Options, crates: &[Dependency<'_>]) -> anyhow::Result<()> {
+    let publishees: Vec<_> = crates
+        .iter()
+        .filter_map(try_to_published_crate_and_new_version)
+        .collect();
+    let publishee_packages: Vec<_> = publishees
+        .iter()
+        .map(|(publishee, _)| *publishee)
+        .filter(|publishee| !is_changelog_only(publishee, &options))
+        .collect();
+    ownership::verify(&publishee_packages, options.clone())?;
+    protected_branch::verify(&ctx.base, options.clone())?;
+    if let Some((package, submodule)) = crates.iter().find_map(|dep| {
+        dep.mode
+            .version_adjustment_bump()
+            .and(ctx.base.submodule_of(dep.package))
+            .map(|submodule| (dep.package, submodule))
+    }) {
+        let message = format!(
+            "'{}' is in the submodule at '{}', so its release must be committed and tagged in the submodule by running there",
+            package.name, submodule.dir
+        );
+        if options.dry_run {
+            tracing::warn!("{message}");
+        } else {
+            bail!("{message}");
+        }
+    }
+    docs_rs::verify(&publishee_packages, options.clone())?;
+    confirm(&publishees, &options)?;
+    for (package, bump) in crates
+        .iter()
+        .filter_map(|c| c.mode.version_adjustment_bump().map(|b| (c.package, b)))
+    {
+        hooks::run(Hook::PreBump, package, &bump.next_release, &ctx.base, options.dry_run)?;
+    }
+    event::step_started(Step::Commit, None, options.dry_run);
     let manifest::Outcome {
         commit_id,
         section_by_package: release_section_by_publishee,
     } = manifest::edit_version_and_fixup_dependent_crates_and_handle_changelog(crates, options.clone(), ctx)?;
 
+    let mut journal = Journal {
+        crates: publishees
+            .iter()
+            .map(|(publishee, new_version)| journal::Release {
+                name: publishee.name.to_string(),
+                version: (*new_version).clone(),
+            })
+            .collect(),
+        commit: commit_id.map(|id| id.to_string()),
+        publish_after_merge: options.publish_after_merge,
+        force_with_lease: options.force_with_lease.clone(),
+        ..Default::default()
+    };
+    if options.publish_after_merge {
+        return open_pull_request_to_publish_after_merge(ctx, options, &publishees, &mut journal);
+    }
+    publish_tag_and_push(
+        ctx,
+        options,
+        &publishees,
+        commit_id,
+        &release_section_by_publishee,
+        journal,
+    )
+}
+
+/// If confirmation is required with `--confirm` or the workspace configuration, ask the user to type a code derived
+/// from the crates to release and their versions before anything is changed with `--execute`.
+fn confirm(publishees: &[(&cargo_metadata::Package, &semver::Version)], options: &Options) -> anyhow::Result<()> {
+    if !options.confirm || options.dry_run || publishees.is_empty() {
+        return Ok(());
+    }
+    if options.yes {
+        tracing::info!("Releasing without confirmation as --yes was given");
+        return Ok(());
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!(
+            "The release must be confirmed, which requires an interactive terminal. Pass --yes to release unattended."
+        );
+    }
+    let summary = crate::utils::names_and_versions(publishees);
+    let code = confirmation_code(&summary);
+    eprintln!("About to release {summary}");
+    eprint!("Type '{code}' to continue: ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if answer.trim() != code {
+        bail!("Aborted the release as the confirmation didn't match");
+    }
+    Ok(())
+}
+
+/// Delete the tags of the release at `HEAD` so it's planned and committed again, once the user confirmed rewriting
+/// history others may have fetched already, and return `opts` with the leases to push over the release on the remote.
+fn unwind_release_to_replace(opts: Options) -> anyhow::Result<Options> {
+    let repo = gix::discover(".")?;
+    let release = git::release_to_replace(&repo, opts.branch.as_deref(), opts.remote.clone())?;
+    let tag_names = release
+        .tags
+        .iter()
+        .map(|(name, _)| name.shorten().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    tracing::warn!(
+        "{} REPLACE the release commit {} and re-point its tags {tag_names}, force-pushing over them on the remote",
+        will(opts.dry_run),
+        release.commit
+    );
+    if opts.dry_run {
+        tracing::warn!(
+            "The tags are only deleted with --execute, so the release plan still sees the release that is replaced"
+        );
+    } else {
+        if opts.yes {
+            tracing::info!("Replacing the release without confirmation as --yes was given");
+        } else {
+            confirm_replacement(&release.commit)?;
+        }
+        git::delete_tags(&repo, &release.tags)?;
+    }
+    Ok(Options {
+        force_with_lease: release.leases,
+        ..opts
+    })
+}
+
+/// Ask the user to type a code to confirm replacing the release at `commit`.
+fn confirm_replacement(commit: &gix::ObjectId) -> anyhow::Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!("Replacing a release must be confirmed, which requires an interactive terminal. Pass --yes to replace it unattended.");
+    }
+    let code = confirmation_code(&format!("replace {commit}"));
+    eprintln!("!!! This rewrites history that others may have fetched already, and can't be undone once pushed !!!");
+    eprintln!("!!! Crates of the release that were published already can't be replaced on the registry !!!");
+    eprint!("Type '{code}' to replace the release at {commit}: ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if answer.trim() != code {
+        bail!("Aborted replacing the release as the confirmation didn't match");
+    }
+    Ok(())
+}
+
+/// If `HEAD` is a release that wasn't pushed or published, like after the push failed, offer to amend its commit and
+/// move its tags instead of stacking another release commit on top. It's amended without asking with `--amend-release`
+/// or `--yes`.
+///
+/// This asks the remote which tags it has, so it's only done with `--execute` and if asked for or running interactively.
+fn amend_unpushed_release(opts: Options) -> anyhow::Result<Options> {
+    let repo = gix::discover(".")?;
+    // Without publishing, crates are recorded as published by the journal as if they were.
+    if !opts.skip_publish && Journal::load(&repo)?.is_some_and(|journal| !journal.published.is_empty()) {
+        return Ok(opts);
+    }
+    let release = match git::unpushed_release(&repo, opts.branch.as_deref(), opts.remote.clone()) {
+        Ok(Some(release)) => release,
+        Ok(None) => return Ok(opts),
+        Err(err) => {
+            tracing::warn!("Could not check if the release at HEAD was pushed: {err:#}");
+            return Ok(opts);
+        }
+    };
+    let tag_names = release
+        .tags
+        .iter()
+        .map(|(name, _)| name.shorten().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let amend = if opts.amend_release || opts.yes {
+        true
+    } else {
+        eprint!(
+            "The release commit {} with tags {tag_names} wasn't pushed. Amend it and move its tags instead of creating another release commit? [y/N] ",
+            release.commit
+        );
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        matches!(answer.trim(), "y" | "Y" | "yes")
+    };
+    if !amend {
+        tracing::info!(
+            "The release commit {} with tags {tag_names} wasn't pushed. Use --amend-release to amend it and move its tags instead of creating another release commit.",
+            release.commit
+        );
+        return Ok(opts);
+    }
+    tracing::info!(
+        "Will amend the unpushed release commit {} and move its tags {tag_names}",
+        release.commit
+    );
+    git::delete_tags(&repo, &release.tags)?;
+    Journal::remove(&repo)?;
+    Ok(Options {
+        replace_release: true,
+        ..opts
+    })
+}
+
+/// Return a short code that changes with the crates and versions in `summary`, so it can't be typed from memory.
+fn confirmation_code(summary: &str) -> String {
+    use sha2::Digest;
+    sha2::Sha256::digest(summary.as_bytes())
+        .iter()
+        .take(3)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Continue the release recorded in the journal of a previous invocation that didn't finish, skipping all steps that
+/// were performed already.
+fn resume(opts: Options) -> anyhow::Result<()> {
+    let repo = gix::discover(".")?;
+    let Some(mut journal) = Journal::load(&repo)? else {
+        bail!("There is no unfinished release to resume")
+    };
+    if journal.crates.is_empty() {
+        return Journal::remove(&repo);
+    }
+    let crate_names = journal.crates.iter().map(|c| c.name.clone()).collect();
+    let ctx = Context::new(crate_names, BumpSpec::Keep, BumpSpec::Keep, false, &opts)?;
+    let releases = journal.crates.clone();
+    let publishees = releases
+        .iter()
+        .map(|c| Ok((crate::utils::package_by_name(&ctx.base.meta, &c.name)?, &c.version)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    for (publishee, version) in &publishees {
+        if let Some(tag_ref) = crate::git::previous_release_tag(publishee, version, &ctx.base)? {
+            crate::git::assure_tag_is_trusted(publishee, &tag_ref, &ctx.base)?;
+        }
+    }
+    if journal.publish_after_merge {
+        if !pull_request_was_merged(&ctx, opts.clone(), &publishees, &mut journal)? {
+            return Ok(());
+        }
+    } else if let Some(commit) = journal.commit.as_deref() {
+        let head = ctx.base.repo.head_id()?;
+        if head.to_string() != commit {
+            bail!("Cannot resume the release as HEAD is at {head} instead of the release commit {commit}. Check it out and try again.")
+        }
+    }
+    let commit_id = journal
+        .commit
+        .as_deref()
+        .map(|id| {
+            gix::ObjectId::from_hex(id.as_bytes()).map(|id| gix::prelude::ObjectIdExt::attach(id, &ctx.base.repo))
+        })
+        .transpose()?;
+    let release_section_by_publishee = publishees
+        .iter()
+        .filter_map(|(publishee, new_version)| {
+            released_section(publishee, new_version, &ctx.base).map(|section| (publishee.name.as_str(), section))
+        })
+        .collect();
+    tracing::info!(
+        "Resuming the release of {}",
+        crate::utils::names_and_versions(&publishees)
+    );
+    confirm(&publishees, &opts)?;
+    let opts = Options {
+        force_with_lease: journal.force_with_lease.clone(),
+        ..opts
+    };
+    publish_tag_and_push(
+        &ctx,
+        opts,
+        &publishees,
+        commit_id,
+        &release_section_by_publishee,
+        journal,
+    )
+}
+
+/// Push the release branch and open its pull request, leaving publishing to `--resume` once it was merged.
+fn open_pull_request_to_publish_after_merge(
+    ctx: &Context,
+    options: Options,
+    publishees: &[(&cargo_metadata::Package, &semver::Version)],
+    journal: &mut Journal,
+) -> anyhow::Result<()> {
+    if !options.dry_run {
+        journal.save(&ctx.base.repo)?;
+    }
+    git::push_head(&ctx.base.repo, options.clone())?;
+    journal.pull_request = github::open_pull_request(publishees, options.clone())?;
+    if !options.dry_run {
+        journal.save(&ctx.base.repo)?;
+    }
+    tracing::info!(
+        "Once the pull request is merged, check out its merge commit and run again with --resume to publish {}",
+        crate::utils::names_and_versions(publishees)
+    );
+    Ok(())
+}
+
+/// Return `true` if the pull request of a release published after merging it was merged, and make its merge commit the
+/// commit of the release in `journal`, which is tagged instead of the commit on the release branch.
+///
+/// The pull request is opened if that failed before, and `false` is returned.
+fn pull_request_was_merged(
+    ctx: &Context,
+    options: Options,
+    publishees: &[(&cargo_metadata::Package, &semver::Version)],
+    journal: &mut Journal,
+) -> anyhow::Result<bool> {
+    if !Program::named("gh").found {
+        bail!("To check if the pull request of the release was merged, please install the 'gh' program and try again");
+    }
+    let Some(url) = journal.pull_request.clone() else {
+        open_pull_request_to_publish_after_merge(
+            ctx,
+            Options {
+                publish_after_merge: true,
+                ..options
+            },
+            publishees,
+            journal,
+        )?;
+        return Ok(false);
+    };
+    let Some(merge_commit) = github::merge_commit(&url)? else {
+        bail!("The pull request {url} of the release isn't merged yet. Run again with --resume once it is.")
+    };
+    let head = ctx.base.repo.head_id()?;
+    if head.to_string() != merge_commit {
+        bail!("The pull request {url} was merged as {merge_commit}, but HEAD is at {head}. Check out the merge commit, for example by pulling the default branch, and try again.")
+    }
+    tracing::info!("The pull request {url} was merged, releasing from its merge commit {merge_commit}");
+    journal.commit = Some(merge_commit);
+    journal.publish_after_merge = false;
+    if !options.dry_run {
+        journal.save(&ctx.base.repo)?;
+    }
+    Ok(true)
+}
+
+/// Read the section of the release of `version` from the changelog of `package`, if there is one.
+fn released_section(
+    package: &cargo_metadata::Package,
+    version: &semver::Version,
+    ctx: &crate::Context,
+) -> Option<Section> {
+    let path = crate::utils::changelog_path(package, &ctx.meta);
+    let markdown = std::fs::read_to_string(path).ok()?;
+    crate::ChangeLog::from_markdown(&markdown, &ctx.changelog_locale)
+        .sections
+        .into_iter()
+        .find(
+            |section| matches!(section, Section::Release { name: changelog::Version::Semantic(v), .. } if v == version),
+        )
+}
+
+fn publish_tag_and_push<'repo>(
+    ctx: &'repo Context,
+    options: Options,
+    publishees: &[(&cargo_metadata::Package, &semver::Version)],
+    commit_id: Option<gix::Id<'repo>>,
+    release_section_by_publishee: &BTreeMap<&str, Section>,
+    mut journal: Journal,
+) -> anyhow::Result<()> {
+    let save = |journal: &Journal| {
+        if options.dry_run {
+            Ok(())
+        } else {
+            journal.save(&ctx.base.repo)
+        }
+    };
+    save(&journal)?;
+
     let should_publish_to_github = options.allow_changelog_github_release
         && if Program::named("gh").found {
             true
         } else {
-            log::warn!("To create github releases, please install the 'gh' program and try again");
+            tracing::warn!("To create github releases, please install the 'gh' program and try again");
             false
         };
-    let mut tag_names = Vec::new();
-    let mut successful_publishees_and_version = Vec::<(&cargo_metadata::Package, &semver::Version)>::new();
+    let unpublished_versions: Vec<_> = publishees
+        .iter()
+        .copied()
+        .filter(|(publishee, _)| {
+            !journal.published.contains(publishee.name.as_str()) && !is_changelog_only(publishee, &options)
+        })
+        .collect();
+    let unpublished: Vec<_> = unpublished_versions.iter().map(|(publishee, _)| *publishee).collect();
+    package_contents::report(&unpublished, options.clone())?;
+    let mut unpublished_by_registry = BTreeMap::<_, Vec<_>>::new();
+    for publishee in &unpublished {
+        unpublished_by_registry
+            .entry(ctx.base.registry_of(publishee))
+            .or_default()
+            .push(*publishee);
+    }
+    for (registry, publishees) in unpublished_by_registry {
+        cargo::verify_packaging(
+            &publishees,
+            &ctx.base.root,
+            Options {
+                registry: registry.map(ToOwned::to_owned),
+                ..options.clone()
+            },
+        )?;
+    }
+    package_contents::verify_size(&unpublished_versions, &ctx.base.meta.target_directory, options.clone())?;
+
+    let mut publishing = crate::progress::add_child("publish");
+    publishing.init(Some(unpublished.len()), Some(prodash::unit::label("crates")));
+    let mut last_published = None::<(&cargo_metadata::Package, &semver::Version)>;
     let mut publish_err = None;
     let prevent_default_members = ctx.base.meta.workspace_members.len() > 1;
-    for (publishee, new_version) in crates.iter().filter_map(try_to_published_crate_and_new_version) {
-        if let Some((crate_, version)) = successful_publishees_and_version.last() {
-            if let Err(err) = wait_for_release(crate_, version, options.clone()) {
-                log::warn!(
-                    "Failed to wait for crates-index update - trying to publish '{} v{}' anyway: {}.",
-                    publishee.name,
-                    new_version,
-                    err
+    let mut publish_results = Vec::new();
+    let mut failed_mirrors = BTreeSet::new();
+    for &(publishee, new_version) in publishees {
+        let name = publishee.name.as_str();
+        let main_registry = ctx.base.registry_of(publishee).unwrap_or("crates-io");
+        if journal.published.contains(name) {
+            tracing::info!("Skipping to publish '{name} v{new_version}' as it was published already");
+            publish_results.push((publishee, new_version, main_registry, mirrors::Outcome::Published));
+        } else {
+            let mut progress = publishing.add_child(format!("{name} v{new_version}"));
+            if let Some((crate_, version)) = last_published {
+                if let Err(err) = crate::stats::time(format!("wait for {}", crate_.name), || {
+                    wait_for_release(crate_, version, options_for(crate_, &ctx.base, &options), &mut progress)
+                }) {
+                    tracing::warn!(
+                        "Failed to wait for crates-index update - trying to publish '{} v{}' anyway: {}.",
+                        publishee.name,
+                        new_version,
+                        err
+                    );
+                }
+            }
+
+            let crate_options = options_for(publishee, &ctx.base, &options);
+            let run_publish_hook = |hook| {
+                if crate_options.skip_publish {
+                    Ok(())
+                } else {
+                    hooks::run(hook, publishee, new_version, &ctx.base, options.dry_run)
+                }
+            };
+            if !crate_options.skip_publish {
+                event::step_started(Step::Publish, Some((name, new_version)), options.dry_run);
+            } else if is_changelog_only(publishee, &options) {
+                tracing::info!(
+                    "{} not publish '{name} v{new_version}' as it's released with --changelog-only",
+                    will(options.dry_run)
                 );
             }
+            progress.init(None, None);
+            progress.set_name(format!("{name} v{new_version}: publishing"));
+            if let Err(err) = run_publish_hook(Hook::PrePublish).and_then(|_| {
+                crate::stats::time(format!("publish {name}"), || {
+                    cargo::publish_crate(
+                        publishee,
+                        new_version,
+                        prevent_default_members,
+                        &ctx.base,
+                        crate_options.clone(),
+                    )
+                })
+            }) {
+                publish_results.push((publishee, new_version, main_registry, mirrors::Outcome::Failed));
+                publish_err = Some(err);
+                break;
+            }
+            if !crate_options.skip_publish {
+                event::crate_published(name, new_version, main_registry, options.dry_run);
+            }
+            publishing.inc();
+            publish_results.push((publishee, new_version, main_registry, mirrors::Outcome::Published));
+            last_published = Some((publishee, new_version));
+            journal.published.insert(name.to_owned());
+            save(&journal)?;
+            if let Err(err) = run_publish_hook(Hook::PostPublish) {
+                publish_err = Some(err);
+                break;
+            }
         }
-
-        if let Err(err) = cargo::publish_crate(publishee, prevent_default_members, options.clone()) {
-            publish_err = Some(err);
-            break;
+        let mirror_registries = if is_changelog_only(publishee, &options) {
+            &[][..]
+        } else {
+            &options.mirror_registries[..]
+        };
+        for registry in mirror_registries {
+            let outcome = if journal.mirrored.get(registry).is_some_and(|names| names.contains(name)) {
+                mirrors::Outcome::Published
+            } else if failed_mirrors.contains(registry) {
+                mirrors::Outcome::Skipped
+            } else {
+                match mirrors::publish(
+                    publishee,
+                    new_version,
+                    registry,
+                    prevent_default_members,
+                    &ctx.base,
+                    options.clone(),
+                ) {
+                    Ok(outcome) => {
+                        if outcome != mirrors::Outcome::NotAllowed {
+                            journal
+                                .mirrored
+                                .entry(registry.clone())
+                                .or_default()
+                                .insert(name.to_owned());
+                            save(&journal)?;
+                        }
+                        outcome
+                    }
+                    Err(err) => {
+                        tracing::warn!("Failed to publish '{name} v{new_version}' to '{registry}': {err:#}");
+                        failed_mirrors.insert(registry);
+                        publish_err.get_or_insert(err);
+                        mirrors::Outcome::Failed
+                    }
+                }
+            };
+            publish_results.push((publishee, new_version, registry.as_str(), outcome));
+        }
+        if !journal.tags.contains_key(name) && !options.skip_tag {
+            event::step_started(Step::Tag, Some((name, new_version)), options.dry_run);
+            if let Some(tag_name) = git::create_version_tag(
+                publishee,
+                new_version,
+                commit_id,
+                release_section_by_publishee
+                    .get(name)
+                    .and_then(|s| section_to_string(s, WriteMode::Tag, &options.clone().into())),
+                &ctx.base,
+                options.clone(),
+            )? {
+                event::tag_created(name, &tag_name.shorten().to_string(), options.dry_run);
+                journal.tags.insert(name.to_owned(), tag_name.as_bstr().to_string());
+                save(&journal)?;
+            }
+        }
+    }
+    if !options.mirror_registries.is_empty() && !options.skip_publish {
+        mirrors::report(&publish_results);
+    }
+    if options.sigstore && !options.skip_publish {
+        for &(publishee, new_version) in publishees {
+            let name = publishee.name.as_str();
+            if !journal.published.contains(name)
+                || journal.signatures.contains_key(name)
+                || is_changelog_only(publishee, &options)
+            {
+                continue;
+            }
+            let tag_name = journal.tags.get(name).map(String::as_str);
+            match sigstore::sign(publishee, new_version, tag_name, &ctx.base, options.dry_run) {
+                Ok(bundles) => {
+                    journal.signatures.insert(name.to_owned(), bundles);
+                    save(&journal)?;
+                }
+                Err(err) => {
+                    publish_err.get_or_insert(err);
+                    break;
+                }
+            }
         }
-        successful_publishees_and_version.push((publishee, new_version));
-        if let Some(tag_name) = git::create_version_tag(
-            publishee,
-            new_version,
-            commit_id,
-            release_section_by_publishee
-                .get(&publishee.name.as_str())
-                .and_then(|s| section_to_string(s, WriteMode::Tag, options.capitalize_commit)),
-            &ctx.base,
-            options.clone(),
-        )? {
-            tag_names.push(tag_name);
-        }
-    }
-    git::push_tags_and_head(&ctx.base.repo, &tag_names, options.clone())?;
+    }
+    if !journal.pushed {
+        for &(publishee, new_version) in publishees {
+            let name = publishee.name.as_str();
+            if !journal.published.contains(name) || journal.verified.contains(name) {
+                continue;
+            }
+            if let Err(err) = checksum::verify(
+                publishee,
+                new_version,
+                &ctx.base,
+                options_for(publishee, &ctx.base, &options),
+            ) {
+                tracing::warn!("The release is incomplete. Investigate the problem and run again with --resume to verify again, or push the tags yourself.");
+                return Err(err);
+            }
+            journal.verified.insert(name.to_owned());
+            save(&journal)?;
+        }
+        let tag_names = journal
+            .tags
+            .values()
+            .map(|name| gix::refs::FullName::try_from(name.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        crate::stats::time("push", || {
+            git::push_tags_and_head(&ctx.base.repo, &tag_names, options.clone())
+        })?;
+        if publish_err.is_none() {
+            journal.pushed = true;
+            save(&journal)?;
+            if options.open_pull_request && journal.pull_request.is_none() {
+                journal.pull_request = github::open_pull_request(publishees, options.clone())?;
+                save(&journal)?;
+            }
+            if !options.skip_push {
+                for &(publishee, new_version) in publishees {
+                    hooks::run(Hook::PostPush, publishee, new_version, &ctx.base, options.dry_run)?;
+                }
+            }
+        }
+    }
     if should_publish_to_github {
-        for (publishee, new_version) in successful_publishees_and_version {
-            release_section_by_publishee
-                .get(&publishee.name.as_str())
-                .and_then(|s| section_to_string(s, WriteMode::GitHubRelease, options.capitalize_commit))
-                .map(|release_notes| {
-                    github::create_release(publishee, new_version, &release_notes, options.clone(), &ctx.base)
-                })
-                .transpose()?;
+        for &(publishee, new_version) in publishees {
+            let name = publishee.name.as_str();
+            if !journal.published.contains(name) || journal.github_releases.contains(name) {
+                continue;
+            }
+            let release_notes = release_section_by_publishee
+                .get(name)
+                .and_then(|s| section_to_string(s, WriteMode::GitHubRelease, &options.clone().into()));
+            if let Some(release_notes) = release_notes {
+                github::create_release(publishee, new_version, &release_notes, options.clone(), &ctx.base)?;
+                let mut assets = journal.signatures.get(name).cloned().unwrap_or_default();
+                let targets = artifacts::binary_targets(publishee, &ctx.base)?;
+                if !targets.is_empty() {
+                    assets.extend(artifacts::build(
+                        publishee,
+                        new_version,
+                        &targets,
+                        &ctx.base,
+                        options.dry_run,
+                    )?);
+                }
+                if !assets.is_empty() {
+                    github::upload_assets(publishee, new_version, &assets, options.clone(), &ctx.base)?;
+                }
+            }
+            journal.github_releases.insert(name.to_owned());
+            save(&journal)?;
         }
     }
 
-    publish_err.map_or(Ok(()), Err)
+    if let Some(path) = &options.summary {
+        if options.dry_run {
+            tracing::info!("WOULD write a summary of the release to '{}'", path.display());
+        } else {
+            let skipped: Vec<_> = [
+                (options.skip_publish, "publish"),
+                (options.skip_tag, "tag"),
+                (options.skip_push, "push"),
+                (!should_publish_to_github, "github-release"),
+            ]
+            .into_iter()
+            .filter_map(|(skipped, step)| skipped.then_some(step))
+            .collect();
+            let res = summary::write(
+                path,
+                &journal,
+                publish_err.is_none(),
+                &skipped,
+                &options.changelog_only,
+                &ctx.base.repo,
+            );
+            match (res, &publish_err) {
+                (Err(err), None) => return Err(err),
+                (Err(err), Some(_)) => tracing::warn!("{err:#}"),
+                (Ok(()), _) => {}
+            }
+        }
+    }
+
+    match publish_err {
+        Some(err) => {
+            if !options.dry_run {
+                tracing::warn!("The release is incomplete. Fix the problem and run again with --resume to continue where it stopped.");
+            }
+            Err(err)
+        }
+        None if options.dry_run => Ok(()),
+        None => Journal::remove(&ctx.base.repo),
+    }
+}
+
+/// Return `options` with the registry to publish `package` to, which may be configured for it specifically, and
+/// without publishing it if it's released with --changelog-only.
+fn options_for(package: &cargo_metadata::Package, ctx: &crate::Context, options: &Options) -> Options {
+    Options {
+        registry: ctx.registry_of(package).map(ToOwned::to_owned),
+        skip_publish: options.skip_publish || is_changelog_only(package, options),
+        ..options.clone()
+    }
+}
+
+fn is_changelog_only(package: &cargo_metadata::Package, options: &Options) -> bool {
+    options.changelog_only.iter().any(|name| *name == package.name.as_str())
 }
 
 fn wait_for_release(
@@ -473,25 +1316,61 @@ fn wait_for_release(
         dry_run,
         dry_run_cargo_publish,
         skip_publish,
+        registry,
+        availability_timeout: timeout,
         ..
     }: Options,
+    progress: &mut crate::progress::Progress,
 ) -> anyhow::Result<()> {
     use anyhow::Context;
 
     if skip_publish || dry_run || dry_run_cargo_publish {
         return Ok(());
     }
-    let timeout = std::time::Duration::from_secs(60);
     let start = std::time::Instant::now();
     let sleep_time = std::time::Duration::from_secs(1);
-    let crate_version = crate_version.to_string();
+    progress.set_name(format!(
+        "waiting for '{} v{crate_version}' to arrive in index",
+        crate_.name
+    ));
+    progress.init(
+        Some(timeout.as_secs() as usize),
+        Some(prodash::unit::dynamic(prodash::unit::Duration)),
+    );
 
-    log::info!("Waiting for '{} v{}' to arrive in index…", crate_.name, crate_version);
+    if registry.is_none() {
+        if crate_
+            .publish
+            .as_ref()
+            .is_some_and(|registries| !registries.iter().any(|r| r == "crates-io"))
+        {
+            tracing::info!(
+                "Not waiting for '{}' as it was published to a registry other than crates.io",
+                crate_.name
+            );
+            return Ok(());
+        }
+        tracing::info!("Waiting for '{} v{}' to arrive in index…", crate_.name, crate_version);
+        let mut attempt = 0;
+        while start.elapsed() < timeout {
+            attempt += 1;
+            tracing::trace!("Querying sparse crates index…");
+            if crate::crates_index::sparse_index_has_version(&crate_.name, crate_version)? {
+                break;
+            }
+            std::thread::sleep(sleep_time);
+            progress.set(start.elapsed().as_secs() as usize);
+            tracing::info!("attempt {attempt}");
+        }
+        return Ok(());
+    }
+
+    tracing::info!("Waiting for '{} v{}' to arrive in index…", crate_.name, crate_version);
     let mut crates_index = crates_index::GitIndex::new_cargo_default()?;
     let mut attempt = 0;
     while start.elapsed() < timeout {
         attempt += 1;
-        log::trace!("Updating crates index…");
+        tracing::trace!("Updating crates index…");
         crates_index.update()?;
         let crate_ = crates_index.crate_(&crate_.name).with_context(|| {
             format!(
@@ -504,13 +1383,17 @@ fn wait_for_release(
             .versions()
             .iter()
             .rev()
-            .any(|version| version.version() == crate_version)
+            // build metadata may or may not be retained by the registry
+            .any(|version| {
+                semver::Version::parse(version.version()).is_ok_and(|v| v.cmp_precedence(crate_version).is_eq())
+            })
         {
             break;
         }
 
         std::thread::sleep(sleep_time);
-        log::info!("attempt {attempt}");
+        progress.set(start.elapsed().as_secs() as usize);
+        tracing::info!("attempt {attempt}");
     }
     Ok(())
 }
@@ -520,7 +1403,7 @@ enum WriteMode {
     GitHubRelease,
 }
 
-fn section_to_string(section: &Section, mode: WriteMode, capitalize_commit: bool) -> Option<String> {
+fn section_to_string(section: &Section, mode: WriteMode, write_options: &changelog::write::Options) -> Option<String> {
     let mut b = String::new();
     section
         .write_to(
@@ -530,7 +1413,7 @@ fn section_to_string(section: &Section, mode: WriteMode, capitalize_commit: bool
                 WriteMode::Tag => changelog::write::Components::empty(),
                 WriteMode::GitHubRelease => changelog::write::Components::DETAIL_TAGS,
             },
-            capitalize_commit,
+            write_options,
         )
         .ok()
         .map(|_| b)
@@ -545,8 +1428,12 @@ mod tests {
         Options {
             dry_run,
             allow_dirty: false,
+            allow_dirty_paths: Vec::new(),
             ignore_instability: false,
             skip_publish,
+            changelog_only: Vec::new(),
+            summary: None,
+            yes: false,
             dry_run_cargo_publish: false,
             conservative_pre_release_version_handling: true,
             no_verify: false,
@@ -560,17 +1447,69 @@ mod tests {
             isolate_dependencies_from_breaking_changes: true,
             changelog: true,
             preview: true,
+            preview_command: None,
             generator_segments: segment::Selection::empty(),
             allow_fully_generated_changelogs: false,
             allow_empty_release_message: false,
             changelog_links: true,
             allow_changelog_github_release: true,
             capitalize_commit: false,
+            changelog_strip_trailing_period: false,
+            changelog_imperative_mood: false,
+            changelog_locale: Default::default(),
+            changelog_pull_request_titles: false,
+            changelog_references_are_pull_requests: false,
+            changelog_authors: false,
+            write_commit_graph: false,
+            changelog_sidecar: false,
+            changelog_anchors: false,
+            changelog_commit_hashes: true,
             registry: None,
+            resume: false,
+            offline: false,
+            confirm: false,
+            skip_preflight: false,
+            publish_policy: Default::default(),
+            availability_timeout: std::time::Duration::from_secs(60),
+            skip_checksum_verification: false,
+            credential_provider: None,
+            mirror_registries: Vec::new(),
+            remote: None,
+            release_branch: None,
+            branch: None,
+            on_existing_tag: crate::command::release::TagConflict::ReplaceIfUnpushed,
+            git_hooks: true,
+            atomic_push: true,
+            verify_tags: false,
+            signed_push: None,
+            release_author: None,
+            open_pull_request: false,
+            replace_release: false,
+            amend_release: false,
+            separate_changelog_commit: false,
+            autostash: false,
+            force_with_lease: Default::default(),
+            publish_after_merge: false,
+            sigstore: false,
             target: None,
             publish_uses_docs_rs_metadata: false,
             signoff: false,
+            sign: false,
             commit_prefix: None,
+            commit_message: None,
+            semver_checks: None,
+            build_metadata: None,
+            bump_overrides: Default::default(),
+            dependent_bump: None,
+            explain: false,
+            print_graph: None,
+            plan_format: None,
+            doctor: false,
+            interactive: false,
+            force: false,
+            pinned: Vec::new(),
+            excluded: Vec::new(),
+            version_scheme: std::rc::Rc::new(crate::version::SemanticVersioning),
         }
     }
 
@@ -597,4 +1536,12 @@ mod tests {
 
         assert!(!should_update_crates_index(&options));
     }
+
+    #[test]
+    fn confirmation_codes_depend_on_the_release() {
+        let code = confirmation_code("a v1.0.0, b v2.0.0");
+        assert_eq!(code.len(), 6);
+        assert_eq!(code, confirmation_code("a v1.0.0, b v2.0.0"));
+        assert_ne!(code, confirmation_code("a v1.0.1, b v2.0.0"));
+    }
 }
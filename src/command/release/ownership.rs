@@ -0,0 +1,177 @@
+//! Check that the crates to publish are owned by the user publishing them, before anything is changed.
+use anyhow::{bail, Context};
+use cargo_metadata::Package;
+
+use super::Options;
+use crate::crates_index::http_get;
+
+const CRATES_IO_API_URL: &str = "https://crates.io/api/v1";
+
+/// Fail if the user authenticated by the crates.io token isn't an owner of each of `publishees` that exists already,
+/// listing all crates they can't publish.
+///
+/// Crates owned by a team are assumed to be publishable, as team membership can't be queried, and the check is skipped
+/// if no token can be found.
+pub(in crate::command::release_impl) fn verify(
+    publishees: &[&Package],
+    Options {
+        dry_run,
+        skip_publish,
+        skip_preflight,
+        registry,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    if dry_run || skip_publish || skip_preflight || publishees.is_empty() {
+        return Ok(());
+    }
+    if let Some(registry) = registry {
+        tracing::debug!("Skipping the ownership check as it is only supported for crates.io, not for '{registry}'");
+        return Ok(());
+    }
+    let Some(token) = registry_token(None)? else {
+        tracing::warn!(
+            "Could not check crate ownership before publishing as no crates.io token was found in CARGO_REGISTRY_TOKEN or in the cargo credentials"
+        );
+        return Ok(());
+    };
+    check(publishees, &token)
+}
+
+/// Fail if the user authenticated by the crates.io `token` isn't an owner of each of `publishees` that exists already.
+pub(in crate::command::release_impl) fn check(publishees: &[&Package], token: &str) -> anyhow::Result<()> {
+    let login = current_user(token)?;
+    let mut offending = Vec::new();
+    for publishee in publishees {
+        let name = publishee.name.as_str();
+        match http_get(&format!("{CRATES_IO_API_URL}/crates/{name}/owners"), &[])? {
+            (200, body) => {
+                let owners: serde_json::Value = serde_json::from_slice(&body)?;
+                if let Err(owners) = may_publish(&owners, &login) {
+                    offending.push(format!("'{name}' (owned by {})", owners.join(", ")));
+                }
+            }
+            (404, _) => tracing::debug!("'{name}' isn't published yet and will be owned by '{login}'"),
+            (code, _) => bail!("crates.io responded with unexpected status {code} when listing the owners of '{name}'"),
+        }
+    }
+    if !offending.is_empty() {
+        bail!(
+            "'{login}' is not an owner of the following crates and can't publish them: {}\nAn owner can add you with 'cargo owner --add {login} <crate>'.",
+            offending.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Return the login of the user the crates.io `token` belongs to.
+fn current_user(token: &str) -> anyhow::Result<String> {
+    match http_get(
+        &format!("{CRATES_IO_API_URL}/me"),
+        &[&format!("Authorization: {token}")],
+    )? {
+        (200, body) => {
+            let me: serde_json::Value = serde_json::from_slice(&body)?;
+            me.pointer("/user/login")
+                .and_then(serde_json::Value::as_str)
+                .map(ToOwned::to_owned)
+                .context("crates.io didn't provide the login of the authenticated user")
+        }
+        (403, _) => bail!("The crates.io token is invalid or expired, please login again with 'cargo login'"),
+        (code, _) => bail!("crates.io responded with unexpected status {code} when querying the authenticated user"),
+    }
+}
+
+/// Return `Ok` if `login` is among the `owners` as returned by the crates.io API, or if a team owns the crate.
+/// Otherwise return the logins of all owners.
+fn may_publish(owners: &serde_json::Value, login: &str) -> Result<(), Vec<String>> {
+    let owners: Vec<_> = owners
+        .get("users")
+        .and_then(serde_json::Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|owner| {
+            let login = owner.get("login")?.as_str()?;
+            let is_team = owner.get("kind").and_then(serde_json::Value::as_str) == Some("team");
+            Some((login, is_team))
+        })
+        .collect();
+    if owners
+        .iter()
+        .any(|(owner, is_team)| *is_team || owner.eq_ignore_ascii_case(login))
+    {
+        Ok(())
+    } else {
+        Err(owners.into_iter().map(|(owner, _)| format!("'{owner}'")).collect())
+    }
+}
+
+/// Obtain the token of `registry`, or of crates.io if `None`, like cargo does, from the environment or from the
+/// credentials file in the cargo home.
+pub(in crate::command::release_impl) fn registry_token(registry: Option<&str>) -> anyhow::Result<Option<String>> {
+    if let Ok(token) = std::env::var(token_env_var(registry)) {
+        return Ok(Some(token));
+    }
+    let cargo_home = home::cargo_home()?;
+    for file_name in ["credentials.toml", "credentials"] {
+        let path = cargo_home.join(file_name);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let credentials: toml_edit::DocumentMut = content
+            .parse()
+            .with_context(|| format!("Could not parse cargo credentials at '{}'", path.display()))?;
+        let table = match registry {
+            Some(name) => credentials
+                .get("registries")
+                .and_then(|registries| registries.get(name)),
+            None => credentials.get("registry"),
+        };
+        return Ok(table
+            .and_then(|registry| registry.get("token"))
+            .and_then(|token| token.as_str())
+            .map(ToOwned::to_owned));
+    }
+    Ok(None)
+}
+
+/// Return the name of the environment variable with the token of `registry`, or of crates.io if `None`.
+pub(in crate::command::release_impl) fn token_env_var(registry: Option<&str>) -> String {
+    match registry {
+        Some(name) => format!("CARGO_REGISTRIES_{}_TOKEN", name.to_ascii_uppercase().replace('-', "_")),
+        None => "CARGO_REGISTRY_TOKEN".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::may_publish;
+
+    #[test]
+    fn owners_are_matched_by_login_or_team() {
+        let owners = json!({"users": [
+            {"id": 1, "login": "Alice", "kind": "user"},
+            {"id": 2, "login": "bob", "kind": "user"}
+        ]});
+        assert_eq!(may_publish(&owners, "alice"), Ok(()));
+        assert_eq!(
+            may_publish(&owners, "mallory"),
+            Err(vec!["'Alice'".to_owned(), "'bob'".into()])
+        );
+
+        let owners = json!({"users": [
+            {"id": 1, "login": "alice", "kind": "user"},
+            {"id": 3, "login": "github:org:maintainers", "kind": "team"}
+        ]});
+        assert_eq!(
+            may_publish(&owners, "mallory"),
+            Ok(()),
+            "team membership can't be checked"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Inspect the files that `cargo package` would put into each crate, to catch mistakes before anything is published.
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use cargo_metadata::{camino::Utf8Path, Package};
+
+use super::Options;
+
+/// Directories whose contents are usually only needed for testing, and which make crates larger without need.
+const TEST_DATA_DIRECTORIES: &[&str] = &["fixtures", "testdata", "test-data", "test_data", "snapshots"];
+
+/// Files larger than this are reported as they might have been included by accident.
+const LARGE_FILE_SIZE: u64 = 1024 * 1024;
+
+/// The largest `.crate` file crates.io accepts.
+const CRATES_IO_MAX_UPLOAD_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Log the amount and the total size of the files each of `publishees` would contain when packaged, and warn about files
+/// that look like they were included by accident, or that should be included but aren't.
+///
+/// With `verbose`, all packaged files are listed as well.
+pub(in crate::command::release_impl) fn report(
+    publishees: &[&Package],
+    Options {
+        skip_publish,
+        skip_preflight,
+        verbose,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    if skip_publish || skip_preflight {
+        return Ok(());
+    }
+    for publishee in publishees {
+        let files = list(publishee)?;
+        let crate_dir = publishee
+            .manifest_path
+            .parent()
+            .expect("parent of a file is always present");
+        let sizes: Vec<_> = files
+            .iter()
+            .map(|file| std::fs::metadata(crate_dir.join(file)).ok().map(|m| m.len()))
+            .collect();
+        tracing::info!(
+            "'{}' would be packaged with {} files and {} in total",
+            publishee.name,
+            files.len(),
+            human_size(sizes.iter().flatten().sum())
+        );
+        if verbose {
+            for (file, size) in files.iter().zip(&sizes) {
+                match size {
+                    Some(size) => tracing::trace!("  {file} ({})", human_size(*size)),
+                    None => tracing::trace!("  {file} (generated)"),
+                }
+            }
+        }
+        let test_data = files_in_test_data_directories(&files);
+        if !test_data.is_empty() {
+            tracing::warn!(
+                "'{}' would be packaged with {} file(s) that look like test data, consider excluding them in Cargo.toml: {}",
+                publishee.name,
+                test_data.len(),
+                abbreviated(&test_data)
+            );
+        }
+        let large: Vec<_> = files
+            .iter()
+            .zip(&sizes)
+            .filter_map(|(file, size)| size.filter(|size| *size > LARGE_FILE_SIZE).map(|size| (file, size)))
+            .map(|(file, size)| format!("{file} ({})", human_size(size)))
+            .collect();
+        if !large.is_empty() {
+            tracing::warn!(
+                "'{}' would be packaged with large files: {}",
+                publishee.name,
+                large.join(", ")
+            );
+        }
+        for (field, path) in [
+            ("readme", publishee.readme.as_deref()),
+            ("license-file", publishee.license_file.as_deref()),
+        ] {
+            if let Some(path) = path.filter(|path| !is_listed(&files, path)) {
+                tracing::warn!(
+                    "'{}' would be packaged without '{path}' even though it's set as its '{field}'",
+                    publishee.name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fail if any of the `.crate` files created for `publishees` when packaging them into `target_directory` exceeds the
+/// upload size limit of crates.io, listing its largest files.
+///
+/// The limits of other registries aren't known, so nothing is checked when publishing to them.
+pub(in crate::command::release_impl) fn verify_size(
+    publishees: &[(&Package, &semver::Version)],
+    target_directory: &Utf8Path,
+    Options {
+        skip_publish,
+        skip_preflight,
+        dry_run,
+        registry,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    if skip_publish || skip_preflight || dry_run || registry.is_some() {
+        return Ok(());
+    }
+    let mut oversized = Vec::new();
+    for (publishee, version) in publishees {
+        let path = super::cargo::crate_file(target_directory, &publishee.name, version);
+        let size = std::fs::metadata(&path)
+            .with_context(|| format!("Could not find the packaged crate at '{path}'"))?
+            .len();
+        if size <= CRATES_IO_MAX_UPLOAD_SIZE {
+            continue;
+        }
+        let crate_dir = publishee
+            .manifest_path
+            .parent()
+            .expect("parent of a file is always present");
+        let files = list(publishee)?
+            .into_iter()
+            .filter_map(|file| Some((file.clone(), std::fs::metadata(crate_dir.join(&file)).ok()?.len())))
+            .collect();
+        oversized.push(format!(
+            "'{}' is {} when packaged, and its largest files are:\n{}",
+            publishee.name,
+            human_size(size),
+            largest(files).join("\n")
+        ));
+    }
+    if !oversized.is_empty() {
+        bail!(
+            "crates.io doesn't accept crates larger than {}, so nothing was published.\n{}\nExclude files in Cargo.toml to make them smaller, or use --no-preflight to try anyway.",
+            human_size(CRATES_IO_MAX_UPLOAD_SIZE),
+            oversized.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Return the paths of all files `cargo package` would put into the crate of `package`, relative to its root.
+fn list(package: &Package) -> anyhow::Result<Vec<String>> {
+    let output = Command::new("cargo")
+        .args(["package", "--list", "--allow-dirty", "--manifest-path"])
+        .arg(package.manifest_path.as_std_path())
+        .args(["--package", package.name.as_str()])
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "Could not list the files to package for '{}':\n{}",
+            package.name,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+fn files_in_test_data_directories(files: &[String]) -> Vec<&str> {
+    files
+        .iter()
+        .filter(|file| {
+            let mut directories = file.split('/').rev().skip(1);
+            directories.any(|dir| TEST_DATA_DIRECTORIES.contains(&dir))
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+/// Return `true` if `path`, as configured in the manifest, is part of the packaged `files`.
+///
+/// Files outside of the crate directory are packaged at its root.
+fn is_listed(files: &[String], path: &Utf8Path) -> bool {
+    let path = path.as_str().replace('\\', "/");
+    let file_name = path.rsplit('/').next().unwrap_or(&path);
+    files.iter().any(|file| *file == path || *file == file_name)
+}
+
+/// Return the five largest of `files` along with their size, largest first.
+fn largest(mut files: Vec<(String, u64)>) -> Vec<String> {
+    files.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    files
+        .into_iter()
+        .take(5)
+        .map(|(file, size)| format!("  {file} ({})", human_size(size)))
+        .collect()
+}
+
+fn abbreviated(files: &[&str]) -> String {
+    const MAX: usize = 5;
+    let mut out = files.iter().take(MAX).copied().collect::<Vec<_>>().join(", ");
+    if files.len() > MAX {
+        out.push_str(&format!(" and {} more", files.len() - MAX));
+    }
+    out
+}
+
+fn human_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..LARGE_FILE_SIZE => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{files_in_test_data_directories, human_size, is_listed, largest};
+
+    #[test]
+    fn test_data_is_detected_by_directory_name() {
+        let files: Vec<_> = [
+            "Cargo.toml",
+            "src/fixtures.rs",
+            "tests/fixtures/repo.tar",
+            "tests/snapshots/a.snap",
+            "testdata",
+        ]
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect();
+        assert_eq!(
+            files_in_test_data_directories(&files),
+            ["tests/fixtures/repo.tar", "tests/snapshots/a.snap"]
+        );
+    }
+
+    #[test]
+    fn manifest_paths_are_found_in_the_package_listing() {
+        let files = vec!["Cargo.toml".to_owned(), "README.md".into(), "docs/LICENSE".into()];
+        assert!(is_listed(&files, "README.md".into()));
+        assert!(
+            is_listed(&files, "../README.md".into()),
+            "files outside of the crate are put at its root"
+        );
+        assert!(is_listed(&files, "docs/LICENSE".into()));
+        assert!(!is_listed(&files, "LICENSE-MIT".into()));
+    }
+
+    #[test]
+    fn the_largest_files_come_first() {
+        let files = (1..=7).map(|n| (format!("file-{n}"), n * 1024 * 1024)).collect();
+        assert_eq!(
+            largest(files),
+            [
+                "  file-7 (7.0 MiB)",
+                "  file-6 (6.0 MiB)",
+                "  file-5 (5.0 MiB)",
+                "  file-4 (4.0 MiB)",
+                "  file-3 (3.0 MiB)"
+            ]
+        );
+    }
+
+    #[test]
+    fn sizes_are_human_readable() {
+        assert_eq!(human_size(12), "12 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
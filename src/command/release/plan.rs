@@ -0,0 +1,204 @@
+//! Describe the computed release in a machine-readable format, for CI gates and dashboards to inspect before releasing.
+use cargo_metadata::Package;
+use serde::Serialize;
+
+use crate::{
+    command::release::PlanFormat,
+    traverse::{dependency, Dependency},
+};
+
+/// Everything a release would do, without any of it being done.
+#[derive(Debug, Serialize)]
+struct Plan<'a> {
+    /// All crates that are part of the release, in the order they are published in.
+    crates: Vec<Crate<'a>>,
+    /// The names of the crates to publish, in the order they are published in.
+    publish_order: Vec<&'a str>,
+    /// The names of the tags to create.
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Crate<'a> {
+    name: &'a str,
+    /// If `true`, the crate was selected by the user, otherwise it's a dependency or dependent of a selected crate.
+    selected: bool,
+    current_version: &'a semver::Version,
+    /// The version the crate is released with, or `None` if its version doesn't change.
+    next_version: Option<&'a semver::Version>,
+    /// If `true`, the crate is published.
+    publish: bool,
+    /// The reason for not publishing the crate.
+    skip_reason: Option<String>,
+    /// The names of the dependencies whose breaking changes cause the version of this crate to be bumped.
+    breaking_dependencies: &'a [String],
+    /// If `true`, the manifest of the crate is adjusted, for its version or its dependency requirements.
+    manifest_changes: bool,
+    /// The name of the tag created for the release of this crate.
+    tag: Option<String>,
+}
+
+/// Return the plan to release `crates` in `format`, with `tag_name` returning the name of the tag to create for the
+/// release of a package at a version, if any.
+pub(in crate::command::release_impl) fn render(
+    crates: &[Dependency<'_>],
+    format: PlanFormat,
+    tag_name: impl Fn(&Package, &semver::Version) -> Option<String>,
+) -> anyhow::Result<String> {
+    let crates: Vec<_> = crates
+        .iter()
+        .map(|dep| {
+            let (publish, skip_reason) = match &dep.mode {
+                dependency::Mode::ToBePublished { .. } => (true, None),
+                dependency::Mode::NotForPublishing { reason, .. } => (false, Some(reason.to_string())),
+            };
+            let next_version = dep.mode.version_adjustment_bump().map(|bump| &bump.next_release);
+            let breaking_dependencies = match &dep.mode {
+                dependency::Mode::ToBePublished {
+                    adjustment:
+                        dependency::VersionAdjustment::Breakage {
+                            causing_dependency_names,
+                            ..
+                        },
+                }
+                | dependency::Mode::NotForPublishing {
+                    adjustment:
+                        Some(dependency::ManifestAdjustment::Version(dependency::VersionAdjustment::Breakage {
+                            causing_dependency_names,
+                            ..
+                        })),
+                    ..
+                } => causing_dependency_names.as_slice(),
+                _ => &[],
+            };
+            Crate {
+                name: dep.package.name.as_str(),
+                selected: matches!(dep.kind, dependency::Kind::UserSelection),
+                current_version: &dep.package.version,
+                next_version,
+                publish,
+                skip_reason,
+                breaking_dependencies,
+                manifest_changes: dep.mode.manifest_will_change(),
+                tag: next_version
+                    .filter(|_| publish)
+                    .and_then(|version| tag_name(dep.package, version)),
+            }
+        })
+        .collect();
+    let plan = Plan {
+        publish_order: crates.iter().filter(|c| c.publish).map(|c| c.name).collect(),
+        tags: crates.iter().filter_map(|c| c.tag.clone()).collect(),
+        crates,
+    };
+    Ok(match format {
+        PlanFormat::Json => serde_json::to_string_pretty(&plan)? + "\n",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::render;
+    use crate::{
+        command::release::PlanFormat,
+        traverse::{dependency, Dependency},
+        version::Bump,
+    };
+
+    fn package(name: &str) -> cargo_metadata::Package {
+        crate::utils::tests::package(name, &[], json!(null))
+    }
+
+    fn bump(package: &cargo_metadata::Package, next: semver::Version) -> Bump {
+        Bump {
+            next_release: next.clone(),
+            package_version: package.version.clone(),
+            latest_release: None,
+            desired_release: next,
+        }
+    }
+
+    #[test]
+    fn the_plan_lists_versions_publish_order_and_tags() {
+        let (a, b, c) = (package("a"), package("b"), package("c"));
+        let crates = [
+            Dependency {
+                package: &a,
+                kind: dependency::Kind::UserSelection,
+                mode: dependency::Mode::ToBePublished {
+                    adjustment: dependency::VersionAdjustment::Changed {
+                        change: None,
+                        bump: bump(&a, semver::Version::new(0, 2, 0)),
+                    },
+                },
+            },
+            Dependency {
+                package: &b,
+                kind: dependency::Kind::DependencyOrDependentOfUserSelection,
+                mode: dependency::Mode::NotForPublishing {
+                    reason: dependency::NoPublishReason::Unchanged,
+                    adjustment: None,
+                },
+            },
+            Dependency {
+                package: &c,
+                kind: dependency::Kind::DependencyOrDependentOfUserSelection,
+                mode: dependency::Mode::ToBePublished {
+                    adjustment: dependency::VersionAdjustment::Breakage {
+                        bump: bump(&c, semver::Version::new(0, 2, 0)),
+                        change: None,
+                        causing_dependency_names: vec!["a".into()],
+                    },
+                },
+            },
+        ];
+        let plan = render(&crates, PlanFormat::Json, |package, version| {
+            Some(format!("{}-v{version}", package.name))
+        })
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&plan).unwrap(),
+            json!({
+                "crates": [
+                    {
+                        "name": "a",
+                        "selected": true,
+                        "current_version": "0.1.0",
+                        "next_version": "0.2.0",
+                        "publish": true,
+                        "skip_reason": null,
+                        "breaking_dependencies": [],
+                        "manifest_changes": true,
+                        "tag": "a-v0.2.0"
+                    },
+                    {
+                        "name": "b",
+                        "selected": false,
+                        "current_version": "0.1.0",
+                        "next_version": null,
+                        "publish": false,
+                        "skip_reason": "unchanged",
+                        "breaking_dependencies": [],
+                        "manifest_changes": false,
+                        "tag": null
+                    },
+                    {
+                        "name": "c",
+                        "selected": false,
+                        "current_version": "0.1.0",
+                        "next_version": "0.2.0",
+                        "publish": true,
+                        "skip_reason": null,
+                        "breaking_dependencies": ["a"],
+                        "manifest_changes": true,
+                        "tag": "c-v0.2.0"
+                    }
+                ],
+                "publish_order": ["a", "c"],
+                "tags": ["a-v0.2.0", "c-v0.2.0"]
+            })
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Check that the branch the release commit is pushed to accepts direct pushes, before anything is published.
+use std::process::Command;
+
+use anyhow::bail;
+
+use super::Options;
+use crate::{changelog::write::RepositoryUrl, utils::Program};
+
+/// The types of GitHub repository rules which reject pushing a new commit directly to a branch.
+const RULES_REJECTING_PUSHES: &[&str] = &["pull_request", "required_status_checks", "update"];
+
+/// Fail if the branch the release commit would be pushed to is protected, so pushing it would be rejected only after
+/// the crates were published, and suggest to release through a release branch instead.
+///
+/// Branches are protected if listed in `protected-branches` of the workspace configuration. Without that configuration
+/// and with a GitHub remote, GitHub is asked with `gh`, as far as the protection is visible to the user. That never
+/// happens with `--offline`, and dry runs only say what they would ask.
+pub(in crate::command::release_impl) fn verify(
+    ctx: &crate::Context,
+    Options {
+        dry_run,
+        offline,
+        skip_push,
+        skip_preflight,
+        release_branch,
+        branch,
+        remote,
+        ..
+    }: Options,
+) -> anyhow::Result<()> {
+    if offline || skip_push || skip_preflight || release_branch.is_some() {
+        return Ok(());
+    }
+    let branch = match branch {
+        Some(branch) => branch,
+        None => match ctx.repo.head_name()? {
+            Some(name) => name.shorten().to_string(),
+            None => return Ok(()),
+        },
+    };
+    let configured = ctx
+        .meta
+        .workspace_metadata
+        .get("smart-release")
+        .and_then(|config| config.get("protected-branches"))
+        .and_then(serde_json::Value::as_array);
+    let reason = match configured {
+        Some(protected) => protected
+            .iter()
+            .any(|name| name.as_str() == Some(branch.as_str()))
+            .then(|| "it's listed in 'protected-branches' of the workspace configuration".to_owned()),
+        None => protected_on_github(ctx, &branch, remote.as_deref(), dry_run)?,
+    };
+    let Some(reason) = reason else {
+        return Ok(());
+    };
+    let message = format!(
+        "Pushing the release commit to '{branch}' would likely be rejected after publishing as {reason}. \
+         Release through a pull request with --open-pull-request, or push to a new branch with --release-branch. \
+         If direct pushes are allowed for you, set 'protected-branches = []' in the workspace configuration."
+    );
+    if dry_run {
+        tracing::warn!("{message}");
+        return Ok(());
+    }
+    bail!(message)
+}
+
+/// Return why `branch` on the GitHub repository of `remote` rejects direct pushes, or `None` if it doesn't seem to or
+/// if that can't be determined. In `dry_run` mode, GitHub isn't asked.
+fn protected_on_github(
+    ctx: &crate::Context,
+    branch: &str,
+    remote: Option<&str>,
+    dry_run: bool,
+) -> anyhow::Result<Option<String>> {
+    let Some(slug) = crate::git::remote_url(&ctx.repo, remote)?
+        .and_then(|url| RepositoryUrl::from(url).github_https())
+        .and_then(|url| url.strip_prefix("https://github.com/").map(ToOwned::to_owned))
+    else {
+        return Ok(None);
+    };
+    if dry_run {
+        tracing::info!("WOULD ask GitHub with 'gh' if '{branch}' of '{slug}' accepts direct pushes");
+        return Ok(None);
+    }
+    if !Program::named("gh").found {
+        tracing::debug!("Not checking if '{branch}' is protected as the 'gh' program isn't installed");
+        return Ok(None);
+    }
+    let (Some(branch_info), Some(rules)) = (
+        gh_api(&format!("repos/{slug}/branches/{branch}"))?,
+        gh_api(&format!("repos/{slug}/rules/branches/{branch}"))?,
+    ) else {
+        return Ok(None);
+    };
+    Ok(rejection_reason(&branch_info, &rules))
+}
+
+fn gh_api(endpoint: &str) -> anyhow::Result<Option<serde_json::Value>> {
+    let mut cmd = Command::new("gh");
+    cmd.args(["api", endpoint]);
+    tracing::trace!("run {:?}", cmd);
+    let out = cmd.output()?;
+    if !out.status.success() {
+        tracing::debug!("Could not query '{endpoint}' with 'gh', assuming the branch isn't protected");
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_slice(&out.stdout)?))
+}
+
+/// Return why a branch described by `branch_info` and the repository `rules` that apply to it rejects direct pushes.
+fn rejection_reason(branch_info: &serde_json::Value, rules: &serde_json::Value) -> Option<String> {
+    if let Some(rule) = rules
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|rule| rule["type"].as_str())
+        .find(|rule| RULES_REJECTING_PUSHES.contains(rule))
+    {
+        return Some(format!("a repository rule requires '{rule}'"));
+    }
+    branch_info["protection"]["required_status_checks"]["enforcement_level"]
+        .as_str()
+        .filter(|level| *level != "off")
+        .map(|_| "its branch protection requires status checks to pass".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::rejection_reason;
+
+    #[test]
+    fn rules_and_required_status_checks_reject_pushes() {
+        let unprotected =
+            json!({"protected": false, "protection": {"required_status_checks": {"enforcement_level": "off"}}});
+        assert_eq!(rejection_reason(&unprotected, &json!([])), None);
+        assert_eq!(
+            rejection_reason(
+                &unprotected,
+                &json!([{"type": "deletion"}, {"type": "non_fast_forward"}])
+            ),
+            None,
+            "rules that only prevent rewriting the branch allow pushing new commits"
+        );
+        assert_eq!(
+            rejection_reason(&unprotected, &json!([{"type": "deletion"}, {"type": "pull_request"}])).as_deref(),
+            Some("a repository rule requires 'pull_request'")
+        );
+        assert_eq!(
+            rejection_reason(
+                &json!({"protected": true, "protection": {"required_status_checks": {"enforcement_level": "everyone"}}}),
+                &json!([])
+            )
+            .as_deref(),
+            Some("its branch protection requires status checks to pass")
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Sign release commits and tags like `git commit -S` and `git tag -s` would, as objects are created without `git`.
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context};
+use gix::bstr::{BString, ByteSlice};
+
+/// A program and key to create detached signatures with.
+pub(in crate::command::release_impl) struct Signer {
+    format: Format,
+    program: String,
+    key: String,
+}
+
+/// The kind of signatures to create, as configured with `gpg.format`.
+enum Format {
+    /// Signatures made by `gpg`, or `gpgsm` for `x509`, which use the same interface.
+    Gpg,
+    /// Signatures made by `ssh-keygen`.
+    Ssh {
+        /// The file listing the keys trusted for each principal, to verify signatures right after creating them.
+        allowed_signers: Option<PathBuf>,
+        /// The identity to verify signatures for, the committer email.
+        principal: String,
+    },
+}
+
+impl Signer {
+    /// Return the signer configured in `repo` if `force` is set or signing is enabled by `config_key`, like
+    /// `commit.gpgSign` or `tag.gpgSign`.
+    ///
+    /// The key is `user.signingKey`, or for GPG the committer identity like `git` would use.
+    pub fn from_config(repo: &gix::Repository, force: bool, config_key: &str) -> anyhow::Result<Option<Self>> {
+        let config = repo.config_snapshot();
+        if !force && !config.boolean(config_key).unwrap_or(false) {
+            return Ok(None);
+        }
+        let format_name = config
+            .string("gpg.format")
+            .map_or_else(|| "openpgp".to_owned(), |format| format.to_str_lossy().into_owned());
+        let default_program = match format_name.as_str() {
+            "openpgp" => "gpg",
+            "x509" => "gpgsm",
+            "ssh" => "ssh-keygen",
+            unknown => bail!("Signing with 'gpg.format = {unknown}' isn't supported, use 'openpgp', 'x509' or 'ssh'"),
+        };
+        let program = config
+            .string(format!("gpg.{format_name}.program").as_str())
+            .or_else(|| {
+                (format_name == "openpgp")
+                    .then(|| config.string("gpg.program"))
+                    .flatten()
+            })
+            .map_or_else(
+                || default_program.to_owned(),
+                |program| program.to_str_lossy().into_owned(),
+            );
+        let signing_key = config
+            .string("user.signingKey")
+            .map(|key| key.to_str_lossy().into_owned());
+        let committer = repo.committer().transpose()?;
+
+        let (format, key) = if format_name == "ssh" {
+            let key = signing_key.context("'user.signingKey' must be set to a key or its path to sign with SSH")?;
+            let principal = committer
+                .context("A committer must be configured to sign with SSH")?
+                .email
+                .to_string();
+            let allowed_signers = config
+                .string("gpg.ssh.allowedSignersFile")
+                .map(|path| expand_home(&path.to_str_lossy()));
+            (
+                Format::Ssh {
+                    allowed_signers,
+                    principal,
+                },
+                key,
+            )
+        } else {
+            let key = match signing_key {
+                Some(key) => key,
+                None => {
+                    let committer = committer.context("A committer or 'user.signingKey' must be configured to sign")?;
+                    format!("{} <{}>", committer.name, committer.email)
+                }
+            };
+            (Format::Gpg, key)
+        };
+        Ok(Some(Signer { format, program, key }))
+    }
+
+    /// The key used for signing.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Return the ASCII-armored detached signature of `data`.
+    pub fn sign(&self, data: &[u8]) -> anyhow::Result<BString> {
+        match &self.format {
+            Format::Gpg => self.sign_with_gpg(data),
+            Format::Ssh {
+                allowed_signers,
+                principal,
+            } => {
+                let files = TempFiles::new();
+                let signature = self.sign_with_ssh(data, &files)?;
+                match allowed_signers {
+                    Some(allowed_signers) => self.verify_with_ssh(data, &files, allowed_signers, principal)?,
+                    None => tracing::debug!(
+                        "Not verifying the SSH signature as 'gpg.ssh.allowedSignersFile' isn't set, so 'git' can't verify it either"
+                    ),
+                }
+                Ok(signature)
+            }
+        }
+    }
+
+    fn sign_with_gpg(&self, data: &[u8]) -> anyhow::Result<BString> {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(["--status-fd=2", "-bsau", &self.key])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        tracing::trace!("run {:?}", cmd);
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("Could not run '{}' to sign", self.program))?;
+        child.stdin.take().expect("configured").write_all(data)?;
+        let out = child.wait_with_output()?;
+        if !out.status.success() || !out.stderr.contains_str("[GNUPG:] SIG_CREATED ") {
+            bail!(
+                "Failed to sign with key '{}': {}",
+                self.key,
+                out.stderr.to_str_lossy().trim()
+            );
+        }
+        Ok(out.stdout.into())
+    }
+
+    /// Sign like `git` does, by letting `ssh-keygen` sign a file with the data.
+    fn sign_with_ssh(&self, data: &[u8], files: &TempFiles) -> anyhow::Result<BString> {
+        std::fs::write(&files.data, data)?;
+        let mut cmd = Command::new(&self.program);
+        cmd.args(["-Y", "sign", "-n", "git", "-f"]);
+        match literal_ssh_key(&self.key) {
+            Some(public_key) => {
+                // The private key is expected to be provided by the SSH agent.
+                std::fs::write(&files.public_key, format!("{public_key}\n"))?;
+                cmd.arg(&files.public_key).arg("-U");
+            }
+            None => {
+                cmd.arg(expand_home(&self.key));
+            }
+        }
+        cmd.arg(&files.data).stdout(Stdio::null()).stderr(Stdio::piped());
+        tracing::trace!("run {:?}", cmd);
+        let out = cmd
+            .output()
+            .with_context(|| format!("Could not run '{}' to sign", self.program))?;
+        if !out.status.success() {
+            bail!(
+                "Failed to sign with SSH key '{}': {}",
+                self.key,
+                out.stderr.to_str_lossy().trim()
+            );
+        }
+        Ok(std::fs::read(files.signature())?.into())
+    }
+
+    /// Verify the signature `ssh-keygen` just made like `git verify-commit` would, to know it's trusted before publishing.
+    fn verify_with_ssh(
+        &self,
+        data: &[u8],
+        files: &TempFiles,
+        allowed_signers: &Path,
+        principal: &str,
+    ) -> anyhow::Result<()> {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(["-Y", "verify", "-n", "git", "-f"])
+            .arg(allowed_signers)
+            .arg("-I")
+            .arg(principal)
+            .arg("-s")
+            .arg(files.signature())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        tracing::trace!("run {:?}", cmd);
+        let mut child = cmd.spawn()?;
+        child.stdin.take().expect("configured").write_all(data)?;
+        let out = child.wait_with_output()?;
+        if !out.status.success() {
+            bail!(
+                "The SSH signature made with '{}' can't be verified for '{principal}' with the allowed signers in '{}': {}",
+                self.key,
+                allowed_signers.display(),
+                out.stderr.to_str_lossy().trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Return the public key if `key` is given literally as `user.signingKey` supports it, instead of as path to a key file.
+fn literal_ssh_key(key: &str) -> Option<&str> {
+    key.strip_prefix("key::")
+        .or_else(|| key.starts_with("ssh-").then_some(key))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// The files to pass data and keys to `ssh-keygen` with, which are removed when dropped.
+struct TempFiles {
+    data: PathBuf,
+    public_key: PathBuf,
+}
+
+impl TempFiles {
+    fn new() -> Self {
+        static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let base = std::env::temp_dir().join(format!(
+            "smart-release-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        TempFiles {
+            data: base.with_extension("data"),
+            public_key: base.with_extension("pub"),
+        }
+    }
+
+    /// The file `ssh-keygen` writes the signature of `data` to.
+    fn signature(&self) -> PathBuf {
+        self.data.with_extension("data.sig")
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for path in [&self.data, &self.public_key, &self.signature()] {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
+
+/// Write `commit` signed by `signer` to the object database of `repo`.
+pub(in crate::command::release_impl) fn write_signed_commit<'repo>(
+    repo: &'repo gix::Repository,
+    mut commit: gix::objs::Commit,
+    signer: &Signer,
+) -> anyhow::Result<gix::Id<'repo>> {
+    let mut data = Vec::new();
+    gix::objs::WriteTo::write_to(&commit, &mut data)?;
+    let signature = signer.sign(&data)?;
+    commit
+        .extra_headers
+        .push(("gpgsig".into(), signature.trim_end().as_bstr().to_owned()));
+    Ok(repo.write_object(&commit)?)
+}
+
+/// Write `tag` signed by `signer` to the object database of `repo`.
+pub(in crate::command::release_impl) fn write_signed_tag<'repo>(
+    repo: &'repo gix::Repository,
+    mut tag: gix::objs::Tag,
+    signer: &Signer,
+) -> anyhow::Result<gix::Id<'repo>> {
+    // The signature directly follows the message, but is written on a new line.
+    if !tag.message.ends_with(b"\n") {
+        tag.message.push(b'\n');
+    }
+    let mut data = Vec::new();
+    gix::objs::WriteTo::write_to(&tag, &mut data)?;
+    tag.pgp_signature = Some(signer.sign(&data)?);
+    tag.message.pop();
+    Ok(repo.write_object(&tag)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::literal_ssh_key;
+
+    #[test]
+    fn ssh_keys_can_be_given_literally_or_by_path() {
+        assert_eq!(
+            literal_ssh_key("key::ssh-ed25519 AAAAC3Nza user@host"),
+            Some("ssh-ed25519 AAAAC3Nza user@host")
+        );
+        assert_eq!(
+            literal_ssh_key("ssh-ed25519 AAAAC3Nza user@host"),
+            Some("ssh-ed25519 AAAAC3Nza user@host")
+        );
+        assert_eq!(literal_ssh_key("~/.ssh/id_ed25519.pub"), None);
+    }
+}
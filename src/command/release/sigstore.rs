@@ -0,0 +1,78 @@
+//! Keyless signatures of released crates and tags, made with `cosign` so consumers can verify where a release came from.
+use std::process::Command;
+
+use anyhow::bail;
+use cargo_metadata::{camino::Utf8PathBuf, Package};
+
+use crate::utils::will;
+
+/// Sign the `.crate` file that was published for `package` at `version`, and the tag named `tag_name` if there is one.
+///
+/// Return the paths to the Sigstore bundles, which contain the signature along with the certificate and the
+/// transparency log entry needed to verify it with `cosign verify-blob --bundle`.
+pub(in crate::command::release_impl) fn sign(
+    package: &Package,
+    version: &semver::Version,
+    tag_name: Option<&str>,
+    ctx: &crate::Context,
+    dry_run: bool,
+) -> anyhow::Result<Vec<Utf8PathBuf>> {
+    let mut bundles = vec![sign_blob(
+        super::cargo::crate_file(&ctx.meta.target_directory, &package.name, version),
+        &format!("'{} v{version}'", package.name),
+        dry_run,
+    )?];
+    if let Some(tag_name) = tag_name {
+        let statement = ctx.meta.target_directory.join("smart-release").join(format!(
+            "{}.tag",
+            tag_name.trim_start_matches("refs/tags/").replace('/', "-")
+        ));
+        tracing::trace!(
+            "{} write tag statement for '{tag_name}' to '{statement}'",
+            will(dry_run)
+        );
+        if !dry_run {
+            let id = ctx.repo.find_reference(tag_name)?.id().detach();
+            std::fs::create_dir_all(statement.parent().expect("statement is in a directory"))?;
+            std::fs::write(&statement, tag_statement(tag_name, &id))?;
+        }
+        bundles.push(sign_blob(statement, &format!("tag '{tag_name}'"), dry_run)?);
+    }
+    Ok(bundles)
+}
+
+fn bundle_path(file: &Utf8PathBuf) -> Utf8PathBuf {
+    format!("{file}.sigstore.json").into()
+}
+
+/// The content that is signed to vouch for a tag, which ties its name to the object it points to.
+fn tag_statement(tag_name: &str, id: &gix::ObjectId) -> String {
+    format!("object {id}\ntag {tag_name}\n")
+}
+
+fn sign_blob(file: Utf8PathBuf, what: &str, dry_run: bool) -> anyhow::Result<Utf8PathBuf> {
+    let bundle = bundle_path(&file);
+    let mut cmd = Command::new("cosign");
+    cmd.args(["sign-blob", "--yes", "--bundle"])
+        .arg(bundle.as_std_path())
+        .arg(file.as_std_path());
+    tracing::trace!("{} run {:?}", will(dry_run), cmd);
+    if !dry_run && !cmd.status()?.success() {
+        bail!("Failed to sign {what} with 'cosign'");
+    }
+    Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tag_statement;
+
+    #[test]
+    fn tag_statements_name_the_tag_and_its_object() {
+        let id = gix::ObjectId::from_hex(b"0123456789012345678901234567890123456789").unwrap();
+        assert_eq!(
+            tag_statement("refs/tags/a-v1.0.0", &id),
+            "object 0123456789012345678901234567890123456789\ntag refs/tags/a-v1.0.0\n"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! A machine-readable summary of what a release did, for automation like announcements or deployments to act on.
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use super::journal::Journal;
+
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    /// If `false`, the release stopped early and can be resumed.
+    complete: bool,
+    /// The id of the release commit, if one was made.
+    commit: Option<&'a str>,
+    crates: Vec<Crate<'a>>,
+    tags: Vec<Tag<'a>>,
+    pushed: bool,
+    /// The URL of the pull request opened for the release branch.
+    pull_request: Option<&'a str>,
+    /// The steps that weren't performed for any crate, like `publish` with --no-publish.
+    skipped: &'a [&'a str],
+}
+
+#[derive(Debug, Serialize)]
+struct Crate<'a> {
+    name: &'a str,
+    version: &'a semver::Version,
+    published: bool,
+    github_release: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Tag<'a> {
+    #[serde(rename = "crate")]
+    crate_name: &'a str,
+    /// The full name of the tag.
+    name: &'a str,
+    /// The id of the tag object, or of the commit for tags that aren't annotated.
+    id: Option<String>,
+}
+
+/// Write a summary of the release recorded in `journal` as JSON to `path`, with `skipped` steps and whether it is
+/// `complete`.
+///
+/// Crates named in `changelog_only` are reported as not published, just like all crates if publishing was skipped.
+pub(in crate::command::release_impl) fn write(
+    path: &Path,
+    journal: &Journal,
+    complete: bool,
+    skipped: &[&str],
+    changelog_only: &[String],
+    repo: &gix::Repository,
+) -> anyhow::Result<()> {
+    let tags = journal
+        .tags
+        .iter()
+        .map(|(crate_name, name)| {
+            let id = repo
+                .try_find_reference(name.as_str())?
+                .and_then(|tag| tag.inner.target.try_id().map(|id| id.to_hex().to_string()));
+            Ok(Tag { crate_name, name, id })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    let summary = Summary {
+        complete,
+        commit: journal.commit.as_deref(),
+        crates: journal
+            .crates
+            .iter()
+            .map(|release| Crate {
+                name: &release.name,
+                version: &release.version,
+                published: journal.published.contains(&release.name)
+                    && !skipped.contains(&"publish")
+                    && !changelog_only.contains(&release.name),
+                github_release: journal.github_releases.contains(&release.name),
+            })
+            .collect(),
+        tags,
+        pushed: journal.pushed && !skipped.contains(&"push"),
+        pull_request: journal.pull_request.as_deref(),
+        skipped,
+    };
+    std::fs::write(path, serde_json::to_vec_pretty(&summary)?)
+        .with_context(|| format!("Could not write the release summary to '{}'", path.display()))?;
+    tracing::info!("Wrote a summary of the release to '{}'", path.display());
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Revert the local effects of a release that didn't finish, as recorded in its journal.
+use std::process::Command;
+
+use anyhow::{bail, Context};
+
+use super::{git, journal::Journal};
+use crate::{command::undo::Options, utils::will};
+
+/// Delete the tags created by the unfinished release and reset the release commit, restoring the manifests and
+/// changelogs as they were before the release, then forget about the release.
+///
+/// Nothing is undone if the release was pushed or a crate was published, as that can't be undone locally. With
+/// `skip_publish`, crates are recorded as published without being published, so they don't prevent undoing it.
+pub fn undo(Options { dry_run, skip_publish }: Options) -> anyhow::Result<()> {
+    let repo = gix::discover(".")?;
+    let Some(journal) = Journal::load(&repo)? else {
+        bail!("There is no unfinished release to undo")
+    };
+    if journal.pushed {
+        bail!("The release was pushed already, so it can't be undone locally. Run again with --resume to finish it.")
+    }
+    if !skip_publish && !journal.published.is_empty() {
+        bail!(
+            "{} {} published already, which can't be undone. Run again with --resume to finish the release instead.",
+            journal
+                .published
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if journal.published.len() == 1 { "was" } else { "were" }
+        )
+    }
+    if let Some(url) = &journal.pull_request {
+        tracing::warn!("The pull request {url} of the release stays open, close it to abandon the release");
+    }
+    let commit = journal
+        .commit
+        .as_deref()
+        .map(|id| gix::ObjectId::from_hex(id.as_bytes()))
+        .transpose()?;
+
+    let mut tags = Vec::new();
+    for tag_name in journal.tags.values() {
+        let Some(mut tag) = repo.try_find_reference(tag_name.as_str())? else {
+            tracing::info!("The tag {tag_name} was deleted already");
+            continue;
+        };
+        let target = tag.inner.target.try_id().map(ToOwned::to_owned);
+        let peeled = tag.peel_to_id()?.detach();
+        match target {
+            Some(target) if commit.is_none_or(|commit| commit == peeled) => {
+                tracing::info!("{} delete the tag {tag_name}", will(dry_run));
+                tags.push((tag.inner.name, target));
+            }
+            _ => tracing::warn!("Keeping the tag {tag_name} as it doesn't point to the release commit anymore"),
+        }
+    }
+    if !dry_run {
+        git::delete_tags(&repo, &tags)?;
+    }
+
+    if let Some(commit) = commit {
+        let head = repo.head_id()?.detach();
+        if head != commit {
+            bail!("HEAD is at {head} instead of the release commit {commit}, so it can't be reset. Reset it manually, then run again to undo the rest.")
+        }
+        let parent = repo
+            .find_commit(commit)?
+            .parent_ids()
+            .next()
+            .with_context(|| format!("The release commit {commit} has no parent to reset to"))?
+            .detach();
+        // Unlike --hard, --keep fails instead of discarding changes to files the release commit didn't touch.
+        let mut cmd = Command::new(gix::path::env::exe_invocation());
+        cmd.args(["reset", "--keep"]).arg(parent.to_string());
+        tracing::info!(
+            "{} reset the release commit {commit} to {parent}, restoring the manifests and changelogs",
+            will(dry_run)
+        );
+        tracing::trace!("{} run {:?}", will(dry_run), cmd);
+        if !dry_run && !cmd.status()?.success() {
+            bail!("Could not reset the release commit {commit}, possibly as changed files would be overwritten. Commit or stash them and try again.")
+        }
+    }
+
+    tracing::info!(
+        "{} forget about the release of {}",
+        will(dry_run),
+        journal
+            .crates
+            .iter()
+            .map(|c| format!("{} v{}", c.name, c.version))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if !dry_run {
+        Journal::remove(&repo)?;
+    }
+    Ok(())
+}
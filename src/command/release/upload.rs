@@ -0,0 +1,699 @@
+//! Upload packaged crates through the web API of their registry like `cargo publish` does after packaging them, so why
+//! the registry refuses a crate is known from its response instead of the messages of `cargo`.
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+use cargo_metadata::{DependencyKind, Package};
+use serde::Serialize;
+
+use super::{artifacts::sha256_hex, mirrors};
+
+/// The web API of crates.io.
+const CRATES_IO_API: &str = "https://crates.io";
+/// The index of crates.io as dependencies and credential providers refer to it.
+const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+
+/// Why the registry refused to publish a crate, as told by its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(in crate::command::release_impl) struct Rejection {
+    /// The HTTP status of the response.
+    pub status: u32,
+    /// The value of the `Retry-After` header, which is either a number of seconds or a date.
+    pub retry_after: Option<String>,
+    /// The details of the errors in the response.
+    pub errors: Vec<String>,
+}
+
+/// A crate that is ready to be uploaded, along with the token to do so.
+pub(in crate::command::release_impl) struct Upload {
+    url: String,
+    token: String,
+    body: Vec<u8>,
+}
+
+/// Prepare uploading the `.crate` file of `publishee` at `version` to `registry`, or to crates.io if `None`.
+///
+/// The metadata sent along is read from the manifest as it is now, with the dependency requirements changed for the
+/// release. The token is obtained like cargo does, through `credential_provider` if set.
+pub(in crate::command::release_impl) fn prepare(
+    publishee: &Package,
+    version: &semver::Version,
+    crate_file: &Path,
+    registry: Option<&str>,
+    credential_provider: Option<&str>,
+    workspace_root: &Path,
+) -> anyhow::Result<Upload> {
+    let index_url = match registry {
+        Some(name) => mirrors::index_url(name, workspace_root)?
+            .with_context(|| format!("Couldn't find the index of the registry '{name}' in the cargo configuration"))?,
+        None => CRATES_IO_INDEX.to_owned(),
+    };
+    let api = match registry {
+        Some(name) => api_url(&index_url)?
+            .with_context(|| format!("The registry '{name}' doesn't allow publishing as its index has no API"))?,
+        None => CRATES_IO_API.to_owned(),
+    };
+    let tarball = std::fs::read(crate_file).with_context(|| format!("Could not read '{}'", crate_file.display()))?;
+    let request = CredentialRequest {
+        v: 1,
+        registry: RegistryInfo {
+            index_url: &index_url,
+            name: registry,
+        },
+        kind: "get",
+        operation: "publish",
+        name: &publishee.name,
+        vers: version.to_string(),
+        cksum: sha256_hex(tarball.as_slice())?,
+        args: Vec::new(),
+    };
+    let token = token(registry, &request, credential_provider, workspace_root)?;
+
+    let manifest = cargo_metadata::MetadataCommand::new()
+        .manifest_path(&publishee.manifest_path)
+        .no_deps()
+        .exec()?;
+    let package = manifest
+        .packages
+        .iter()
+        .find(|package| package.name == publishee.name)
+        .with_context(|| format!("Couldn't find '{}' in its own manifest", publishee.name))?;
+    let json = serde_json::to_vec(&new_crate(package, version, &index_url)?)?;
+
+    let mut body = Vec::with_capacity(json.len() + tarball.len() + 8);
+    body.extend_from_slice(&u32::try_from(json.len())?.to_le_bytes());
+    body.extend_from_slice(&json);
+    body.extend_from_slice(&u32::try_from(tarball.len())?.to_le_bytes());
+    body.extend_from_slice(&tarball);
+    Ok(Upload {
+        url: format!("{}/api/v1/crates/new", api.trim_end_matches('/')),
+        token,
+        body,
+    })
+}
+
+impl Upload {
+    /// Upload the crate, and return why the registry refused it if it did.
+    ///
+    /// Warnings of the registry about the metadata are logged.
+    pub(in crate::command::release_impl) fn send(
+        &self,
+        timeout: Option<Duration>,
+    ) -> anyhow::Result<Result<(), Rejection>> {
+        let mut handle = curl::easy::Easy::new();
+        handle.url(&self.url)?;
+        handle.useragent(concat!("cargo-smart-release/", env!("CARGO_PKG_VERSION")))?;
+        handle.custom_request("PUT")?;
+        handle.post_fields_copy(&self.body)?;
+        if let Some(timeout) = timeout {
+            handle.timeout(timeout)?;
+        }
+        let mut list = curl::easy::List::new();
+        list.append("Content-Type: application/octet-stream")?;
+        list.append("Accept: application/json")?;
+        list.append(&format!("Authorization: {}", self.token))?;
+        handle.http_headers(list)?;
+
+        let mut headers = Vec::new();
+        let mut body = Vec::new();
+        {
+            let mut transfer = handle.transfer();
+            transfer.header_function(|header| {
+                headers.push(String::from_utf8_lossy(header).trim().to_owned());
+                true
+            })?;
+            transfer.write_function(|data| {
+                body.extend_from_slice(data);
+                Ok(data.len())
+            })?;
+            transfer.perform()?;
+        }
+        let status = handle.response_code()?;
+        if (200..300).contains(&status) {
+            for warning in warnings(&body) {
+                tracing::warn!("The registry warned: {warning}");
+            }
+            return Ok(Ok(()));
+        }
+        Ok(Err(rejection(status, &headers, &body)))
+    }
+}
+
+/// Return the rejection described by the response with `status`, `headers` and `body`.
+fn rejection(status: u32, headers: &[String], body: &[u8]) -> Rejection {
+    let retry_after = headers.iter().find_map(|header| {
+        let (name, value) = header.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("retry-after")
+            .then(|| value.trim().to_owned())
+    });
+    let errors = serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .and_then(|body| {
+            body.get("errors")?.as_array().map(|errors| {
+                errors
+                    .iter()
+                    .filter_map(|error| error.get("detail")?.as_str().map(ToOwned::to_owned))
+                    .collect()
+            })
+        })
+        .unwrap_or_default();
+    Rejection {
+        status,
+        retry_after,
+        errors,
+    }
+}
+
+/// Return the warnings in the `body` of the response to a successful upload.
+fn warnings(body: &[u8]) -> Vec<String> {
+    let Ok(body) = serde_json::from_slice::<serde_json::Value>(body) else {
+        return Vec::new();
+    };
+    let Some(warnings) = body.get("warnings").and_then(serde_json::Value::as_object) else {
+        return Vec::new();
+    };
+    warnings
+        .values()
+        .filter_map(serde_json::Value::as_array)
+        .flatten()
+        .filter_map(|warning| warning.as_str().map(ToOwned::to_owned))
+        .collect()
+}
+
+/// Return the URL of the web API of the registry with the index at `index_url`, or `None` if it has none.
+fn api_url(index_url: &str) -> anyhow::Result<Option<String>> {
+    let config: serde_json::Value = match index_url.strip_prefix("sparse+") {
+        Some(url) => match crate::crates_index::http_get(&format!("{}/config.json", url.trim_end_matches('/')), &[])? {
+            (200, body) => serde_json::from_slice(&body)?,
+            (status, _) => bail!("Could not download the configuration of the index at '{url}' (status {status})"),
+        },
+        None => {
+            let index = crates_index::GitIndex::from_url(index_url.strip_prefix("registry+").unwrap_or(index_url))?;
+            return Ok(index.index_config()?.api);
+        }
+    };
+    Ok(config.get("api").and_then(|api| api.as_str()).map(ToOwned::to_owned))
+}
+
+/// The metadata of a crate to publish, as sent to the registry along with the `.crate` file.
+///
+/// See <https://doc.rust-lang.org/cargo/reference/registry-web-api.html#publish>.
+#[derive(Debug, Serialize)]
+struct NewCrate {
+    name: String,
+    vers: String,
+    deps: Vec<NewCrateDependency>,
+    features: BTreeMap<String, Vec<String>>,
+    authors: Vec<String>,
+    description: Option<String>,
+    documentation: Option<String>,
+    homepage: Option<String>,
+    readme: Option<String>,
+    readme_file: Option<String>,
+    keywords: Vec<String>,
+    categories: Vec<String>,
+    license: Option<String>,
+    license_file: Option<String>,
+    repository: Option<String>,
+    badges: BTreeMap<String, BTreeMap<String, String>>,
+    links: Option<String>,
+    rust_version: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct NewCrateDependency {
+    optional: bool,
+    default_features: bool,
+    name: String,
+    features: Vec<String>,
+    version_req: String,
+    target: Option<String>,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registry: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explicit_name_in_toml: Option<String>,
+}
+
+/// Return the metadata to publish `package` at `version` with to the registry with the index at `index_url`, like
+/// `cargo publish` sends it.
+fn new_crate(package: &Package, version: &semver::Version, index_url: &str) -> anyhow::Result<NewCrate> {
+    let manifest_dir = package.manifest_path.parent().context("manifests are in a directory")?;
+    let readme = package
+        .readme
+        .as_ref()
+        .map(|path| std::fs::read_to_string(manifest_dir.join(path)))
+        .transpose()
+        .context("Could not read the readme to publish along with the crate")?;
+    let deps = package
+        .dependencies
+        .iter()
+        // `cargo package` removes development dependencies that only have a path.
+        .filter(|dep| {
+            !(dep.kind == DependencyKind::Development && dep.path.is_some() && dep.req == semver::VersionReq::STAR)
+        })
+        .map(|dep| {
+            let registry = dep.registry.clone().unwrap_or_else(|| CRATES_IO_INDEX.to_owned());
+            NewCrateDependency {
+                optional: dep.optional,
+                default_features: dep.uses_default_features,
+                name: dep.name.clone(),
+                features: dep.features.clone(),
+                version_req: dep.req.to_string(),
+                target: dep.target.as_ref().map(ToString::to_string),
+                kind: match dep.kind {
+                    DependencyKind::Development => "dev",
+                    DependencyKind::Build => "build",
+                    _ => "normal",
+                },
+                registry: (registry.trim_start_matches("sparse+") != index_url.trim_start_matches("sparse+"))
+                    .then_some(registry),
+                explicit_name_in_toml: dep.rename.clone(),
+            }
+        })
+        .collect();
+    Ok(NewCrate {
+        name: package.name.to_string(),
+        vers: version.to_string(),
+        deps,
+        features: package.features.clone().into_iter().collect(),
+        authors: package.authors.clone(),
+        description: package.description.clone(),
+        documentation: package.documentation.clone(),
+        homepage: package.homepage.clone(),
+        readme,
+        readme_file: package.readme.as_ref().map(ToString::to_string),
+        keywords: package.keywords.clone(),
+        categories: package.categories.clone(),
+        license: package.license.clone(),
+        license_file: package.license_file.as_ref().map(ToString::to_string),
+        repository: package.repository.clone(),
+        badges: BTreeMap::new(),
+        links: package.links.clone(),
+        rust_version: package.rust_version.as_ref().map(ToString::to_string),
+    })
+}
+
+/// What credential providers are asked for a token, in the format of cargo's credential provider protocol.
+#[derive(Debug, Clone, Serialize)]
+struct CredentialRequest<'a> {
+    v: u32,
+    registry: RegistryInfo<'a>,
+    kind: &'static str,
+    operation: &'static str,
+    name: &'a str,
+    vers: String,
+    cksum: String,
+    args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RegistryInfo<'a> {
+    #[serde(rename = "index-url")]
+    index_url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+}
+
+/// Return the token to publish to `registry`, or crates.io if `None`, from the first of its credential providers that
+/// has one, trying `credential_provider` only if set.
+fn token(
+    registry: Option<&str>,
+    request: &CredentialRequest<'_>,
+    credential_provider: Option<&str>,
+    workspace_root: &Path,
+) -> anyhow::Result<String> {
+    let providers = match credential_provider {
+        Some(provider) => vec![provider.split_whitespace().map(ToOwned::to_owned).collect()],
+        None => credential_providers(registry, workspace_root)?,
+    };
+    for provider in &providers {
+        let Some((program, args)) = provider.split_first() else {
+            continue;
+        };
+        let token = match program.as_str() {
+            "cargo:token" => configured_token(registry, workspace_root)?,
+            "cargo:token-from-stdout" => Some(token_from_stdout(args, request)?),
+            builtin if builtin.starts_with("cargo:") => bail!(
+                "The credential provider '{builtin}' can't be used to publish, set the token with {} or use 'cargo:token-from-stdout <command>'",
+                token_env_var(registry)
+            ),
+            _ => token_from_provider(program, args, request)?,
+        };
+        if let Some(token) = token.filter(|token| !token.is_empty()) {
+            return Ok(token);
+        }
+    }
+    bail!(
+        "Couldn't find a token to publish '{}' with, log in with 'cargo login{}' or set {}",
+        request.name,
+        registry.map(|name| format!(" --registry {name}")).unwrap_or_default(),
+        token_env_var(registry)
+    )
+}
+
+/// Return the credential providers of `registry` as configured for cargo, with the one to try first coming first.
+fn credential_providers(registry: Option<&str>, workspace_root: &Path) -> anyhow::Result<Vec<Vec<String>>> {
+    if let Ok(provider) = std::env::var(super::cargo::credential_provider_env_var(registry)) {
+        return Ok(vec![provider.split_whitespace().map(ToOwned::to_owned).collect()]);
+    }
+    let mut global = None;
+    for config in configs(workspace_root)? {
+        let registry_table = match registry {
+            Some(name) => config.get("registries").and_then(|registries| registries.get(name)),
+            None => config.get("registry"),
+        };
+        if let Some(provider) = registry_table
+            .and_then(|table| table.get("credential-provider"))
+            .and_then(command_line)
+        {
+            return Ok(vec![provider]);
+        }
+        if global.is_none() {
+            global = config
+                .get("registry")
+                .and_then(|table| table.get("global-credential-providers"))
+                .and_then(|providers| providers.as_array())
+                .map(|providers| {
+                    let mut providers: Vec<_> = providers
+                        .iter()
+                        .filter_map(|provider| provider.as_str())
+                        .map(|provider| provider.split_whitespace().map(ToOwned::to_owned).collect())
+                        .collect();
+                    // Later providers take precedence.
+                    providers.reverse();
+                    providers
+                });
+        }
+    }
+    Ok(global.unwrap_or_else(|| vec![vec!["cargo:token".to_owned()]]))
+}
+
+/// Return the program and arguments in `value`, which is either a string with arguments separated by spaces, or an
+/// array of strings.
+fn command_line(value: &toml_edit::Item) -> Option<Vec<String>> {
+    match value.as_str() {
+        Some(line) => Some(line.split_whitespace().map(ToOwned::to_owned).collect()),
+        None => value.as_array().map(|args| {
+            args.iter()
+                .filter_map(|arg| arg.as_str().map(ToOwned::to_owned))
+                .collect()
+        }),
+    }
+}
+
+/// Return the name of the environment variable with the token for `registry`, or for crates.io if `None`.
+fn token_env_var(registry: Option<&str>) -> String {
+    match registry {
+        Some(name) => format!("CARGO_REGISTRIES_{}_TOKEN", name.to_ascii_uppercase().replace('-', "_")),
+        None => "CARGO_REGISTRY_TOKEN".into(),
+    }
+}
+
+/// Return the token of `registry` from the environment or the cargo configuration and credentials, like the
+/// `cargo:token` credential provider does.
+fn configured_token(registry: Option<&str>, workspace_root: &Path) -> anyhow::Result<Option<String>> {
+    if let Ok(token) = std::env::var(token_env_var(registry)) {
+        return Ok(Some(token));
+    }
+    for config in configs(workspace_root)? {
+        let registry_table = match registry {
+            Some(name) => config.get("registries").and_then(|registries| registries.get(name)),
+            None => config.get("registry"),
+        };
+        if let Some(token) = registry_table
+            .and_then(|table| table.get("token"))
+            .and_then(|token| token.as_str())
+        {
+            return Ok(Some(token.to_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Return the cargo configuration files that exist, along with the credentials in the cargo home, in the order of their
+/// precedence.
+fn configs(workspace_root: &Path) -> anyhow::Result<Vec<toml_edit::DocumentMut>> {
+    let cargo_home = home::cargo_home()?;
+    let mut configs = Vec::new();
+    for path in [cargo_home.join("credentials.toml"), cargo_home.join("credentials")]
+        .into_iter()
+        .chain(mirrors::config_files(workspace_root)?)
+    {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        configs.push(
+            content
+                .parse()
+                .with_context(|| format!("Could not parse cargo configuration at '{}'", path.display()))?,
+        );
+    }
+    Ok(configs)
+}
+
+/// Run the program and `args` of `cargo:token-from-stdout` and return the token it prints.
+fn token_from_stdout(args: &[String], request: &CredentialRequest<'_>) -> anyhow::Result<String> {
+    let (program, args) = args
+        .split_first()
+        .context("'cargo:token-from-stdout' needs the program to run")?;
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .env("CARGO_REGISTRY_INDEX_URL", request.registry.index_url)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    if let Some(name) = request.registry.name {
+        cmd.env("CARGO_REGISTRY_NAME_OPT", name);
+    }
+    let out = cmd
+        .output()
+        .with_context(|| format!("Could not run the credential provider '{program}'"))?;
+    if !out.status.success() {
+        bail!("The credential provider '{program}' failed");
+    }
+    Ok(String::from_utf8(out.stdout)?
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_owned())
+}
+
+/// Ask the credential provider `program` with `args` for a token, speaking cargo's credential provider protocol, and
+/// return `None` if it has none.
+fn token_from_provider(
+    program: &str,
+    args: &[String],
+    request: &CredentialRequest<'_>,
+) -> anyhow::Result<Option<String>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .arg("--cargo-plugin")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("Could not run the credential provider '{program}'"))?;
+    let mut stdin = child.stdin.take().expect("piped");
+    let mut stdout = BufReader::new(child.stdout.take().expect("piped"));
+    let mut hello = String::new();
+    stdout.read_line(&mut hello)?;
+    let hello: serde_json::Value = serde_json::from_str(&hello)
+        .with_context(|| format!("The credential provider '{program}' didn't say hello"))?;
+    if !hello["v"]
+        .as_array()
+        .is_some_and(|versions| versions.iter().any(|v| v.as_u64() == Some(1)))
+    {
+        bail!("The credential provider '{program}' doesn't support version 1 of the protocol");
+    }
+    let request = CredentialRequest {
+        args: args.to_vec(),
+        ..request.clone()
+    };
+    writeln!(stdin, "{}", serde_json::to_string(&request)?)?;
+    stdin.flush()?;
+    let mut response = String::new();
+    stdout.read_line(&mut response)?;
+    drop(stdin);
+    child.wait()?;
+    provider_token(program, &serde_json::from_str(&response)?)
+}
+
+/// Return the token in the `response` of the credential provider `program`, or `None` if it has none.
+fn provider_token(program: &str, response: &serde_json::Value) -> anyhow::Result<Option<String>> {
+    if let Some(token) = response.pointer("/Ok/token").and_then(serde_json::Value::as_str) {
+        return Ok(Some(token.to_owned()));
+    }
+    match response.pointer("/Err/kind").and_then(serde_json::Value::as_str) {
+        Some("not-found" | "url-not-supported" | "operation-not-supported") => Ok(None),
+        _ => bail!(
+            "The credential provider '{program}' failed: {}",
+            response
+                .pointer("/Err/message")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("it gave no reason")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{command_line, new_crate, provider_token, rejection, warnings, NewCrateDependency, Rejection};
+
+    #[test]
+    fn rejections_carry_the_status_the_retry_time_and_the_errors_of_the_registry() {
+        assert_eq!(
+            rejection(
+                429,
+                &[
+                    "HTTP/2 429".into(),
+                    "content-type: application/json".into(),
+                    "Retry-After: Mon, 01 Jan 2024 12:10:00 GMT".into()
+                ],
+                br#"{"errors":[{"detail":"You have published too many new crates in a short period of time."}]}"#
+            ),
+            Rejection {
+                status: 429,
+                retry_after: Some("Mon, 01 Jan 2024 12:10:00 GMT".into()),
+                errors: vec!["You have published too many new crates in a short period of time.".into()],
+            }
+        );
+        assert_eq!(
+            rejection(502, &[], b"<html>Bad Gateway</html>"),
+            Rejection {
+                status: 502,
+                retry_after: None,
+                errors: Vec::new(),
+            },
+            "responses that aren't from the registry itself have no details"
+        );
+    }
+
+    #[test]
+    fn warnings_of_successful_uploads_are_listed() {
+        assert_eq!(
+            warnings(br#"{"warnings":{"invalid_categories":["no-such-category"],"invalid_badges":[],"other":["be careful"]}}"#),
+            ["no-such-category", "be careful"]
+        );
+        assert!(warnings(b"{}").is_empty());
+        assert!(warnings(b"").is_empty());
+    }
+
+    #[test]
+    fn dependencies_are_sent_like_cargo_sends_them() {
+        let mut package = crate::utils::tests::package(
+            "a",
+            &[
+                json!({"name": "b", "req": "^1.0.1", "rename": "bee", "path": "/b"}),
+                json!({"name": "c", "req": "^2", "kind": "build", "optional": true, "uses_default_features": false,
+                       "features": ["x"], "target": "cfg(unix)", "registry": "sparse+https://example.com/index/"}),
+                json!({"name": "d", "req": "*", "kind": "dev", "path": "/d"}),
+            ],
+            json!(null),
+        );
+        package.features = [("default".into(), vec!["x".into()]), ("x".into(), vec![])].into();
+        let new_crate = new_crate(&package, &semver::Version::new(1, 1, 0), super::CRATES_IO_INDEX).unwrap();
+        assert_eq!(new_crate.vers, "1.1.0");
+        assert_eq!(
+            new_crate.deps,
+            [
+                NewCrateDependency {
+                    optional: false,
+                    default_features: true,
+                    name: "b".into(),
+                    features: vec![],
+                    version_req: "^1.0.1".into(),
+                    target: None,
+                    kind: "normal",
+                    registry: None,
+                    explicit_name_in_toml: Some("bee".into()),
+                },
+                NewCrateDependency {
+                    optional: true,
+                    default_features: false,
+                    name: "c".into(),
+                    features: vec!["x".into()],
+                    version_req: "^2".into(),
+                    target: Some("cfg(unix)".into()),
+                    kind: "build",
+                    registry: Some("sparse+https://example.com/index/".into()),
+                    explicit_name_in_toml: None,
+                },
+            ],
+            "development dependencies only with a path are removed"
+        );
+
+        let to_other_registry = super::new_crate(
+            &package,
+            &semver::Version::new(1, 1, 0),
+            "sparse+https://example.com/index/",
+        )
+        .unwrap();
+        assert_eq!(
+            to_other_registry
+                .deps
+                .iter()
+                .map(|dep| dep.registry.as_deref())
+                .collect::<Vec<_>>(),
+            [Some(super::CRATES_IO_INDEX), None],
+            "dependencies from crates.io need to say so if published elsewhere"
+        );
+    }
+
+    #[test]
+    fn credential_providers_are_configured_as_strings_or_arrays() {
+        let config: toml_edit::DocumentMut = r#"
+            a = "cargo:token-from-stdout pass show token"
+            b = ["my-provider", "--with space"]
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            command_line(&config["a"]),
+            Some(vec![
+                "cargo:token-from-stdout".into(),
+                "pass".into(),
+                "show".into(),
+                "token".into()
+            ])
+        );
+        assert_eq!(
+            command_line(&config["b"]),
+            Some(vec!["my-provider".into(), "--with space".into()])
+        );
+    }
+
+    #[test]
+    fn credential_providers_may_not_have_a_token() {
+        assert_eq!(
+            provider_token(
+                "p",
+                &json!({"Ok": {"kind": "get", "token": "secret", "cache": "session"}})
+            )
+            .unwrap(),
+            Some("secret".into())
+        );
+        assert_eq!(
+            provider_token("p", &json!({"Err": {"kind": "not-found"}})).unwrap(),
+            None,
+            "the next provider is asked"
+        );
+        assert_eq!(
+            provider_token("p", &json!({"Err": {"kind": "other", "message": "locked"}}))
+                .unwrap_err()
+                .to_string(),
+            "The credential provider 'p' failed: locked"
+        );
+    }
+}
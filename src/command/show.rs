@@ -0,0 +1,92 @@
+use anyhow::{bail, Context as _};
+
+use crate::{
+    changelog::{self, write::Linkables, Section},
+    command::show::Options,
+    git,
+    utils::{changelog_path, package_by_name},
+    ChangeLog,
+};
+
+/// Print the section of the changelog of the crate named `crate_name` for the release of `version`, or of its unreleased
+/// changes if `version` is `unreleased`.
+pub fn show(
+    Options {
+        no_links,
+        components,
+        locale,
+    }: Options,
+    crate_name: String,
+    version: String,
+) -> anyhow::Result<()> {
+    let version = to_changelog_version(&version)?;
+    let meta = cargo_metadata::MetadataCommand::new().no_deps().exec()?;
+    let package = package_by_name(&meta, &crate_name)?;
+    let path = changelog_path(package, &meta);
+    let markdown = std::fs::read_to_string(&path)
+        .with_context(|| format!("Could not read the changelog of '{crate_name}' at '{path}'"))?;
+    let log = ChangeLog::from_markdown(&markdown, &locale);
+    let Some(section) = log
+        .sections
+        .iter()
+        .find(|section| matches!(section, Section::Release { name, .. } if *name == version))
+    else {
+        bail!(
+            "The changelog at '{path}' has no section for {}",
+            match &version {
+                changelog::Version::Unreleased => "unreleased changes".into(),
+                changelog::Version::Semantic(version) => format!("v{version}"),
+            }
+        )
+    };
+
+    let linkables = if no_links {
+        Linkables::AsText
+    } else {
+        let repo = gix::discover(meta.workspace_root.as_std_path())?;
+        git::remote_url(&repo, None)?.map_or(Linkables::AsText, |url| Linkables::AsLinks {
+            repository_url: url.into(),
+        })
+    };
+    let mut notes = String::new();
+    section.write_to(
+        &mut notes,
+        &linkables,
+        components,
+        &changelog::write::Options {
+            locale,
+            ..Default::default()
+        },
+    )?;
+    print!("{notes}");
+    Ok(())
+}
+
+/// Parse `version` like `1.2.3` or `v1.2.3`, or `unreleased`.
+fn to_changelog_version(version: &str) -> anyhow::Result<changelog::Version> {
+    if version.eq_ignore_ascii_case("unreleased") {
+        return Ok(changelog::Version::Unreleased);
+    }
+    Ok(changelog::Version::Semantic(
+        version
+            .strip_prefix('v')
+            .unwrap_or(version)
+            .parse()
+            .with_context(|| format!("Invalid version {version:?}, use one like '1.2.3' or 'unreleased'"))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_changelog_version;
+    use crate::changelog::Version;
+
+    #[test]
+    fn versions_may_have_a_prefix_or_be_unreleased() {
+        let expected = Version::Semantic(semver::Version::new(1, 2, 3));
+        assert_eq!(to_changelog_version("1.2.3").unwrap(), expected);
+        assert_eq!(to_changelog_version("v1.2.3").unwrap(), expected);
+        assert_eq!(to_changelog_version("Unreleased").unwrap(), Version::Unreleased);
+        assert!(to_changelog_version("latest").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+
+use crate::{git, git::history::SegmentScope, utils::package_by_name, version::BumpSpec};
+
+/// What is known about the unreleased changes of a crate.
+#[derive(Debug, PartialEq, Eq)]
+struct Status {
+    /// The version of the latest release, as known by its tag.
+    last_release: Option<semver::Version>,
+    /// The number of commits since the last release by their git-conventional type, with `other` for those without one.
+    commits_by_kind: BTreeMap<&'static str, usize>,
+    /// The number of unreleased commits which are marked as breaking.
+    breaking: usize,
+    /// Why the crate isn't released even though it changed, or `None` if it can be released.
+    not_releasable: Option<&'static str>,
+    /// If `true`, the crate changed since its last release.
+    changed: bool,
+}
+
+impl Status {
+    fn num_commits(&self) -> usize {
+        self.commits_by_kind.values().sum()
+    }
+
+    fn release_is_warranted(&self) -> bool {
+        self.changed && self.not_releasable.is_none()
+    }
+}
+
+/// Print the latest release of each of `crates`, or all workspace crates if empty, along with their unreleased commits
+/// and whether they should be released.
+pub fn status(crates: Vec<String>) -> anyhow::Result<()> {
+    let crates = if crates.is_empty() {
+        cargo_metadata::MetadataCommand::new()
+            .no_deps()
+            .exec()?
+            .workspace_packages()
+            .into_iter()
+            .map(|package| package.name.to_string())
+            .collect()
+    } else {
+        crates
+    };
+    let ctx = crate::Context::new(
+        crates,
+        crate::context::Options {
+            force_history_segmentation: true,
+            bump: BumpSpec::Auto,
+            bump_dependencies: BumpSpec::Auto,
+            ..Default::default()
+        },
+    )?;
+    let history = ctx
+        .history
+        .as_ref()
+        .context("Did not have access to the Git history - please assure to not be on a detached HEAD")?;
+    for name in &ctx.crate_names {
+        let package = package_by_name(&ctx.meta, name)?;
+        let segments = git::history::crate_ref_segments(package, &ctx, history, SegmentScope::Unreleased)?;
+        let unreleased = &segments[0];
+        let mut commits_by_kind = BTreeMap::new();
+        for item in &unreleased.history {
+            *commits_by_kind.entry(item.message.kind.unwrap_or("other")).or_insert(0) += 1;
+        }
+        let last_release = match git::find_release_tag(package, &package.version, &ctx)? {
+            Some(_) => Some(package.version.clone()),
+            None => git::previous_release_tag(package, &package.version, &ctx)?.and_then(|tag_ref| {
+                ctx.version_scheme.parse_tag_name(
+                    crate::utils::tag_prefix(package, ctx.repo_of(package)),
+                    git::strip_tag_path(tag_ref.name()),
+                )
+            }),
+        };
+        let status = Status {
+            last_release,
+            breaking: unreleased.history.iter().filter(|item| item.message.breaking).count(),
+            commits_by_kind,
+            not_releasable: if !ctx.may_publish_to_registry(package) {
+                Some("publishing is disabled")
+            } else if ctx.is_frozen(package) {
+                Some("frozen")
+            } else {
+                None
+            },
+            changed: git::change_since_last_release(package, &ctx)?.is_some(),
+        };
+        println!("{}", describe(package.name.as_str(), &status));
+    }
+    Ok(())
+}
+
+fn describe(name: &str, status: &Status) -> String {
+    let release = match &status.last_release {
+        Some(version) => format!("v{version}"),
+        None => "never released".into(),
+    };
+    let commits = match status.num_commits() {
+        0 => "no unreleased commits".to_owned(),
+        num_commits => {
+            let mut kinds: Vec<_> = status
+                .commits_by_kind
+                .iter()
+                .map(|(kind, count)| format!("{count} {kind}"))
+                .collect();
+            if status.breaking != 0 {
+                kinds.push(format!("{} breaking", status.breaking));
+            }
+            format!(
+                "{num_commits} unreleased commit{} ({})",
+                if num_commits == 1 { "" } else { "s" },
+                kinds.join(", ")
+            )
+        }
+    };
+    let verdict = if status.release_is_warranted() {
+        "release warranted".to_owned()
+    } else if let Some(reason) = status.not_releasable.filter(|_| status.changed) {
+        format!("not released as {reason}")
+    } else {
+        "up to date".to_owned()
+    };
+    format!("{name} {release}: {commits}, {verdict}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{describe, Status};
+
+    #[test]
+    fn crates_are_described_by_their_unreleased_commits() {
+        let status = Status {
+            last_release: Some(semver::Version::new(0, 3, 0)),
+            commits_by_kind: BTreeMap::from_iter([("feat", 1), ("fix", 2), ("other", 1)]),
+            breaking: 1,
+            not_releasable: None,
+            changed: true,
+        };
+        assert_eq!(
+            describe("a", &status),
+            "a v0.3.0: 4 unreleased commits (1 feat, 2 fix, 1 other, 1 breaking), release warranted"
+        );
+        assert_eq!(
+            describe(
+                "b",
+                &Status {
+                    last_release: None,
+                    commits_by_kind: BTreeMap::from_iter([("fix", 1)]),
+                    breaking: 0,
+                    not_releasable: Some("frozen"),
+                    changed: true,
+                }
+            ),
+            "b never released: 1 unreleased commit (1 fix), not released as frozen"
+        );
+        assert_eq!(
+            describe(
+                "c",
+                &Status {
+                    commits_by_kind: Default::default(),
+                    breaking: 0,
+                    changed: false,
+                    ..status
+                }
+            ),
+            "c v0.3.0: no unreleased commits, up to date"
+        );
+    }
+}
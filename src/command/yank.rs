@@ -0,0 +1,201 @@
+use std::process::Command;
+
+use anyhow::bail;
+use cargo_metadata::Package;
+
+use crate::{command::yank::Options, utils::will};
+
+/// The beginning of the line which marks a release section as yanked.
+const YANKED_MARKER: &str = "**Yanked**";
+
+pub fn yank(opts: Options, crate_name: String, version: semver::Version) -> anyhow::Result<()> {
+    let Options {
+        dry_run,
+        ref registry,
+        changelog,
+        plan_dependents,
+    } = opts;
+    let meta = cargo_metadata::MetadataCommand::new().exec()?;
+    let package = crate::utils::package_by_name(&meta, &crate_name)?;
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["yank", "--version"])
+        .arg(version.to_string())
+        .arg(package.name.as_str());
+    if let Some(registry) = registry {
+        cmd.arg("--registry").arg(registry);
+    }
+    tracing::trace!("{} run {:?}", will(dry_run), cmd);
+    if !dry_run && !cmd.status()?.success() {
+        bail!("Failed to yank '{crate_name} v{version}'");
+    }
+
+    if changelog {
+        mark_yanked_in_changelog(package, &meta, &version, dry_run)?;
+    }
+    if plan_dependents {
+        log_dependents_plan(&meta, package, &version);
+    }
+    Ok(())
+}
+
+fn mark_yanked_in_changelog(
+    package: &Package,
+    meta: &cargo_metadata::Metadata,
+    version: &semver::Version,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let path = crate::utils::changelog_path(package, meta);
+    let markdown = match std::fs::read_to_string(&path) {
+        Ok(markdown) => markdown,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            tracing::info!("Not marking the release as yanked as '{path}' doesn't exist");
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let Some(marked) = mark_yanked(&markdown, version, jiff::Zoned::now().date()) else {
+        tracing::warn!("Could not find the section of v{version} in '{path}' to mark it as yanked");
+        return Ok(());
+    };
+    if marked == markdown {
+        tracing::info!("The section of v{version} in '{path}' is already marked as yanked");
+        return Ok(());
+    }
+    tracing::info!(
+        "{} mark the section of v{version} as yanked in '{path}', which is left for you to commit",
+        will(dry_run)
+    );
+    if !dry_run {
+        std::fs::write(&path, marked)?;
+    }
+    Ok(())
+}
+
+/// Return `markdown` with a line marking the release section of `version` as yanked at `date` right below its heading,
+/// or `None` if there is no such section.
+///
+/// The heading itself is left untouched so the section is still recognized when the changelog is parsed.
+fn mark_yanked(markdown: &str, version: &semver::Version, date: jiff::civil::Date) -> Option<String> {
+    let lines: Vec<_> = markdown.split_inclusive('\n').collect();
+    let heading = lines.iter().position(|line| is_heading_of(line, version))?;
+    let (head, body) = lines.split_at(heading + 1);
+    let first_content = body.iter().find(|line| !line.trim().is_empty());
+    if first_content.is_some_and(|line| line.starts_with(YANKED_MARKER)) {
+        return Some(markdown.to_owned());
+    }
+
+    let mut out: String = head.concat();
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push('\n');
+    out.push_str(&format!("{YANKED_MARKER} on {date}.\n"));
+    if body.first().is_some_and(|line| !line.trim().is_empty()) {
+        out.push('\n');
+    }
+    out.push_str(&body.concat());
+    Some(out)
+}
+
+/// Return `true` if `line` is a markdown heading naming `version`, possibly prefixed like in `v1.0.0`.
+fn is_heading_of(line: &str, version: &semver::Version) -> bool {
+    let Some(rest) = line.strip_prefix('#') else {
+        return false;
+    };
+    let version = version.to_string();
+    rest.trim_start_matches('#')
+        .split_whitespace()
+        .next()
+        .and_then(|token| token.strip_suffix(version.as_str()))
+        .is_some_and(|prefix| !prefix.ends_with(|c: char| c.is_ascii_digit() || c == '.'))
+}
+
+/// Log which workspace crates depend on exactly the yanked `version` of `package`, and how to release them once a
+/// fixed version is available.
+fn log_dependents_plan(meta: &cargo_metadata::Metadata, package: &Package, version: &semver::Version) {
+    let dependents: Vec<_> = meta
+        .workspace_members
+        .iter()
+        .map(|id| crate::utils::package_by_id(meta, id))
+        .filter(|dependent| {
+            dependent
+                .dependencies
+                .iter()
+                .any(|dep| dep.name == package.name.as_str() && is_pinned_to(&dep.req, version))
+        })
+        .map(|dependent| dependent.name.as_str())
+        .collect();
+    if dependents.is_empty() {
+        tracing::info!(
+            "No workspace crate is pinned to '{} v{version}', so no follow-up releases are needed",
+            package.name
+        );
+        return;
+    }
+    tracing::info!(
+        "These crates are pinned to '{} v{version}' and need a patch release once they depend on a fixed version: {}",
+        package.name,
+        dependents
+            .iter()
+            .map(|name| format!("'{name}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    tracing::info!(
+        "To release them after adjusting their dependency on '{}', run: cargo smart-release {} --bump patch --no-bump-on-demand",
+        package.name,
+        dependents.join(" ")
+    );
+}
+
+/// Return `true` if `req` only matches `version`, as with `=1.2.3`.
+fn is_pinned_to(req: &semver::VersionReq, version: &semver::Version) -> bool {
+    match req.comparators.as_slice() {
+        [comparator] => {
+            comparator.op == semver::Op::Exact
+                && comparator.major == version.major
+                && comparator.minor == Some(version.minor)
+                && comparator.patch == Some(version.patch)
+                && comparator.pre == version.pre
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_pinned_to, mark_yanked};
+
+    #[test]
+    fn yanked_releases_are_marked_below_their_heading() {
+        let markdown =
+            "# Changelog\n\n## Unreleased\n\n## v1.1.0 (2024-02-01)\n\nA fix.\n\n## v1.0.0 (2024-01-01)\n\nFirst.\n";
+        let version = semver::Version::new(1, 0, 0);
+        let date = jiff::civil::date(2024, 3, 1);
+        let marked = mark_yanked(markdown, &version, date).expect("section exists");
+        assert_eq!(
+            marked,
+            "# Changelog\n\n## Unreleased\n\n## v1.1.0 (2024-02-01)\n\nA fix.\n\n## v1.0.0 (2024-01-01)\n\n**Yanked** on 2024-03-01.\n\nFirst.\n"
+        );
+        assert_eq!(
+            mark_yanked(&marked, &version, date).as_deref(),
+            Some(marked.as_str()),
+            "marking is idempotent"
+        );
+        assert_eq!(mark_yanked(markdown, &semver::Version::new(0, 1, 0), date), None);
+        assert_eq!(
+            mark_yanked(markdown, &semver::Version::new(1, 1, 0), date).as_deref(),
+            Some("# Changelog\n\n## Unreleased\n\n## v1.1.0 (2024-02-01)\n\n**Yanked** on 2024-03-01.\n\nA fix.\n\n## v1.0.0 (2024-01-01)\n\nFirst.\n"),
+            "only the section of exactly this version is marked"
+        );
+    }
+
+    #[test]
+    fn only_exact_requirements_pin_a_version() {
+        let version = semver::Version::new(1, 2, 3);
+        for (req, pinned) in [("=1.2.3", true), ("1.2.3", false), ("=1.2", false), ("=1.2.4", false)] {
+            assert_eq!(is_pinned_to(&req.parse().unwrap(), &version), pinned, "{req}");
+        }
+    }
+}
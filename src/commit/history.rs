@@ -13,6 +13,15 @@ pub struct Item {
     pub commit_time: gix::date::Time,
     pub tree_id: gix::ObjectId,
     pub parent_tree_id: Option<gix::ObjectId>,
+    /// The mailmap-resolved commit author.
+    pub author: Author,
+}
+
+/// A commit author as resolved by the mailmap, so people who changed their name or email address are the same author.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Author {
+    pub name: String,
+    pub email: String,
 }
 
 #[cfg(test)]
@@ -24,11 +33,11 @@ mod tests {
         // The expected size is for the *test* build: the `gix-testtools` dev-dependency
         // enables `gix-hash/sha256`, so `gix::ObjectId` is its wider SHA-1/SHA-256 enum
         // (33 bytes) here and `Item` holds three ids; a production (sha1-only) build is
-        // ~200. Growth from either our fields or gix's types trips this deliberately, so
+        // ~224. Growth from either our fields or gix's types trips this deliberately, so
         // the bump can be reviewed.
         assert_eq!(
             std::mem::size_of::<Item>(),
-            240,
+            312,
             "there are plenty of these loaded at a time and we should not let it grow unnoticed."
         )
     }
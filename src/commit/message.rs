@@ -9,6 +9,8 @@ use crate::commit::Message;
 pub enum Addition {
     /// The plain issue ID, like "123".
     IssueId(String),
+    /// The plain ID of a pull request, like "123".
+    PullRequestId(String),
 }
 
 mod additions {
@@ -54,10 +56,53 @@ mod additions {
         (title, additions)
     }
 
+    /// Turn issue ids mentioned by `Pull-Request:` or `PR:` trailers in `message` into pull request ids,
+    /// adding those that weren't mentioned in the title.
+    pub fn apply_pull_request_trailers(message: &str, additions: &mut Vec<Addition>) {
+        for id in message.lines().filter_map(pull_request_trailer_id) {
+            match additions
+                .iter_mut()
+                .find(|a| matches!(a, Addition::IssueId(issue) | Addition::PullRequestId(issue) if *issue == id))
+            {
+                Some(addition) => *addition = Addition::PullRequestId(id),
+                None => additions.push(Addition::PullRequestId(id)),
+            }
+        }
+    }
+
+    fn pull_request_trailer_id(line: &str) -> Option<String> {
+        let (token, value) = line.split_once(':')?;
+        if !["pull-request", "pr"].contains(&token.trim().to_ascii_lowercase().as_str()) {
+            return None;
+        }
+        let value = value.trim();
+        let id = value
+            .strip_prefix('#')
+            .or_else(|| value.rsplit_once("/pull/").map(|(_, id)| id.trim_end_matches('/')))?;
+        (!id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())).then(|| id.to_owned())
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
 
+        #[test]
+        fn pull_request_trailers_turn_issues_into_pull_requests() {
+            let mut a = vec![Addition::IssueId("12".into()), Addition::IssueId("13".into())];
+            apply_pull_request_trailers(
+                "title (#12) (#13)\n\nbody\n\nPR: #12\nPull-Request: https://github.com/user/repo/pull/14",
+                &mut a,
+            );
+            assert_eq!(
+                a,
+                vec![
+                    Addition::PullRequestId("12".into()),
+                    Addition::IssueId("13".into()),
+                    Addition::PullRequestId("14".into())
+                ]
+            );
+        }
+
         #[test]
         fn no_addition() {
             let (nt, a) = strip("hello there [abc] (abc)".into());
@@ -87,6 +132,78 @@ impl From<&'_ str> for Message {
     }
 }
 
+impl Message {
+    /// Consider all issue ids in our title to be pull request ids, as is the case for squash-merged pull requests.
+    pub fn issues_to_pull_requests(&mut self) {
+        for addition in &mut self.additions {
+            if let Addition::IssueId(id) = addition {
+                *addition = Addition::PullRequestId(std::mem::take(id));
+            }
+        }
+    }
+
+    /// Mark this message as breaking if the git-conventional type of its original message `m` is one of `types`,
+    /// or if its scope is one of `scopes`.
+    ///
+    /// This allows types like `remove` to always count as breaking change, without needing the `!` marker.
+    pub fn mark_breaking_by_type_or_scope(&mut self, m: &str, types: &[String], scopes: &[String]) {
+        if self.kind.is_none() {
+            return;
+        }
+        let scope_is_breaking = self.scope.as_ref().is_some_and(|scope| scopes.contains(scope));
+        let type_is_breaking = || {
+            conventional_type(m).is_some_and(|kind| types.iter().any(|breaking| breaking.eq_ignore_ascii_case(&kind)))
+        };
+        if scope_is_breaking || (!types.is_empty() && type_is_breaking()) {
+            self.breaking = true;
+        }
+    }
+
+    /// Parse `m` as message of a GitHub pull request merge commit, like `Merge pull request #42 from user/branch`,
+    /// and use the title of the pull request found in its body as title.
+    ///
+    /// Return `None` if `m` isn't such a message or if the pull request title is missing.
+    pub fn from_pull_request_merge(m: &str) -> Option<Self> {
+        let msg = gix::objs::commit::MessageRef::from_bytes(m.as_bytes());
+        let summary = msg.summary();
+        let number = summary
+            .to_str()
+            .ok()?
+            .strip_prefix("Merge pull request #")?
+            .split(' ')
+            .next()
+            .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))?
+            .to_owned();
+        let body = msg.body()?.without_trailer().to_str_lossy();
+        if body.trim().is_empty() {
+            return None;
+        }
+        let mut message = get_message(&body);
+        message
+            .additions
+            .retain(|a| !matches!(a, Addition::IssueId(id) | Addition::PullRequestId(id) if *id == number));
+        message.additions.push(Addition::PullRequestId(number));
+        Some(message)
+    }
+}
+
+/// Return the git-conventional type of `m` as written, looking at the pull request title in the body of merge commits as well.
+fn conventional_type(m: &str) -> Option<String> {
+    let msg = gix::objs::commit::MessageRef::from_bytes(m.as_bytes());
+    let pull_request_title = msg
+        .body
+        .and_then(|body| body.lines().find(|line| !line.trim().is_empty()))
+        .map(|line| line.to_str_lossy());
+    std::iter::once(msg.summary().to_str_lossy())
+        .chain(pull_request_title)
+        .find_map(|line| {
+            let line = line.trim_start_matches(|c: char| !c.is_alphanumeric());
+            git_conventional::Commit::parse(line)
+                .ok()
+                .map(|c| c.type_().as_str().to_owned())
+        })
+}
+
 #[cfg(feature = "allow-emoji")]
 fn get_message(m: &str) -> Message {
     use unicode_properties::{EmojiStatus, UnicodeEmoji};
@@ -104,12 +221,13 @@ fn get_message(m: &str) -> Message {
 }
 
 fn get_message_inner(m: &str) -> Message {
-    let (title, kind, body, breaking, breaking_description) = git_conventional::Commit::parse(m).map_or_else(
+    let (title, kind, scope, body, breaking, breaking_description) = git_conventional::Commit::parse(m).map_or_else(
         |_| {
             let m = gix::objs::commit::MessageRef::from_bytes(m.as_bytes());
             (
                 m.summary().as_ref().to_string().into(),
                 None,
+                None,
                 m.body().map(|b| b.without_trailer().to_str_lossy()),
                 false,
                 None,
@@ -119,16 +237,19 @@ fn get_message_inner(m: &str) -> Message {
             (
                 c.description().into(),
                 Some(c.type_()),
+                c.scope().map(|scope| scope.as_str().to_owned()),
                 c.body().map(Into::into),
                 c.breaking(),
                 c.breaking_description().filter(|&d| d != c.description()),
             )
         },
     );
-    let (title, additions) = additions::strip(title);
+    let (title, mut additions) = additions::strip(title);
+    additions::apply_pull_request_trailers(m, &mut additions);
     Message {
         title: title.into_owned(),
         kind: as_static_str(kind),
+        scope,
         body: body.map(Cow::into_owned),
         breaking,
         breaking_description: breaking_description.map(ToOwned::to_owned),
@@ -165,6 +286,7 @@ mod tests {
                 title: "hi".into(),
                 body: None,
                 kind: None,
+                scope: None,
                 breaking: false,
                 breaking_description: None,
                 additions: vec![]
@@ -180,6 +302,7 @@ mod tests {
                 title: "hi ho foo".into(),
                 body: Some("body".into()),
                 kind: None,
+                scope: None,
                 breaking: false,
                 breaking_description: None,
                 additions: vec![]
@@ -195,6 +318,7 @@ mod tests {
                 title: "hi".into(),
                 body: Some("body\nother".into()),
                 kind: None,
+                scope: None,
                 breaking: false,
                 breaking_description: None,
                 additions: vec![Addition::IssueId("14123".into())]
@@ -210,6 +334,7 @@ mod tests {
                 title: "hi".into(),
                 body: Some("the body".into()),
                 kind: Some("feat"),
+                scope: None,
                 breaking: true,
                 breaking_description: Some("breaks".into()),
                 additions: vec![Addition::IssueId("123".into())]
@@ -225,6 +350,7 @@ mod tests {
                 title: "restructure Cargo.toml for workspace management".into(),
                 body: Some("- transition from single package to workspace format\n- update dependencies and remove obsolete sections".into()),
                 kind: Some("refactor"),
+                scope: Some("workspace".into()),
                 breaking: true,
                 breaking_description: None,
                 additions: vec![]
@@ -243,6 +369,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pull_request_merge_uses_pull_request_title() {
+        assert_eq!(
+            Message::from_pull_request_merge(
+                "Merge pull request #42 from user/branch\n\nfeat: the pull request title\n\nthe description"
+            ),
+            Some(Message {
+                title: "the pull request title".into(),
+                body: Some("the description".into()),
+                kind: Some("feat"),
+                scope: None,
+                breaking: false,
+                breaking_description: None,
+                additions: vec![Addition::PullRequestId("42".into())]
+            })
+        );
+        assert_eq!(
+            Message::from_pull_request_merge("Merge pull request #42 from user/branch"),
+            None,
+            "without title there is nothing to prefer"
+        );
+        assert_eq!(
+            Message::from_pull_request_merge("Merge branch 'main' into feature\n\nbody"),
+            None
+        );
+    }
+
+    #[test]
+    fn breaking_by_type_or_scope() {
+        let types = ["remove".to_string()];
+        let scopes = ["api".to_string()];
+        for (input, expected) in [
+            ("remove: old function", true),
+            ("Remove: old function", true),
+            ("revert: old function", false),
+            ("fix(api): signature", true),
+            ("fix(cli): signature", false),
+            ("remove old function", false),
+            (
+                "Merge pull request #42 from user/branch\n\nremove: old function\n\ndetails",
+                true,
+            ),
+        ] {
+            let mut message = Message::from_pull_request_merge(input).unwrap_or_else(|| Message::from(input));
+            message.mark_breaking_by_type_or_scope(input, &types, &scopes);
+            assert_eq!(message.breaking, expected, "{input}");
+        }
+    }
+
     #[cfg(feature = "allow-emoji")]
     #[test]
     fn conventional_with_scope_and_emoji() {
@@ -252,6 +427,7 @@ mod tests {
                 title: "restructure Cargo.toml for workspace ⚠️management ⚠️ ".into(),
                 body: Some("- transition from single package to workspace format\n- update dependencies and remove obsolete sections".into()),
                 kind: Some("refactor"),
+                scope: Some("workspace".into()),
                 breaking: true,
                 breaking_description: None,
                 additions: vec![]
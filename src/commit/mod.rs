@@ -11,6 +11,8 @@ pub struct Message {
     pub body: Option<String>,
     /// If set, the git-conventional scope to help organizing changes.
     pub kind: Option<&'static str>,
+    /// If set, the git-conventional scope, like `parser` in `fix(parser): …`.
+    pub scope: Option<String>,
     /// If set, this is a breaking change as indicated git-conventional.
     pub breaking: bool,
     /// If set, this commit message body contains a specific description of the breaking change.
@@ -1,9 +1,14 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
+
 use cargo_metadata::{
     camino::{Utf8Path, Utf8PathBuf},
     Metadata, Package,
 };
 
-use crate::version::BumpSpec;
+use crate::version::{BumpSpec, VersionScheme};
 
 pub struct Context {
     pub root: Utf8PathBuf,
@@ -14,43 +19,239 @@ pub struct Context {
     pub history: Option<crate::commit::History>,
     pub bump: BumpSpec,
     pub bump_dependencies: BumpSpec,
+    /// Bump specifications of individual crates by name, which take precedence over `bump` and `bump_dependencies`.
+    pub bump_overrides: BTreeMap<String, BumpSpec>,
+    /// The way the history is collected, including settings from the workspace manifest.
+    pub history_options: crate::git::history::Options,
+    /// If set, automatic version bumps are validated against API changes.
+    pub semver_checks: Option<crate::semver_checks::SemverChecks>,
+    /// The names of crates to attribute commits to by their git-conventional scope, instead of by the paths they change.
+    pub crates_by_scope: BTreeMap<String, String>,
+    /// The build metadata to append to the versions of new releases.
+    pub build_metadata: Option<semver::BuildMetadata>,
+    /// The names of crates which are never bumped or published.
+    pub frozen: BTreeSet<String>,
+    /// The names of crates with `version.workspace = true`, which share the version in `[workspace.package]`.
+    pub workspace_version_inheritors: BTreeSet<String>,
+    /// The way versions of new releases are computed, and how their tags and changelog sections are named.
+    pub version_scheme: Rc<dyn VersionScheme>,
+    /// The registry to publish to, or `None` to let cargo choose, which usually is crates.io.
+    pub registry: Option<String>,
+    /// The submodules of `repo` that contain workspace members.
+    pub submodules: Vec<Submodule>,
+    /// If `true`, the tags of previous releases must be signed by a key trusted by `git verify-tag` to be used.
+    pub verify_tags: bool,
+    /// The headings of generated changelog content, which are also recognized when parsing existing changelogs.
+    pub changelog_locale: crate::changelog::locale::Locale,
+}
+
+/// A git submodule containing workspace members, whose history and tags are those of the submodule repository.
+pub struct Submodule {
+    /// The directory the submodule is checked out in.
+    pub dir: Utf8PathBuf,
+    pub repo: gix::Repository,
+    pub history: Option<crate::commit::History>,
+}
+
+/// Control how the [`Context`] of a command is created.
+#[derive(Clone)]
+pub struct Options {
+    /// If `true`, collect the history even if no version bump needs it.
+    pub force_history_segmentation: bool,
+    pub bump: BumpSpec,
+    pub bump_dependencies: BumpSpec,
+    /// The way the history is collected, which is amended by settings from the workspace manifest.
+    pub history: crate::git::history::Options,
+    /// If set, automatic version bumps are validated against API changes.
+    pub semver_checks: Option<crate::semver_checks::Policy>,
+    /// The template of the build metadata to append to the versions of new releases, or `None` to use the one of the
+    /// workspace manifest.
+    pub build_metadata: Option<String>,
+    /// Bump specifications of individual crates by name, which take precedence over those in their manifests.
+    pub bump_overrides: BTreeMap<String, BumpSpec>,
+    /// The names of crates which are never bumped or published, in addition to those configured as frozen.
+    pub pinned: Vec<String>,
+    /// The names of crates to leave out when selecting all workspace members.
+    pub excluded: Vec<String>,
+    pub version_scheme: Rc<dyn VersionScheme>,
+    /// The registry to publish to, or `None` to let cargo choose.
+    pub registry: Option<String>,
+    /// If `true`, the tags of previous releases must be signed, even if the workspace manifest doesn't require it.
+    pub verify_tags: bool,
+    /// The headings of generated changelog content.
+    pub changelog_locale: crate::changelog::locale::Locale,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            force_history_segmentation: false,
+            bump: BumpSpec::Keep,
+            bump_dependencies: BumpSpec::Keep,
+            history: Default::default(),
+            semver_checks: None,
+            build_metadata: None,
+            bump_overrides: Default::default(),
+            pinned: Vec::new(),
+            excluded: Vec::new(),
+            version_scheme: Rc::new(crate::version::SemanticVersioning),
+            registry: None,
+            verify_tags: false,
+            changelog_locale: Default::default(),
+        }
+    }
 }
 
 impl Context {
     pub fn new(
         crate_names: Vec<String>,
-        force_history_segmentation: bool,
-        bump: BumpSpec,
-        bump_dependencies: BumpSpec,
+        Options {
+            force_history_segmentation,
+            bump,
+            bump_dependencies,
+            history: history_options,
+            semver_checks,
+            build_metadata,
+            bump_overrides,
+            pinned,
+            excluded,
+            version_scheme,
+            registry,
+            verify_tags,
+            changelog_locale,
+        }: Options,
     ) -> anyhow::Result<Self> {
-        let meta = cargo_metadata::MetadataCommand::new().exec()?;
+        let meta = crate::stats::time("collect metadata", || cargo_metadata::MetadataCommand::new().exec())
+            .map_err(|err| with_hint_for_missing_members(err.into()))?;
+        let is_workspace_member = |name: &str| meta.workspace_packages().iter().any(|p| p.name.as_str() == name);
+        if let Some(unknown) = bump_overrides.keys().find(|name| !is_workspace_member(name)) {
+            anyhow::bail!("Cannot set the version bump of '{unknown}' as it isn't a workspace member");
+        }
+        if let Some(unknown) = pinned.iter().find(|name| !is_workspace_member(name)) {
+            anyhow::bail!("Cannot pin '{unknown}' as it isn't a workspace member");
+        }
+        let mut frozen = frozen_packages(&meta);
+        frozen.extend(pinned.iter().cloned());
+        let mut bump_overrides_with_metadata = package_bump_overrides(&meta)?;
+        bump_overrides_with_metadata.extend(bump_overrides);
+        let bump_overrides = bump_overrides_with_metadata;
+        let crates_by_scope = workspace_crates_by_scope(&meta)?;
+        let workspace_version_inheritors = packages_inheriting_workspace_version(&meta)?;
+        let history_options = crate::git::history::Options {
+            authors: history_options.authors || workspace_flag(&meta, "changelog-authors"),
+            write_commit_graph: history_options.write_commit_graph || workspace_flag(&meta, "write-commit-graph"),
+            breaking_types: workspace_string_list(&meta, "breaking-types")?,
+            breaking_scopes: workspace_string_list(&meta, "breaking-scopes")?,
+            ..history_options
+        };
+        let tag_patterns = workspace_string_list(&meta, "tag-patterns")?;
+        let version_scheme: Rc<dyn VersionScheme> = if tag_patterns.is_empty() {
+            version_scheme
+        } else {
+            Rc::new(crate::version::WithTagPatterns::new(version_scheme, tag_patterns)?)
+        };
         let root = meta.workspace_root.clone();
         let repo = gix::discover(&root)?;
+        let build_metadata = build_metadata
+            .or_else(|| workspace_build_metadata(&meta))
+            .map(|template| expand_build_metadata(&template, &repo))
+            .transpose()?;
         let crates_index = crate::crates_index::Index::new_cargo_default()?;
         let history = (force_history_segmentation
-            || matches!(bump, BumpSpec::Auto)
-            || matches!(bump_dependencies, BumpSpec::Auto))
-        .then(|| crate::git::history::collect(&repo))
+            || [bump, bump_dependencies]
+                .iter()
+                .chain(bump_overrides.values())
+                .any(|spec| matches!(spec, BumpSpec::Auto | BumpSpec::PreRelease(_))))
+        .then(|| {
+            crate::stats::time("traverse history", || {
+                crate::git::history::collect(&repo, history_options.clone())
+            })
+        })
         .transpose()?
         .flatten();
+        let submodules = workspace_submodules(&repo, &meta, &history_options)?;
+        let verify_tags = verify_tags || workspace_flag(&meta, "verify-tags");
+        let crate_names = select_crates(&meta, fill_in_root_crate_if_needed(crate_names)?, &excluded)?;
         Ok(Context {
             root,
             repo,
             meta,
-            crate_names: fill_in_root_crate_if_needed(crate_names)?,
+            crate_names,
             crates_index,
             history,
             bump,
             bump_dependencies,
+            bump_overrides,
+            history_options,
+            semver_checks: semver_checks.map(crate::semver_checks::SemverChecks::new),
+            crates_by_scope,
+            build_metadata,
+            frozen,
+            workspace_version_inheritors,
+            version_scheme,
+            registry,
+            submodules,
+            verify_tags,
+            changelog_locale,
         })
     }
 
+    /// Return the submodule `package` is in, if it's not tracked by the workspace repository itself.
+    pub(crate) fn submodule_of(&self, package: &Package) -> Option<&Submodule> {
+        self.submodules
+            .iter()
+            .find(|submodule| package.manifest_path.starts_with(&submodule.dir))
+    }
+
+    /// Return the repository tracking `package`, which is the one of its submodule if it's in one.
+    pub(crate) fn repo_of(&self, package: &Package) -> &gix::Repository {
+        self.submodule_of(package)
+            .map_or(&self.repo, |submodule| &submodule.repo)
+    }
+
+    /// Return `true` if `package` must keep its version and must not be published.
+    pub(crate) fn is_frozen(&self, package: &Package) -> bool {
+        self.frozen.contains(package.name.as_str())
+    }
+
+    /// Return the registry to publish `package` to, which is `registry` in its `[package.metadata.smart-release]`, or
+    /// else the one all crates are published to.
+    pub(crate) fn registry_of<'a>(&'a self, package: &'a Package) -> Option<&'a str> {
+        package
+            .metadata
+            .get("smart-release")
+            .and_then(|config| config.get("registry"))
+            .and_then(serde_json::Value::as_str)
+            .or(self.registry.as_deref())
+    }
+
+    /// Return `true` if the `publish` field in the manifest of `package` allows publishing it to the registry we publish
+    /// it to.
+    ///
+    /// Without a registry set, cargo publishes crates which may only be published to a single registry to that one.
+    pub(crate) fn may_publish_to_registry(&self, package: &Package) -> bool {
+        let Some(registries) = package.publish.as_ref() else {
+            return true;
+        };
+        match self.registry_of(package) {
+            Some(registry) => registries.iter().any(|r| r == registry),
+            None => registries.len() == 1 || registries.iter().any(|r| r == "crates-io"),
+        }
+    }
+
+    /// Return `true` if `package` inherits its version from `[workspace.package]`.
+    pub(crate) fn inherits_workspace_version(&self, package: &Package) -> bool {
+        self.workspace_version_inheritors.contains(package.name.as_str())
+    }
+
+    /// Return the directory of `p` relative to the root of the repository tracking it, or `None` if it's at the root.
     pub(crate) fn repo_relative_path<'a>(&self, p: &'a Package) -> Option<&'a Utf8Path> {
+        let root = self.submodule_of(p).map_or(&self.root, |submodule| &submodule.dir);
         let dir = p
             .manifest_path
             .parent()
             .expect("parent of a file is always present")
-            .strip_prefix(&self.root)
+            .strip_prefix(root)
             .unwrap_or_else(|_| {
                 panic!(
                     "workspace members are relative to the root directory: {:?} should contain {:?}",
@@ -67,6 +268,272 @@ impl Context {
     }
 }
 
+/// Open the submodules of `repo` that contain members of the workspace in `meta`, along with their history.
+fn workspace_submodules(
+    repo: &gix::Repository,
+    meta: &Metadata,
+    history_options: &crate::git::history::Options,
+) -> anyhow::Result<Vec<Submodule>> {
+    use anyhow::Context as _;
+    let Some(submodules) = repo.submodules()? else {
+        return Ok(Vec::new());
+    };
+    let mut out = Vec::new();
+    for submodule in submodules {
+        let dir = Utf8PathBuf::try_from(submodule.work_dir()?)?;
+        let members: Vec<_> = meta
+            .workspace_packages()
+            .into_iter()
+            .filter(|package| package.manifest_path.starts_with(&dir))
+            .map(|package| format!("'{}'", package.name))
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        let submodule_repo = submodule.open()?.with_context(|| {
+            format!(
+                "The submodule at '{dir}' with the workspace members {} isn't checked out. Run 'git submodule update --init' and try again.",
+                members.join(", ")
+            )
+        })?;
+        // Submodules are usually small, and their history is needed whenever changelogs are generated.
+        let history = crate::stats::time("traverse history", || {
+            crate::git::history::collect(&submodule_repo, history_options.clone())
+        })?;
+        tracing::debug!(
+            "Using the history of the submodule at '{dir}' for the workspace members {}",
+            members.join(", ")
+        );
+        out.push(Submodule {
+            dir,
+            repo: submodule_repo,
+            history,
+        });
+    }
+    Ok(out)
+}
+
+/// Add a hint to `err` about submodules in the current repository which aren't checked out, or about its sparse checkout,
+/// as workspace members in them can't be found.
+fn with_hint_for_missing_members(err: anyhow::Error) -> anyhow::Error {
+    let Ok(repo) = gix::discover(".") else {
+        return err;
+    };
+    let missing: Vec<_> = repo
+        .submodules()
+        .ok()
+        .flatten()
+        .map(|submodules| {
+            submodules
+                .filter(|submodule| submodule.state().is_ok_and(|state| !state.worktree_checkout))
+                .filter_map(|submodule| submodule.path().ok().map(|path| format!("'{path}'")))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !missing.is_empty() {
+        err.context(format!(
+            "The submodules at {} aren't checked out, but may contain workspace members. Run 'git submodule update --init' and try again.",
+            missing.join(", ")
+        ))
+    } else if crate::git::is_sparse_checkout(&repo) {
+        err.context(
+            "The repository is a sparse checkout, which may not contain all workspace members. Add their directories with 'git sparse-checkout add <dir>' and try again.",
+        )
+    } else {
+        err
+    }
+}
+
+/// Read the boolean `key` from `[workspace.metadata.smart-release]`, or `false` if it isn't set.
+fn workspace_flag(meta: &Metadata, key: &str) -> bool {
+    meta.workspace_metadata
+        .get("smart-release")
+        .and_then(|config| config.get(key))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Read the list of strings named `key` from `[workspace.metadata.smart-release]`, or an empty list if it isn't set.
+fn workspace_string_list(meta: &Metadata, key: &str) -> anyhow::Result<Vec<String>> {
+    let Some(value) = meta
+        .workspace_metadata
+        .get("smart-release")
+        .and_then(|config| config.get(key))
+    else {
+        return Ok(Vec::new());
+    };
+    value
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|v| v.as_str().map(ToOwned::to_owned))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| anyhow::anyhow!("'{key}' in [workspace.metadata.smart-release] must be a list of strings"))
+}
+
+/// Read `bump` from `[package.metadata.smart-release]` of all workspace members, by crate name.
+fn package_bump_overrides(meta: &Metadata) -> anyhow::Result<BTreeMap<String, BumpSpec>> {
+    let mut overrides = BTreeMap::new();
+    for package in meta.workspace_packages() {
+        let Some(spec) = package
+            .metadata
+            .get("smart-release")
+            .and_then(|config| config.get("bump"))
+            .and_then(serde_json::Value::as_str)
+        else {
+            continue;
+        };
+        let spec = spec.parse().map_err(|err: anyhow::Error| {
+            err.context(format!(
+                "Invalid 'bump' in [package.metadata.smart-release] of '{}'",
+                package.name
+            ))
+        })?;
+        overrides.insert(package.name.to_string(), spec);
+    }
+    Ok(overrides)
+}
+
+/// Return the names of all workspace members whose manifest contains `version.workspace = true`.
+fn packages_inheriting_workspace_version(meta: &Metadata) -> anyhow::Result<BTreeSet<String>> {
+    let mut names = BTreeSet::new();
+    for package in meta.workspace_packages() {
+        let manifest = std::fs::read_to_string(&package.manifest_path)?;
+        let doc: toml_edit::DocumentMut = manifest.parse().map_err(|err| {
+            anyhow::Error::from(err).context(format!("Could not parse manifest at '{}'", package.manifest_path))
+        })?;
+        if crate::utils::inherits_workspace_version(&doc) {
+            names.insert(package.name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// Return the names of all workspace members with `frozen = true` in their `[package.metadata.smart-release]`.
+fn frozen_packages(meta: &Metadata) -> BTreeSet<String> {
+    meta.workspace_packages()
+        .into_iter()
+        .filter(|package| {
+            package
+                .metadata
+                .get("smart-release")
+                .and_then(|config| config.get("frozen"))
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false)
+        })
+        .map(|package| package.name.to_string())
+        .collect()
+}
+
+/// Read the `[workspace.metadata.smart-release.scopes]` table which maps git-conventional scopes to crate names.
+fn workspace_crates_by_scope(meta: &Metadata) -> anyhow::Result<BTreeMap<String, String>> {
+    let Some(scopes) = meta
+        .workspace_metadata
+        .get("smart-release")
+        .and_then(|config| config.get("scopes"))
+    else {
+        return Ok(Default::default());
+    };
+    let Some(scopes) = scopes.as_object() else {
+        anyhow::bail!("[workspace.metadata.smart-release.scopes] must be a table of scope names to crate names")
+    };
+    scopes
+        .iter()
+        .map(|(scope, name)| {
+            let Some(name) = name.as_str() else {
+                anyhow::bail!("The crate name of scope '{scope}' in [workspace.metadata.smart-release.scopes] must be a string")
+            };
+            if !meta.workspace_packages().iter().any(|p| p.name.as_str() == name) {
+                anyhow::bail!(
+                    "Scope '{scope}' in [workspace.metadata.smart-release.scopes] maps to '{name}' which isn't a workspace member"
+                )
+            }
+            Ok((scope.clone(), name.to_owned()))
+        })
+        .collect()
+}
+
+/// Read `build-metadata` from `[workspace.metadata.smart-release]`.
+fn workspace_build_metadata(meta: &Metadata) -> Option<String> {
+    meta.workspace_metadata
+        .get("smart-release")
+        .and_then(|config| config.get("build-metadata"))
+        .and_then(serde_json::Value::as_str)
+        .map(ToOwned::to_owned)
+}
+
+/// Turn `template` into build metadata, replacing `{commit}` with the short hash of the `HEAD` commit.
+fn expand_build_metadata(template: &str, repo: &gix::Repository) -> anyhow::Result<semver::BuildMetadata> {
+    let metadata = if template.contains("{commit}") {
+        template.replace("{commit}", &repo.head_id()?.shorten_or_id().to_string())
+    } else {
+        template.to_owned()
+    };
+    semver::BuildMetadata::new(metadata.trim_start_matches('+')).map_err(|err| {
+        anyhow::anyhow!(
+            "Invalid build metadata '{metadata}': {err} - only ASCII alphanumerics, '-' and '.' are allowed"
+        )
+    })
+}
+
+/// Replace glob patterns like `gix-*` in `crate_names` with the names of the workspace members matching them, and paths
+/// like `./crates/parser` with the name of the workspace member in that directory, and remove all crates matching one of
+/// the `excluded` patterns.
+fn select_crates(meta: &Metadata, crate_names: Vec<String>, excluded: &[String]) -> anyhow::Result<Vec<String>> {
+    let matches = |pattern: &str, name: &str| {
+        gix::glob::wildmatch(pattern.into(), name.into(), gix::glob::wildmatch::Mode::empty())
+    };
+    let is_pattern = |name: &str| name.contains(['*', '?', '[']);
+    let mut selected = Vec::new();
+    for name in crate_names {
+        if is_path(&name) {
+            selected.push(member_at_path(meta, &name)?);
+        } else if is_pattern(&name) {
+            let members: Vec<_> = meta
+                .workspace_packages()
+                .into_iter()
+                .map(|package| package.name.to_string())
+                .filter(|member| matches(&name, member))
+                .collect();
+            if members.is_empty() {
+                anyhow::bail!("No workspace member matches '{name}'");
+            }
+            selected.extend(members);
+        } else {
+            selected.push(name);
+        }
+    }
+    let mut seen = BTreeSet::new();
+    selected.retain(|name| seen.insert(name.clone()) && !excluded.iter().any(|pattern| matches(pattern, name)));
+    if selected.is_empty() {
+        anyhow::bail!("All selected crates are excluded, so there is nothing left to release");
+    }
+    Ok(selected)
+}
+
+/// Crate names can't contain path separators, so anything that does is a path, as are `.` and `..`.
+fn is_path(name: &str) -> bool {
+    name.contains(std::path::is_separator) || name == "." || name == ".."
+}
+
+/// Return the name of the workspace member whose manifest is in the directory at `path`.
+fn member_at_path(meta: &Metadata, path: &str) -> anyhow::Result<String> {
+    let dir = std::fs::canonicalize(path).map_err(|err| anyhow::anyhow!("Cannot select crate at '{path}': {err}"))?;
+    meta.workspace_packages()
+        .into_iter()
+        .find(|package| {
+            package
+                .manifest_path
+                .parent()
+                .and_then(|member_dir| std::fs::canonicalize(member_dir).ok())
+                .is_some_and(|member_dir| member_dir == dir)
+        })
+        .map(|package| package.name.to_string())
+        .ok_or_else(|| anyhow::anyhow!("There is no workspace member in '{path}'"))
+}
+
 fn fill_in_root_crate_if_needed(crate_names: Vec<String>) -> anyhow::Result<Vec<String>> {
     Ok(if crate_names.is_empty() {
         let current_dir = std::env::current_dir()?;
@@ -84,7 +551,7 @@ fn fill_in_root_crate_if_needed(crate_names: Vec<String>) -> anyhow::Result<Vec<
         } else {
             dir_name.to_owned()
         };
-        log::warn!("Using '{crate_name}' as crate name as no one was provided. Specify one if this isn't correct");
+        tracing::warn!("Using '{crate_name}' as crate name as no one was provided. Specify one if this isn't correct");
         vec![crate_name]
     } else {
         crate_names
@@ -22,3 +22,94 @@ impl Index {
         self.inner.as_ref().and_then(|idx| idx.crate_(name))
     }
 }
+
+/// The location of the crates.io index which is accessed through plain HTTP requests.
+const SPARSE_INDEX_URL: &str = "https://index.crates.io";
+
+/// Return `true` if `version` of the crate named `name` is available in the sparse crates.io index, which is quick to
+/// query as only the file of this crate is downloaded.
+///
+/// Build metadata is ignored, as it may or may not be retained by the registry.
+pub fn sparse_index_has_version(name: &str, version: &semver::Version) -> anyhow::Result<bool> {
+    index_at_has_version(SPARSE_INDEX_URL, name, version)
+}
+
+/// Like [`sparse_index_has_version()`], but for the sparse index at `index_url`, without the `sparse+` prefix.
+pub fn index_at_has_version(index_url: &str, name: &str, version: &semver::Version) -> anyhow::Result<bool> {
+    let url = format!("{}/{}", index_url.trim_end_matches('/'), sparse_index_path(name));
+    match http_get(&url, &["Cache-Control: no-cache"])? {
+        (200, body) => Ok(index_file_has_version(&body, version)),
+        (404 | 410 | 451, _) => Ok(false),
+        (code, _) => anyhow::bail!("The sparse index responded with unexpected status {code} for crate '{name}'"),
+    }
+}
+
+/// Perform a `GET` request to `url` with additional `headers`, and return the status code along with the body.
+pub(crate) fn http_get(url: &str, headers: &[&str]) -> anyhow::Result<(u32, Vec<u8>)> {
+    let mut handle = curl::easy::Easy::new();
+    handle.url(url)?;
+    handle.useragent(concat!("cargo-smart-release/", env!("CARGO_PKG_VERSION")))?;
+    handle.follow_location(true)?;
+    let mut list = curl::easy::List::new();
+    for header in headers {
+        list.append(header)?;
+    }
+    handle.http_headers(list)?;
+
+    let mut body = Vec::new();
+    {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|data| {
+            body.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()?;
+    }
+    Ok((handle.response_code()?, body))
+}
+
+/// Return the path of the index file of the crate named `name`, relative to the root of the index.
+fn sparse_index_path(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Return `true` if the index file `content`, with one JSON object per line and version, lists `version`.
+fn index_file_has_version(content: &[u8], version: &semver::Version) -> bool {
+    content
+        .split(|b| *b == b'\n')
+        .filter_map(|line| serde_json::from_slice::<serde_json::Value>(line).ok())
+        .filter_map(|entry| entry.get("vers")?.as_str().and_then(|v| semver::Version::parse(v).ok()))
+        .any(|v| v.cmp_precedence(version).is_eq())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{index_file_has_version, sparse_index_path};
+
+    #[test]
+    fn sparse_index_paths_depend_on_name_length() {
+        assert_eq!(sparse_index_path("a"), "1/a");
+        assert_eq!(sparse_index_path("ab"), "2/ab");
+        assert_eq!(sparse_index_path("abc"), "3/a/abc");
+        assert_eq!(sparse_index_path("Cargo-Smart-Release"), "ca/rg/cargo-smart-release");
+    }
+
+    #[test]
+    fn versions_are_found_in_index_files() {
+        let content = br#"{"name":"a","vers":"0.1.0","deps":[],"cksum":"","features":{},"yanked":false}
+{"name":"a","vers":"0.2.0+build.1","deps":[],"cksum":"","features":{},"yanked":false}
+"#;
+        assert!(index_file_has_version(content, &"0.1.0".parse().unwrap()));
+        assert!(
+            index_file_has_version(content, &"0.2.0".parse().unwrap()),
+            "build metadata is ignored"
+        );
+        assert!(!index_file_has_version(content, &"0.3.0".parse().unwrap()));
+    }
+}
@@ -0,0 +1,90 @@
+use std::io;
+
+use gix::diff::blob::{
+    unified_diff::{ConsumeHunk, ContextSize, DiffLineKind, HunkHeader},
+    Algorithm, InternedInput, UnifiedDiff,
+};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Return a unified diff between `before` and `after`, using ANSI colors if `color` is `true`,
+/// or `None` if there is no difference.
+///
+/// `path` is used in the diff header for both sides, and `before` is treated as non-existing file if it is `None`.
+pub fn unified(path: &str, before: Option<&str>, after: &str, color: bool) -> io::Result<Option<String>> {
+    let input = InternedInput::new(before.unwrap_or_default(), after);
+    let diff = gix::diff::blob::diff_with_slider_heuristics(Algorithm::Histogram, &input);
+    let hunks = UnifiedDiff::new(
+        &diff,
+        &input,
+        Hunks {
+            color,
+            out: String::new(),
+        },
+        ContextSize::symmetrical(3),
+    )
+    .consume()?;
+    if hunks.is_empty() {
+        return Ok(None);
+    }
+    let (bold, reset) = if color { (BOLD, RESET) } else { ("", "") };
+    let old = match before {
+        Some(_) => format!("a/{path}"),
+        None => "/dev/null".into(),
+    };
+    Ok(Some(format!(
+        "{bold}--- {old}{reset}\n{bold}+++ b/{path}{reset}\n{hunks}"
+    )))
+}
+
+struct Hunks {
+    color: bool,
+    out: String,
+}
+
+impl ConsumeHunk for Hunks {
+    type Out = String;
+
+    fn consume_hunk(&mut self, header: HunkHeader, lines: &[(DiffLineKind, &[u8])]) -> io::Result<()> {
+        let paint = |style: &'static str| if self.color { style } else { "" };
+        let reset = paint(RESET);
+        self.out.push_str(&format!("{}{header}{reset}\n", paint(CYAN)));
+        for (kind, line) in lines {
+            let (prefix, style) = match kind {
+                DiffLineKind::Context => (' ', ""),
+                DiffLineKind::Add => ('+', GREEN),
+                DiffLineKind::Remove => ('-', RED),
+            };
+            let line = String::from_utf8_lossy(line);
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            self.out.push_str(&format!("{}{prefix}{line}{reset}\n", paint(style)));
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Self::Out {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified;
+
+    #[test]
+    fn plain_diff_of_changed_and_new_files() {
+        assert_eq!(
+            unified("CHANGELOG.md", Some("a\nb\nc\n"), "a\nB\nc\n", false).unwrap(),
+            Some("--- a/CHANGELOG.md\n+++ b/CHANGELOG.md\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n".into())
+        );
+        assert_eq!(
+            unified("CHANGELOG.md", None, "new\n", false).unwrap(),
+            Some("--- /dev/null\n+++ b/CHANGELOG.md\n@@ -1,0 +1,1 @@\n+new\n".into())
+        );
+        assert_eq!(unified("CHANGELOG.md", Some("same\n"), "same\n", false).unwrap(), None);
+    }
+}
@@ -0,0 +1,79 @@
+//! Structured events about the progress of a release, for CI systems and release dashboards to track it.
+//!
+//! Events are logged with [`TARGET`] along with their fields, so they can be shown as they are with
+//! `--log-format json` and hidden otherwise, as they repeat what's logged for humans already.
+
+/// The log target of all events.
+pub const TARGET: &str = "smart_release::event";
+
+/// A step of a release that is performed for all crates at once, or for each crate.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Step {
+    /// Adjusting manifests and changelogs, and committing them.
+    Commit,
+    /// Publishing a crate.
+    Publish,
+    /// Creating the tag of a crate.
+    Tag,
+    /// Pushing the release commit and its tags.
+    Push,
+}
+
+impl Step {
+    fn as_str(self) -> &'static str {
+        match self {
+            Step::Commit => "commit",
+            Step::Publish => "publish",
+            Step::Tag => "tag",
+            Step::Push => "push",
+        }
+    }
+}
+
+/// Note that `step` is started, for the crate named `crate_name` at `version` if it's performed for each crate.
+pub(crate) fn step_started(step: Step, crate_and_version: Option<(&str, &semver::Version)>, dry_run: bool) {
+    match crate_and_version {
+        Some((name, version)) => tracing::info!(
+            target: TARGET,
+            event = "step-started", step = step.as_str(), "crate" = name, version = %version, dry_run,
+            "step-started"
+        ),
+        None => tracing::info!(
+            target: TARGET,
+            event = "step-started", step = step.as_str(), dry_run,
+            "step-started"
+        ),
+    }
+}
+
+/// Note that the crate named `name` was published at `version` to `registry`.
+pub(crate) fn crate_published(name: &str, version: &semver::Version, registry: &str, dry_run: bool) {
+    tracing::info!(
+        target: TARGET,
+        event = "crate-published", "crate" = name, version = %version, registry, dry_run,
+        "crate-published"
+    );
+}
+
+/// Note that the tag named `tag` was created for the release of the crate named `name`.
+pub(crate) fn tag_created(name: &str, tag: &str, dry_run: bool) {
+    tracing::info!(
+        target: TARGET,
+        event = "tag-created", "crate" = name, tag, dry_run,
+        "tag-created"
+    );
+}
+
+/// Note that the release commit and the tags named `tags` were pushed.
+pub(crate) fn push_done(tags: &[gix::refs::FullName], dry_run: bool) {
+    let tags = tags
+        .iter()
+        .map(|name| name.shorten().to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    tracing::info!(
+        target: TARGET,
+        event = "push-done", tags = tags.as_str(), dry_run,
+        "push-done"
+    );
+}
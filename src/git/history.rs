@@ -12,12 +12,13 @@ use gix::{
     traverse::commit::simple::CommitTimeOrder,
     Reference,
 };
+use prodash::{Count, Progress};
 
 use crate::{
     commit,
     commit::history::{Item, Segment},
     git::strip_tag_path,
-    utils::{component_to_bytes, is_tag_name, is_tag_version, tag_prefix},
+    utils::{component_to_bytes, tag_prefix},
     Context,
 };
 
@@ -28,7 +29,31 @@ pub enum SegmentScope {
     EntireHistory,
 }
 
-pub fn collect(repo: &gix::Repository) -> anyhow::Result<Option<commit::History>> {
+/// Control how the commit history is collected.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// If `true`, only follow the first parent of each commit and use the titles of merged pull requests
+    /// instead of the subjects of the individual commits they contain.
+    pub pull_request_titles: bool,
+    /// If `true`, issue references like `(#123)` in commit titles refer to pull requests, as is the case for
+    /// squash-merged pull requests.
+    pub title_references_are_pull_requests: bool,
+    /// If `true`, attribute generated changelog entries to the mailmap-resolved name of their commit author and count
+    /// the distinct authors of each release.
+    pub authors: bool,
+    /// Git-conventional types, like `remove`, of commits which are always considered breaking.
+    pub breaking_types: Vec<String>,
+    /// Git-conventional scopes of commits which are always considered breaking.
+    pub breaking_scopes: Vec<String>,
+    /// If not empty, only commits with a git-conventional scope matching one of these glob patterns, like `parser` or
+    /// `gix-*`, are attributed to crates.
+    pub scopes: Vec<String>,
+    /// If `true`, write a commit-graph with `git` if the repository doesn't have one yet, to accelerate this and all
+    /// future traversals.
+    pub write_commit_graph: bool,
+}
+
+pub fn collect(repo: &gix::Repository, opts: Options) -> anyhow::Result<Option<commit::History>> {
     use anyhow::Context;
     let mut handle = repo.clone();
     handle.object_cache_size(64 * 1024);
@@ -37,43 +62,75 @@ pub fn collect(repo: &gix::Repository) -> anyhow::Result<Option<commit::History>
         .try_peel_to_id()?
         .context("Refusing to operate on a unborn head.")?;
     let reference = match head.kind {
-        head::Kind::Detached { .. } => return Ok(None),
+        // Like in CI, where the commit to release is checked out without a branch.
+        head::Kind::Detached { target, .. } => gix::refs::Reference {
+            name: "HEAD".try_into()?,
+            target: gix::refs::Target::Object(target),
+            peeled: Some(target),
+        }
+        .attach(&handle),
         head::Kind::Unborn { .. } => unreachable!("handled above"),
         head::Kind::Symbolic(r) => r.attach(&handle),
     };
 
+    if opts.write_commit_graph {
+        write_commit_graph_if_missing(&handle)?;
+    }
+    // The walk uses the commit-graph on its own if it's enabled, but it also knows the trees of parent commits.
+    let graph = handle.commit_graph_if_enabled().unwrap_or_else(|err| {
+        tracing::debug!("Not using the commit-graph to traverse the history as it couldn't be opened: {err}");
+        None
+    });
+    let mailmap = handle.open_mailmap();
     let mut items = Vec::new();
     let mut data_by_tree_id = HashMap::default();
-    for commit_id in id
+    let mut walk = id
         .ancestors()
-        .sorting(gix::revision::walk::Sorting::ByCommitTime(CommitTimeOrder::NewestFirst))
-        .use_commit_graph(false)
-        .all()?
-    {
+        .sorting(gix::revision::walk::Sorting::ByCommitTime(CommitTimeOrder::NewestFirst));
+    if opts.pull_request_titles {
+        walk = walk.first_parent_only();
+    }
+    let mut progress = crate::progress::add_child("traverse history");
+    progress.init(None, Some(prodash::unit::label("commits")));
+    let start = std::time::Instant::now();
+    for commit_id in walk.all()? {
         let commit = commit_id?;
-        let (message, tree_id, parent_tree_id, commit_time) = {
-            let (message, tree_id, commit_time, parent_commit_id) = {
+        progress.inc();
+        let (message, tree_id, parent_tree_id, commit_time, author) = {
+            let (message, tree_id, commit_time, parent_commit_id, author) = {
                 let object = commit.object()?;
                 let commit = object.decode()?;
                 let parent = commit.parents().next();
+                let author = mailmap.resolve_cow(commit.author()?);
+                let author = commit::history::Author {
+                    name: author.name.to_string(),
+                    email: author.email.to_string(),
+                };
                 (
                     commit.message.to_vec(),
                     commit.tree(),
                     commit.committer()?.time()?,
                     parent,
+                    author,
                 )
             };
             (
                 message,
                 tree_id,
-                parent_commit_id.map(|id| id.attach(&handle).object().expect("present").to_commit_ref().tree()),
+                parent_commit_id.map(|id| {
+                    graph.as_ref().and_then(|graph| graph.commit_by_id(id)).map_or_else(
+                        || id.attach(&handle).object().expect("present").to_commit_ref().tree(),
+                        |parent| parent.root_tree_id().to_owned(),
+                    )
+                }),
                 commit_time,
+                author,
             )
         };
 
-        let message = match message.to_str() {
+        let message_text = match message.to_str() {
             Err(_) => {
-                log::warn!(
+                tracing::warn!(
                     "Commit message of {} could not be decoded to UTF-8 - ignored",
                     commit.id
                 );
@@ -85,14 +142,27 @@ pub fn collect(repo: &gix::Repository) -> anyhow::Result<Option<commit::History>
         if let Some(tree_id) = parent_tree_id {
             data_by_tree_id.insert(tree_id, handle.find_object(tree_id)?.data.to_owned());
         }
+        let mut message = opts
+            .pull_request_titles
+            .then(|| commit::Message::from_pull_request_merge(message_text))
+            .flatten()
+            .unwrap_or_else(|| commit::Message::from(message_text));
+        if opts.title_references_are_pull_requests {
+            message.issues_to_pull_requests();
+        }
+        if !message.breaking {
+            message.mark_breaking_by_type_or_scope(message_text, &opts.breaking_types, &opts.breaking_scopes);
+        }
         items.push(commit::history::Item {
             id: commit.id,
             commit_time,
-            message: commit::Message::from(message),
+            message,
             tree_id,
             parent_tree_id,
+            author,
         });
     }
+    progress.show_throughput(start);
 
     Ok(Some(commit::History {
         head: reference.detach(),
@@ -101,38 +171,61 @@ pub fn collect(repo: &gix::Repository) -> anyhow::Result<Option<commit::History>
     }))
 }
 
+/// Write a commit-graph for all commits reachable from references with `git`, unless `repo` has one already.
+fn write_commit_graph_if_missing(repo: &gix::Repository) -> anyhow::Result<()> {
+    let info_dir = repo.objects.store_ref().path().join("info");
+    if info_dir.join("commit-graph").is_file() || info_dir.join("commit-graphs").is_dir() {
+        return Ok(());
+    }
+    tracing::info!("Writing a commit-graph to accelerate traversing the history");
+    let mut cmd = std::process::Command::new(gix::path::env::exe_invocation());
+    cmd.args(["commit-graph", "write", "--reachable"])
+        .current_dir(repo.git_dir());
+    tracing::trace!("run {:?}", cmd);
+    let status = cmd.status()?;
+    if !status.success() {
+        tracing::warn!("Could not write a commit-graph, traversing the history without it");
+    }
+    Ok(())
+}
+
 /// Return the head reference followed by all tags affecting `crate_name` as per our tag name rules, ordered by ancestry.
+///
+/// Crates in submodules use the history of their submodule instead of `history`.
 pub fn crate_ref_segments<'h>(
     package: &Package,
-    ctx: &crate::Context,
+    ctx: &'h crate::Context,
     history: &'h commit::History,
     scope: SegmentScope,
 ) -> anyhow::Result<Vec<commit::history::Segment<'h>>> {
-    let tag_prefix = tag_prefix(package, &ctx.repo);
+    use anyhow::Context as _;
+    let (repo, history) = match ctx.submodule_of(package) {
+        Some(submodule) => (
+            &submodule.repo,
+            submodule
+                .history
+                .as_ref()
+                .with_context(|| format!("The submodule at '{}' has no history", submodule.dir))?,
+        ),
+        None => (&ctx.repo, history),
+    };
+    let tag_prefix = tag_prefix(package, repo);
     let mut tags_by_commit = {
-        let refs = ctx.repo.references()?;
-        match tag_prefix {
-            Some(prefix) => BTreeMap::from_iter(
-                refs.prefixed(format!("refs/tags/{prefix}-").as_str())?
-                    .peeled()?
-                    .filter_map(|r| r.ok().map(Reference::detach))
-                    .filter(|r| is_tag_name(prefix, strip_tag_path(r.name.as_ref())))
-                    .map(|r| {
-                        let t = r.peeled.expect("already peeled");
-                        (t, r)
-                    }),
-            ),
-            None => BTreeMap::from_iter(
-                refs.prefixed("refs/tags/")?
-                    .peeled()?
-                    .filter_map(|r| r.ok().map(Reference::detach))
-                    .filter(|r| is_tag_version(strip_tag_path(r.name.as_ref())))
-                    .map(|r| {
-                        let t = r.peeled.expect("already peeled");
-                        (t, r)
-                    }),
-            ),
-        }
+        let refs = repo.references()?;
+        BTreeMap::from_iter(
+            refs.prefixed("refs/tags/")?
+                .peeled()?
+                .filter_map(|r| r.ok().map(Reference::detach))
+                .filter(|r| {
+                    ctx.version_scheme
+                        .parse_tag_name(tag_prefix, strip_tag_path(r.name.as_ref()))
+                        .is_some()
+                })
+                .map(|r| {
+                    let t = r.peeled.expect("already peeled");
+                    (t, r)
+                }),
+        )
     };
 
     let mut segments = Vec::new();
@@ -144,10 +237,11 @@ pub fn crate_ref_segments<'h>(
     let dir = ctx.repo_relative_path(package);
     let mut filter = dir.map_or_else(
         || {
-            if ctx.meta.workspace_members.len() == 1 {
+            // A crate at the root of a submodule is all the submodule contains.
+            if ctx.meta.workspace_members.len() == 1 || ctx.submodule_of(package).is_some() {
                 Filter::None
             } else {
-                log::info!(
+                tracing::info!(
                     "{}: Tracking top-level crate's changes in multi-crate workspace through 'src/' directory only.",
                     package.name
                 );
@@ -173,7 +267,9 @@ pub fn crate_ref_segments<'h>(
 
     for item in &history.items {
         match tags_by_commit.remove(&item.id) {
-            None => add_item_if_package_changed(ctx, &mut segment, &mut filter, item, &history.data_by_tree_id)?,
+            None => {
+                add_item_if_package_changed(ctx, package, &mut segment, &mut filter, item, &history.data_by_tree_id)?
+            }
             Some(next_ref) => {
                 match scope {
                     SegmentScope::EntireHistory => {
@@ -190,14 +286,14 @@ pub fn crate_ref_segments<'h>(
                         return Ok(segments);
                     }
                 }
-                add_item_if_package_changed(ctx, &mut segment, &mut filter, item, &history.data_by_tree_id)?
+                add_item_if_package_changed(ctx, package, &mut segment, &mut filter, item, &history.data_by_tree_id)?
             }
         }
     }
     segments.push(segment);
 
     if matches!(scope, SegmentScope::EntireHistory) && !tags_by_commit.is_empty() {
-        log::warn!(
+        tracing::warn!(
             "{}: The following tags were not encountered during commit graph traversal: {}",
             package.name,
             tags_by_commit
@@ -220,8 +316,54 @@ enum Filter<'a> {
     Slow { components: Vec<&'a [u8]> },
 }
 
+/// Add `item` to `segment` if it changed `package`, or if its scope is configured to belong to `package`.
+/// Items with a scope that belongs to another crate, or that doesn't match the scopes to include, are never added.
 fn add_item_if_package_changed<'a>(
     ctx: &Context,
+    package: &Package,
+    segment: &mut Segment<'a>,
+    filter: &mut Filter<'_>,
+    item: &'a Item,
+    data_by_tree_id: &HashMap<gix::ObjectId, Vec<u8>>,
+) -> anyhow::Result<()> {
+    if !scope_matches(item.message.scope.as_deref(), &ctx.history_options.scopes) {
+        return Ok(());
+    }
+    let scoped_crate = item
+        .message
+        .scope
+        .as_deref()
+        .and_then(|scope| ctx.crates_by_scope.get(scope));
+    match scoped_crate {
+        Some(name) if name.as_str() != package.name.as_str() => Ok(()),
+        Some(_) => {
+            let num_items = segment.history.len();
+            add_item_if_path_changed(ctx.repo_of(package), segment, filter, item, data_by_tree_id)?;
+            if segment.history.len() == num_items {
+                segment.history.push(item);
+            }
+            Ok(())
+        }
+        None => add_item_if_path_changed(ctx.repo_of(package), segment, filter, item, data_by_tree_id),
+    }
+}
+
+/// Return `true` if `scope` matches one of the glob `patterns`, or if there are no patterns to match.
+fn scope_matches(scope: Option<&str>, patterns: &[String]) -> bool {
+    patterns.is_empty()
+        || scope.is_some_and(|scope| {
+            patterns.iter().any(|pattern| {
+                gix::glob::wildmatch(
+                    pattern.as_str().into(),
+                    scope.into(),
+                    gix::glob::wildmatch::Mode::empty(),
+                )
+            })
+        })
+}
+
+fn add_item_if_path_changed<'a>(
+    repo: &gix::Repository,
     segment: &mut Segment<'a>,
     filter: &mut Filter<'_>,
     item: &'a Item,
@@ -251,7 +393,7 @@ fn add_item_if_package_changed<'a>(
                             .filter_map(Result::ok)
                             .find(|e| e.oid == current.oid)
                     }) {
-                        log::debug!(
+                        tracing::debug!(
                             "Tracking package named {:?} as {:?}",
                             name.as_bstr(),
                             prev_item.filename
@@ -264,12 +406,12 @@ fn add_item_if_package_changed<'a>(
             };
         }
         Filter::Slow { ref components } => {
-            let mut repo = ctx.repo.clone();
+            let mut repo = repo.clone();
             repo.object_cache_size(1024 * 1024);
-            let current = gix::Tree::from_data(item.id, data_by_tree_id[&item.tree_id].to_owned(), &ctx.repo)
+            let current = gix::Tree::from_data(item.id, data_by_tree_id[&item.tree_id].to_owned(), &repo)
                 .peel_to_entry(components.iter().copied())?;
             let parent = match item.parent_tree_id {
-                Some(tree_id) => gix::Tree::from_data(tree_id, data_by_tree_id[&tree_id].to_owned(), &ctx.repo)
+                Some(tree_id) => gix::Tree::from_data(tree_id, data_by_tree_id[&tree_id].to_owned(), &repo)
                     .peel_to_entry(components.iter().copied())?,
                 None => None,
             };
@@ -286,3 +428,18 @@ fn add_item_if_package_changed<'a>(
     };
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::scope_matches;
+
+    #[test]
+    fn scopes_match_any_pattern_and_unscoped_commits_only_without_patterns() {
+        let patterns = ["parser".to_string(), "gix-*".to_string()];
+        assert!(scope_matches(Some("parser"), &patterns));
+        assert!(scope_matches(Some("gix-ref"), &patterns));
+        assert!(!scope_matches(Some("parser-utils"), &patterns));
+        assert!(!scope_matches(None, &patterns));
+        assert!(scope_matches(None, &[]));
+    }
+}
@@ -1,4 +1,4 @@
-use std::process::Command;
+use std::{path::Path, process::Command};
 
 use anyhow::{anyhow, bail, Context};
 use cargo_metadata::{camino::Utf8Path, Package};
@@ -11,6 +11,7 @@ use gix::{
 use crate::utils::{component_to_bytes, tag_name};
 
 pub mod history;
+pub mod push;
 
 #[derive(Clone, Debug)]
 pub enum PackageChangeKind {
@@ -22,26 +23,42 @@ pub enum PackageChangeKind {
 }
 
 pub fn change_since_last_release(package: &Package, ctx: &crate::Context) -> anyhow::Result<Option<PackageChangeKind>> {
-    let version_tag_name = tag_name(package, &package.version, &ctx.repo);
-    let mut tag_ref = match ctx.repo.try_find_reference(&version_tag_name)? {
+    let mut tag_ref = match find_release_tag(package, &package.version, ctx)? {
         None => {
             return Ok(Some(PackageChangeKind::Untagged {
-                wanted_tag_name: version_tag_name,
+                wanted_tag_name: tag_name(package, &package.version, ctx),
             }));
         }
         Some(r) => r,
     };
+    assure_tag_is_trusted(package, &tag_ref, ctx)?;
+    let repo = ctx.repo_of(package);
     let repo_relative_crate_dir = ctx.repo_relative_path(package);
-    Ok(match ctx.repo.head()?.try_into_peeled_id()? {
+    let ignore_patterns = ignored_paths(package);
+    Ok(match repo.head()?.try_into_peeled_id()? {
         Some(current_commit) => {
             let released_target = tag_ref.peel_to_id()?;
 
             match repo_relative_crate_dir
                 // If it's a top-level crate, use the src-directory for now
                 // KEEP THIS IN SYNC with gix::create_ref_history()!
-                .or_else(|| (ctx.meta.workspace_members.len() != 1).then(|| Utf8Path::new("src")))
-            {
-                None => (current_commit != released_target).then_some(PackageChangeKind::ChangedOrNew),
+                .or_else(|| {
+                    (ctx.meta.workspace_members.len() != 1 && ctx.submodule_of(package).is_none())
+                        .then(|| Utf8Path::new("src"))
+                }) {
+                None => {
+                    let changed = current_commit != released_target
+                        && !only_ignored_paths_changed(
+                            released_target.object()?.peel_to_tree()?,
+                            current_commit.object()?.peel_to_tree()?,
+                            "",
+                            &ignore_patterns,
+                        )?;
+                    if !changed && current_commit != released_target {
+                        tracing::info!("'{}' only has changes in ignored paths", package.name);
+                    }
+                    changed.then_some(PackageChangeKind::ChangedOrNew)
+                }
                 Some(dir) => {
                     let components = dir.components().map(component_to_bytes);
                     let current_dir_id = current_commit
@@ -58,9 +75,22 @@ pub fn change_since_last_release(package: &Package, ctx: &crate::Context) -> any
                         .peel_to_entry(components)?
                     {
                         let released_dir_id = released_dir_entry.object_id();
-                        (released_dir_id != current_dir_id).then_some(PackageChangeKind::ChangedOrNew)
+                        if released_dir_id == current_dir_id {
+                            None
+                        } else if only_ignored_paths_changed(
+                            repo.find_tree(released_dir_id)?,
+                            repo.find_tree(current_dir_id)?,
+                            // Patterns are relative to the crate, which for top-level crates isn't the directory we look at.
+                            if repo_relative_crate_dir.is_none() { "src/" } else { "" },
+                            &ignore_patterns,
+                        )? {
+                            tracing::info!("'{}' only has changes in ignored paths", package.name);
+                            None
+                        } else {
+                            Some(PackageChangeKind::ChangedOrNew)
+                        }
                     } else {
-                        log::warn!("Expected path '{dir}' to exist in the last released commit `{released_target}` but it doesn't. Was it moved from a different directory? The generated changelog may miss some changes.");
+                        tracing::warn!("Expected path '{dir}' to exist in the last released commit `{released_target}` but it doesn't. Was it moved from a different directory? The generated changelog may miss some changes.");
                         Some(PackageChangeKind::ChangedOrNew)
                     }
                 }
@@ -70,15 +100,175 @@ pub fn change_since_last_release(package: &Package, ctx: &crate::Context) -> any
     })
 }
 
-pub fn assure_clean_working_tree() -> anyhow::Result<()> {
-    let tracked_changed = !Command::new(gix::path::env::exe_invocation())
+/// Fail unless `tag_ref` of `package` is an annotated tag with a signature that `git verify-tag` trusts, if the tags
+/// of previous releases are to be verified, so releases aren't based on tampered history.
+pub fn assure_tag_is_trusted(
+    package: &Package,
+    tag_ref: &gix::Reference<'_>,
+    ctx: &crate::Context,
+) -> anyhow::Result<()> {
+    if !ctx.verify_tags {
+        return Ok(());
+    }
+    let name = tag_ref.name().as_bstr().to_str()?;
+    let mut cmd = Command::new(gix::path::env::exe_invocation());
+    cmd.arg("--git-dir")
+        .arg(ctx.repo_of(package).git_dir())
+        .args(["verify-tag", name]);
+    tracing::trace!("run {:?}", cmd);
+    let out = cmd.output().context("Could not run 'git verify-tag'")?;
+    if !out.status.success() {
+        bail!(
+            "The signature of the tag '{}' of the last release of '{}' couldn't be verified, refusing to base a release on it: {}",
+            tag_ref.name().shorten(),
+            package.name,
+            out.stderr.to_str_lossy().trim()
+        );
+    }
+    tracing::debug!("Verified the signature of tag '{}'", tag_ref.name().shorten());
+    Ok(())
+}
+
+/// Return the tag of the release of `version` of `package`, which usually has the name we would give it, but may also be
+/// any other tag that the version scheme recognizes, like ones matching custom tag patterns.
+pub fn find_release_tag<'repo>(
+    package: &Package,
+    version: &semver::Version,
+    ctx: &'repo crate::Context,
+) -> anyhow::Result<Option<gix::Reference<'repo>>> {
+    let repo = ctx.repo_of(package);
+    if let Some(tag_ref) = repo.try_find_reference(&tag_name(package, version, ctx))? {
+        return Ok(Some(tag_ref));
+    }
+    let tag_prefix = crate::utils::tag_prefix(package, repo);
+    let refs = repo.references()?;
+    for tag_ref in refs.prefixed("refs/tags/")? {
+        let tag_ref = tag_ref.map_err(|err| anyhow!(err))?;
+        if ctx
+            .version_scheme
+            .parse_tag_name(tag_prefix, strip_tag_path(tag_ref.name()))
+            .is_some_and(|tagged| tagged == *version)
+        {
+            return Ok(Some(tag_ref));
+        }
+    }
+    Ok(None)
+}
+
+/// Return the tag of the latest release of `package` before `version`, which is the one a release of `version` is based on.
+pub fn previous_release_tag<'repo>(
+    package: &Package,
+    version: &semver::Version,
+    ctx: &'repo crate::Context,
+) -> anyhow::Result<Option<gix::Reference<'repo>>> {
+    let repo = ctx.repo_of(package);
+    let tag_prefix = crate::utils::tag_prefix(package, repo);
+    let mut previous: Option<(semver::Version, gix::Reference<'repo>)> = None;
+    for tag_ref in repo.references()?.prefixed("refs/tags/")? {
+        let tag_ref = tag_ref.map_err(|err| anyhow!(err))?;
+        let Some(tagged) = ctx
+            .version_scheme
+            .parse_tag_name(tag_prefix, strip_tag_path(tag_ref.name()))
+        else {
+            continue;
+        };
+        if tagged < *version && previous.as_ref().is_none_or(|(latest, _)| tagged > *latest) {
+            previous = Some((tagged, tag_ref));
+        }
+    }
+    Ok(previous.map(|(_, tag_ref)| tag_ref))
+}
+
+/// Return the contents of the file at `path`, relative to the workspace root, as it was in the last release of `package`,
+/// or `None` if there is no tag for its current version or the file didn't exist back then.
+pub fn file_at_last_release(
+    package: &Package,
+    path: &Utf8Path,
+    ctx: &crate::Context,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let Some(mut tag_ref) = find_release_tag(package, &package.version, ctx)? else {
+        return Ok(None);
+    };
+    let mut tree = tag_ref.peel_to_id()?.object()?.peel_to_tree()?;
+    let Some(entry) = tree.peel_to_entry(path.components().map(component_to_bytes))? else {
+        return Ok(None);
+    };
+    Ok(Some(entry.object()?.detach().data))
+}
+
+/// Read the `ignore` patterns from `[package.metadata.smart-release]`, which match paths relative to the crate directory
+/// that don't make a crate releasable when changed, like `benches/**` or `*.md`.
+fn ignored_paths(package: &Package) -> Vec<String> {
+    package
+        .metadata
+        .get("smart-release")
+        .and_then(|config| config.get("ignore"))
+        .and_then(serde_json::Value::as_array)
+        .map(|patterns| {
+            patterns
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(ToOwned::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return `true` if all files that changed between `released` and `current` match one of `patterns`, after prefixing
+/// their path with `prefix`.
+fn only_ignored_paths_changed(
+    released: gix::Tree<'_>,
+    current: gix::Tree<'_>,
+    prefix: &str,
+    patterns: &[String],
+) -> anyhow::Result<bool> {
+    if patterns.is_empty() {
+        return Ok(false);
+    }
+    let mut only_ignored = true;
+    released
+        .changes()?
+        .options(|opts| {
+            opts.track_path().track_rewrites(None);
+        })
+        .for_each_to_obtain_tree(&current, |change| {
+            if change.entry_mode().is_tree() {
+                return Ok::<_, std::convert::Infallible>(std::ops::ControlFlow::Continue(()));
+            }
+            let path = format!("{prefix}{}", change.location());
+            let is_ignored = patterns.iter().any(|pattern| {
+                gix::glob::wildmatch(
+                    pattern.as_bytes().as_bstr(),
+                    path.as_bytes().as_bstr(),
+                    gix::glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL,
+                )
+            });
+            Ok(if is_ignored {
+                std::ops::ControlFlow::Continue(())
+            } else {
+                only_ignored = false;
+                std::ops::ControlFlow::Break(())
+            })
+        })?;
+    Ok(only_ignored)
+}
+
+/// Fail if there are changed or untracked files in the worktree, except for those at or below one of the
+/// repository-relative `allowed` paths.
+pub fn assure_clean_working_tree(allowed: &[std::path::PathBuf]) -> anyhow::Result<()> {
+    let is_unexpected = |path: &&str| !allowed.iter().any(|allowed| Path::new(path).starts_with(allowed));
+    let changed = Command::new(gix::path::env::exe_invocation())
         .arg("diff")
         .arg("HEAD")
-        .arg("--exit-code")
         .arg("--name-only")
-        .status()?
-        .success();
-    if tracked_changed {
+        .output()?;
+    if !changed.status.success() {
+        bail!(
+            "Could not list the changes in the working tree: {}",
+            changed.stderr.to_str_lossy().trim()
+        )
+    }
+    if changed.stdout.to_str_lossy().lines().any(|path| is_unexpected(&path)) {
         bail!("Detected working tree changes. Please commit beforehand as otherwise these would be committed as part of manifest changes, or use --allow-dirty to force it.")
     }
 
@@ -86,17 +276,22 @@ pub fn assure_clean_working_tree() -> anyhow::Result<()> {
         .arg("ls-files")
         .arg("--exclude-standard")
         .arg("--others")
+        .arg("--full-name")
         .output()?
         .stdout;
-    if !untracked.trim().is_empty() {
-        let err = anyhow!(gix::bstr::BString::from(untracked));
+    let untracked = untracked.to_str_lossy();
+    let untracked: Vec<_> = untracked.lines().filter(is_unexpected).collect();
+    if !untracked.is_empty() {
+        let err = anyhow!(untracked.join("\n"));
         return Err(err.context("Found untracked files which would possibly be packaged when publishing."));
     }
     Ok(())
 }
 
-pub fn has_tracked_modifications(repo: &gix::Repository) -> anyhow::Result<bool> {
-    let mut status = repo
+/// Return the repository-relative paths of tracked files which were modified, removed or changed their type in the worktree.
+pub fn tracked_modifications(repo: &gix::Repository) -> anyhow::Result<Vec<gix::bstr::BString>> {
+    use gix::status::index_worktree::iter::Summary;
+    let status = repo
         .status(gix::progress::Discard)?
         .untracked_files(gix::status::UntrackedFiles::None)
         .index_worktree_rewrites(None)
@@ -104,38 +299,88 @@ pub fn has_tracked_modifications(repo: &gix::Repository) -> anyhow::Result<bool>
         .into_index_worktree_iter(None)
         .context("While creating git status for tracked modifications")?;
 
-    for item in &mut status {
-        match item.context("While reading git status for tracked modifications")? {
-            item if item.summary().is_some() => return Ok(true),
-            _ => {}
+    let mut paths = Vec::new();
+    for item in status {
+        let item = item.context("While reading git status for tracked modifications")?;
+        if matches!(
+            item.summary(),
+            Some(Summary::Modified | Summary::Removed | Summary::TypeChange)
+        ) {
+            paths.push(item.rela_path().to_owned());
         }
     }
-    Ok(false)
+    Ok(paths)
 }
 
-pub fn has_staged_changes(repo: &gix::Repository) -> anyhow::Result<bool> {
-    let head_tree_id = repo.head_tree_id_or_empty()?;
+/// Return `true` if the worktree of `repo` is a sparse checkout, which may not contain all tracked files.
+pub fn is_sparse_checkout(repo: &gix::Repository) -> bool {
+    repo.config_snapshot().boolean("core.sparseCheckout").unwrap_or(false)
+}
+
+/// Fail if `path` in the worktree of `repo` doesn't exist only because it's outside the sparse checkout, as it would be
+/// mistaken for a new file and overwritten otherwise.
+pub fn assure_path_is_checked_out(repo: &gix::Repository, path: &std::path::Path) -> anyhow::Result<()> {
+    if !is_sparse_checkout(repo) || path.exists() {
+        return Ok(());
+    }
+    let Some(workdir) = repo.workdir() else {
+        return Ok(());
+    };
+    let Ok(rela_path) = path.strip_prefix(workdir) else {
+        return Ok(());
+    };
+    let rela_path = gix::path::to_unix_separators_on_windows(gix::path::into_bstr(rela_path));
     let index = repo.index_or_empty()?;
-    let mut has_staged_changes = false;
-    repo.tree_index_status(
-        &head_tree_id,
-        &index,
-        None,
-        gix::status::tree_index::TrackRenames::Disabled,
-        |_, _, _| {
-            has_staged_changes = true;
-            Ok::<_, std::convert::Infallible>(std::ops::ControlFlow::Break(()))
-        },
-    )?;
-    Ok(has_staged_changes)
-}
-
-pub fn remote_url(repo: &gix::Repository) -> anyhow::Result<Option<gix::Url>> {
+    let skipped = index
+        .entry_by_path(rela_path.as_ref())
+        .is_some_and(|entry| entry.flags.contains(gix::index::entry::Flags::SKIP_WORKTREE));
+    if skipped {
+        // Cone mode only takes directories, while other patterns can add the file itself.
+        let pattern = if repo
+            .config_snapshot()
+            .boolean("core.sparseCheckoutCone")
+            .unwrap_or(true)
+        {
+            Path::new(gix::path::from_bstr(rela_path.as_ref()).as_ref())
+                .parent()
+                .map(|dir| dir.display().to_string())
+                .filter(|dir| !dir.is_empty())
+                .unwrap_or_else(|| ".".into())
+        } else {
+            format!("/{rela_path}")
+        };
+        bail!(
+            "'{rela_path}' is tracked but not checked out as it's outside the sparse checkout. Run 'git sparse-checkout add {pattern}' and try again."
+        );
+    }
+    Ok(())
+}
+
+/// Return `true` if `path` in the worktree of `repo` is in its index.
+pub fn is_tracked(repo: &gix::Repository, path: &std::path::Path) -> anyhow::Result<bool> {
+    let Some(rela_path) = repo.workdir().and_then(|workdir| path.strip_prefix(workdir).ok()) else {
+        return Ok(false);
+    };
+    let rela_path = gix::path::to_unix_separators_on_windows(gix::path::into_bstr(rela_path));
+    Ok(repo.index_or_empty()?.entry_by_path(rela_path.as_ref()).is_some())
+}
+
+/// Return the remote to push `HEAD` to, or the default remote if `HEAD` is detached.
+pub fn push_remote(repo: &gix::Repository) -> anyhow::Result<Option<gix::Remote<'_>>> {
     Ok(repo
         .head()?
         .into_remote(gix::remote::Direction::Push)
-        .transpose()?
-        .and_then(|r| r.url(gix::remote::Direction::Push).map(ToOwned::to_owned)))
+        .or_else(|| repo.find_default_remote(gix::remote::Direction::Push))
+        .transpose()?)
+}
+
+/// Return the push URL of the remote named `name`, or of the push remote of `HEAD` if `None`.
+pub fn remote_url(repo: &gix::Repository, name: Option<&str>) -> anyhow::Result<Option<gix::Url>> {
+    let remote = match name {
+        Some(name) => Some(repo.find_remote(name)?),
+        None => push_remote(repo)?,
+    };
+    Ok(remote.and_then(|r| r.url(gix::remote::Direction::Push).map(ToOwned::to_owned)))
 }
 
 pub fn author() -> anyhow::Result<gix::actor::Signature> {
@@ -0,0 +1,616 @@
+//! Push refs with `gix` by speaking the send-pack side of the protocol with the `git-receive-pack` of the remote, so
+//! no `git` binary is needed locally.
+use std::{io::Write, sync::atomic::AtomicBool};
+
+use anyhow::{anyhow, bail, Context};
+use gix::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    prelude::ObjectIdExt,
+    protocol::transport::{
+        client::{
+            blocking_io::{connect, ExtendedBufRead, Transport},
+            MessageKind, TransportWithoutIO, WriteMode,
+        },
+        packetline::{self, PacketLineRef},
+        Protocol, Service,
+    },
+    refs::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    remote::Direction,
+};
+use gix_pack::data::output;
+
+/// A ref to set on the remote.
+#[derive(Debug, Clone)]
+pub struct Update {
+    /// The name of what is pushed, like `HEAD` or `refs/tags/v1.0.0`, to report the outcome with.
+    pub local_ref: String,
+    /// The object to set the remote ref to.
+    pub id: gix::ObjectId,
+    /// The full name of the ref on the remote, like `refs/heads/main`.
+    pub remote_ref: String,
+    /// The object the remote ref must point to so it's replaced even if that isn't a fast-forward, or the null id if
+    /// it must not exist. If `None`, branches can only be fast-forwarded and existing tags aren't changed.
+    pub lease: Option<gix::ObjectId>,
+}
+
+/// A ref update as it's sent to the remote, like it's passed to the `pre-push` hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub local_ref: String,
+    /// The object the remote ref is set to.
+    pub new: gix::ObjectId,
+    pub remote_ref: String,
+    /// The object the remote ref points to now, or the null id if it doesn't exist.
+    pub old: gix::ObjectId,
+}
+
+/// A push certificate to sign the push with, like `git push --signed` creates.
+pub struct Certificate<'a> {
+    /// If `true`, fail if the remote doesn't accept push certificates instead of pushing without one.
+    pub required: bool,
+    /// The identity of the person pushing, along with the time of the push.
+    pub pusher: gix::actor::Signature,
+    /// Return the detached signature of the given certificate.
+    pub sign: &'a dyn Fn(&[u8]) -> anyhow::Result<BString>,
+}
+
+/// How to push.
+pub struct Options<'a> {
+    /// Update all refs or none of them, which fails if the remote doesn't support it.
+    pub atomic: bool,
+    /// Sign the push with a push certificate.
+    pub certificate: Option<Certificate<'a>>,
+    /// Called with the ref updates right before they are sent, to be able to prevent the push like the `pre-push` hook.
+    pub before_sending: &'a dyn Fn(&[Command]) -> anyhow::Result<()>,
+}
+
+/// The outcome of pushing a single ref.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushedRef {
+    pub local_ref: String,
+    pub remote_ref: String,
+    pub status: PushStatus,
+    /// What happened, like `[new tag]` or `1410442..2bd3f91`, along with the reason if it was rejected.
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushStatus {
+    Updated,
+    UpToDate,
+    Rejected,
+}
+
+/// What happened when pushing.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The outcome for each of the updates, in order.
+    pub refs: Vec<PushedRef>,
+    /// What the remote printed while receiving the push, like the output of its hooks.
+    pub remote_messages: String,
+    /// If `true`, a push certificate was sent along with the push.
+    pub signed: bool,
+}
+
+/// Push `updates` to `remote` as configured in `repo`, and update the remote-tracking branches of the branches that
+/// were pushed.
+///
+/// Updates which the remote would reject anyway, like those which aren't fast-forwards, are rejected without sending
+/// them, and none are sent with [`atomic`](Options::atomic) pushes then. Only refs that change are sent.
+pub fn push(
+    repo: &gix::Repository,
+    remote: &gix::Remote<'_>,
+    updates: Vec<Update>,
+    opts: Options<'_>,
+) -> anyhow::Result<Outcome> {
+    let (url, _version) = remote.sanitized_url_and_version(Direction::Push)?;
+    let trace = repo.config_snapshot().boolean("gitoxide.tracePacket").unwrap_or(false);
+    let ssh = if url.scheme == gix::url::Scheme::Ssh {
+        repo.ssh_connect_options()?
+    } else {
+        Default::default()
+    };
+    // `git-receive-pack` doesn't speak protocol V2.
+    let mut transport = connect::connect(
+        url.clone(),
+        connect::Options {
+            version: Protocol::V1,
+            ssh,
+            trace,
+        },
+    )?;
+    let url_string = url.to_bstring();
+    if let Some(options) = repo.transport_options(url_string.as_bstr(), remote.name().map(|name| name.as_bstr()))? {
+        transport.configure(&*options).map_err(|err| anyhow!(err))?;
+    }
+    let (mut credentials, _, prompt) = repo.config_snapshot().credential_helpers(url.clone())?;
+    let handshake = gix::protocol::handshake(
+        &mut transport,
+        Service::ReceivePack,
+        move |action| credentials.invoke(action, prompt.clone()),
+        Vec::new(),
+        &mut gix::progress::Discard,
+    )
+    .with_context(|| format!("Could not connect to '{url_string}' to push"))?;
+
+    let mut on_remote = Vec::new();
+    let mut remote_has = Vec::new();
+    for remote_ref in handshake.refs.iter().flatten() {
+        let (name, Some(target), peeled) = remote_ref.unpack() else {
+            continue;
+        };
+        remote_has.push(peeled.unwrap_or(target).to_owned());
+        // `.have` lines name objects of alternates the remote has, without being refs.
+        if name != ".have" {
+            on_remote.push((name.to_owned(), target.to_owned()));
+        }
+    }
+
+    let null = gix::ObjectId::null(repo.object_hash());
+    let mut outcome = Outcome::default();
+    let mut commands = Vec::new();
+    for update in updates {
+        let old = on_remote
+            .iter()
+            .find_map(|(name, id)| (*name == update.remote_ref).then_some(*id));
+        let (status, summary) = match check(repo, &update, old)? {
+            Check::Send { old } => {
+                commands.push((
+                    outcome.refs.len(),
+                    Command {
+                        local_ref: update.local_ref.clone(),
+                        new: update.id,
+                        remote_ref: update.remote_ref.clone(),
+                        old: old.unwrap_or(null),
+                    },
+                ));
+                (PushStatus::Updated, String::new())
+            }
+            Check::UpToDate => (PushStatus::UpToDate, "[up to date]".into()),
+            Check::Rejected(reason) => (PushStatus::Rejected, format!("[rejected] ({reason})")),
+        };
+        outcome.refs.push(PushedRef {
+            local_ref: update.local_ref,
+            remote_ref: update.remote_ref,
+            status,
+            summary,
+        });
+    }
+    let rejected = outcome.refs.iter().any(|pushed| pushed.status == PushStatus::Rejected);
+    if commands.is_empty() || (opts.atomic && rejected) {
+        for (index, _) in commands {
+            outcome.refs[index].status = PushStatus::Rejected;
+            outcome.refs[index].summary = "[rejected] (atomic push failed)".into();
+        }
+        gix::protocol::indicate_end_of_interaction(&mut transport, trace)?;
+        return Ok(outcome);
+    }
+
+    let capabilities = &handshake.capabilities;
+    let mut requested = vec!["report-status"];
+    let side_band = capabilities.contains("side-band-64k");
+    if side_band {
+        requested.push("side-band-64k");
+    }
+    if opts.atomic {
+        if !capabilities.contains("atomic") {
+            bail!("The remote doesn't support atomic pushes. Use --no-atomic-push to push refs one by one.");
+        }
+        requested.push("atomic");
+    }
+    let nonce = capabilities
+        .capability("push-cert")
+        .and_then(|capability| capability.value().map(ToOwned::to_owned));
+    let certificate = match (opts.certificate, nonce) {
+        (Some(certificate), Some(nonce)) => Some((certificate, nonce)),
+        (Some(certificate), None) if certificate.required => {
+            bail!("The remote doesn't support signed pushes. Use --signed-push=if-asked to push without a push certificate to such remotes.")
+        }
+        (Some(_), None) | (None, _) => None,
+    };
+    let sent: Vec<_> = commands.iter().map(|(_, command)| command.clone()).collect();
+    (opts.before_sending)(&sent)?;
+
+    let mut lines: Vec<BString> = Vec::new();
+    let requested = requested.join(" ");
+    match certificate {
+        Some((certificate, nonce)) => {
+            let mut url = url.clone();
+            url.set_user(None);
+            url.set_password(None);
+            let cert = certificate_text(&certificate.pusher, url.to_bstring().as_bstr(), nonce.as_bstr(), &sent)?;
+            let signature = (certificate.sign)(&cert).context("Could not sign the push certificate")?;
+            lines.push(format!("push-cert\0{requested}").into());
+            lines.extend(
+                cert.lines_with_terminator()
+                    .chain(signature.lines_with_terminator())
+                    .map(|line| line.into()),
+            );
+            lines.push("push-cert-end\n".into());
+            outcome.signed = true;
+        }
+        None => {
+            for (index, command) in sent.iter().enumerate() {
+                let mut line = BString::from(format!("{} {} {}", command.old, command.new, command.remote_ref));
+                if index == 0 {
+                    line.push(0);
+                    line.extend_from_slice(requested.as_bytes());
+                }
+                lines.push(line);
+            }
+        }
+    }
+
+    let mut writer = transport.request(WriteMode::Binary, MessageKind::Flush, trace)?;
+    for line in &lines {
+        writer.write_all(line)?;
+    }
+    writer.write_message(MessageKind::Flush)?;
+    let (mut raw, mut reader) = writer.into_parts();
+    let tips: Vec<_> = sent
+        .iter()
+        .map(|command| command.new)
+        .filter(|id| !id.is_null())
+        .collect();
+    if !tips.is_empty() {
+        write_pack(repo, tips, remote_has, &mut raw)?;
+    }
+    raw.flush()?;
+    // The remote only answers once it has everything, which may require the request to be finished.
+    drop(raw);
+
+    reader.reset(Protocol::V1);
+    let mut report = Vec::new();
+    let mut sideband_data = Vec::new();
+    let mut messages = RemoteMessages::default();
+    while let Some(line) = reader.readline() {
+        let Some(line) = line??.as_slice() else {
+            continue;
+        };
+        if !side_band {
+            report.push(line.into());
+            continue;
+        }
+        match line.split_first() {
+            Some((1, data)) => sideband_data.extend_from_slice(data),
+            Some((2, text)) => messages.push(text),
+            Some((3, text)) => bail!("The remote failed to receive the push: {}", text.to_str_lossy().trim()),
+            _ => {}
+        }
+    }
+    drop(reader);
+    if side_band {
+        report = decode_lines(&sideband_data)?;
+    }
+    outcome.remote_messages = messages.finish();
+
+    let report = parse_report(&report)?;
+    if let Some(Err(err)) = &report.unpack {
+        bail!("The remote couldn't unpack the pushed objects: {err}");
+    }
+    let mut pushed = Vec::new();
+    for (index, command) in &commands {
+        let pushed_ref = &mut outcome.refs[*index];
+        match report.refs.iter().find(|(name, _)| *name == command.remote_ref) {
+            Some((_, Ok(()))) => {
+                pushed_ref.summary = summary(repo, command);
+                pushed.push(command);
+            }
+            Some((_, Err(reason))) => {
+                pushed_ref.status = PushStatus::Rejected;
+                pushed_ref.summary = format!("[remote rejected] ({reason})");
+            }
+            None => {
+                pushed_ref.status = PushStatus::Rejected;
+                pushed_ref.summary = "[remote failure] (remote failed to report status)".into();
+            }
+        }
+    }
+    update_remote_tracking_refs(repo, remote, &pushed)?;
+    Ok(outcome)
+}
+
+enum Check {
+    Send { old: Option<gix::ObjectId> },
+    UpToDate,
+    Rejected(&'static str),
+}
+
+/// Decide if `update` can be sent to the remote, where its ref points to `old` if it exists, like `git push` would.
+fn check(repo: &gix::Repository, update: &Update, old: Option<gix::ObjectId>) -> anyhow::Result<Check> {
+    if old == Some(update.id) {
+        return Ok(Check::UpToDate);
+    }
+    if let Some(lease) = update.lease {
+        return Ok(
+            if old.unwrap_or_else(|| gix::ObjectId::null(repo.object_hash())) == lease {
+                Check::Send { old }
+            } else {
+                Check::Rejected("stale info")
+            },
+        );
+    }
+    let Some(old_id) = old else {
+        return Ok(Check::Send { old });
+    };
+    if update.remote_ref.starts_with("refs/tags/") {
+        return Ok(Check::Rejected("already exists"));
+    }
+    if !repo.has_object(old_id) {
+        return Ok(Check::Rejected("fetch first"));
+    }
+    let is_fast_forward = repo
+        .rev_walk([update.id])
+        .all()?
+        .map(|info| info.map(|info| info.id))
+        .collect::<Result<Vec<_>, _>>()?
+        .contains(&old_id);
+    Ok(if is_fast_forward {
+        Check::Send { old }
+    } else {
+        Check::Rejected("non-fast-forward")
+    })
+}
+
+/// Describe the successful `command` like `git push` does, like `[new tag]` or `1410442..2bd3f91`.
+fn summary(repo: &gix::Repository, command: &Command) -> String {
+    if command.old.is_null() {
+        return if command.remote_ref.starts_with("refs/tags/") {
+            "[new tag]"
+        } else if command.remote_ref.starts_with("refs/heads/") {
+            "[new branch]"
+        } else {
+            "[new reference]"
+        }
+        .into();
+    }
+    let old = command.old.attach(repo).shorten_or_id();
+    let new = command.new.attach(repo).shorten_or_id();
+    let is_fast_forward = repo
+        .rev_walk([command.new])
+        .all()
+        .is_ok_and(|mut walk| walk.any(|info| info.is_ok_and(|info| info.id == command.old)));
+    if is_fast_forward {
+        format!("{old}..{new}")
+    } else {
+        format!("+ {old}...{new} (forced update)")
+    }
+}
+
+/// Return the certificate to sign for `commands`, as created by `git push --signed`.
+fn certificate_text(
+    pusher: &gix::actor::Signature,
+    pushee: &BStr,
+    nonce: &BStr,
+    commands: &[Command],
+) -> anyhow::Result<BString> {
+    let mut cert = b"certificate version 0.1\npusher ".to_vec();
+    pusher.write_to(&mut cert)?;
+    let mut cert = BString::from(cert);
+    cert.push_str(format!("\npushee {pushee}\nnonce {nonce}\n\n"));
+    for command in commands {
+        cert.push_str(format!("{} {} {}\n", command.old, command.new, command.remote_ref));
+    }
+    Ok(cert)
+}
+
+/// Write a pack with the objects reachable from `tips` which aren't reachable from the objects the remote has.
+fn write_pack(
+    repo: &gix::Repository,
+    tips: Vec<gix::ObjectId>,
+    remote_has: Vec<gix::ObjectId>,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let mut input = Vec::new();
+    let mut commits = Vec::new();
+    for tip in tips {
+        let object = repo.find_object(tip)?;
+        if object.kind != gix::object::Kind::Commit {
+            input.push(tip);
+        }
+        if let Ok(commit) = object.peel_to_kind(gix::object::Kind::Commit) {
+            commits.push(commit.id);
+        }
+    }
+    let hidden: Vec<_> = remote_has
+        .into_iter()
+        .filter_map(|id| repo.try_find_object(id).ok().flatten())
+        .filter_map(|object| object.peel_to_kind(gix::object::Kind::Commit).ok())
+        .map(|commit| commit.id)
+        .collect();
+    for info in repo.rev_walk(commits).with_hidden(hidden).all()? {
+        input.push(info?.id);
+    }
+
+    let mut db = repo.objects.clone().into_arc()?.into_inner();
+    // Entries refer to packs by index, which must not be unloaded while the pack is written.
+    db.prevent_pack_unload();
+    let (counts, _) = output::count::objects(
+        db.clone(),
+        Box::new(input.into_iter().map(Ok)),
+        &gix::progress::Discard,
+        &AtomicBool::new(false),
+        output::count::objects::Options {
+            input_object_expansion: output::count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+            ..Default::default()
+        },
+    )?;
+    let num_entries = counts.len().try_into()?;
+    let entries = output::entry::iter_from_counts(
+        counts,
+        db,
+        Box::new(gix::progress::Discard),
+        output::entry::iter_from_counts::Options::default(),
+    );
+    let pack = output::bytes::FromEntriesIter::new(
+        gix::features::parallel::InOrderIter::from(entries),
+        out,
+        num_entries,
+        gix_pack::data::Version::V2,
+        repo.object_hash(),
+    );
+    for written in pack {
+        written?;
+    }
+    Ok(())
+}
+
+/// The messages of the remote while receiving a push, which are printed line by line as they arrive.
+#[derive(Default)]
+struct RemoteMessages {
+    text: String,
+    printed: usize,
+}
+
+impl RemoteMessages {
+    fn push(&mut self, text: &[u8]) {
+        self.text.push_str(&text.to_str_lossy());
+        if let Some(end) = self.text.rfind(['\n', '\r']) {
+            self.print(end + 1);
+        }
+    }
+
+    fn print(&mut self, end: usize) {
+        for line in self.text[self.printed..end].split(['\n', '\r']) {
+            if !line.trim().is_empty() {
+                eprintln!("remote: {line}");
+            }
+        }
+        self.printed = end;
+    }
+
+    /// Print what's left and return all messages.
+    fn finish(mut self) -> String {
+        self.print(self.text.len());
+        self.text
+    }
+}
+
+/// Return the data of the packet lines in `data` up to the first flush packet.
+fn decode_lines(mut data: &[u8]) -> anyhow::Result<Vec<BString>> {
+    let mut lines = Vec::new();
+    while !data.is_empty() {
+        match packetline::decode::streaming(data)? {
+            packetline::decode::Stream::Complete { line, bytes_consumed } => {
+                match line {
+                    PacketLineRef::Data(line) => lines.push(line.into()),
+                    PacketLineRef::Flush => break,
+                    PacketLineRef::Delimiter | PacketLineRef::ResponseEnd => {}
+                }
+                data = &data[bytes_consumed..];
+            }
+            packetline::decode::Stream::Incomplete { .. } => bail!("The remote sent an incomplete status report"),
+        }
+    }
+    Ok(lines)
+}
+
+/// The status report of the remote after receiving a push.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Report {
+    /// If the pack could be unpacked, or why not. `None` if the remote didn't say.
+    unpack: Option<Result<(), String>>,
+    /// If each ref was updated, or why not.
+    refs: Vec<(String, Result<(), String>)>,
+}
+
+/// Parse the `report-status` `lines` like `unpack ok`, `ok refs/heads/main` or `ng refs/tags/v1.0.0 hook declined`.
+fn parse_report(lines: &[BString]) -> anyhow::Result<Report> {
+    let mut report = Report::default();
+    for line in lines {
+        let line = line.to_str()?.trim_end_matches('\n');
+        if let Some(status) = line.strip_prefix("unpack ") {
+            report.unpack = Some(if status == "ok" { Ok(()) } else { Err(status.to_owned()) });
+        } else if let Some(name) = line.strip_prefix("ok ") {
+            report.refs.push((name.to_owned(), Ok(())));
+        } else if let Some(rest) = line.strip_prefix("ng ") {
+            let (name, reason) = rest.split_once(' ').unwrap_or((rest, "failed"));
+            report.refs.push((name.to_owned(), Err(reason.to_owned())));
+        }
+    }
+    Ok(report)
+}
+
+/// Update the remote-tracking branches of `remote` for the branches updated by `pushed`, like `git push` does.
+fn update_remote_tracking_refs(
+    repo: &gix::Repository,
+    remote: &gix::Remote<'_>,
+    pushed: &[&Command],
+) -> anyhow::Result<()> {
+    let Some(remote_name) = remote.name() else {
+        return Ok(());
+    };
+    let items: Vec<_> = pushed
+        .iter()
+        .filter(|command| command.remote_ref.starts_with("refs/heads/"))
+        .map(|command| gix::refspec::match_group::Item {
+            full_ref_name: command.remote_ref.as_bytes().as_bstr(),
+            target: &command.new,
+            object: None,
+        })
+        .collect();
+    let group = gix::refspec::MatchGroup::from_fetch_specs(
+        remote
+            .refspecs(Direction::Fetch)
+            .iter()
+            .map(gix::refspec::RefSpec::to_ref),
+    );
+    let mut edits = Vec::new();
+    for mapping in group.match_lhs(items.iter().copied()).mappings {
+        let (Some(index), Some(local)) = (mapping.item_index, mapping.rhs) else {
+            continue;
+        };
+        let Ok(name) = gix::refs::FullName::try_from(local.into_owned()) else {
+            continue;
+        };
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("update by push to {}", remote_name.as_bstr()).into(),
+                },
+                expected: PreviousValue::Any,
+                new: gix::refs::Target::Object(items[index].target.to_owned()),
+            },
+            name,
+            deref: false,
+        });
+    }
+    if !edits.is_empty() {
+        repo.edit_references(edits)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use gix::bstr::BString;
+
+    use super::{decode_lines, parse_report, Report};
+
+    #[test]
+    fn status_reports_are_parsed_per_ref() {
+        let lines: Vec<BString> = vec![
+            "unpack ok\n".into(),
+            "ok refs/heads/main\n".into(),
+            "ng refs/tags/v1.0.0 hook declined\n".into(),
+        ];
+        assert_eq!(
+            parse_report(&lines).unwrap(),
+            Report {
+                unpack: Some(Ok(())),
+                refs: vec![
+                    ("refs/heads/main".into(), Ok(())),
+                    ("refs/tags/v1.0.0".into(), Err("hook declined".into())),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn status_reports_are_decoded_up_to_the_flush_packet() {
+        let lines = decode_lines(b"000eunpack ok\n0017ok refs/heads/main\n0000").unwrap();
+        assert_eq!(lines, ["unpack ok\n", "ok refs/heads/main\n"]);
+    }
+}
@@ -13,9 +13,16 @@ pub mod command;
 pub(crate) mod commit;
 
 pub(crate) mod bat;
+pub mod color;
 mod context;
 mod crates_index;
+pub(crate) mod diff;
+pub mod event;
 pub(crate) mod git;
+pub(crate) mod markdown;
+pub mod progress;
+pub mod semver_checks;
+pub mod stats;
 pub(crate) mod traverse;
 mod utils;
 pub mod version;
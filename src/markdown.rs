@@ -0,0 +1,162 @@
+const BOLD: &str = "\x1b[1m";
+const UNDERLINE: &str = "\x1b[4m";
+const CYAN: &str = "\x1b[36m";
+const YELLOW: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Return `markdown` rendered for display in a terminal, styled with ANSI escape codes if `color` is `true`.
+///
+/// Headings lose their `#` markers and the top-level ones are underlined, list items get bullets and code is
+/// highlighted. Lines with nothing but HTML tags, like `<details>`, are left out, as are HTML tags within lines.
+pub fn render(markdown: &str, color: bool) -> String {
+    let paint = |style: &'static str| if color { style } else { "" };
+    let mut out = String::new();
+    let mut in_code_block = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            out.push_str(&format!("    {}{line}{}\n", paint(CYAN), paint(RESET)));
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.starts_with('<') && trimmed.ends_with('>') {
+            continue;
+        }
+        let level = line.bytes().take_while(|b| *b == b'#').count();
+        if level > 0 && line[level..].starts_with(' ') {
+            let text = inline(line[level..].trim(), color);
+            match level {
+                1 => {
+                    out.push_str(&format!("{}{text}{}\n", paint(BOLD), paint(RESET)));
+                    out.push_str(&format!("{}\n", "=".repeat(visible_width(&text))));
+                }
+                2 => {
+                    out.push_str(&format!("{}{}{text}{}\n", paint(BOLD), paint(YELLOW), paint(RESET)));
+                    out.push_str(&format!("{}\n", "-".repeat(visible_width(&text))));
+                }
+                _ => out.push_str(&format!("{}{text}{}\n", paint(BOLD), paint(RESET))),
+            }
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        match trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            Some(item) => out.push_str(&format!("{:indent$}• {}\n", "", inline(item, color).trim_start())),
+            None => out.push_str(&format!("{}\n", inline(line, color))),
+        }
+    }
+    out
+}
+
+/// Render code spans, bold text and links in `text`, and remove inline HTML tags.
+fn inline(text: &str, color: bool) -> String {
+    let paint = |style: &'static str| if color { style } else { "" };
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let closed = |marker: &str| {
+            rest[marker.len()..]
+                .find(marker)
+                .map(|end| &rest[marker.len()..][..end])
+        };
+        match c {
+            '`' => {
+                if let Some(code) = closed("`") {
+                    // Without color, the backticks are all that sets code apart.
+                    if color {
+                        out.push_str(&format!("{CYAN}{code}{RESET}"));
+                    } else {
+                        out.push_str(&format!("`{code}`"));
+                    }
+                    rest = &rest[code.len() + 2..];
+                    continue;
+                }
+            }
+            '*' if rest.starts_with("**") => {
+                if let Some(bold) = closed("**") {
+                    out.push_str(&format!("{}{}{}", paint(BOLD), inline(bold, color), paint(RESET)));
+                    rest = &rest[bold.len() + 4..];
+                    continue;
+                }
+            }
+            '[' => {
+                if let Some((label, url, len)) = link(rest) {
+                    out.push_str(&format!(
+                        "{}{}{} {}({url}){}",
+                        paint(UNDERLINE),
+                        inline(label, color),
+                        paint(RESET),
+                        paint(DIM),
+                        paint(RESET)
+                    ));
+                    rest = &rest[len..];
+                    continue;
+                }
+            }
+            '<' => {
+                if let Some(end) = rest.find('>').filter(|end| is_html_tag(&rest[1..*end])) {
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Parse a link like `[label](url)` at the start of `text`, and return its label, url and length.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let url_len = text[label_end + 2..].find(')')?;
+    let url = &text[label_end + 2..][..url_len];
+    Some((&text[1..label_end], url, label_end + 2 + url_len + 1))
+}
+
+/// Return `true` if `inner`, the text between `<` and `>`, looks like an HTML tag rather than an autolink or a comparison.
+fn is_html_tag(inner: &str) -> bool {
+    let name = inner.trim_start_matches('/');
+    name.starts_with(|c: char| c.is_ascii_alphabetic()) && !name.contains("://") && !name.contains('@')
+}
+
+/// Return the amount of characters in `text` that take up space in the terminal, ignoring ANSI escape codes.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if in_escape => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+
+    #[test]
+    fn headings_lists_and_inline_markup_without_color() {
+        let markdown = "# Changelog\n\n## v1.0.0 (2024-01-01)\n\n<csr-id-abc/>\n### New Features\n\n - <csr-id-123/> add `--flag` to **all** [crates](https://example.com)\n   * nested\n```\ncode\n```\n";
+        assert_eq!(
+            render(markdown, false),
+            "Changelog\n=========\n\nv1.0.0 (2024-01-01)\n-------------------\n\nNew Features\n\n • add `--flag` to all crates (https://example.com)\n   • nested\n    code\n"
+        );
+    }
+
+    #[test]
+    fn code_spans_and_headings_are_styled_with_color() {
+        assert_eq!(
+            render("## `v1`\n", true),
+            "\x1b[1m\x1b[33m\x1b[36mv1\x1b[0m\x1b[0m\n--\n"
+        );
+    }
+}
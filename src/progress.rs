@@ -0,0 +1,39 @@
+//! Progress of long operations like traversing the history, publishing crates and waiting for them to be available.
+//!
+//! Like log records, progress is reported to a single tree for the whole process, which is only kept once [`enable()`]
+//! was called, usually by the binary to render it. Otherwise all progress is discarded.
+use std::sync::{Arc, OnceLock};
+
+use prodash::{progress::DoOrDiscard, tree};
+
+/// The progress of a single operation, possibly with sub-progress of its own.
+pub(crate) type Progress = DoOrDiscard<tree::Item>;
+
+static ROOT: OnceLock<Arc<tree::Root>> = OnceLock::new();
+
+/// Keep all progress reported from now on, and return the tree it's reported to for rendering.
+pub fn enable() -> Arc<tree::Root> {
+    ROOT.get_or_init(|| {
+        tree::root::Options {
+            message_buffer_capacity: 200,
+            ..Default::default()
+        }
+        .create()
+        .into()
+    })
+    .clone()
+}
+
+/// Return the progress of a new top-level operation named `name`.
+pub(crate) fn add_child(name: impl Into<String>) -> Progress {
+    ROOT.get().map(|root| root.add_child(name)).into()
+}
+
+/// Print `line` to stderr, or show it as message of `progress` while progress is rendered so it isn't overwritten.
+pub(crate) fn eprintln(progress: &Progress, line: &str) {
+    if ROOT.get().is_some() {
+        prodash::Progress::info(progress, line.to_owned());
+    } else {
+        std::eprintln!("{line}");
+    }
+}
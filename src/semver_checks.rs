@@ -0,0 +1,126 @@
+//! Validate automatically computed version bumps by comparing the public API with the latest release
+//! using [`cargo-semver-checks`](https://github.com/obi1kenobi/cargo-semver-checks).
+use std::{cell::RefCell, collections::BTreeMap, process::Command};
+
+use anyhow::bail;
+use cargo_metadata::Package;
+
+use crate::utils::Program;
+
+/// What to do if the API shows breaking changes even though the commit history doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Abort the release, leaving it to the user to mark the breaking change or to bump the version explicitly.
+    Refuse,
+    /// Use a breaking version bump instead of the one derived from commit messages.
+    Upgrade,
+}
+
+impl Policy {
+    /// Parse a policy from its name, `refuse` or `upgrade`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "refuse" => Policy::Refuse,
+            "upgrade" => Policy::Upgrade,
+            _unknown => return None,
+        })
+    }
+}
+
+pub struct SemverChecks {
+    policy: Policy,
+    program: Program,
+    /// Whether or not breaking changes were found, by crate name, as each check is expensive.
+    breaking_by_crate: RefCell<BTreeMap<String, bool>>,
+}
+
+impl SemverChecks {
+    pub fn new(policy: Policy) -> Self {
+        SemverChecks {
+            policy,
+            program: Program::named("cargo-semver-checks"),
+            breaking_by_crate: Default::default(),
+        }
+    }
+
+    /// Return whether or not breaking changes were found in the crate named `name`, or `None` if it wasn't checked.
+    pub(crate) fn found_breaking_changes(&self, name: &str) -> Option<bool> {
+        self.breaking_by_crate.borrow().get(name).copied()
+    }
+
+    /// Return `true` if the bump of `package` computed from its commit history, which isn't breaking and is named by
+    /// `bump_name`, should be upgraded to a breaking one as `cargo-semver-checks` found breaking changes compared to
+    /// `latest_release`.
+    ///
+    /// Fails if breaking changes were found but our policy is to refuse the release in that case.
+    pub(crate) fn upgrade_to_breaking(
+        &self,
+        package: &Package,
+        latest_release: &semver::Version,
+        bump_name: &str,
+    ) -> anyhow::Result<bool> {
+        if !self.program.found {
+            bail!("Cannot validate version bumps as 'cargo-semver-checks' wasn't found in PATH - install it with 'cargo install cargo-semver-checks'.")
+        }
+        let cached = self.breaking_by_crate.borrow().get(package.name.as_str()).copied();
+        let is_breaking = match cached {
+            Some(is_breaking) => is_breaking,
+            None => {
+                let is_breaking = has_breaking_changes(package, latest_release)?;
+                self.breaking_by_crate
+                    .borrow_mut()
+                    .insert(package.name.to_string(), is_breaking);
+                is_breaking
+            }
+        };
+        if !is_breaking {
+            return Ok(false);
+        }
+        match self.policy {
+            Policy::Refuse => bail!(
+                "cargo-semver-checks found breaking changes in '{}' since v{latest_release}, but its commits only call for a {bump_name} release. \
+                 Mark the breaking change in a commit message like 'fix!: …', bump the version explicitly, or use '--semver-checks upgrade'.",
+                package.name
+            ),
+            Policy::Upgrade => {
+                tracing::warn!(
+                    "cargo-semver-checks found breaking changes in '{}' since v{latest_release}, upgrading its {bump_name} release to a breaking one.",
+                    package.name
+                );
+                Ok(true)
+            }
+        }
+    }
+}
+
+fn has_breaking_changes(package: &Package, latest_release: &semver::Version) -> anyhow::Result<bool> {
+    // In 0.x versions, minor releases are breaking, so check for compatibility with a patch release instead.
+    let release_type = if crate::utils::is_pre_release_version(latest_release) {
+        "patch"
+    } else {
+        "minor"
+    };
+    tracing::info!(
+        "Running cargo-semver-checks for '{}' against v{latest_release}",
+        package.name
+    );
+    let output = Command::new("cargo")
+        .args(["semver-checks", "check-release", "--manifest-path"])
+        .arg(package.manifest_path.as_std_path())
+        .args(["--package", package.name.as_str()])
+        .args(["--baseline-version", &latest_release.to_string()])
+        .args(["--release-type", release_type])
+        .output()?;
+    if output.status.success() {
+        return Ok(false);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("semver requires new major version") || stderr.contains("semver requires new minor version") {
+        Ok(true)
+    } else {
+        bail!(
+            "cargo-semver-checks failed for '{}' without reporting semver violations:\n{stderr}",
+            package.name
+        )
+    }
+}
@@ -0,0 +1,73 @@
+//! The wall-time spent in each phase of a run, like collecting metadata or waiting for published crates to be available.
+//!
+//! Like progress, phases are timed for the whole process, but only once [`enable()`] was called, usually by the binary
+//! to print a [`report()`] at the end of the run. Otherwise nothing is recorded.
+use std::{
+    fmt::Write,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// The name of each phase with the time spent in it, in the order they first started.
+static PHASES: OnceLock<Mutex<Vec<(String, Duration)>>> = OnceLock::new();
+
+/// Record the time spent in phases from now on.
+pub fn enable() {
+    PHASES.get_or_init(Default::default);
+}
+
+/// Run `f` and add the time it took to the phase named `name`, which is created if it doesn't exist yet.
+pub(crate) fn time<T>(name: impl Into<String>, f: impl FnOnce() -> T) -> T {
+    let Some(phases) = PHASES.get() else {
+        return f();
+    };
+    let start = Instant::now();
+    let res = f();
+    add(phases, name.into(), start.elapsed());
+    res
+}
+
+fn add(phases: &Mutex<Vec<(String, Duration)>>, name: String, elapsed: Duration) {
+    let mut phases = phases.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    match phases.iter_mut().find(|(existing, _)| *existing == name) {
+        Some((_, duration)) => *duration += elapsed,
+        None => phases.push((name, elapsed)),
+    }
+}
+
+/// Return a table with the time spent in each recorded phase, or `None` if [`enable()`] wasn't called.
+pub fn report() -> Option<String> {
+    let phases = PHASES.get()?.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    Some(format_report(&phases))
+}
+
+fn format_report(phases: &[(String, Duration)]) -> String {
+    let width = phases.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut out = String::from("Time spent per phase:\n");
+    for (name, duration) in phases {
+        writeln!(out, "  {name:<width$}  {:>8.2}s", duration.as_secs_f64()).expect("writing to a string cannot fail");
+    }
+    if phases.is_empty() {
+        out.push_str("  (nothing was timed)\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Duration};
+
+    use super::{add, format_report};
+
+    #[test]
+    fn repeated_phases_are_summed_up_in_order_of_their_first_start() {
+        let phases = Mutex::new(Vec::new());
+        add(&phases, "metadata".into(), Duration::from_millis(250));
+        add(&phases, "wait for crate-a".into(), Duration::from_secs(2));
+        add(&phases, "metadata".into(), Duration::from_millis(250));
+        assert_eq!(
+            format_report(&phases.into_inner().unwrap()),
+            "Time spent per phase:\n  metadata              0.50s\n  wait for crate-a      2.00s\n"
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::BTreeSet;
 
+use anyhow::bail;
+
 use cargo_metadata::{DependencyKind, Package, PackageId};
 
 use crate::{
@@ -10,7 +12,7 @@ use crate::{
         workspace_package_by_dependency,
     },
     version,
-    version::{Bump, BumpSpec},
+    version::{Bump, DependentBump},
     Context,
 };
 
@@ -24,6 +26,10 @@ pub mod dependency {
         DeniedAutopublishOfProductionCrate,
         PublishDisabledInManifest,
         BreakingChangeCausesManifestUpdate,
+        /// The crate is frozen or pinned and keeps its version.
+        Frozen,
+        /// The manifest only allows publishing the crate to registries other than the one we publish to.
+        RestrictedToOtherRegistries,
     }
 
     impl std::fmt::Display for NoPublishReason {
@@ -33,6 +39,8 @@ pub mod dependency {
                 NoPublishReason::DeniedAutopublishOfProductionCrate => "denied",
                 NoPublishReason::Unchanged => "unchanged",
                 NoPublishReason::BreakingChangeCausesManifestUpdate => "dep-breaking",
+                NoPublishReason::Frozen => "frozen",
+                NoPublishReason::RestrictedToOtherRegistries => "other-registry",
             })
         }
     }
@@ -73,6 +81,12 @@ pub mod dependency {
                 VersionAdjustment::Breakage { bump, .. } | VersionAdjustment::Changed { bump, .. } => bump,
             }
         }
+
+        pub fn bump_mut(&mut self) -> &mut version::Bump {
+            match self {
+                VersionAdjustment::Breakage { bump, .. } | VersionAdjustment::Changed { bump, .. } => bump,
+            }
+        }
     }
 
     #[allow(clippy::large_enum_variant)]
@@ -153,6 +167,10 @@ pub struct Options {
     pub bump_when_needed: bool,
     pub isolate_dependencies_from_breaking_changes: bool,
     pub traverse_graph: bool,
+    /// How to bump crates because of breaking changes in their dependencies, or `None` to use the workspace configuration.
+    pub dependent_bump: Option<DependentBump>,
+    /// Release user-selected crates even if they have no releasable changes.
+    pub force: bool,
 }
 
 pub fn dependencies(
@@ -162,8 +180,14 @@ pub fn dependencies(
         bump_when_needed,
         isolate_dependencies_from_breaking_changes,
         traverse_graph,
+        dependent_bump,
+        force,
     }: Options,
 ) -> anyhow::Result<Vec<Dependency<'_>>> {
+    let dependent_bump = match dependent_bump {
+        Some(policy) => policy,
+        None => DependentBump::from_workspace_metadata(&ctx.meta)?.unwrap_or_default(),
+    };
     let mut seen = BTreeSet::new();
     let mut crates = Vec::new();
     for crate_name in &ctx.crate_names {
@@ -172,6 +196,11 @@ pub fn dependencies(
         if seen.contains(&&package.id) {
             continue;
         }
+        if ctx.is_frozen(package) {
+            seen.insert(&package.id);
+            merge_crates(&mut crates, vec![frozen(package, dependency::Kind::UserSelection)]);
+            continue;
+        }
         if traverse_graph {
             depth_first_traversal(
                 ctx,
@@ -183,21 +212,35 @@ pub fn dependencies(
             )?;
         }
 
-        match git::change_since_last_release(package, ctx)? {
+        let change = match git::change_since_last_release(package, ctx)? {
+            None if force => {
+                tracing::info!(
+                    "Forcing release of '{}' even though it has no releasable changes.",
+                    package.name
+                );
+                Some(git::PackageChangeKind::ChangedOrNew)
+            }
+            change => change,
+        };
+        match change {
             Some(user_package_change) => {
                 crates_this_round.push(Dependency {
                     package,
                     kind: dependency::Kind::UserSelection,
-                    mode: if package_may_be_published(package) {
+                    mode: if package_may_be_published(package, ctx) {
                         dependency::Mode::ToBePublished {
                             adjustment: VersionAdjustment::Changed {
                                 change: Some(user_package_change),
-                                bump: version::bump_package(package, ctx, bump_when_needed)?,
+                                bump: if force {
+                                    forced_bump(package, ctx, bump_when_needed)?
+                                } else {
+                                    version::bump_package(package, ctx, bump_when_needed)?
+                                },
                             },
                         }
                     } else {
                         dependency::Mode::NotForPublishing {
-                            reason: dependency::NoPublishReason::PublishDisabledInManifest,
+                            reason: publish_restriction(package),
                             adjustment: None,
                         }
                     },
@@ -218,24 +261,112 @@ pub fn dependencies(
         merge_crates(&mut crates, crates_this_round);
     }
 
+    unify_inherited_workspace_versions(ctx, &mut crates, bump_when_needed)?;
     if isolate_dependencies_from_breaking_changes {
         forward_propagate_breaking_changes_for_publishing(
             ctx,
             &mut crates,
             bump_when_needed,
             allow_auto_publish_of_stable_crates,
+            dependent_bump,
         )?;
         forward_propagate_breaking_changes_for_manifest_updates(
             ctx,
             &mut crates,
             bump_when_needed,
             allow_auto_publish_of_stable_crates,
+            dependent_bump,
         )?;
+        // Safety bumps may have raised the version of crates that inherit the workspace version.
+        unify_inherited_workspace_versions(ctx, &mut crates, bump_when_needed)?;
     }
     adjust_workspace_crates_depending_on_adjusted_crates(ctx, &mut crates, bump_when_needed)?;
     Ok(crates)
 }
 
+/// Assure all crates with `version.workspace = true` see the same version change, as they share the version
+/// in `[workspace.package]`, using the greatest version of all of them that are adjusted already.
+///
+/// Those that wouldn't see a version change otherwise are added to `crates` with a manifest adjustment, without
+/// being published.
+fn unify_inherited_workspace_versions<'meta>(
+    ctx: &'meta Context,
+    crates: &mut Vec<Dependency<'meta>>,
+    bump_when_needed: bool,
+) -> anyhow::Result<()> {
+    let Some(shared_version) = crates
+        .iter()
+        .filter(|c| ctx.inherits_workspace_version(c.package))
+        .filter_map(|c| c.mode.version_adjustment_bump())
+        .map(|bump| &bump.next_release)
+        .max()
+        .cloned()
+    else {
+        return Ok(());
+    };
+    for package in ctx
+        .meta
+        .workspace_packages()
+        .into_iter()
+        .filter(|p| ctx.inherits_workspace_version(p))
+    {
+        if ctx.is_frozen(package) {
+            if package.version != shared_version {
+                bail!(
+                    "'{}' is frozen, but inherits the workspace version which would change from {} to {}",
+                    package.name,
+                    package.version,
+                    shared_version
+                );
+            }
+            continue;
+        }
+        let existing = crates.iter_mut().find(|c| c.package.id == package.id);
+        match existing.map(|c| &mut c.mode) {
+            Some(
+                dependency::Mode::ToBePublished { adjustment }
+                | dependency::Mode::NotForPublishing {
+                    adjustment: Some(ManifestAdjustment::Version(adjustment)),
+                    ..
+                },
+            ) => {
+                adjustment.bump_mut().next_release = shared_version.clone();
+            }
+            Some(dependency::Mode::NotForPublishing { adjustment, .. }) => {
+                *adjustment = Some(ManifestAdjustment::Version(VersionAdjustment::Changed {
+                    change: None,
+                    bump: inherited_version_bump(package, ctx, &shared_version, bump_when_needed)?,
+                }));
+            }
+            None => crates.push(Dependency {
+                package,
+                kind: dependency::Kind::DependencyOrDependentOfUserSelection,
+                mode: dependency::Mode::NotForPublishing {
+                    reason: dependency::NoPublishReason::Unchanged,
+                    adjustment: Some(ManifestAdjustment::Version(VersionAdjustment::Changed {
+                        change: None,
+                        bump: inherited_version_bump(package, ctx, &shared_version, bump_when_needed)?,
+                    })),
+                },
+            }),
+        }
+    }
+    Ok(())
+}
+
+/// Return a bump of `package` to the `shared_version` of the workspace which it inherits.
+fn inherited_version_bump(
+    package: &Package,
+    ctx: &Context,
+    shared_version: &semver::Version,
+    bump_when_needed: bool,
+) -> anyhow::Result<Bump> {
+    let mut bump = version::bump_package_with_spec(package, version::BumpSpec::Keep, ctx, bump_when_needed)?;
+    bump.next_release = shared_version.clone();
+    bump.desired_release = shared_version.clone();
+    Ok(bump)
+}
+
 fn merge_crates<'meta>(dest: &mut Vec<Dependency<'meta>>, src: Vec<Dependency<'meta>>) {
     if dest.is_empty() {
         *dest = src;
@@ -253,6 +384,7 @@ fn forward_propagate_breaking_changes_for_manifest_updates<'meta>(
     crates: &mut Vec<Dependency<'meta>>,
     bump_when_needed: bool,
     allow_auto_publish_of_stable_crates: bool,
+    dependent_bump: DependentBump,
 ) -> anyhow::Result<()> {
     let mut non_publishing_crates_with_safety_bumps = Vec::new();
     let mut backing = crates
@@ -267,7 +399,7 @@ fn forward_propagate_breaking_changes_for_manifest_updates<'meta>(
         .workspace_members
         .iter()
         .map(|wmid| package_by_id(&ctx.meta, wmid))
-        .filter(|p| package_may_be_published(p)) // will publish, non-publishing ones need no safety bumps
+        .filter(|p| package_may_be_published(p, ctx) && !ctx.is_frozen(p)) // will publish, non-publishing ones need no safety bumps
         .collect();
     let mut set_to_expand_from = &backing;
     let mut seen = BTreeSet::default();
@@ -283,7 +415,7 @@ fn forward_propagate_breaking_changes_for_manifest_updates<'meta>(
                     continue;
                 }
                 seen.insert(&dependant.id);
-                let bump = breaking_version_bump(ctx, dependant, bump_when_needed)?;
+                let bump = breaking_version_bump(ctx, dependant, bump_when_needed, dependent_bump)?;
                 if bump.next_release_changes_manifest() {
                     // Propagate a breaking dependency bump to an already-known dependant,
                     // but only upgrade entries that were explicitly selected by the user
@@ -312,7 +444,7 @@ fn forward_propagate_breaking_changes_for_manifest_updates<'meta>(
                                 let is_breaking =
                                     existing.mode.version_adjustment_bump().is_some_and(Bump::is_breaking);
                                 if !is_breaking {
-                                    log::debug!(
+                                    tracing::debug!(
                                         "Wanted to mark '{}' for breaking manifest change, but its already known without breaking change.",
                                         dependant.name
                                     );
@@ -344,7 +476,7 @@ fn forward_propagate_breaking_changes_for_manifest_updates<'meta>(
                             },
                         });
                     } else {
-                        log::trace!(
+                        tracing::trace!(
                             "Ignored stable crate '{}' despite being eligible for safety bump and manifest change.",
                             dependant.name
                         );
@@ -364,8 +496,17 @@ fn forward_propagate_breaking_changes_for_manifest_updates<'meta>(
     Ok(())
 }
 
-fn package_may_be_published(p: &Package) -> bool {
-    p.publish.is_none()
+fn package_may_be_published(p: &Package, ctx: &Context) -> bool {
+    ctx.may_publish_to_registry(p)
+}
+
+/// Return why `p` can't be published, assuming its manifest doesn't allow it.
+fn publish_restriction(p: &Package) -> dependency::NoPublishReason {
+    if p.publish.as_ref().is_some_and(|registries| registries.is_empty()) {
+        dependency::NoPublishReason::PublishDisabledInManifest
+    } else {
+        dependency::NoPublishReason::RestrictedToOtherRegistries
+    }
 }
 
 fn forward_propagate_breaking_changes_for_publishing(
@@ -373,6 +514,7 @@ fn forward_propagate_breaking_changes_for_publishing(
     crates: &mut [Dependency<'_>],
     bump_when_needed: bool,
     allow_auto_publish_of_stable_crates: bool,
+    dependent_bump: DependentBump,
 ) -> anyhow::Result<()> {
     let mut previous_edits = Vec::new();
     loop {
@@ -400,7 +542,13 @@ fn forward_propagate_breaking_changes_for_publishing(
 
         previous_edits.clone_from(&edits);
         for edit_for_publish in edits {
-            edit_for_publish.apply(crates, ctx, bump_when_needed, allow_auto_publish_of_stable_crates)?;
+            edit_for_publish.apply(
+                crates,
+                ctx,
+                bump_when_needed,
+                allow_auto_publish_of_stable_crates,
+                dependent_bump,
+            )?;
         }
     }
     Ok(())
@@ -426,6 +574,7 @@ impl EditForPublish {
         ctx: &Context,
         bump_when_needed: bool,
         allow_auto_publish_of_stable_crates: bool,
+        dependent_bump: DependentBump,
     ) -> anyhow::Result<()> {
         let causing_dependency_names = self
             .causing_dependency_indices
@@ -433,8 +582,13 @@ impl EditForPublish {
             .map(|idx| crates[idx].package.name.to_string())
             .collect();
         let dep_mut = &mut crates[self.crates_idx];
-        if is_pre_release_version(&dep_mut.package.version) || allow_auto_publish_of_stable_crates {
-            let breaking_bump = breaking_version_bump(ctx, dep_mut.package, bump_when_needed)?;
+        if ctx.is_frozen(dep_mut.package) {
+            tracing::trace!(
+                "Ignored frozen crate '{}' despite being eligible for safety bump and publishing.",
+                dep_mut.package.name
+            );
+        } else if is_pre_release_version(&dep_mut.package.version) || allow_auto_publish_of_stable_crates {
+            let breaking_bump = breaking_version_bump(ctx, dep_mut.package, bump_when_needed, dependent_bump)?;
             match &mut dep_mut.mode {
                 dependency::Mode::NotForPublishing {
                     adjustment: maybe_adjustment,
@@ -461,7 +615,7 @@ impl EditForPublish {
                 }
             }
         } else {
-            log::trace!(
+            tracing::trace!(
                 "Ignored stable crate '{}' despite being eligible for safety bump and publishing.",
                 dep_mut.package.name
             );
@@ -470,20 +624,39 @@ impl EditForPublish {
     }
 }
 
-fn breaking_version_bump(ctx: &Context, package: &Package, bump_when_needed: bool) -> anyhow::Result<Bump> {
-    let breaking_spec = if is_pre_release_version(&package.version) {
-        BumpSpec::Minor
+/// Compute the bump of the user-selected `package` which is released even without changes, falling back to a patch
+/// bump if its version wouldn't change otherwise.
+fn forced_bump(package: &Package, ctx: &Context, bump_when_needed: bool) -> anyhow::Result<Bump> {
+    let bump = version::bump_package(package, ctx, bump_when_needed)?;
+    let is_published = bump
+        .latest_release
+        .as_ref()
+        .is_some_and(|latest| *latest >= bump.next_release);
+    if is_published {
+        version::bump_package_with_spec(package, version::BumpSpec::Patch, ctx, bump_when_needed)
     } else {
-        BumpSpec::Major
-    };
-    version::bump_package_with_spec(package, breaking_spec, ctx, bump_when_needed)
+        Ok(bump)
+    }
+}
+
+/// Compute the bump of `package` which has to be released due to a breaking change in one of its dependencies.
+fn breaking_version_bump(
+    ctx: &Context,
+    package: &Package,
+    bump_when_needed: bool,
+    dependent_bump: DependentBump,
+) -> anyhow::Result<Bump> {
+    version::bump_package_with_spec(package, dependent_bump.spec_for(package), ctx, bump_when_needed)
 }
 
 fn make_breaking(adjustment: &mut VersionAdjustment, breaking_bump: Bump, breaking_crate_names: Vec<String>) {
     match adjustment {
         VersionAdjustment::Breakage { .. } => {}
         VersionAdjustment::Changed { change, bump } => {
-            bump.next_release = breaking_bump.next_release;
+            // The dependent bump policy may yield a smaller bump than the one the crate already has.
+            if breaking_bump.next_release > bump.next_release {
+                bump.next_release = breaking_bump.next_release;
+            }
             *adjustment = VersionAdjustment::Breakage {
                 bump: bump.clone(),
                 change: change.clone(),
@@ -557,6 +730,13 @@ fn depth_first_traversal<'meta>(
             continue;
         }
         seen.insert(&workspace_dependency.id);
+        if ctx.is_frozen(workspace_dependency) {
+            crates.push(frozen(
+                workspace_dependency,
+                dependency::Kind::DependencyOrDependentOfUserSelection,
+            ));
+            continue;
+        }
         depth_first_traversal(
             ctx,
             seen,
@@ -641,7 +821,8 @@ fn adjust_workspace_crates_depending_on_adjusted_crates<'meta>(
 
             match crates.iter_mut().find(|c| c.package.id == wsp.id) {
                 Some(existing) => {
-                    changed |= maybe_promote_selected_dependency(existing, ctx, bump_when_needed)?;
+                    changed |= allow_manifest_update_of_frozen_crate(existing)
+                        || maybe_promote_selected_dependency(existing, ctx, bump_when_needed)?;
                 }
                 None => {
                     crates.push(Dependency {
@@ -649,7 +830,11 @@ fn adjust_workspace_crates_depending_on_adjusted_crates<'meta>(
                         package: wsp,
                         mode: dependency::Mode::NotForPublishing {
                             adjustment: ManifestAdjustment::DueToDependencyChange.into(),
-                            reason: dependency::NoPublishReason::Unchanged,
+                            reason: if ctx.is_frozen(wsp) {
+                                dependency::NoPublishReason::Frozen
+                            } else {
+                                dependency::NoPublishReason::Unchanged
+                            },
                         },
                     });
                     changed = true;
@@ -664,6 +849,33 @@ fn adjust_workspace_crates_depending_on_adjusted_crates<'meta>(
     Ok(())
 }
 
+/// Return a dependency for the frozen `package` which will neither be published nor see a version change.
+fn frozen(package: &Package, kind: dependency::Kind) -> Dependency<'_> {
+    Dependency {
+        package,
+        kind,
+        mode: dependency::Mode::NotForPublishing {
+            reason: dependency::NoPublishReason::Frozen,
+            adjustment: None,
+        },
+    }
+}
+
+/// Let the manifest of the frozen `dependency` be updated to the new versions of its dependencies, without changing
+/// its own version, and return `true` if that wasn't the case before.
+fn allow_manifest_update_of_frozen_crate(dependency: &mut Dependency<'_>) -> bool {
+    match &mut dependency.mode {
+        dependency::Mode::NotForPublishing {
+            reason: dependency::NoPublishReason::Frozen,
+            adjustment: adjustment @ None,
+        } => {
+            *adjustment = Some(ManifestAdjustment::DueToDependencyChange);
+            true
+        }
+        _ => false,
+    }
+}
+
 /// Promote `dependency` from `NotForPublishing` to `ToBePublished` only when
 /// it already exists in the traversal result but is still considered
 /// effectively untouched: the crate must have been a direct `UserSelection`,
@@ -684,7 +896,7 @@ fn maybe_promote_selected_dependency(
             if dependency.kind == dependency::Kind::UserSelection
                 && adjustment.is_none()
                 && *reason == dependency::NoPublishReason::Unchanged
-                && package_may_be_published(dependency.package)
+                && package_may_be_published(dependency.package, ctx)
             {
                 dependency.mode = dependency::Mode::ToBePublished {
                     adjustment: VersionAdjustment::Changed {
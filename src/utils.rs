@@ -2,7 +2,7 @@ use std::process::Stdio;
 
 use anyhow::anyhow;
 use cargo_metadata::{
-    camino::{Utf8Component, Utf8Path},
+    camino::{Utf8Component, Utf8Path, Utf8PathBuf},
     Dependency, DependencyKind, Metadata, Package, PackageId,
 };
 use gix::bstr::{BStr, ByteSlice};
@@ -62,6 +62,15 @@ pub fn version_req_unset_or_default(req: &VersionReq) -> bool {
     req.comparators.last().is_none_or(|comp| comp.op == semver::Op::Caret)
 }
 
+/// Return `true` if the package manifest in `doc` declares `version.workspace = true`.
+pub fn inherits_workspace_version(doc: &toml_edit::DocumentMut) -> bool {
+    doc.get("package")
+        .and_then(|package| package.get("version"))
+        .and_then(|version| version.get("workspace"))
+        .and_then(toml_edit::Item::as_bool)
+        .unwrap_or(false)
+}
+
 pub fn package_eq_dependency_ignore_dev_without_version(package: &Package, dependency: &Dependency) -> bool {
     (dependency.kind != DependencyKind::Development || !version_req_unset_or_default(&dependency.req))
         && package.name.as_str() == dependency.name
@@ -89,6 +98,30 @@ pub fn names_and_versions<'a>(publishees: impl IntoIterator<Item = &'a (&'a Pack
         .join(", ")
 }
 
+/// Return `key` from `[package.metadata.smart-release]` of `package`, or else from `[workspace.metadata.smart-release]`.
+pub fn package_setting<'a>(package: &'a Package, meta: &'a Metadata, key: &str) -> Option<&'a serde_json::Value> {
+    package
+        .metadata
+        .get("smart-release")
+        .and_then(|config| config.get(key))
+        .or_else(|| {
+            meta.workspace_metadata
+                .get("smart-release")
+                .and_then(|config| config.get(key))
+        })
+}
+
+/// Return the path to the changelog of `package`, which is `CHANGELOG.md` in its directory unless `changelog` is set to
+/// a path relative to it in the `smart-release` metadata of the package or workspace.
+pub fn changelog_path(package: &Package, meta: &Metadata) -> Utf8PathBuf {
+    let dir = package.manifest_path.parent().expect("parent for Cargo.toml");
+    dir.join(
+        package_setting(package, meta, "changelog")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("CHANGELOG.md"),
+    )
+}
+
 pub fn package_by_id<'a>(meta: &'a Metadata, id: &PackageId) -> &'a Package {
     meta.packages
         .iter()
@@ -104,11 +137,12 @@ pub fn tag_prefix<'p>(package: &'p Package, repo: &gix::Repository) -> Option<&'
     }
 }
 
-pub fn tag_name(package: &Package, version: &semver::Version, repo: &gix::Repository) -> String {
-    tag_name_inner(tag_prefix(package, repo), version)
+pub fn tag_name(package: &Package, version: &semver::Version, ctx: &crate::Context) -> String {
+    ctx.version_scheme
+        .tag_name(tag_prefix(package, ctx.repo_of(package)), version)
 }
 
-fn tag_name_inner(package_name: Option<&str>, version: &semver::Version) -> String {
+pub fn tag_name_inner(package_name: Option<&str>, version: &semver::Version) -> String {
     match package_name {
         Some(name) => format!("{name}-v{version}"),
         None => format!("v{version}"),
@@ -135,20 +169,6 @@ pub fn parse_tag_version(name: &BStr) -> Option<Version> {
     Version::parse(version).ok()
 }
 
-pub fn is_tag_name(package_name: &str, tag_name: &gix::bstr::BStr) -> bool {
-    match tag_name
-        .strip_prefix(package_name.as_bytes())
-        .and_then(|r| r.strip_prefix(b"-"))
-    {
-        None => false,
-        Some(possibly_version) => parse_tag_version(possibly_version.as_bstr()).is_some(),
-    }
-}
-
-pub fn is_tag_version(name: &gix::bstr::BStr) -> bool {
-    parse_tag_version(name).is_some()
-}
-
 pub fn component_to_bytes(c: Utf8Component<'_>) -> &[u8] {
     match c {
         Utf8Component::Normal(c) => c.as_bytes(),
@@ -161,7 +181,62 @@ pub fn time_to_zoned_time(time: gix::date::Time) -> anyhow::Result<jiff::Zoned>
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use gix::bstr::BStr;
+    use serde_json::json;
+
+    use crate::version::{SemanticVersioning, VersionScheme};
+
+    /// Return the package `name` at version 0.1.0 with `dependencies` and `metadata`.
+    ///
+    /// Dependencies are objects with at least a `name`, and all fields they don't have are set like for normal
+    /// dependencies on crates.io with `req = "^0.1.0"`.
+    pub(crate) fn package(
+        name: &str,
+        dependencies: &[serde_json::Value],
+        metadata: serde_json::Value,
+    ) -> cargo_metadata::Package {
+        let dependencies: Vec<_> = dependencies
+            .iter()
+            .map(|dependency| {
+                let mut defaults = json!({
+                    "req": "^0.1.0",
+                    "kind": null,
+                    "rename": null,
+                    "optional": false,
+                    "uses_default_features": true,
+                    "features": [],
+                    "target": null,
+                    "source": null
+                });
+                let fields = defaults.as_object_mut().expect("object");
+                fields.extend(dependency.as_object().expect("dependencies are objects").clone());
+                defaults
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "name": name,
+            "version": "0.1.0",
+            "id": format!("{name} 0.1.0"),
+            "dependencies": dependencies,
+            "targets": [],
+            "features": {},
+            "manifest_path": format!("/{name}/Cargo.toml"),
+            "metadata": metadata
+        }))
+        .expect("valid package")
+    }
+
+    fn is_tag_name(package_name: &str, tag_name: &BStr) -> bool {
+        SemanticVersioning
+            .parse_tag_name(Some(package_name), tag_name)
+            .is_some()
+    }
+
+    fn is_tag_version(name: &BStr) -> bool {
+        SemanticVersioning.parse_tag_name(None, name).is_some()
+    }
+
     mod parse_possibly_prefixed_tag_version {
         mod matches {
             use std::str::FromStr;
@@ -213,7 +288,7 @@ mod tests {
             use gix::bstr::ByteSlice;
             use semver::Version;
 
-            use crate::utils::{is_tag_name, tag_name_inner};
+            use crate::utils::{tag_name_inner, tests::is_tag_name};
 
             #[test]
             fn due_to_crate_name() {
@@ -231,7 +306,7 @@ mod tests {
             use gix::bstr::ByteSlice;
             use semver::Version;
 
-            use crate::utils::{is_tag_name, tag_name_inner};
+            use crate::utils::{tag_name_inner, tests::is_tag_name};
 
             #[test]
             fn whatever_tag_name_would_return() {
@@ -255,7 +330,7 @@ mod tests {
         mod no_match {
             use gix::bstr::ByteSlice;
 
-            use crate::utils::is_tag_version;
+            use crate::utils::tests::is_tag_version;
 
             #[test]
             fn not_enough_numbers() {
@@ -285,7 +360,7 @@ mod tests {
                 assert!(is_tag_version(b"vers0.0.1".as_bstr()));
             }
 
-            use crate::utils::is_tag_version;
+            use crate::utils::tests::is_tag_version;
 
             #[test]
             fn pre_release() {
@@ -300,4 +375,67 @@ mod tests {
             }
         }
     }
+
+    mod inherits_workspace_version {
+        use crate::utils::inherits_workspace_version;
+
+        #[test]
+        fn dotted_keys_and_inline_tables() {
+            for (manifest, expected) in [
+                ("[package]\nversion.workspace = true", true),
+                ("[package]\nversion = { workspace = true }", true),
+                ("[package]\nversion = \"1.0.0\"", false),
+                ("[package]\nversion.workspace = false", false),
+                ("[workspace.package]\nversion = \"1.0.0\"", false),
+            ] {
+                assert_eq!(
+                    inherits_workspace_version(&manifest.parse().expect("valid")),
+                    expected,
+                    "{manifest}"
+                );
+            }
+        }
+    }
+
+    mod changelog_path {
+        use serde_json::json;
+
+        use crate::utils::changelog_path;
+
+        fn package(metadata: serde_json::Value) -> cargo_metadata::Package {
+            let mut package = super::package("a", &[], metadata);
+            package.manifest_path = "/ws/a/Cargo.toml".into();
+            package
+        }
+
+        fn workspace(metadata: serde_json::Value) -> cargo_metadata::Metadata {
+            serde_json::from_value(json!({
+                "packages": [],
+                "workspace_members": [],
+                "resolve": null,
+                "workspace_root": "/ws",
+                "target_directory": "/ws/target",
+                "version": 1,
+                "metadata": metadata
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn package_settings_take_precedence_over_workspace_settings() {
+            let ws = workspace(json!({"smart-release": {"changelog": "CHANGES.md"}}));
+            assert_eq!(
+                changelog_path(&package(json!(null)), &workspace(json!(null))),
+                "/ws/a/CHANGELOG.md"
+            );
+            assert_eq!(changelog_path(&package(json!(null)), &ws), "/ws/a/CHANGES.md");
+            assert_eq!(
+                changelog_path(
+                    &package(json!({"smart-release": {"changelog": "docs/CHANGELOG.md"}})),
+                    &ws
+                ),
+                "/ws/a/docs/CHANGELOG.md"
+            );
+        }
+    }
 }
@@ -1,15 +1,73 @@
 use cargo_metadata::Package;
+use gix::bstr::{BStr, ByteSlice};
 use semver::{Prerelease, Version};
 
 use crate::Context;
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub enum BumpSpec {
     Auto,
     Keep,
     Patch,
     Minor,
     Major,
+    /// Produce a pre-release like `1.3.0-rc.1` of the version `Auto` would produce, or increment the counter
+    /// of an existing pre-release.
+    PreRelease(PreRelease),
+    /// Turn a pre-release into its final version, like `1.3.0-rc.2` into `1.3.0`.
+    Release,
+}
+
+/// The kind of pre-release, in the order they are typically released in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PreRelease {
+    Alpha,
+    Beta,
+    Rc,
+}
+
+impl PreRelease {
+    /// Parse a pre-release kind from its `name`, like `alpha`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "alpha" => PreRelease::Alpha,
+            "beta" => PreRelease::Beta,
+            "rc" => PreRelease::Rc,
+            _unknown => return None,
+        })
+    }
+
+    /// The identifier used in versions.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PreRelease::Alpha => "alpha",
+            PreRelease::Beta => "beta",
+            PreRelease::Rc => "rc",
+        }
+    }
+
+    fn first(&self) -> Prerelease {
+        Prerelease::new(&format!("{}.1", self.as_str())).expect("valid identifier")
+    }
+
+    /// Return the pre-release identifier that follows `current`, either by incrementing its counter if it's of our kind,
+    /// or by starting over if we follow the kind of `current`.
+    fn next_after(&self, current: &Prerelease) -> anyhow::Result<Prerelease> {
+        let (name, counter) = current.as_str().split_once('.').unwrap_or((current.as_str(), ""));
+        let Some(current_kind) = Self::from_name(name) else {
+            anyhow::bail!("Cannot bump pre-release '{current}' as only 'alpha', 'beta' and 'rc' are understood");
+        };
+        match current_kind.cmp(self) {
+            std::cmp::Ordering::Less => Ok(self.first()),
+            std::cmp::Ordering::Equal => {
+                let counter: u64 = if counter.is_empty() { 0 } else { counter.parse()? };
+                Ok(Prerelease::new(&format!("{name}.{}", counter + 1))?)
+            }
+            std::cmp::Ordering::Greater => {
+                anyhow::bail!("Cannot go back from pre-release '{current}' to '{}'", self.as_str())
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for BumpSpec {
@@ -20,13 +78,275 @@ impl std::fmt::Display for BumpSpec {
             BumpSpec::Patch => "patch",
             BumpSpec::Minor => "minor",
             BumpSpec::Major => "major",
+            BumpSpec::PreRelease(kind) => kind.as_str(),
+            BumpSpec::Release => "release",
+        })
+    }
+}
+
+impl std::str::FromStr for BumpSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        use BumpSpec::*;
+        Ok(match spec {
+            "patch" | "Patch" => Patch,
+            "minor" | "Minor" => Minor,
+            "major" | "Major" => Major,
+            "keep" | "Keep" => Keep,
+            "auto" | "Auto" => Auto,
+            "release" | "Release" => Release,
+            unknown_spec => match self::PreRelease::from_name(&unknown_spec.to_ascii_lowercase()) {
+                Some(kind) => PreRelease(kind),
+                None => anyhow::bail!("Unknown bump specification: {:?}", unknown_spec),
+            },
         })
     }
 }
 
+/// The version bump of crates that are released only because a breaking change in one of their dependencies
+/// requires updating their manifest.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum DependentBump {
+    /// Use a breaking bump just like the dependency, as the dependency may be part of the dependent's public API.
+    #[default]
+    Mirror,
+    /// Use a minor bump, which is still breaking for `0.x` versions.
+    Minor,
+    /// Always use a patch bump, assuming dependencies are never part of the public API.
+    Patch,
+}
+
+impl DependentBump {
+    /// Parse a policy from its `name`, like `mirror`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "mirror" => DependentBump::Mirror,
+            "minor" => DependentBump::Minor,
+            "patch" => DependentBump::Patch,
+            _unknown => return None,
+        })
+    }
+
+    /// Read `dependent-bump` from `[workspace.metadata.smart-release]`, if set.
+    pub(crate) fn from_workspace_metadata(meta: &cargo_metadata::Metadata) -> anyhow::Result<Option<Self>> {
+        let Some(name) = meta
+            .workspace_metadata
+            .get("smart-release")
+            .and_then(|config| config.get("dependent-bump"))
+        else {
+            return Ok(None);
+        };
+        name.as_str()
+            .and_then(Self::from_name)
+            .map(Some)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid 'dependent-bump' in [workspace.metadata.smart-release]: {name}, use 'mirror', 'minor' or 'patch'"
+                )
+            })
+    }
+
+    /// The bump specification to use for `package` according to this policy.
+    pub(crate) fn spec_for(&self, package: &Package) -> BumpSpec {
+        match self {
+            DependentBump::Mirror if is_pre_release(&package.version) => BumpSpec::Minor,
+            DependentBump::Mirror => BumpSpec::Major,
+            DependentBump::Minor => BumpSpec::Minor,
+            DependentBump::Patch => BumpSpec::Patch,
+        }
+    }
+}
+
+/// The kind of change in a release, as derived from the commit history, ordered by severity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    /// All changes are fixes or otherwise don't affect the API.
+    Fix,
+    /// At least one feature was added, and nothing breaks.
+    Feature,
+    /// At least one change breaks the API.
+    Breaking,
+}
+
+/// A way to compute versions of new releases, to name their tags and to title their changelog sections.
+///
+/// Implement it to use custom schemes, like epoch-based or product-line versions, and pass it to the release and
+/// changelog commands via their options. All methods default to plain [semantic versioning](https://semver.org),
+/// which is available as [`SemanticVersioning`].
+///
+/// Note that versions must stay valid semantic versions as cargo relies on them, and that cargo considers a change of
+/// the left-most non-zero component breaking no matter the scheme.
+pub trait VersionScheme: std::fmt::Debug {
+    /// Return the version that follows `current` to release a `change` derived from the commit history.
+    fn next_version(&self, current: &Version, change: Change) -> Version {
+        let mut v = current.clone();
+        let spec = match change {
+            Change::Breaking if is_pre_release(&v) => BumpSpec::Minor,
+            Change::Breaking => BumpSpec::Major,
+            Change::Feature if is_pre_release(&v) => BumpSpec::Patch,
+            Change::Feature => BumpSpec::Minor,
+            Change::Fix => BumpSpec::Patch,
+        };
+        bump_major_minor_patch(&mut v, spec);
+        v
+    }
+
+    /// Return the name of the tag for the release of `version`, which is prefixed with `package_name` unless the
+    /// package is at the top-level of the repository.
+    fn tag_name(&self, package_name: Option<&str>, version: &Version) -> String {
+        crate::utils::tag_name_inner(package_name, version)
+    }
+
+    /// Return the version of the release that the tag `tag_name` refers to, or `None` if it isn't a release tag of the
+    /// package named `package_name`, which is `None` if the package is at the top-level of the repository.
+    fn parse_tag_name(&self, package_name: Option<&str>, tag_name: &BStr) -> Option<Version> {
+        crate::utils::parse_possibly_prefixed_tag_version(package_name, tag_name)
+    }
+
+    /// Return the text that precedes the version in the headlines of new changelog sections, like `v` in `v1.0.0`.
+    ///
+    /// Existing changelogs keep the prefix of their most recent release.
+    fn headline_prefix(&self) -> &str {
+        crate::changelog::Section::DEFAULT_PREFIX
+    }
+}
+
+/// The default [`VersionScheme`] which follows [semantic versioning](https://semver.org).
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SemanticVersioning;
+
+impl VersionScheme for SemanticVersioning {}
+
+/// A [`VersionScheme`] for releases on a channel other than the stable one, like `nightly`.
+///
+/// Its releases are pre-releases of the version the stable channel would produce, identified by the channel name and a
+/// date like `1.3.0-nightly.20240601`, and their tags are prefixed with the channel name like `nightly/v1.3.0-nightly.20240601`
+/// so they don't delimit the history of stable releases.
+#[derive(Debug, Clone)]
+pub struct ReleaseChannel {
+    name: String,
+    date: String,
+}
+
+impl ReleaseChannel {
+    /// Create a new channel named `name` whose releases are made on `date`.
+    ///
+    /// Fails if `name` can't be used as pre-release identifier, which allows only ASCII alphanumerics and hyphens.
+    pub fn new(name: &str, date: jiff::civil::Date) -> anyhow::Result<Self> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            anyhow::bail!(
+                "Invalid channel name '{name}' - it may only contain ASCII letters, digits and hyphens, like 'nightly'"
+            );
+        }
+        Ok(ReleaseChannel {
+            name: name.to_owned(),
+            date: date.strftime("%Y%m%d").to_string(),
+        })
+    }
+
+    fn is_channel_version(&self, version: &Version) -> bool {
+        version
+            .pre
+            .as_str()
+            .strip_prefix(self.name.as_str())
+            .is_some_and(|rest| rest.starts_with('.'))
+    }
+}
+
+impl VersionScheme for ReleaseChannel {
+    fn next_version(&self, current: &Version, change: Change) -> Version {
+        let mut v = if self.is_channel_version(current) {
+            current.clone()
+        } else {
+            SemanticVersioning.next_version(current, change)
+        };
+        v.pre = Prerelease::new(&format!("{}.{}", self.name, self.date)).expect("name and date are valid identifiers");
+        v
+    }
+
+    fn tag_name(&self, package_name: Option<&str>, version: &Version) -> String {
+        let name = crate::utils::tag_name_inner(package_name, version);
+        if self.is_channel_version(version) {
+            format!("{}/{name}", self.name)
+        } else {
+            name
+        }
+    }
+
+    fn parse_tag_name(&self, package_name: Option<&str>, tag_name: &BStr) -> Option<Version> {
+        match tag_name
+            .strip_prefix(self.name.as_bytes())
+            .and_then(|name| name.strip_prefix(b"/"))
+        {
+            Some(name) => crate::utils::parse_possibly_prefixed_tag_version(package_name, name.as_bstr())
+                .filter(|version| self.is_channel_version(version)),
+            None => crate::utils::parse_possibly_prefixed_tag_version(package_name, tag_name),
+        }
+    }
+}
+
+/// A [`VersionScheme`] which also recognizes release tags by custom patterns like `release-{version}` or `{crate}_{version}`,
+/// to find previous releases in repositories which didn't always use our tag names.
+///
+/// Patterns with `{crate}` match tags of crates below the top-level of the repository, and all others match tags of the
+/// top-level crate. New tags are still named by the wrapped scheme.
+#[derive(Debug)]
+pub struct WithTagPatterns {
+    inner: std::rc::Rc<dyn VersionScheme>,
+    patterns: Vec<String>,
+}
+
+impl WithTagPatterns {
+    /// Wrap `inner` to additionally parse tags matching one of `patterns`.
+    ///
+    /// Fails if a pattern doesn't contain `{version}` exactly once.
+    pub fn new(inner: std::rc::Rc<dyn VersionScheme>, patterns: Vec<String>) -> anyhow::Result<Self> {
+        if let Some(invalid) = patterns.iter().find(|p| p.matches("{version}").count() != 1) {
+            anyhow::bail!("Tag pattern '{invalid}' must contain '{{version}}' exactly once");
+        }
+        Ok(WithTagPatterns { inner, patterns })
+    }
+}
+
+impl VersionScheme for WithTagPatterns {
+    fn next_version(&self, current: &Version, change: Change) -> Version {
+        self.inner.next_version(current, change)
+    }
+
+    fn tag_name(&self, package_name: Option<&str>, version: &Version) -> String {
+        self.inner.tag_name(package_name, version)
+    }
+
+    fn parse_tag_name(&self, package_name: Option<&str>, tag_name: &BStr) -> Option<Version> {
+        self.inner.parse_tag_name(package_name, tag_name).or_else(|| {
+            let tag_name = tag_name.to_str().ok()?;
+            self.patterns
+                .iter()
+                .find_map(|pattern| parse_tag_with_pattern(pattern, package_name, tag_name))
+        })
+    }
+
+    fn headline_prefix(&self) -> &str {
+        self.inner.headline_prefix()
+    }
+}
+
+fn parse_tag_with_pattern(pattern: &str, package_name: Option<&str>, tag_name: &str) -> Option<Version> {
+    let pattern = match (pattern.contains("{crate}"), package_name) {
+        (true, Some(name)) => pattern.replace("{crate}", name),
+        (false, None) => pattern.to_owned(),
+        _ => return None,
+    };
+    let (prefix, suffix) = pattern.split_once("{version}")?;
+    Version::parse(tag_name.strip_prefix(prefix)?.strip_suffix(suffix)?).ok()
+}
+
 #[allow(clippy::ptr_arg)]
 pub(crate) fn select_publishee_bump_spec(name: &String, ctx: &Context) -> BumpSpec {
-    if ctx.crate_names.contains(name) {
+    if let Some(spec) = ctx.bump_overrides.get(name.as_str()) {
+        *spec
+    } else if ctx.crate_names.contains(name) {
         ctx.bump
     } else {
         ctx.bump_dependencies
@@ -55,7 +375,9 @@ fn bump_major_minor_patch(v: &mut semver::Version, bump_spec: BumpSpec) -> bool
             v.pre = Prerelease::EMPTY;
             false
         }
-        Keep | Auto => unreachable!("BUG: auto mode or keep are unsupported"),
+        Keep | Auto | PreRelease(_) | Release => {
+            unreachable!("BUG: auto mode, keep and pre-releases are unsupported")
+        }
     }
 }
 
@@ -90,49 +412,34 @@ pub(crate) fn bump_package_with_spec(
     let package_version_must_be_breaking = match bump_spec {
         Major | Minor | Patch => bump_major_minor_patch(&mut v, bump_spec),
         Keep => false,
-        Auto => {
-            use anyhow::Context;
-            let segments = crate::git::history::crate_ref_segments(
-                package,
-                ctx,
-                ctx.history
-                    .as_ref()
-                    .context("Did not have access to the Git history - please assure to not be on a detached HEAD")?,
-                crate::git::history::SegmentScope::Unreleased,
-            )?;
-            assert_eq!(
-                segments.len(),
-                1,
-                "there should be exactly one section, the 'unreleased' one"
-            );
-            let unreleased = &segments[0];
-            if unreleased.history.is_empty() {
-                false
-            } else if unreleased.history.iter().any(|item| item.message.breaking) {
-                let is_breaking = if is_pre_release(&v) {
-                    bump_major_minor_patch(&mut v, Minor)
-                } else {
-                    bump_major_minor_patch(&mut v, Major)
-                };
-                assert!(is_breaking, "BUG: breaking changes are…breaking :D");
-                is_breaking
-            } else if unreleased.history.iter().any(|item| item.message.kind == Some("feat")) {
-                let is_breaking = if is_pre_release(&v) {
-                    bump_major_minor_patch(&mut v, Patch)
-                } else {
-                    bump_major_minor_patch(&mut v, Minor)
-                };
-                assert!(!is_breaking, "BUG: new features are never breaking");
+        Auto => bump_automatically(&mut v, package, ctx)?,
+        PreRelease(channel) => {
+            if v.pre.is_empty() {
+                let is_breaking = bump_automatically(&mut v, package, ctx)?;
+                if v == package.version {
+                    bump_major_minor_patch(&mut v, Patch);
+                }
+                v.pre = channel.first();
                 is_breaking
             } else {
-                let is_breaking = bump_major_minor_patch(&mut v, Patch);
-                assert!(!is_breaking, "BUG: patch releases are never breaking");
+                v.pre = channel.next_after(&v.pre)?;
                 false
             }
         }
+        Release => {
+            if v.pre.is_empty() {
+                anyhow::bail!(
+                    "Cannot release '{}' at v{} as final version as it isn't a pre-release",
+                    package.name,
+                    package.version
+                );
+            }
+            v.pre = Prerelease::EMPTY;
+            false
+        }
     };
     let desired_release = v;
-    let (latest_release, next_release) = match ctx.crates_index.crate_(&package.name) {
+    let (latest_release, mut next_release) = match ctx.crates_index.crate_(&package.name) {
         Some(published_crate) => {
             let latest_release = semver::Version::parse(published_crate.highest_version().version())
                 .expect("valid version in crate index");
@@ -162,6 +469,15 @@ pub(crate) fn bump_package_with_spec(
             },
         ),
     };
+    if let Some(build) = ctx.build_metadata.as_ref() {
+        // Only new releases get build metadata, as it doesn't make versions distinct.
+        if latest_release
+            .as_ref()
+            .is_none_or(|latest| latest.cmp_precedence(&next_release).is_lt())
+        {
+            next_release.build = build.clone();
+        }
+    }
     Ok(Bump {
         next_release,
         package_version: package.version.clone(),
@@ -170,6 +486,251 @@ pub(crate) fn bump_package_with_spec(
     })
 }
 
+/// Bump `v` of `package` according to the conventional commits since its last release, and return `true` if the bump
+/// is breaking.
+fn bump_automatically(v: &mut semver::Version, package: &Package, ctx: &Context) -> anyhow::Result<bool> {
+    use anyhow::Context;
+    let segments = crate::git::history::crate_ref_segments(
+        package,
+        ctx,
+        ctx.history
+            .as_ref()
+            .context("Did not have access to the Git history - please assure to not be on a detached HEAD")?,
+        crate::git::history::SegmentScope::Unreleased,
+    )?;
+    assert_eq!(
+        segments.len(),
+        1,
+        "there should be exactly one section, the 'unreleased' one"
+    );
+    let unreleased = &segments[0];
+    let has_features = unreleased.history.iter().any(|item| item.message.kind == Some("feat"));
+    let change = if unreleased.history.is_empty() {
+        return Ok(false);
+    } else if unreleased.history.iter().any(|item| item.message.breaking)
+        || api_is_breaking(package, ctx, v, has_features)?
+    {
+        Change::Breaking
+    } else if has_features {
+        Change::Feature
+    } else {
+        Change::Fix
+    };
+    let change = match unreleased_changelog_change(package, ctx)? {
+        Some(documented) if documented > change => {
+            tracing::info!(
+                "Using a {} bump for '{}' as its changelog documents {} by hand in the 'Unreleased' section.",
+                if documented == Change::Breaking {
+                    "breaking"
+                } else {
+                    "feature"
+                },
+                package.name,
+                if documented == Change::Breaking {
+                    "breaking changes"
+                } else {
+                    "new features"
+                },
+            );
+            documented
+        }
+        _ => change,
+    };
+    let change = if change < Change::Breaking {
+        let dependencies = incompatibly_changed_dependencies(package, ctx)?;
+        if dependencies.is_empty() {
+            change
+        } else {
+            tracing::info!(
+                "Using a breaking bump for '{}' as the version requirements of its public dependencies changed incompatibly: {}",
+                package.name,
+                dependencies
+                    .iter()
+                    .map(|name| format!("'{name}'"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            Change::Breaking
+        }
+    } else {
+        change
+    };
+    *v = ctx.version_scheme.next_version(v, change);
+    Ok(change == Change::Breaking)
+}
+
+/// Return the most severe change that was documented by hand in the 'Unreleased' section of the changelog of `package`,
+/// if there is one.
+pub(crate) fn unreleased_changelog_change(package: &Package, ctx: &Context) -> anyhow::Result<Option<Change>> {
+    let path = crate::utils::changelog_path(package, &ctx.meta);
+    let markdown = match std::fs::read_to_string(&path) {
+        Ok(markdown) => markdown,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(crate::ChangeLog::from_markdown(&markdown, &ctx.changelog_locale)
+        .sections
+        .iter()
+        .find(|section| {
+            matches!(
+                section,
+                crate::changelog::Section::Release {
+                    name: crate::changelog::Version::Unreleased,
+                    ..
+                }
+            )
+        })
+        .and_then(|section| section.manually_documented_change(&ctx.changelog_locale)))
+}
+
+/// Return the names of the dependencies of `package` whose version requirement changed incompatibly since its last
+/// release, like from `1.2` to `2.0`, as their types may be part of its public API.
+///
+/// Only normal dependencies are considered, without those which are workspace members as these cause safety bumps anyway,
+/// and without those listed in `private-dependencies` of `[package.metadata.smart-release]`.
+pub(crate) fn incompatibly_changed_dependencies(package: &Package, ctx: &Context) -> anyhow::Result<Vec<String>> {
+    let Some(released) = released_dependency_requirements(package, ctx)? else {
+        return Ok(Vec::new());
+    };
+    let private = private_dependencies(package);
+    let mut names: Vec<_> = package
+        .dependencies
+        .iter()
+        .filter(|dep| dep.kind == cargo_metadata::DependencyKind::Normal)
+        .filter(|dep| crate::utils::workspace_package_by_dependency(&ctx.meta, dep).is_none())
+        .filter(|dep| !private.contains(&dep.name))
+        .filter(|dep| {
+            released
+                .get(&dep.name)
+                .and_then(|req| semver::VersionReq::parse(req).ok())
+                .is_some_and(|released| is_incompatible_requirement_change(&released, &dep.req))
+        })
+        .map(|dep| dep.name.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Read the version requirements of all normal dependencies of `package` by name, as they were in its last release,
+/// or `None` if there was no release.
+fn released_dependency_requirements(
+    package: &Package,
+    ctx: &Context,
+) -> anyhow::Result<Option<std::collections::BTreeMap<String, String>>> {
+    use toml_edit::{DocumentMut, Item};
+    let read_manifest = |path: &cargo_metadata::camino::Utf8Path| -> anyhow::Result<Option<DocumentMut>> {
+        crate::git::file_at_last_release(package, path, ctx)?
+            .map(|data| Ok(String::from_utf8(data)?.parse::<DocumentMut>()?))
+            .transpose()
+    };
+    let manifest_path = ctx
+        .repo_relative_path(package)
+        .map_or_else(|| "Cargo.toml".into(), |dir| dir.join("Cargo.toml"));
+    let Some(manifest) = read_manifest(&manifest_path)? else {
+        return Ok(None);
+    };
+    let mut workspace_manifest = None;
+    let mut requirements = std::collections::BTreeMap::new();
+    let tables = manifest.get("dependencies").into_iter().chain(
+        manifest
+            .get("target")
+            .and_then(Item::as_table_like)
+            .into_iter()
+            .flat_map(|targets| targets.iter().filter_map(|(_, target)| target.get("dependencies"))),
+    );
+    for table in tables.filter_map(Item::as_table_like) {
+        for (key, dep) in table.iter() {
+            let mut dep = dep;
+            if dep.get("workspace").and_then(Item::as_bool) == Some(true) {
+                if workspace_manifest.is_none() {
+                    workspace_manifest = read_manifest("Cargo.toml".into())?;
+                }
+                match workspace_manifest
+                    .as_ref()
+                    .and_then(|manifest| manifest.get("workspace"))
+                    .and_then(|workspace| workspace.get("dependencies"))
+                    .and_then(|deps| deps.get(key))
+                {
+                    Some(workspace_dep) => dep = workspace_dep,
+                    None => continue,
+                }
+            }
+            let (name, req) = match dep.as_str() {
+                Some(req) => (key, Some(req)),
+                None => (
+                    dep.get("package").and_then(Item::as_str).unwrap_or(key),
+                    dep.get("version").and_then(Item::as_str),
+                ),
+            };
+            if let Some(req) = req {
+                requirements.insert(name.to_owned(), req.to_owned());
+            }
+        }
+    }
+    Ok(Some(requirements))
+}
+
+/// Read the names of dependencies whose types aren't part of the public API of `package` from `private-dependencies`
+/// of `[package.metadata.smart-release]`.
+fn private_dependencies(package: &Package) -> Vec<String> {
+    package
+        .metadata
+        .get("smart-release")
+        .and_then(|config| config.get("private-dependencies"))
+        .and_then(serde_json::Value::as_array)
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(ToOwned::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return `true` if `current` allows only versions that are semver-incompatible with the ones allowed by `released`,
+/// judged by their first comparator, like `1.2` and `2`, or `0.3.1` and `0.4`.
+fn is_incompatible_requirement_change(released: &semver::VersionReq, current: &semver::VersionReq) -> bool {
+    fn compatibility_range(req: &semver::VersionReq) -> Option<(u64, Option<u64>, Option<u64>)> {
+        let c = req.comparators.first()?;
+        Some(match (c.major, c.minor, c.patch) {
+            (0, Some(0), patch) => (0, Some(0), patch),
+            (0, minor, _) => (0, minor, None),
+            (major, _, _) => (major, None, None),
+        })
+    }
+    match (compatibility_range(released), compatibility_range(current)) {
+        (Some(released), Some(current)) => released != current,
+        _ => false,
+    }
+}
+
+/// Return `true` if a non-breaking release of `package` at `version` would contain breaking API changes according to
+/// cargo-semver-checks, if enabled.
+fn api_is_breaking(package: &Package, ctx: &Context, version: &Version, has_features: bool) -> anyhow::Result<bool> {
+    let Some(checks) = ctx.semver_checks.as_ref() else {
+        return Ok(false);
+    };
+    let Some(latest_release) = ctx
+        .crates_index
+        .crate_(&package.name)
+        .and_then(|c| semver::Version::parse(c.highest_version().version()).ok())
+    else {
+        tracing::info!(
+            "Skipping cargo-semver-checks for '{}' as it wasn't released yet.",
+            package.name
+        );
+        return Ok(false);
+    };
+    let bump_name = if has_features && !is_pre_release(version) {
+        "minor"
+    } else {
+        "patch"
+    };
+    checks.upgrade_to_breaking(package, &latest_release, bump_name)
+}
+
 pub(crate) fn bump_package(package: &Package, ctx: &Context, bump_when_needed: bool) -> anyhow::Result<Bump> {
     let bump_spec = select_publishee_bump_spec(&package.name, ctx);
     bump_package_with_spec(package, bump_spec, ctx, bump_when_needed)
@@ -182,3 +743,126 @@ pub(crate) fn is_pre_release(semver: &Version) -> bool {
 pub(crate) fn rhs_is_breaking_bump_for_lhs(lhs: &Version, rhs: &Version) -> bool {
     rhs.major > lhs.major || rhs.minor > lhs.minor
 }
+
+#[cfg(test)]
+mod tests {
+    use semver::Prerelease;
+
+    use super::{Change, PreRelease, ReleaseChannel, SemanticVersioning, VersionScheme, WithTagPatterns};
+
+    #[test]
+    fn pre_release_counters_increment_and_advance() {
+        let next = |kind: PreRelease, current: &str| {
+            kind.next_after(&Prerelease::new(current).unwrap())
+                .map(|pre| pre.to_string())
+        };
+        assert_eq!(PreRelease::Rc.first().as_str(), "rc.1");
+        assert_eq!(next(PreRelease::Rc, "rc.1").unwrap(), "rc.2");
+        assert_eq!(next(PreRelease::Beta, "beta").unwrap(), "beta.1");
+        assert_eq!(next(PreRelease::Beta, "alpha.3").unwrap(), "beta.1");
+        assert!(next(PreRelease::Alpha, "rc.1").is_err(), "cannot go backwards");
+        assert!(
+            next(PreRelease::Alpha, "dev.1").is_err(),
+            "unknown kinds aren't understood"
+        );
+    }
+
+    #[test]
+    fn release_channels_use_dated_pre_releases_and_namespaced_tags() {
+        let channel = ReleaseChannel::new("nightly", jiff::civil::date(2024, 6, 1)).unwrap();
+        let next = |v: &str, change| {
+            channel
+                .next_version(&semver::Version::parse(v).unwrap(), change)
+                .to_string()
+        };
+        assert_eq!(next("1.2.0", Change::Feature), "1.3.0-nightly.20240601");
+        assert_eq!(
+            next("1.3.0-nightly.20240531", Change::Breaking),
+            "1.3.0-nightly.20240601"
+        );
+        assert_eq!(next("0.2.3", Change::Fix), "0.2.4-nightly.20240601");
+
+        let version = semver::Version::parse("1.3.0-nightly.20240601").unwrap();
+        assert_eq!(
+            channel.tag_name(Some("a"), &version),
+            "nightly/a-v1.3.0-nightly.20240601"
+        );
+        assert_eq!(
+            channel.tag_name(None, &"1.2.0".parse().unwrap()),
+            "v1.2.0",
+            "stable releases keep their tags"
+        );
+        assert_eq!(
+            channel.parse_tag_name(Some("a"), "nightly/a-v1.3.0-nightly.20240601".into()),
+            Some(version.clone())
+        );
+        assert_eq!(
+            channel.parse_tag_name(None, "v1.2.0".into()),
+            Some("1.2.0".parse().unwrap())
+        );
+        assert_eq!(channel.parse_tag_name(None, "nightly/v1.2.0".into()), None);
+        assert_eq!(
+            SemanticVersioning.parse_tag_name(None, "nightly/v1.3.0-nightly.20240601".into()),
+            None,
+            "the stable channel doesn't see channel releases"
+        );
+        assert!(ReleaseChannel::new("night.ly", jiff::civil::date(2024, 6, 1)).is_err());
+    }
+
+    #[test]
+    fn tag_patterns_find_releases_with_custom_tag_names() {
+        let scheme = WithTagPatterns::new(
+            std::rc::Rc::new(SemanticVersioning),
+            vec!["release-{version}".into(), "{crate}_{version}".into()],
+        )
+        .unwrap();
+        let parse = |package_name, tag_name: &str| scheme.parse_tag_name(package_name, tag_name.into());
+        assert_eq!(parse(None, "release-1.2.3"), Some("1.2.3".parse().unwrap()));
+        assert_eq!(
+            parse(None, "v1.2.3"),
+            Some("1.2.3".parse().unwrap()),
+            "our own tags are still known"
+        );
+        assert_eq!(parse(Some("a"), "a_0.1.0"), Some("0.1.0".parse().unwrap()));
+        assert_eq!(parse(Some("a"), "b_0.1.0"), None);
+        assert_eq!(
+            parse(Some("a"), "release-1.2.3"),
+            None,
+            "patterns without crate name are for the top-level crate"
+        );
+        assert_eq!(parse(None, "release-1.2"), None);
+        assert_eq!(scheme.tag_name(Some("a"), &"1.0.0".parse().unwrap()), "a-v1.0.0");
+        assert!(WithTagPatterns::new(std::rc::Rc::new(SemanticVersioning), vec!["release".into()]).is_err());
+    }
+
+    #[test]
+    fn incompatible_requirement_changes() {
+        let incompatible = |released: &str, current: &str| {
+            super::is_incompatible_requirement_change(
+                &semver::VersionReq::parse(released).unwrap(),
+                &semver::VersionReq::parse(current).unwrap(),
+            )
+        };
+        assert!(incompatible("1.2", "2"));
+        assert!(incompatible("0.3.1", "0.4"));
+        assert!(incompatible("0.0.1", "0.0.2"));
+        assert!(incompatible("2", "1.5"));
+        assert!(!incompatible("1.2", "1.5.3"));
+        assert!(!incompatible("0.3.1", "^0.3.7"));
+        assert!(!incompatible("*", "2"));
+    }
+
+    #[test]
+    fn semantic_versioning_treats_zero_versions_as_pre_releases() {
+        let next = |current: &str, change| {
+            SemanticVersioning
+                .next_version(&current.parse().unwrap(), change)
+                .to_string()
+        };
+        assert_eq!(next("1.2.3", Change::Breaking), "2.0.0");
+        assert_eq!(next("1.2.3", Change::Feature), "1.3.0");
+        assert_eq!(next("1.2.3-rc.1", Change::Fix), "1.2.4");
+        assert_eq!(next("0.2.3", Change::Breaking), "0.3.0");
+        assert_eq!(next("0.2.3", Change::Feature), "0.2.4");
+    }
+}
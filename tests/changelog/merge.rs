@@ -191,6 +191,7 @@ fn segments() {
                                 id: changed_message_id,
                                 title: "content changed by user".to_string(),
                                 body: None,
+                                author: None,
                             },
                         ],
                     }), // conventional is present and prevents new conventionals from showing up
@@ -240,6 +241,7 @@ fn segments() {
         count: 1,
         duration: None,
         time_passed_since_last_release: None,
+        authors: None,
         conventional_count: 2,
         unique_issues: vec![],
     }));
@@ -256,16 +258,19 @@ fn segments() {
                 id: removed_message_id,
                 title: "something removed".to_string(),
                 body: None,
+                author: None,
             },
             section::segment::conventional::Message::Generated {
                 id: changed_message_id,
                 title: "something added/changed".to_string(),
                 body: None,
+                author: None,
             },
             section::segment::conventional::Message::Generated {
                 id: added_message_id,
                 title: "to be inserted after user message".to_string(),
                 body: None,
+                author: None,
             },
         ],
     });
@@ -350,12 +355,14 @@ fn segments() {
                                 section::segment::conventional::Message::Generated {
                                     id: added_message_id,
                                     title: "to be inserted after user message".to_string(),
-                                    body: None
+                                    body: None,
+                                    author: None
                                 }, // new messages are inserted after user content
                                 section::segment::conventional::Message::Generated {
                                     id: changed_message_id,
                                     title: "content changed by user".to_string(),
-                                    body: None
+                                    body: None,
+                                    author: None
                                 }, // changed user content is preserved, don't overwrite, ever
                             ],
                         }), // conventional is present and prevents new conventionals from showing up, they have messages merged though
@@ -436,6 +443,7 @@ fn stale_generated_conventional_messages_are_removed() {
                             id: stale_fix_id,
                             title: "old generated fix".into(),
                             body: None,
+                            author: None,
                         },
                         section::segment::conventional::Message::User {
                             markdown: " - keep this user-authored note".into(),
@@ -450,6 +458,7 @@ fn stale_generated_conventional_messages_are_removed() {
                         id: stale_breaking_id,
                         title: "old generated breaking feature".into(),
                         body: None,
+                        author: None,
                     }],
                 }),
             ],
@@ -459,6 +468,7 @@ fn stale_generated_conventional_messages_are_removed() {
         count: 1,
         duration: None,
         time_passed_since_last_release: Some(28),
+        authors: None,
         conventional_count: 0,
         unique_issues: vec![],
     }));
@@ -519,6 +529,7 @@ fn generated_conventional_messages_survive_when_conventional_generation_is_disab
                     id: existing_id,
                     title: "existing generated fix".into(),
                     body: None,
+                    author: None,
                 }],
             })],
         }],
@@ -527,6 +538,7 @@ fn generated_conventional_messages_survive_when_conventional_generation_is_disab
         count: 1,
         duration: None,
         time_passed_since_last_release: Some(28),
+        authors: None,
         conventional_count: 0,
         unique_issues: vec![],
     }));
@@ -564,6 +576,7 @@ fn generated_conventional_messages_survive_when_conventional_generation_is_disab
                         id: existing_id,
                         title: "existing generated fix".into(),
                         body: None,
+                        author: None,
                     }],
                 }),
                 statistics,
@@ -32,7 +32,7 @@ fn fixture(name: &str) -> std::io::Result<String> {
 #[test]
 fn all_unknown_in_section() {
     let fixture = fixture("known-section-unknown-content.md").unwrap();
-    let log = ChangeLog::from_markdown(&fixture);
+    let log = ChangeLog::from_markdown(&fixture, &Default::default());
     assert_eq!(
         log.sections,
         vec![
@@ -65,7 +65,7 @@ fn all_unknown_in_section() {
 #[test]
 fn unknown_link_and_headline() {
     let fixture = fixture("known-section-unknown-headline-with-link.md").unwrap();
-    let log = ChangeLog::from_markdown(&fixture);
+    let log = ChangeLog::from_markdown(&fixture, &Default::default());
     assert_eq!(
         log.sections,
         vec![Section::Release {
@@ -85,7 +85,7 @@ fn unknown_link_and_headline() {
 #[test]
 fn known_and_unknown_sections_are_sorted() {
     let fixture = fixture("unknown-known-unknown-known-unsorted.md").unwrap();
-    let log = ChangeLog::from_markdown(&fixture);
+    let log = ChangeLog::from_markdown(&fixture, &Default::default());
     assert_eq!(
         log.sections,
         vec![
@@ -127,7 +127,7 @@ fn known_and_unknown_sections_are_sorted() {
 #[test]
 fn releases_are_sorted_by_date() {
     let fixture = fixture("releases-sorted-by-date.md").unwrap();
-    let log = ChangeLog::from_markdown(&fixture);
+    let log = ChangeLog::from_markdown(&fixture, &Default::default());
 
     // Extract the version numbers and dates from the parsed sections
     let release_versions: Vec<_> = log
@@ -152,6 +152,30 @@ fn releases_are_sorted_by_date() {
     assert_eq!(release_versions[4].0, semver::Version::parse("0.74.0").unwrap());
 }
 
+#[test]
+fn custom_version_prefixes_are_preserved() {
+    let log = ChangeLog::from_markdown(
+        "## release-2.1.0 (2024-06-01)\n\nText\n\n## product2-1.0.0\n\nMore text\n",
+        &Default::default(),
+    );
+    let versions_and_prefixes: Vec<_> = log
+        .sections
+        .iter()
+        .filter_map(|s| match s {
+            Section::Release {
+                name: Version::Semantic(v),
+                version_prefix,
+                ..
+            } => Some((v.to_string(), version_prefix.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        versions_and_prefixes,
+        [("2.1.0".to_string(), "release-"), ("1.0.0".to_string(), "product2-")]
+    );
+}
+
 #[test]
 fn title_case_refactor_breaking_section_parses_as_conventional() {
     let input = r#"## Unreleased
@@ -164,7 +188,7 @@ fn title_case_refactor_breaking_section_parses_as_conventional() {
    refactor!: rename `Exn::from_iter` to `raise_all`
 "#;
 
-    let log = ChangeLog::from_markdown(input);
+    let log = ChangeLog::from_markdown(input, &Default::default());
 
     let Section::Release { segments, unknown, .. } = &log.sections[0] else {
         panic!("expected release");
@@ -191,6 +215,7 @@ fn title_case_refactor_breaking_section_parses_as_conventional() {
             id,
             title,
             body: Some(body),
+            author: None,
         } if *id == hex_to_id("829393ac596bf2684bd8a837ae931773b24ee033")
             && title == "ErrorExt::raise_iter to raise_all + remove Frame::downcast"
             && body == "Be more compatible to `exn`."
@@ -201,6 +226,7 @@ fn title_case_refactor_breaking_section_parses_as_conventional() {
             id,
             title,
             body: Some(body),
+            author: None,
         } if *id == hex_to_id("f8517bedcbb9b3328f435aa37f4c63bd30b19fc0")
             && title == "catch up Exn designs with the upstream"
             && body == "refactor!: rename `Exn::from_iter` to `raise_all`"
@@ -216,7 +242,7 @@ fn partial_conventional_headline_prefix_is_preserved_as_user_markdown() {
 This is a user-authored heading, not a generated refactor section.
 "#;
 
-    let log = ChangeLog::from_markdown(input);
+    let log = ChangeLog::from_markdown(input, &Default::default());
 
     let Section::Release { segments, unknown, .. } = &log.sections[0] else {
         panic!("expected release");
@@ -254,7 +280,7 @@ fn nested_list_items_with_csr_id_round_trips_stably() {
    - `open`
 "#;
 
-    let log = ChangeLog::from_markdown(input);
+    let log = ChangeLog::from_markdown(input, &Default::default());
 
     // Verify the nested list items are properly captured in the body
     assert_eq!(log.sections.len(), 1);
@@ -289,20 +315,25 @@ fn nested_list_items_with_csr_id_round_trips_stably() {
 
     // Test round-trip stability: parse → write → parse → write should be stable
     let mut output1 = String::new();
-    log.write_to(&mut output1, &Linkables::AsText, Components::all(), false)
+    log.write_to(&mut output1, &Linkables::AsText, Components::all(), &Default::default())
         .unwrap();
 
-    let log2 = ChangeLog::from_markdown(&output1);
+    let log2 = ChangeLog::from_markdown(&output1, &Default::default());
     let mut output2 = String::new();
-    log2.write_to(&mut output2, &Linkables::AsText, Components::all(), false)
+    log2.write_to(&mut output2, &Linkables::AsText, Components::all(), &Default::default())
         .unwrap();
 
     // Multiple round-trips should produce identical output
     for round in 3..=5 {
-        let log_n = ChangeLog::from_markdown(&output2);
+        let log_n = ChangeLog::from_markdown(&output2, &Default::default());
         let mut output_n = String::new();
         log_n
-            .write_to(&mut output_n, &Linkables::AsText, Components::all(), false)
+            .write_to(
+                &mut output_n,
+                &Linkables::AsText,
+                Components::all(),
+                &Default::default(),
+            )
             .unwrap();
         assert_eq!(
             output2, output_n,
@@ -327,7 +358,7 @@ fn user_message_with_nested_list_round_trips_stably() {
    - `open`
 "#;
 
-    let log = ChangeLog::from_markdown(input);
+    let log = ChangeLog::from_markdown(input, &Default::default());
 
     // Verify it parses as a User message (not Generated, since no csr-id)
     assert_eq!(log.sections.len(), 1);
@@ -355,12 +386,12 @@ fn user_message_with_nested_list_round_trips_stably() {
 
     // Test round-trip stability
     let mut output1 = String::new();
-    log.write_to(&mut output1, &Linkables::AsText, Components::all(), false)
+    log.write_to(&mut output1, &Linkables::AsText, Components::all(), &Default::default())
         .unwrap();
 
-    let log2 = ChangeLog::from_markdown(&output1);
+    let log2 = ChangeLog::from_markdown(&output1, &Default::default());
     let mut output2 = String::new();
-    log2.write_to(&mut output2, &Linkables::AsText, Components::all(), false)
+    log2.write_to(&mut output2, &Linkables::AsText, Components::all(), &Default::default())
         .unwrap();
 
     assert_eq!(
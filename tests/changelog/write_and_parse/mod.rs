@@ -50,11 +50,13 @@ fn issue_30_body_with_unordered_list_does_not_flatten() -> Result {
                              - The software can never be run on Tuesday."
                                 .into(),
                         ),
+                        author: None,
                     },
                     conventional::Message::Generated {
                         id: hex_to_id("0000000000000000000000000000000000000002"),
                         title: "Time zones are remembered across sessions".into(),
                         body: None,
+                        author: None,
                     },
                 ],
             })],
@@ -68,7 +70,7 @@ fn issue_30_body_with_unordered_list_does_not_flatten() -> Result {
         &mut md,
         &changelog::write::Linkables::AsText,
         changelog::write::Components::all(),
-        false,
+        &Default::default(),
     )?;
 
     // Verify the markdown structure: There should be exactly 2 top-level bullet points
@@ -84,7 +86,7 @@ fn issue_30_body_with_unordered_list_does_not_flatten() -> Result {
     );
 
     // Parse back and verify round-trip stability
-    let parsed_log = ChangeLog::from_markdown(&md);
+    let parsed_log = ChangeLog::from_markdown(&md, &Default::default());
     assert_eq!(parsed_log, log, "should round-trip losslessly");
 
     insta::assert_snapshot!(md, @"
@@ -131,11 +133,13 @@ fn conventional_write_empty_messages() -> Result {
                         id: hex_to_id("0000000000000000000000000000000000000003"),
                         title: "this messages comes straight from git conventional and _may_ contain markdown".into(),
                         body: Some("first line\nsecond line\n\nanother paragraph".into()),
+                        author: None,
                     },
                     conventional::Message::Generated {
                         id: hex_to_id("0000000000000000000000000000000000000004"),
                         title: "spelling. Hello".into(),
                         body: None,
+                        author: None,
                     },
                     conventional::Message::User {
                         markdown:
@@ -157,10 +161,15 @@ fn conventional_write_empty_messages() -> Result {
         let log = log.clone();
         for _round in 1..=2 {
             let mut md = String::new();
-            log.write_to(&mut md, link_mode, changelog::write::Components::all(), false)?;
+            log.write_to(
+                &mut md,
+                link_mode,
+                changelog::write::Components::all(),
+                &Default::default(),
+            )?;
             insta::assert_snapshot!(md);
 
-            let parsed_log = ChangeLog::from_markdown(&md);
+            let parsed_log = ChangeLog::from_markdown(&md, &Default::default());
             assert_eq!(parsed_log, log, "we can parse this back losslessly");
         }
     }
@@ -170,7 +179,12 @@ fn conventional_write_empty_messages() -> Result {
     ] {
         for section in &log.sections {
             let mut buf = String::new();
-            section.write_to(&mut buf, &changelog::write::Linkables::AsText, *components, false)?;
+            section.write_to(
+                &mut buf,
+                &changelog::write::Linkables::AsText,
+                *components,
+                &Default::default(),
+            )?;
             insta::assert_snapshot!(buf);
         }
     }
@@ -210,6 +224,7 @@ fn all_section_types_round_trips_lossy() -> Result {
                         duration: Some(32),
                         conventional_count: 20,
                         time_passed_since_last_release: Some(60),
+                        authors: None,
                         unique_issues: vec![
                             section::segment::details::Category::Issue("1".into()),
                             section::segment::details::Category::Uncategorized,
@@ -262,10 +277,15 @@ fn all_section_types_round_trips_lossy() -> Result {
     ] {
         // NOTE: we can't run this a second time as the statistical information will be gone (it was never parsed back)
         let mut md = String::new();
-        log.write_to(&mut md, link_mode, changelog::write::Components::all(), false)?;
+        log.write_to(
+            &mut md,
+            link_mode,
+            changelog::write::Components::all(),
+            &Default::default(),
+        )?;
         insta::assert_snapshot!(md);
 
-        let parsed_log = ChangeLog::from_markdown(&md);
+        let parsed_log = ChangeLog::from_markdown(&md, &Default::default());
         assert_eq!(parsed_log, log, "we must be able to parse the exact input back");
     }
 
@@ -276,9 +296,509 @@ fn all_section_types_round_trips_lossy() -> Result {
     ] {
         for section in &log.sections {
             let mut buf = String::new();
-            section.write_to(&mut buf, &changelog::write::Linkables::AsText, *components, false)?;
+            section.write_to(
+                &mut buf,
+                &changelog::write::Linkables::AsText,
+                *components,
+                &Default::default(),
+            )?;
             insta::assert_snapshot!(buf);
         }
     }
     Ok(())
 }
+
+#[test]
+fn localized_headings_round_trip() -> Result {
+    let log = ChangeLog {
+        sections: vec![Section::Release {
+            heading_level: 2,
+            version_prefix: Section::DEFAULT_PREFIX.into(),
+            date: Some(jiff::Timestamp::new(0, 0)?.to_zoned(jiff::tz::TimeZone::UTC)),
+            name: changelog::Version::Semantic("1.0.0".parse()?),
+            removed_messages: vec![],
+            segments: vec![
+                section::Segment::Conventional(section::segment::Conventional {
+                    kind: "feat",
+                    is_breaking: false,
+                    removed: vec![],
+                    messages: vec![conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000001"),
+                        title: "a new feature".into(),
+                        body: None,
+                        author: None,
+                    }],
+                }),
+                section::Segment::Conventional(section::segment::Conventional {
+                    kind: "fix",
+                    is_breaking: true,
+                    removed: vec![],
+                    messages: vec![conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000002"),
+                        title: "a breaking fix".into(),
+                        body: None,
+                        author: None,
+                    }],
+                }),
+                section::Segment::Clippy(section::Data::Generated(section::segment::ThanksClippy { count: 1 })),
+            ],
+            unknown: String::new(),
+        }],
+    };
+
+    let mut md = String::new();
+    log.write_to(
+        &mut md,
+        &changelog::write::Linkables::AsText,
+        changelog::write::Components::all(),
+        &changelog::write::Options {
+            locale: changelog::locale::Locale::from_name("de").expect("built-in"),
+            ..Default::default()
+        },
+    )?;
+    insta::assert_snapshot!(md, @"
+    ## v1.0.0 (1970-01-01)
+
+    ### Neue Funktionen
+
+     - <csr-id-0000000000000000000000000000000000000001/> a new feature
+
+    ### Fehlerbehebungen (BREAKING)
+
+     - <csr-id-0000000000000000000000000000000000000002/> a breaking fix
+
+    ### Danke Clippy
+
+    <csr-read-only-do-not-edit/>
+
+    [Clippy](https://github.com/rust-lang/rust-clippy) helped 1 time to make code idiomatic.
+    ");
+
+    let parsed_log = ChangeLog::from_markdown(&md, &Default::default());
+    assert_eq!(parsed_log, log, "translated headings are understood when parsing");
+    Ok(())
+}
+
+#[test]
+fn custom_headings_round_trip() -> Result {
+    let log = ChangeLog {
+        sections: vec![Section::Release {
+            heading_level: 2,
+            version_prefix: Section::DEFAULT_PREFIX.into(),
+            date: Some(jiff::Timestamp::new(0, 0)?.to_zoned(jiff::tz::TimeZone::UTC)),
+            name: changelog::Version::Semantic("1.0.0".parse()?),
+            removed_messages: vec![],
+            segments: vec![
+                section::Segment::Conventional(section::segment::Conventional {
+                    kind: "feat",
+                    is_breaking: false,
+                    removed: vec![],
+                    messages: vec![conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000001"),
+                        title: "a new feature".into(),
+                        body: None,
+                        author: None,
+                    }],
+                }),
+                section::Segment::Conventional(section::segment::Conventional {
+                    kind: "fix",
+                    is_breaking: false,
+                    removed: vec![],
+                    messages: vec![conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000002"),
+                        title: "a fix".into(),
+                        body: None,
+                        author: None,
+                    }],
+                }),
+                section::Segment::Clippy(section::Data::Generated(section::segment::ThanksClippy { count: 1 })),
+            ],
+            unknown: String::new(),
+        }],
+    };
+    let locale = changelog::locale::Locale::from_toml("feat = \"Shiny\"\nclippy = \"Lints\"\n")?;
+
+    let mut md = String::new();
+    log.write_to(
+        &mut md,
+        &changelog::write::Linkables::AsText,
+        changelog::write::Components::all(),
+        &changelog::write::Options {
+            locale: locale.clone(),
+            ..Default::default()
+        },
+    )?;
+    insta::assert_snapshot!(md, @"
+    ## v1.0.0 (1970-01-01)
+
+    ### Shiny
+
+     - <csr-id-0000000000000000000000000000000000000001/> a new feature
+
+    ### Bug Fixes
+
+     - <csr-id-0000000000000000000000000000000000000002/> a fix
+
+    ### Lints
+
+    <csr-read-only-do-not-edit/>
+
+    [Clippy](https://github.com/rust-lang/rust-clippy) helped 1 time to make code idiomatic.
+    ");
+
+    let parsed_log = ChangeLog::from_markdown(&md, &locale);
+    assert_eq!(parsed_log, log, "given headings are understood when parsing");
+    assert!(
+        changelog::locale::Locale::from_toml("feature = \"Shiny\"").is_err(),
+        "unknown keys are rejected"
+    );
+    Ok(())
+}
+
+#[test]
+fn pull_request_references_link_to_pulls() -> Result {
+    let log = ChangeLog {
+        sections: vec![Section::Release {
+            heading_level: 2,
+            version_prefix: Section::DEFAULT_PREFIX.into(),
+            date: None,
+            name: changelog::Version::Unreleased,
+            removed_messages: vec![],
+            segments: vec![section::Segment::Details(section::Data::Generated(
+                section::segment::Details {
+                    commits_by_category: {
+                        let mut h = BTreeMap::default();
+                        h.insert(
+                            section::segment::details::Category::Issue("1".into()),
+                            vec![section::segment::details::Message {
+                                title: "fix the issue".into(),
+                                id: hex_to_id("0000000000000000000000000000000000000001"),
+                            }],
+                        );
+                        h.insert(
+                            section::segment::details::Category::PullRequest("2".into()),
+                            vec![section::segment::details::Message {
+                                title: "merge the pull request".into(),
+                                id: hex_to_id("0000000000000000000000000000000000000002"),
+                            }],
+                        );
+                        h
+                    },
+                },
+            ))],
+            unknown: String::new(),
+        }],
+    };
+
+    let mut md = String::new();
+    log.write_to(
+        &mut md,
+        &changelog::write::Linkables::AsLinks {
+            repository_url: gix::Url::try_from(b"https://github.com/user/repo".as_bstr())?.into(),
+        },
+        changelog::write::Components::empty(),
+        &Default::default(),
+    )?;
+    insta::assert_snapshot!(md, @"
+    ### Commit Details
+
+     * **[#1](https://github.com/user/repo/issues/1)**
+        - Fix the issue ([`0000000`](https://github.com/user/repo/commit/0000000000000000000000000000000000000001))
+     * **[#2](https://github.com/user/repo/pull/2)**
+        - Merge the pull request ([`0000000`](https://github.com/user/repo/commit/0000000000000000000000000000000000000002))
+    ");
+    Ok(())
+}
+
+#[test]
+fn authors_round_trip() -> Result {
+    let log = ChangeLog {
+        sections: vec![Section::Release {
+            heading_level: 2,
+            version_prefix: "".into(),
+            date: None,
+            name: changelog::Version::Unreleased,
+            removed_messages: vec![],
+            segments: vec![section::Segment::Conventional(section::segment::Conventional {
+                kind: "feat",
+                is_breaking: false,
+                removed: vec![],
+                messages: vec![
+                    conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000001"),
+                        title: "a new feature".into(),
+                        body: Some("with details".into()),
+                        author: Some("Jane Doe".into()),
+                    },
+                    conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000002"),
+                        title: "an anonymous feature (by nobody)".into(),
+                        body: None,
+                        author: None,
+                    },
+                ],
+            })],
+            unknown: String::new(),
+        }],
+    };
+
+    let mut md = String::new();
+    log.write_to(
+        &mut md,
+        &changelog::write::Linkables::AsText,
+        changelog::write::Components::all(),
+        &Default::default(),
+    )?;
+    insta::assert_snapshot!(md, @"
+    ## Unreleased
+
+    ### New Features
+
+     - <csr-id-0000000000000000000000000000000000000001/> a new feature <csr-author/>(by Jane Doe)
+       with details
+     - <csr-id-0000000000000000000000000000000000000002/> an anonymous feature (by nobody)
+    ");
+
+    let parsed_log = ChangeLog::from_markdown(&md, &Default::default());
+    assert_eq!(parsed_log, log, "authors are parsed back and titles stay untouched");
+    Ok(())
+}
+
+#[test]
+fn statistics_count_authors() -> Result {
+    let release = |authors| Section::Release {
+        heading_level: 2,
+        version_prefix: "".into(),
+        date: None,
+        name: changelog::Version::Unreleased,
+        removed_messages: vec![],
+        segments: vec![section::Segment::Statistics(section::Data::Generated(
+            section::segment::CommitStatistics {
+                count: 3,
+                duration: None,
+                conventional_count: 1,
+                time_passed_since_last_release: None,
+                authors: Some(authors),
+                unique_issues: vec![],
+            },
+        ))],
+        unknown: String::new(),
+    };
+
+    for (authors, expected) in [
+        (1, " - 1 author contributed to the release."),
+        (3, " - 3 authors contributed to the release."),
+    ] {
+        let log = ChangeLog {
+            sections: vec![release(authors)],
+        };
+        let mut md = String::new();
+        log.write_to(
+            &mut md,
+            &changelog::write::Linkables::AsText,
+            changelog::write::Components::all(),
+            &Default::default(),
+        )?;
+        assert!(
+            md.lines().any(|line| line == expected),
+            "the amount of authors is part of the statistics:\n{md}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn sidecar_tracking_state_round_trips_without_html_tags() -> Result {
+    let log = ChangeLog {
+        sections: vec![Section::Release {
+            heading_level: 2,
+            version_prefix: "".into(),
+            date: None,
+            name: changelog::Version::Unreleased,
+            removed_messages: vec![hex_to_id("0000000000000000000000000000000000000003")],
+            segments: vec![section::Segment::Conventional(section::segment::Conventional {
+                kind: "feat",
+                is_breaking: false,
+                removed: vec![hex_to_id("0000000000000000000000000000000000000004")],
+                messages: vec![
+                    conventional::Message::User {
+                        markdown: "- written by hand".into(),
+                    },
+                    conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000001"),
+                        title: "a new feature".into(),
+                        body: Some("with details".into()),
+                        author: Some("Jane Doe".into()),
+                    },
+                    conventional::Message::Generated {
+                        id: hex_to_id("0000000000000000000000000000000000000002"),
+                        title: "another feature".into(),
+                        body: None,
+                        author: None,
+                    },
+                ],
+            })],
+            unknown: "something we didn't understand\n".into(),
+        }],
+    };
+
+    let options = changelog::write::Options::default();
+    let mut md = String::new();
+    log.write_to(
+        &mut md,
+        &changelog::write::Linkables::AsText,
+        changelog::write::Components::all() - changelog::write::Components::HTML_TAGS,
+        &options,
+    )?;
+    insta::assert_snapshot!(md, @"
+    ## Unreleased
+
+    ### New Features
+
+    - written by hand
+     - a new feature (by Jane Doe)
+       with details
+     - another feature
+    ");
+    let state: changelog::tracking::State = log.tracking_state(&options).to_toml().parse()?;
+
+    let mut parsed_log = ChangeLog::from_markdown(&md, &Default::default());
+    parsed_log.apply_tracking_state(&state);
+    assert_eq!(
+        parsed_log, log,
+        "the sidecar restores everything that tags would have stored"
+    );
+
+    let edited_md = md.replace(" - another feature\n", " - another feature, edited\n");
+    let mut parsed_log = ChangeLog::from_markdown(&edited_md, &Default::default());
+    parsed_log.apply_tracking_state(&state);
+    match &parsed_log.sections[0] {
+        Section::Release { segments, .. } => match &segments[0] {
+            section::Segment::Conventional(conventional) => {
+                assert_eq!(
+                    conventional.removed,
+                    vec![
+                        hex_to_id("0000000000000000000000000000000000000004"),
+                        hex_to_id("0000000000000000000000000000000000000002")
+                    ],
+                    "edited messages are owned by the user and won't be generated again"
+                );
+                assert!(matches!(
+                    &conventional.messages[2],
+                    conventional::Message::User { markdown } if markdown == " - another feature, edited"
+                ));
+            }
+            _ => unreachable!("conventional segment"),
+        },
+        _ => unreachable!("release section"),
+    }
+    Ok(())
+}
+
+#[test]
+fn anchors_round_trip() -> Result {
+    let log = ChangeLog {
+        sections: vec![
+            Section::Release {
+                heading_level: 2,
+                version_prefix: "".into(),
+                date: None,
+                name: changelog::Version::Unreleased,
+                removed_messages: vec![],
+                segments: vec![section::Segment::User {
+                    markdown: "- not yet released\n\n".into(),
+                }],
+                unknown: String::new(),
+            },
+            Section::Release {
+                heading_level: 2,
+                version_prefix: Section::DEFAULT_PREFIX.into(),
+                date: Some(jiff::Timestamp::new(0, 0)?.to_zoned(jiff::tz::TimeZone::UTC)),
+                name: changelog::Version::Semantic("1.2.3".parse()?),
+                removed_messages: vec![],
+                segments: vec![section::Segment::User {
+                    markdown: "- released\n\n".into(),
+                }],
+                unknown: String::new(),
+            },
+        ],
+    };
+
+    let mut md = String::new();
+    log.write_to(
+        &mut md,
+        &changelog::write::Linkables::AsText,
+        changelog::write::Components::all(),
+        &changelog::write::Options {
+            anchors: true,
+            ..Default::default()
+        },
+    )?;
+    insta::assert_snapshot!(md, @r#"
+    ## Unreleased
+
+    - not yet released
+
+    <a id="v1.2.3"></a>
+    ## v1.2.3 (1970-01-01)
+
+    - released
+    "#);
+    assert!(changelog::write::has_anchors(&md));
+
+    let parsed_log = ChangeLog::from_markdown(&md, &Default::default());
+    assert_eq!(
+        parsed_log, log,
+        "anchors are recognized and don't end up in the previous section"
+    );
+    Ok(())
+}
+
+#[test]
+fn commit_details_without_hashes() -> Result {
+    let log = ChangeLog {
+        sections: vec![Section::Release {
+            heading_level: 2,
+            version_prefix: Section::DEFAULT_PREFIX.into(),
+            date: None,
+            name: changelog::Version::Unreleased,
+            removed_messages: vec![],
+            segments: vec![section::Segment::Details(section::Data::Generated(
+                section::segment::Details {
+                    commits_by_category: {
+                        let mut h = BTreeMap::default();
+                        h.insert(
+                            section::segment::details::Category::Uncategorized,
+                            vec![section::segment::details::Message {
+                                title: "just the title".into(),
+                                id: hex_to_id("0000000000000000000000000000000000000001"),
+                            }],
+                        );
+                        h
+                    },
+                },
+            ))],
+            unknown: String::new(),
+        }],
+    };
+
+    let mut md = String::new();
+    log.write_to(
+        &mut md,
+        &changelog::write::Linkables::AsLinks {
+            repository_url: gix::Url::try_from(b"https://github.com/user/repo".as_bstr())?.into(),
+        },
+        changelog::write::Components::empty(),
+        &changelog::write::Options {
+            omit_commit_hashes: true,
+            ..Default::default()
+        },
+    )?;
+    insta::assert_snapshot!(md, @"
+    ### Commit Details
+
+     * **Uncategorized**
+        - Just the title
+    ");
+    Ok(())
+}
@@ -16,7 +16,7 @@ snapshot="$root/snapshots"
 fixtures="$root/fixtures"
 
 SUCCESSFULLY=0
-# WITH_FAILURE=1
+WITH_FAILURE=1
 
 function set-static-git-environment() {
   set -a
@@ -231,10 +231,120 @@ EOF
 
   (with_program gh
     (with "docs.rs publish metadata"
-      it "forwards docs.rs feature flags to cargo publish" && {
+      it "forwards docs.rs feature flags to cargo package" && {
         expect_run_sh $SUCCESSFULLY \
-          "\"$exe\" smart-release a --no-push -v --allow-dirty -b minor --publish-uses-docs-rs-metadata 2>&1 | grep -F '\"cargo\" \"publish\" \"--features\" \"docs-rs-feature\" \"--all-features\" \"--no-default-features\"'"
+          "\"$exe\" smart-release a --no-push -v --allow-dirty -b minor --publish-uses-docs-rs-metadata 2>&1 | grep -F '\"cargo\" \"package\" \"--features\" \"docs-rs-feature\" \"--all-features\" \"--no-default-features\"'"
       }
     )
   )
 )
+
+(sandbox
+  set-static-git-environment
+  export CARGO_HOME="$(mktemp -t cargo-home.XXXXXX -d)"
+
+  cp -R $fixtures/tri-depth-workspace/* .
+  { echo 'target/' > .gitignore && init-git-repo && git remote add origin https://github.com/example/example.git; } &>/dev/null
+
+  fake_bin="$(mktemp -t fake-bin.XXXXXX -d)"
+  printf '#!/bin/sh\n[ "${1:-}" != api ] || touch "%s/gh-api-was-called"\nexit 1\n' "$fake_bin" > "$fake_bin/gh"
+  chmod +x "$fake_bin/gh"
+
+  (with "--offline and a GitHub remote"
+    it "succeeds" && {
+      PATH="$fake_bin:$PATH" expect_run $SUCCESSFULLY "$exe" smart-release a --offline
+    }
+    it "doesn't ask GitHub if the branch is protected" && {
+      expect_run 1 test -e "$fake_bin/gh-api-was-called"
+    }
+  )
+)
+
+(sandbox
+  set-static-git-environment
+
+  { cargo init --vcs none --name status . && echo 'target/' > .gitignore && init-git-repo; } &>/dev/null
+
+  (with "a workspace crate named like a subcommand"
+    it "refuses to guess if the subcommand should run" && {
+      expect_run $WITH_FAILURE "$exe" smart-release status
+    }
+    it "releases the crate if it's given after --" && {
+      expect_run $SUCCESSFULLY "$exe" smart-release --offline --no-push -- status
+    }
+  )
+)
+
+(sandbox
+  set-static-git-environment
+
+  cp -R $fixtures/tri-depth-workspace/* .
+  { rm a/CHANGELOG.md && echo 'target/' > .gitignore && printf '\n[workspace.metadata.smart-release]\nchangelog-locale = "de"\nchangelog-sidecar = true\n' >> Cargo.toml && init-git-repo && echo '// fixed' >> a/src/lib.rs && git commit -qam "fix: a bug in a"; } &>/dev/null
+
+  (with "changelog settings in the workspace configuration"
+    it "creates changelogs" && {
+      expect_run $SUCCESSFULLY "$exe" smart-release init --execute a
+    }
+    it "writes headings in the configured language" && {
+      expect_run $SUCCESSFULLY grep -F "### Fehlerbehebungen" a/CHANGELOG.md
+    }
+    it "keeps the tracking data in a sidecar file" && {
+      expect_run $WITH_FAILURE grep -F "<csr-id-" a/CHANGELOG.md
+      expect_run $SUCCESSFULLY test -f a/.changelog-state.toml
+    }
+  )
+)
+
+(sandbox
+  set-static-git-environment
+  export CARGO_HOME="$(mktemp -t cargo-home.XXXXXX -d)"
+
+  git init -q --bare remote.git
+  mkdir workspace && cd workspace
+  cp -R $fixtures/tri-depth-workspace/* .
+  { echo 'target/' > .gitignore && init-git-repo && git branch -M main && git remote add origin ../remote.git; } &>/dev/null
+
+  (with "a local remote without the branch"
+    it "pushes the release commit and its tag" && {
+      expect_run $SUCCESSFULLY "$exe" smart-release a -b keep -d keep --no-publish --execute --allow-dirty --no-changelog-preview --auto-publish-of-stable-crates
+    }
+    it "moves the remote branch to the release commit" && {
+      expect_equals "$(git -C ../remote.git rev-parse main)" "$(git rev-parse HEAD)"
+    }
+    it "creates the tag on the remote" && {
+      expect_equals "$(git -C ../remote.git rev-parse a-v0.8.0)" "$(git rev-parse a-v0.8.0)"
+    }
+    it "sends all objects the remote needs" && {
+      expect_run $SUCCESSFULLY git -C ../remote.git fsck --strict
+    }
+    it "updates the remote-tracking branch" && {
+      expect_equals "$(git rev-parse origin/main)" "$(git rev-parse HEAD)"
+    }
+  )
+
+  (with "a pre-push hook that fails"
+    { printf '#!/bin/sh\nexit 1\n' > .git/hooks/pre-push && chmod +x .git/hooks/pre-push && sed -i 's/^version = .*/version = "0.8.1"/' a/Cargo.toml && git commit -qam "bump a"; } &>/dev/null
+    it "fails before pushing" && {
+      expect_run_sh $SUCCESSFULLY \
+        "{ \"$exe\" smart-release a -b keep -d keep --no-publish --execute --allow-dirty --no-changelog-preview --auto-publish-of-stable-crates 2>&1 || true; } | grep -F \"pre-push' failed, so nothing was pushed\""
+    }
+    it "leaves the remote alone" && {
+      expect_run $WITH_FAILURE git -C ../remote.git rev-parse --verify -q a-v0.8.1
+    }
+  )
+
+  (with "git hooks disabled in the workspace configuration"
+    { printf '\n[workspace.metadata.smart-release]\nno-verify = true\n' >> Cargo.toml && git commit -qam "configure the old key"; } &>/dev/null
+    it "refuses the key which is ambiguous with the one of packages" && {
+      expect_run_sh $SUCCESSFULLY \
+        "{ \"$exe\" smart-release a -b keep -d keep --no-publish --allow-dirty 2>&1 || true; } | grep -F \"set 'git-hooks = false' instead\""
+    }
+    { sed -i 's/^no-verify = true/git-hooks = false/; s/^version = .*/version = "0.8.2"/' Cargo.toml a/Cargo.toml && git commit -qam "skip the hooks"; } &>/dev/null
+    it "pushes without running the pre-push hook" && {
+      expect_run $SUCCESSFULLY "$exe" smart-release a -b keep -d keep --no-publish --execute --allow-dirty --no-changelog-preview --auto-publish-of-stable-crates
+    }
+    it "creates the tag on the remote" && {
+      expect_equals "$(git -C ../remote.git rev-parse a-v0.8.2)" "$(git rev-parse a-v0.8.2)"
+    }
+  )
+)